@@ -1,3 +1,5 @@
+import {token} from "./utils.js";
+
 export class Api {
   constructor(url) {
     this.url = url;
@@ -13,6 +15,16 @@ export class Api {
       path = encodePath(path);
     }
 
+    let t = token();
+
+    if (t) {
+      data = Object.assign({}, data, {
+        headers: Object.assign({}, data.headers || {}, {
+          "Authorization": `Bearer ${t}`,
+        }),
+      });
+    }
+
     return fetch(`${this.url}/${path}`, data).then((r) => {
       if (!r.ok) {
         return r.text().then(text => {
@@ -161,6 +173,178 @@ export class Api {
     });
   }
 
+  /**
+   * Import settings from JSON or YAML.
+   *
+   * @param {string} text the settings to import
+   * @param {bool} dryRun only report what would change
+   */
+  importSettings(text, dryRun) {
+    return this.fetch(`settings/import?dry_run=${dryRun}`, {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/yaml",
+      },
+      body: text,
+    });
+  }
+
+  /**
+   * List the most recent changes to settings.
+   *
+   * @param {number} limit maximum number of changes to list
+   */
+  settingsHistory(limit) {
+    return this.fetch(`settings/history?limit=${limit}`);
+  }
+
+  /**
+   * Undo the most recent changes to settings.
+   *
+   * @param {number} count number of changes to undo
+   */
+  settingsUndo(count) {
+    return this.fetch(`settings/undo?count=${count}`, {
+      method: "POST",
+    });
+  }
+
+  /**
+   * List all tokens used to access the web API.
+   */
+  tokens() {
+    return this.fetch(["tokens"]);
+  }
+
+  /**
+   * Generate a new token.
+   *
+   * @param {string} name name of the token
+   * @param {string} access the access of the token, `read` or `admin`
+   */
+  tokensCreate(name, access) {
+    return this.fetch(["tokens", name], {
+      method: "PUT",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({access}),
+    });
+  }
+
+  /**
+   * Delete the given token.
+   *
+   * @param {string} name name of the token
+   */
+  tokensDelete(name) {
+    return this.fetch(["tokens", name], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List all webhooks.
+   */
+  webhooks() {
+    return this.fetch(["webhooks"]);
+  }
+
+  /**
+   * List the events which can be delivered to webhooks.
+   */
+  webhookEvents() {
+    return this.fetch(["webhooks", "events"]);
+  }
+
+  /**
+   * List the most recent webhook deliveries.
+   *
+   * @param {string | null} webhook only list deliveries to the given webhook
+   * @param {number} limit the number of deliveries to list
+   */
+  webhookDeliveries(webhook, limit) {
+    let query = `?limit=${limit}`;
+
+    if (!!webhook) {
+      query = `${query}&webhook=${encodeURIComponent(webhook)}`;
+    }
+
+    return this.fetch(`webhooks/deliveries${query}`);
+  }
+
+  /**
+   * Create or update a webhook.
+   *
+   * @param {string} name name of the webhook
+   * @param {object} webhook the url, secret, events, and enabled state of the webhook
+   */
+  webhooksEdit(name, webhook) {
+    return this.fetch(["webhooks", name], {
+      method: "PUT",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(webhook),
+    });
+  }
+
+  /**
+   * Delete the given webhook.
+   *
+   * @param {string} name name of the webhook
+   */
+  webhooksDelete(name) {
+    return this.fetch(["webhooks", name], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List all settings profiles.
+   */
+  profiles() {
+    return this.fetch(["profiles"]);
+  }
+
+  /**
+   * Store the current value of the given settings as a profile.
+   *
+   * @param {string} name name of the profile
+   * @param {array} keys settings keys or prefixes to store in the profile
+   */
+  profilesEdit(name, keys) {
+    return this.fetch(["profiles", name], {
+      method: "PUT",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({keys}),
+    });
+  }
+
+  /**
+   * Switch to the given settings profile.
+   *
+   * @param {string} name name of the profile
+   */
+  profilesApply(name) {
+    return this.fetch(["profiles", name, "apply"], {
+      method: "POST",
+    });
+  }
+
+  /**
+   * Delete the given settings profile.
+   *
+   * @param {string} name name of the profile
+   */
+  profilesDelete(name) {
+    return this.fetch(["profiles", name], {
+      method: "DELETE",
+    });
+  }
+
   /**
    * Export balances.
    */
@@ -181,6 +365,22 @@ export class Api {
     });
   }
 
+  /**
+   * Import balances from CSV exported by another bot.
+   *
+   * @param {string} channel channel to import balances for
+   * @param {object} request the CSV data, column mapping and merge strategy
+   */
+  importBalancesCsv(channel, request) {
+    return this.fetch(["currency", channel, "import", "csv"], {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(request),
+    });
+  }
+
   /**
    * Get information on the current user.
    */
@@ -271,6 +471,298 @@ export class Api {
     });
   }
 
+  /**
+   * List all counters from a channel.
+   */
+  counters(channel) {
+    return this.fetch(["counters", channel]);
+  }
+
+  /**
+   * Delete the given counter.
+   *
+   * @param {string} channel channel the counter belongs to
+   * @param {string} name name of the counter to delete
+   */
+  countersDelete(channel, name) {
+    return this.fetch(["counters", channel, name], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List all timers from a channel.
+   */
+  timers(channel) {
+    return this.fetch(["timers", channel]);
+  }
+
+  /**
+   * Start a timer, replacing any existing timer with the same name.
+   *
+   * @param {string} channel channel the timer belongs to
+   * @param {string} name name of the timer to start
+   * @param {object} timer the kind, duration and template of the timer
+   */
+  timersStart(channel, name, timer) {
+    return this.fetch(["timers", channel, name], {
+      method: "PUT",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(timer),
+    });
+  }
+
+  /**
+   * Pause the given timer.
+   *
+   * @param {string} channel channel the timer belongs to
+   * @param {string} name name of the timer to pause
+   */
+  timersPause(channel, name) {
+    return this.fetch(["timers", channel, name, "pause"], {
+      method: "POST",
+    });
+  }
+
+  /**
+   * Resume the given timer.
+   *
+   * @param {string} channel channel the timer belongs to
+   * @param {string} name name of the timer to resume
+   */
+  timersResume(channel, name) {
+    return this.fetch(["timers", channel, name, "resume"], {
+      method: "POST",
+    });
+  }
+
+  /**
+   * Reset the given timer.
+   *
+   * @param {string} channel channel the timer belongs to
+   * @param {string} name name of the timer to reset
+   */
+  timersReset(channel, name) {
+    return this.fetch(["timers", channel, name, "reset"], {
+      method: "POST",
+    });
+  }
+
+  /**
+   * Delete the given timer.
+   *
+   * @param {string} channel channel the timer belongs to
+   * @param {string} name name of the timer to delete
+   */
+  timersDelete(channel, name) {
+    return this.fetch(["timers", channel, name], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List all giveaways from a channel.
+   */
+  giveaways(channel) {
+    return this.fetch(["giveaways", channel]);
+  }
+
+  /**
+   * List all entrants of a giveaway.
+   *
+   * @param {string} channel channel the giveaway belongs to
+   * @param {number} id id of the giveaway
+   */
+  giveawayEntrants(channel, id) {
+    return this.fetch(["giveaways", channel, id, "entrants"]);
+  }
+
+  /**
+   * List all polls from a channel.
+   */
+  polls(channel) {
+    return this.fetch(["polls", channel]);
+  }
+
+  /**
+   * Get the results of a poll.
+   *
+   * @param {string} channel channel the poll belongs to
+   * @param {number} id id of the poll
+   */
+  pollResults(channel, id) {
+    return this.fetch(["polls", channel, id, "results"]);
+  }
+
+  /**
+   * List all bets from a channel.
+   */
+  bets(channel) {
+    return this.fetch(["bets", channel]);
+  }
+
+  /**
+   * List all wagers of a bet.
+   *
+   * @param {string} channel channel the bet belongs to
+   * @param {number} id id of the bet
+   */
+  betWagers(channel, id) {
+    return this.fetch(["bets", channel, id, "wagers"]);
+  }
+
+  /**
+   * List the latest currency transactions in a channel.
+   *
+   * @param {string} channel channel to list transactions for
+   * @param {string | null} user only list transactions for the given user
+   */
+  ledger(channel, user = null) {
+    let query = "";
+
+    if (!!user) {
+      query = `?user=${encodeURIComponent(user)}`;
+    }
+
+    return this.fetch(`${encodePath(["ledger", channel])}${query}`);
+  }
+
+  /**
+   * Reverse a currency transaction.
+   *
+   * @param {string} channel channel the transaction belongs to
+   * @param {number} id id of the transaction to reverse
+   */
+  ledgerReverse(channel, id) {
+    return this.fetch(["ledger", channel, id, "reverse"], {
+      method: "POST",
+    });
+  }
+
+  /**
+   * List all items in the shop of a channel.
+   */
+  shopItems(channel) {
+    return this.fetch(["shop", channel, "items"]);
+  }
+
+  /**
+   * Insert or update a shop item.
+   *
+   * @param {string} channel channel the item belongs to
+   * @param {string} name name of the item
+   * @param {object} item the item to store
+   */
+  shopItemsEdit(channel, name, item) {
+    return this.fetch(["shop", channel, "items", name], {
+      method: "PUT",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(item),
+    });
+  }
+
+  /**
+   * Delete the given shop item.
+   *
+   * @param {string} channel channel the item belongs to
+   * @param {string} name name of the item to delete
+   */
+  shopItemsDelete(channel, name) {
+    return this.fetch(["shop", channel, "items", name], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List shop purchases waiting to be fulfilled.
+   */
+  shopPending(channel) {
+    return this.fetch(`${encodePath(["shop", channel, "purchases"])}?pending=true`);
+  }
+
+  /**
+   * Mark a shop purchase as fulfilled.
+   *
+   * @param {string} channel channel the purchase belongs to
+   * @param {number} id id of the purchase
+   */
+  shopFulfill(channel, id) {
+    return this.fetch(["shop", channel, "purchases", id, "fulfill"], {
+      method: "POST",
+    });
+  }
+
+  /**
+   * List the users with the most watch time in a channel.
+   *
+   * @param {string} channel channel to list watch time for
+   * @param {number} limit maximum number of users to list
+   */
+  watchTime(channel, limit) {
+    return this.fetch(`${encodePath(["watch-time", channel])}?limit=${limit}`);
+  }
+
+  /**
+   * List the actions mapped to channel point rewards.
+   *
+   * @param {string} channel channel to list actions for
+   */
+  redemptions(channel) {
+    return this.fetch(["redemptions", channel]);
+  }
+
+  /**
+   * Insert or update the action mapped to a channel point reward.
+   *
+   * @param {string} channel channel the reward belongs to
+   * @param {string} reward title or id of the reward
+   * @param {object} action the action to store
+   */
+  redemptionsEdit(channel, reward, action) {
+    return this.fetch(["redemptions", channel, reward], {
+      method: "PUT",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(action),
+    });
+  }
+
+  /**
+   * Delete the action mapped to a channel point reward.
+   *
+   * @param {string} channel channel the reward belongs to
+   * @param {string} reward title or id of the reward
+   */
+  redemptionsDelete(channel, reward) {
+    return this.fetch(["redemptions", channel, reward], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List all quotes from a channel.
+   */
+  quotes(channel) {
+    return this.fetch(["quotes", channel]);
+  }
+
+  /**
+   * Delete the given quote.
+   *
+   * @param {string} channel channel the quote belongs to
+   * @param {number} id id of the quote to delete
+   */
+  quotesDelete(channel, id) {
+    return this.fetch(["quotes", channel, id], {
+      method: "DELETE",
+    });
+  }
+
   /**
    * Get a list of all available scopes.
    */
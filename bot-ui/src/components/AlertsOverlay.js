@@ -0,0 +1,94 @@
+import Websocket from "react-websocket";
+import React from "react";
+import {alertAssetUrl, topicsUrl} from "../utils.js";
+
+export default class AlertsOverlay extends React.Component {
+  constructor(props) {
+    super(props);
+
+    this.timeout = null;
+
+    this.state = {
+      alert: null,
+    };
+  }
+
+  componentWillUnmount() {
+    if (this.timeout !== null) {
+      clearTimeout(this.timeout);
+    }
+  }
+
+  handleData(d) {
+    let data = null;
+
+    try {
+      data = JSON.parse(d);
+    } catch(e) {
+      console.log("failed to deserialize message");
+      return;
+    }
+
+    // NB: never replay alerts we missed.
+    if (data.type !== "message" || data.snapshot) {
+      return;
+    }
+
+    data = data.data;
+
+    if (data.type !== "alert") {
+      return;
+    }
+
+    this.show(data);
+  }
+
+  /**
+   * Show the given alert, replacing any alert currently being shown.
+   *
+   * @param {object} alert the alert to show
+   */
+  show(alert) {
+    if (this.timeout !== null) {
+      clearTimeout(this.timeout);
+    }
+
+    this.setState({alert});
+
+    if (!!alert.sound) {
+      let audio = new Audio(alertAssetUrl(alert.sound));
+      audio.play().catch(e => console.log(`failed to play alert sound: ${e}`));
+    }
+
+    this.timeout = setTimeout(() => {
+      this.timeout = null;
+      this.setState({alert: null});
+    }, alert.duration * 1000);
+  }
+
+  render() {
+    let alert = null;
+
+    if (this.state.alert !== null) {
+      let image = null;
+
+      if (!!this.state.alert.image) {
+        image = <img src={alertAssetUrl(this.state.alert.image)} />;
+      }
+
+      alert = (
+        <div key={this.state.alert.id} className={`alert-box alert-${this.state.alert.kind}`}>
+          {image}
+          <div className="alert-text">{this.state.alert.text}</div>
+        </div>
+      );
+    }
+
+    return (
+      <div id="alerts">
+        <Websocket url={topicsUrl(["alerts"])} onMessage={this.handleData.bind(this)} />
+        {alert}
+      </div>
+    );
+  }
+}
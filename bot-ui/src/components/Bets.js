@@ -0,0 +1,183 @@
+import React from "react";
+import {Button, Alert, Table} from "react-bootstrap";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+export default class Bets extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      data: null,
+      selected: null,
+      wagers: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of bets.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.bets(this.props.current.channel);
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request bets: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Show the wagers of the given bet.
+   *
+   * @param {number} id bet id to show wagers for
+   */
+  async showWagers(id) {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let wagers = await this.api.betWagers(this.props.current.channel, id);
+
+      this.setState({
+        loading: false,
+        error: null,
+        selected: id,
+        wagers,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request wagers: ${e}`,
+        selected: null,
+        wagers: null,
+      });
+    }
+  }
+
+  renderWagers() {
+    if (this.state.wagers === null) {
+      return null;
+    }
+
+    if (this.state.wagers.length === 0) {
+      return (
+        <Alert variant="info">
+          No wagers in bet #{this.state.selected}!
+        </Alert>
+      );
+    }
+
+    return <>
+      <h2>Wagers in bet #{this.state.selected}</h2>
+
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th className="table-fill">User</th>
+            <th>Placed</th>
+            <th>Outcome</th>
+            <th>Amount</th>
+            <th>Paid</th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.wagers.map(w => {
+            return (
+              <tr key={w.user}>
+                <td><a href={`https://twitch.tv/${w.user}`}>@{w.user}</a></td>
+                <td><span className="datetime">{w.placed_at}</span></td>
+                <td><code>{w.outcome}</code></td>
+                <td>{w.amount}</td>
+                <td>{w.paid ? "yes" : "no"}</td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    </>;
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Bets!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>#</th>
+                <th className="table-fill">Title</th>
+                <th>Opened</th>
+                <th>Locked</th>
+                <th>Resolved</th>
+                <th>Winner</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(b => {
+                return (
+                  <tr key={b.id}>
+                    <td>{b.id}</td>
+                    <td>{b.title}</td>
+                    <td><span className="datetime">{b.opened_at}</span></td>
+                    <td><span className="datetime">{b.locked_at}</span></td>
+                    <td><span className="datetime">{b.resolved_at}</span></td>
+                    <td>{b.cancelled ? <em>cancelled</em> : <code>{b.winner}</code>}</td>
+                    <td>
+                      <Button size="sm" className="action" onClick={() => this.showWagers(b.id)}>
+                        Wagers
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Bets</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["bet"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {content}
+      {this.renderWagers()}
+    </>;
+  }
+}
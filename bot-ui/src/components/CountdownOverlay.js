@@ -0,0 +1,62 @@
+import Websocket from "react-websocket";
+import React from "react";
+import {topicsUrl} from "../utils.js";
+
+export default class CountdownOverlay extends React.Component {
+  constructor(props) {
+    super(props);
+
+    let query = new URLSearchParams(props.location.search);
+
+    this.state = {
+      name: query.get("name"),
+      timers: [],
+    };
+  }
+
+  handleData(d) {
+    let data = null;
+
+    try {
+      data = JSON.parse(d);
+    } catch(e) {
+      console.log("failed to deserialize message");
+      return;
+    }
+
+    if (data.type !== "message") {
+      return;
+    }
+
+    data = data.data;
+
+    if (data.type !== "timers") {
+      return;
+    }
+
+    this.setState({timers: data.timers});
+  }
+
+  render() {
+    let timers = this.state.timers;
+
+    if (this.state.name !== null) {
+      timers = timers.filter(t => t.name === this.state.name);
+    }
+
+    return (
+      <div id="countdown">
+        <Websocket url={topicsUrl(["countdown"])} onMessage={this.handleData.bind(this)} />
+        {timers.map(t => {
+          let className = `timer timer-${t.kind}`;
+
+          if (!t.running) {
+            className += " timer-paused";
+          }
+
+          return <div key={t.name} className={className}>{t.text}</div>;
+        })}
+      </div>
+    );
+  }
+}
@@ -0,0 +1,120 @@
+import React from "react";
+import {Button, Alert, Table} from "react-bootstrap";
+import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+export default class Counters extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      data: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of counters.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.counters(this.props.current.channel);
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request counters: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Delete the given counter.
+   *
+   * @param {string} name name of the counter to delete
+   */
+  async delete(name) {
+    try {
+      await this.api.countersDelete(this.props.current.channel, name);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to delete counter: ${e}`,
+      });
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Counters!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th className="table-fill">Name</th>
+                <th>Total</th>
+                <th>Last Stream</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(c => {
+                return (
+                  <tr key={c.name}>
+                    <td><code>{c.name}</code></td>
+                    <td>{c.count}</td>
+                    <td>{c.stream_count}</td>
+                    <td>
+                      <Button size="sm" variant="danger" className="action" onClick={() => this.delete(c.name)}>
+                        <FontAwesomeIcon icon="trash" />
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Counters</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["counter"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {content}
+    </>;
+  }
+}
@@ -0,0 +1,181 @@
+import React from "react";
+import {Button, Alert, Table} from "react-bootstrap";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+export default class Giveaways extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      data: null,
+      selected: null,
+      entrants: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of giveaways.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.giveaways(this.props.current.channel);
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request giveaways: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Show the entrants of the given giveaway.
+   *
+   * @param {number} id giveaway id to show entrants for
+   */
+  async showEntrants(id) {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let entrants = await this.api.giveawayEntrants(this.props.current.channel, id);
+
+      this.setState({
+        loading: false,
+        error: null,
+        selected: id,
+        entrants,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request entrants: ${e}`,
+        selected: null,
+        entrants: null,
+      });
+    }
+  }
+
+  renderEntrants() {
+    if (this.state.entrants === null) {
+      return null;
+    }
+
+    if (this.state.entrants.length === 0) {
+      return (
+        <Alert variant="info">
+          No entrants in giveaway #{this.state.selected}!
+        </Alert>
+      );
+    }
+
+    return <>
+      <h2>Entrants of giveaway #{this.state.selected}</h2>
+
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th className="table-fill">User</th>
+            <th>Entered</th>
+            <th>Tickets</th>
+            <th>Subscriber</th>
+            <th>Drawn</th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.entrants.map(e => {
+            return (
+              <tr key={e.user}>
+                <td><a href={`https://twitch.tv/${e.user}`}>@{e.user}</a></td>
+                <td><span className="datetime">{e.entered_at}</span></td>
+                <td>{e.tickets}</td>
+                <td>{e.subscriber ? "yes" : "no"}</td>
+                <td>{e.drawn ? "yes" : "no"}</td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    </>;
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Giveaways!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>#</th>
+                <th className="table-fill">Keyword</th>
+                <th>Opened</th>
+                <th>Closed</th>
+                <th>Winner</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(g => {
+                return (
+                  <tr key={g.id}>
+                    <td>{g.id}</td>
+                    <td><code>{g.keyword}</code></td>
+                    <td><span className="datetime">{g.opened_at}</span></td>
+                    <td><span className="datetime">{g.closed_at}</span></td>
+                    <td>{g.winner}</td>
+                    <td>
+                      <Button size="sm" className="action" onClick={() => this.showEntrants(g.id)}>
+                        Entrants
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Giveaways</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["giveaway"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {content}
+      {this.renderEntrants()}
+    </>;
+  }
+}
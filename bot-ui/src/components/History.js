@@ -0,0 +1,178 @@
+import React from "react";
+import {Form, Button, Alert, Table, Row, Col} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+
+/**
+ * Number of changes to show.
+ */
+const LIMIT = 100;
+
+/**
+ * Render a value in the history.
+ *
+ * @param {any} value the value to render
+ */
+function renderValue(value) {
+  if (value === null) {
+    return <em>unset</em>;
+  }
+
+  return <code>{JSON.stringify(value)}</code>;
+}
+
+export default class History extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      success: null,
+      history: null,
+      count: 1,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the history.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let history = await this.api.settingsHistory(LIMIT);
+
+      this.setState({
+        loading: false,
+        error: null,
+        history,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request settings history: ${e}`,
+        history: null,
+      });
+    }
+  }
+
+  /**
+   * Undo the most recent changes.
+   */
+  async undo(e) {
+    e.preventDefault();
+
+    let count = parseInt(this.state.count, 10) || 1;
+
+    try {
+      let undone = await this.api.settingsUndo(count);
+
+      let success = undone.length === 0
+        ? "No changes to undo"
+        : `Undid ${undone.length} change(s): ${undone.map(entry => entry.key).join(", ")}`;
+
+      this.setState({
+        error: null,
+        success,
+      });
+
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to undo changes: ${e}`,
+        success: null,
+      });
+    }
+  }
+
+  renderHistory() {
+    if (!this.state.history) {
+      return null;
+    }
+
+    if (this.state.history.length === 0) {
+      return (
+        <Alert variant="info">
+          No settings have been changed!
+        </Alert>
+      );
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>When</th>
+            <th>Setting</th>
+            <th className="table-fill">Change</th>
+            <th>Source</th>
+            <th>User</th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.history.map(entry => {
+            return (
+              <tr key={entry.id} className={entry.undone ? "text-muted" : null}>
+                <td>{entry.changed_at}</td>
+                <td><code>{entry.key}</code></td>
+                <td>
+                  {renderValue(entry.before)} &rarr; {renderValue(entry.after)}
+                  {entry.undone ? <> (undone)</> : null}
+                </td>
+                <td>{entry.source}</td>
+                <td>{entry.user || <em>unknown</em>}</td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  renderForm() {
+    return (
+      <Form className="mt-4 mb-4" onSubmit={e => this.undo(e)}>
+        <Row>
+          <Col xs="2">
+            <Form.Control type="number" min="1" value={this.state.count} onChange={e => this.setState({count: e.target.value})} />
+          </Col>
+          <Col xs="auto">
+            <Button variant="danger" type="submit">Undo</Button>
+          </Col>
+        </Row>
+        <Form.Text>
+          Undo the given number of most recent changes which haven't already been undone.
+        </Form.Text>
+      </Form>
+    );
+  }
+
+  render() {
+    let success = null;
+
+    if (!!this.state.success) {
+      success = <Alert variant="info">{this.state.success}</Alert>;
+    }
+
+    return <>
+      <p>
+        Every change to a setting is recorded here, except for secret settings.
+        Changes can also be undone with <code>!admin undo [count]</code> in chat.
+      </p>
+
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+      {success}
+
+      {this.renderForm()}
+      {this.renderHistory()}
+    </>;
+  }
+}
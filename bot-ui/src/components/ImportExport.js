@@ -23,12 +23,20 @@ export default class ImportExport extends React.Component {
             <Nav.Link as={Link} active={path === "/import-export/drangrybot"} to="/import-export/drangrybot">
               DrangryBot
             </Nav.Link>
+            <Nav.Link as={Link} active={path === "/import-export/csv"} to="/import-export/csv">
+              CSV
+            </Nav.Link>
+            <Nav.Link as={Link} active={path === "/import-export/settings"} to="/import-export/settings">
+              Settings
+            </Nav.Link>
           </Nav>
         </Col>
         <Col>
           <Route path="/import-export" exact render={props => <Index {...props} />} />
           <Route path="/import-export/phantombot" render={props => <PhantomBot api={this.props.api} {...props} />} />
           <Route path="/import-export/drangrybot" render={props => <DrangryBot api={this.props.api} {...props} />} />
+          <Route path="/import-export/csv" render={props => <CsvImport api={this.props.api} {...props} />} />
+          <Route path="/import-export/settings" render={props => <SettingsImportExport api={this.props.api} {...props} />} />
         </Col>
       </Row>
     );
@@ -550,6 +558,432 @@ class PhantomBotImportCsvForm extends React.Component {
   }
 }
 
+class CsvImport extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      success: null,
+      report: null,
+      channel: "",
+      text: "",
+      user: "user",
+      balance: "balance",
+      watchTime: "",
+      watchTimeUnit: "seconds",
+      delimiter: ",",
+      strategy: "replace",
+    };
+  }
+
+  async import(e, dryRun) {
+    e.preventDefault();
+
+    if (!this.state.channel) {
+      this.setState({
+        error: "Channel must be specified",
+        success: null,
+      });
+
+      return;
+    }
+
+    this.setState({
+      loading: true,
+    });
+
+    let request = {
+      data: this.state.text,
+      mapping: {
+        user: this.state.user,
+        balance: this.state.balance,
+        watch_time: this.state.watchTime || null,
+      },
+      strategy: this.state.strategy,
+      delimiter: this.state.delimiter || null,
+      watch_time_unit: this.state.watchTimeUnit,
+      dry_run: dryRun,
+    };
+
+    try {
+      let report = await this.api.importBalancesCsv(this.state.channel, request);
+
+      let success = null;
+
+      if (!dryRun) {
+        success = `Successfully imported ${report.changes.length} balance(s)!`;
+      }
+
+      this.setState({
+        loading: false,
+        error: null,
+        success,
+        report,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `Failed to import balances: ${e}`,
+        success: null,
+        report: null,
+      });
+    }
+  }
+
+  handleChange(key, e) {
+    this.setState({
+      [key]: e.target.value
+    });
+  }
+
+  renderReport() {
+    let report = this.state.report;
+
+    if (!report) {
+      return null;
+    }
+
+    let errors = null;
+
+    if (report.errors.length > 0) {
+      errors = (
+        <Alert variant="warning">
+          <b>{report.errors.length} row(s) could not be imported:</b>
+          <ul>
+            {report.errors.map((e, i) => <li key={i}>Line {e.line}: {e.message}</li>)}
+          </ul>
+        </Alert>
+      );
+    }
+
+    let title = report.dry_run ? "Preview" : "Imported";
+
+    return (
+      <div className="mt-3">
+        {errors}
+
+        <h4>{title}</h4>
+
+        <table className="table table-sm">
+          <thead>
+            <tr>
+              <th>User</th>
+              <th>Before</th>
+              <th>After</th>
+              <th>Watch Time</th>
+            </tr>
+          </thead>
+          <tbody>
+            {report.changes.map(c => (
+              <tr key={c.user}>
+                <td>{c.user}</td>
+                <td>{c.before === null ? <em>none</em> : c.before}</td>
+                <td>{c.amount}</td>
+                <td>{utils.formatSeconds(c.watch_time)}</td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      </div>
+    );
+  }
+
+  render() {
+    var message = null;
+
+    if (!!this.state.success) {
+      message = <Alert variant="info">{this.state.success}</Alert>;
+    }
+
+    if (!!this.state.error) {
+      message = <Alert variant="danger">{this.state.error}</Alert>;
+    }
+
+    return (
+      <div>
+        <h2>CSV</h2>
+
+        <p>
+          Import balances from a CSV file exported by another bot.
+          The first line of the file must name the columns.
+        </p>
+
+        {message}
+
+        <Form onSubmit={e => this.import(e, false)} disabled={this.state.loading}>
+          <Form.Group id="channel">
+            <Form.Label>Channel</Form.Label>
+            <Form.Control
+              disabled={this.state.loading}
+              value={this.state.channel}
+              onChange={e => this.handleChange("channel", e)}
+              placeholder="#setbac" />
+            <Form.Text>
+              Name of channel to import balances for. Like <b>#setbac</b>.
+            </Form.Text>
+          </Form.Group>
+
+          <Row>
+            <Col>
+              <Form.Group id="user-column">
+                <Form.Label>User column</Form.Label>
+                <Form.Control
+                  disabled={this.state.loading}
+                  value={this.state.user}
+                  onChange={e => this.handleChange("user", e)} />
+              </Form.Group>
+            </Col>
+            <Col>
+              <Form.Group id="balance-column">
+                <Form.Label>Balance column</Form.Label>
+                <Form.Control
+                  disabled={this.state.loading}
+                  value={this.state.balance}
+                  onChange={e => this.handleChange("balance", e)} />
+              </Form.Group>
+            </Col>
+            <Col>
+              <Form.Group id="watch-time-column">
+                <Form.Label>Watch time column</Form.Label>
+                <Form.Control
+                  disabled={this.state.loading}
+                  value={this.state.watchTime}
+                  onChange={e => this.handleChange("watchTime", e)}
+                  placeholder="optional" />
+              </Form.Group>
+            </Col>
+            <Col>
+              <Form.Group id="watch-time-unit">
+                <Form.Label>Watch time unit</Form.Label>
+                <Form.Control as="select"
+                  disabled={this.state.loading}
+                  value={this.state.watchTimeUnit}
+                  onChange={e => this.handleChange("watchTimeUnit", e)}>
+                  <option value="seconds">Seconds</option>
+                  <option value="minutes">Minutes</option>
+                  <option value="hours">Hours</option>
+                </Form.Control>
+              </Form.Group>
+            </Col>
+          </Row>
+
+          <Row>
+            <Col>
+              <Form.Group id="delimiter">
+                <Form.Label>Delimiter</Form.Label>
+                <Form.Control
+                  disabled={this.state.loading}
+                  value={this.state.delimiter}
+                  maxLength="1"
+                  onChange={e => this.handleChange("delimiter", e)} />
+              </Form.Group>
+            </Col>
+            <Col>
+              <Form.Group id="strategy">
+                <Form.Label>Merge strategy</Form.Label>
+                <Form.Control as="select"
+                  disabled={this.state.loading}
+                  value={this.state.strategy}
+                  onChange={e => this.handleChange("strategy", e)}>
+                  <option value="replace">Replace existing balances</option>
+                  <option value="add">Add to existing balances</option>
+                  <option value="max">Keep the largest balance</option>
+                </Form.Control>
+              </Form.Group>
+            </Col>
+          </Row>
+
+          <Form.Group id="content">
+            <Form.Control as="textarea" rows="10"
+              disabled={this.state.loading}
+              value={this.state.text}
+              onChange={e => this.handleChange("text", e)}
+              placeholder={"user,balance\nsetbac,1000"} />
+          </Form.Group>
+
+          <Button variant="secondary" className="mr-2" disabled={this.state.loading} onClick={e => this.import(e, true)}>
+            Preview
+          </Button>
+
+          <Button variant="primary" type="submit" disabled={this.state.loading}>
+            Import
+          </Button>
+
+          <Loading isLoading={this.state.loading} />
+        </Form>
+
+        {this.renderReport()}
+      </div>
+    );
+  }
+}
+
+class SettingsImportExport extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      success: null,
+      report: null,
+      text: "",
+    };
+  }
+
+  async import(e, dryRun) {
+    e.preventDefault();
+
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let report = await this.api.importSettings(this.state.text, dryRun);
+
+      let success = null;
+
+      if (report.applied) {
+        success = `Successfully imported ${report.changes.length} setting(s)!`;
+      }
+
+      this.setState({
+        loading: false,
+        error: null,
+        success,
+        report,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `Failed to import settings: ${e}`,
+        success: null,
+        report: null,
+      });
+    }
+  }
+
+  renderReport() {
+    let report = this.state.report;
+
+    if (!report) {
+      return null;
+    }
+
+    let errors = null;
+
+    if (report.errors.length > 0) {
+      errors = (
+        <Alert variant="warning">
+          <b>Nothing will be imported until these settings are fixed:</b>
+          <ul>
+            {report.errors.map(e => <li key={e.key}><code>{e.key}</code>: {e.message}</li>)}
+          </ul>
+        </Alert>
+      );
+    }
+
+    if (report.changes.length === 0) {
+      return <div className="mt-3">{errors}<Alert variant="info">No settings would change.</Alert></div>;
+    }
+
+    let value = v => v === null ? <em>default</em> : <code>{JSON.stringify(v)}</code>;
+
+    return (
+      <div className="mt-3">
+        {errors}
+
+        <h4>{report.applied ? "Changed" : "Changes"}</h4>
+
+        <table className="table table-sm">
+          <thead>
+            <tr>
+              <th>Setting</th>
+              <th>Before</th>
+              <th>After</th>
+            </tr>
+          </thead>
+          <tbody>
+            {report.changes.map(c => (
+              <tr key={c.key}>
+                <td><code>{c.key}</code></td>
+                <td>{value(c.before)}</td>
+                <td>{value(c.after)}</td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      </div>
+    );
+  }
+
+  render() {
+    var message = null;
+
+    if (!!this.state.success) {
+      message = <Alert variant="info">{this.state.success}</Alert>;
+    }
+
+    if (!!this.state.error) {
+      message = <Alert variant="danger">{this.state.error}</Alert>;
+    }
+
+    return (
+      <div>
+        <h2>Settings</h2>
+
+        <p>
+          Back up settings or move them to another machine.
+          Secret settings, like connection tokens, are never exported.
+        </p>
+
+        <h4>Export</h4>
+
+        <Button className="mr-2" href={utils.withToken(`${utils.apiUrl()}/settings/export?format=json`)} download="settings.json">
+          Export JSON
+        </Button>
+
+        <Button href={utils.withToken(`${utils.apiUrl()}/settings/export?format=yaml`)} download="settings.yaml">
+          Export YAML
+        </Button>
+
+        <h4 className="mt-4">Import</h4>
+
+        {message}
+
+        <Form onSubmit={e => this.import(e, false)} disabled={this.state.loading}>
+          <Form.Group id="content">
+            <Form.Control as="textarea" rows="10"
+              disabled={this.state.loading}
+              value={this.state.text}
+              onChange={e => this.setState({text: e.target.value})}
+              placeholder={"song/enabled: true\nsong/volume: 50"} />
+            <Form.Text>
+              Settings to import as JSON or YAML. Settings which are missing are left untouched.
+            </Form.Text>
+          </Form.Group>
+
+          <Button variant="secondary" className="mr-2" disabled={this.state.loading} onClick={e => this.import(e, true)}>
+            Preview
+          </Button>
+
+          <Button variant="primary" type="submit" disabled={this.state.loading}>
+            Import
+          </Button>
+
+          <Loading isLoading={this.state.loading} />
+        </Form>
+
+        {this.renderReport()}
+      </div>
+    );
+  }
+}
+
 function importCsv(text) {
   let out = [];
   let lines = text.split('\n');
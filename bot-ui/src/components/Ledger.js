@@ -0,0 +1,147 @@
+import React from "react";
+import {Form, Button, Alert, Table, InputGroup} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+
+export default class Ledger extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      user: "",
+      data: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of transactions.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.ledger(this.props.current.channel, this.state.user);
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request ledger: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Reverse the given transaction.
+   *
+   * @param {number} id transaction id to reverse
+   */
+  async reverse(id) {
+    try {
+      await this.api.ledgerReverse(this.props.current.channel, id);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to reverse transaction: ${e}`,
+      });
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Transactions!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>#</th>
+                <th>When</th>
+                <th className="table-fill">User</th>
+                <th>Amount</th>
+                <th>Reason</th>
+                <th>Counterparty</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(e => {
+                let action = null;
+
+                if (e.reversed) {
+                  action = <em>reversed</em>;
+                } else if (e.reverses === null) {
+                  action = (
+                    <Button size="sm" variant="danger" className="action" onClick={() => this.reverse(e.transaction_id)}>
+                      Reverse
+                    </Button>
+                  );
+                } else {
+                  action = <em>reverses #{e.reverses}</em>;
+                }
+
+                return (
+                  <tr key={e.id}>
+                    <td>{e.transaction_id}</td>
+                    <td><span className="datetime">{e.created_at}</span></td>
+                    <td>{e.user}</td>
+                    <td>{e.amount}</td>
+                    <td><code>{e.reason}</code></td>
+                    <td>{e.counterparty}</td>
+                    <td>{action}</td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    let search = e => {
+      e.preventDefault();
+      this.list();
+    };
+
+    let filter = (
+      <Form className="mt-4 mb-4" onSubmit={search}>
+        <InputGroup>
+          <Form.Control value={this.state.user} placeholder="Filter by user" onChange={e => this.setState({user: e.target.value})}></Form.Control>
+          <InputGroup.Append>
+            <Button variant="primary" type="submit">Search</Button>
+          </InputGroup.Append>
+        </InputGroup>
+      </Form>
+    );
+
+    return <>
+      <h1 className='oxi-page-title'>Currency Ledger</h1>
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+
+      {filter}
+      {content}
+    </>;
+  }
+}
@@ -1,6 +1,6 @@
 import Websocket from "react-websocket";
 import React from "react";
-import {formatDuration, percentage, pickArtist, pickAlbumArt, websocketUrl} from "../utils.js";
+import {formatDuration, percentage, pickArtist, pickAlbumArt, topicsUrl} from "../utils.js";
 import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
 
 /**
@@ -134,11 +134,120 @@ class CurrentSong extends React.Component {
   }
 }
 
+/// Number of queued songs to show, unless overridden with `?up-next=<count>`.
+const DEFAULT_UP_NEXT_LIMIT = 3;
+
+/**
+ * Parse the number of queued songs to show.
+ *
+ * @param {string | null} value the value of the `up-next` query parameter
+ */
+function upNextLimit(value) {
+  let limit = parseInt(value, 10);
+
+  if (isNaN(limit) || limit < 0) {
+    return DEFAULT_UP_NEXT_LIMIT;
+  }
+
+  return limit;
+}
+
+class UpNext extends React.Component {
+  constructor(props) {
+    super(props);
+  }
+
+  render() {
+    let items = this.props.items.slice(0, this.props.limit);
+
+    if (items.length === 0) {
+      return null;
+    }
+
+    return (
+      <div id="up-next">
+        <div className="up-next-title">Up next</div>
+
+        {items.map((item, index) => {
+          let image = null;
+
+          if (!!item.image_url) {
+            image = <img className="up-next-art" src={item.image_url} />;
+          }
+
+          let requestBy = null;
+
+          if (!!item.user) {
+            requestBy = <span className="up-next-user">{item.user}</span>;
+          }
+
+          return (
+            <div key={index} className="up-next-item">
+              {image}
+              <span className="up-next-name">{item.name}</span>
+              {!!item.artists ? <span className="up-next-artists">{item.artists}</span> : null}
+              {requestBy}
+            </div>
+          );
+        })}
+      </div>
+    );
+  }
+}
+
+class CurrentPoll extends React.Component {
+  constructor(props) {
+    super(props);
+  }
+
+  render() {
+    let total = this.props.options.reduce((sum, o) => sum + o.votes, 0);
+
+    let classes = "poll";
+
+    if (this.props.closed) {
+      classes += " poll-closed";
+    }
+
+    return (
+      <div id="current-poll" className={classes}>
+        <div className="poll-question">{this.props.question}</div>
+
+        {this.props.options.map(o => {
+          let p = total > 0 ? percentage(o.votes, total) : 0;
+
+          return (
+            <div key={o.key} className="poll-option">
+              <div className="poll-option-name">
+                {o.description || o.key}
+                <span className="poll-option-votes">{o.votes} ({p}%)</span>
+              </div>
+
+              <div className="progress">
+                <div
+                  className="progress-bar"
+                  role="progressbar"
+                  aria-valuenow={p}
+                  aria-valuemin="0"
+                  aria-valuemax="100"
+                  style={{width: `${p}%`}} />
+              </div>
+            </div>
+          );
+        })}
+      </div>
+    );
+  }
+}
+
 export default class Overlay extends React.Component {
   constructor(props) {
     super(props);
 
+    let query = new URLSearchParams(props.location.search);
+
     this.state = {
+      upNextLimit: upNextLimit(query.get("up-next")),
       artist: "Unknown",
       track: null,
       requestBy: null,
@@ -146,6 +255,8 @@ export default class Overlay extends React.Component {
       elapsed: 0,
       duration: 0,
       isPlaying: false,
+      queue: [],
+      poll: null,
     };
   }
 
@@ -159,6 +270,12 @@ export default class Overlay extends React.Component {
       return;
     }
 
+    if (data.type !== "message") {
+      return;
+    }
+
+    data = data.data;
+
     switch (data.type) {
       case "song/current":
         let update = {
@@ -202,6 +319,12 @@ export default class Overlay extends React.Component {
         }
 
         this.setState(update);
+        break;
+      case "song/queue":
+        this.setState({
+          queue: data.items,
+        });
+
         break;
       case "song/progress":
         this.setState({
@@ -209,6 +332,16 @@ export default class Overlay extends React.Component {
           duration: data.duration,
         });
 
+        break;
+      case "poll":
+        this.setState({
+          poll: {
+            question: data.question,
+            options: data.options,
+            closed: data.closed,
+          },
+        });
+
         break;
     }
   }
@@ -216,7 +349,7 @@ export default class Overlay extends React.Component {
   render() {
     return (
       <div id="overlay">
-        <Websocket url={websocketUrl("ws/overlay")} onMessage={this.handleData.bind(this)} />
+        <Websocket url={topicsUrl(["song", "polls"])} onMessage={this.handleData.bind(this)} />
 
         <CurrentSong
           artist={this.state.artist}
@@ -228,6 +361,16 @@ export default class Overlay extends React.Component {
           source={this.state.source}
           isPlaying={this.state.isPlaying}
         />
+
+        <UpNext items={this.state.queue} limit={this.state.upNextLimit} />
+
+        {this.state.poll !== null ? (
+          <CurrentPoll
+            question={this.state.poll.question}
+            options={this.state.poll.options}
+            closed={this.state.poll.closed}
+          />
+        ) : null}
       </div>
     );
   }
@@ -0,0 +1,174 @@
+import React from "react";
+import {Button, Alert, Table} from "react-bootstrap";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+import {percentage} from "../utils.js";
+
+export default class Polls extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      data: null,
+      selected: null,
+      results: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of polls.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.polls(this.props.current.channel);
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request polls: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Show the results of the given poll.
+   *
+   * @param {number} id poll id to show results for
+   */
+  async showResults(id) {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let results = await this.api.pollResults(this.props.current.channel, id);
+
+      this.setState({
+        loading: false,
+        error: null,
+        selected: id,
+        results,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request results: ${e}`,
+        selected: null,
+        results: null,
+      });
+    }
+  }
+
+  renderResults() {
+    if (this.state.results === null) {
+      return null;
+    }
+
+    let total = this.state.results.reduce((sum, r) => sum + r.votes, 0);
+
+    return <>
+      <h2>Results of poll #{this.state.selected}</h2>
+
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>Key</th>
+            <th className="table-fill">Description</th>
+            <th>Votes</th>
+            <th>%</th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.results.map(r => {
+            return (
+              <tr key={r.key}>
+                <td><code>{r.key}</code></td>
+                <td>{r.description}</td>
+                <td>{r.votes}</td>
+                <td>{total > 0 ? percentage(r.votes, total) : 0}%</td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    </>;
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Polls!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>#</th>
+                <th className="table-fill">Question</th>
+                <th>Created</th>
+                <th>Closes</th>
+                <th>Closed</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(p => {
+                return (
+                  <tr key={p.id}>
+                    <td>{p.id}</td>
+                    <td>{p.question}</td>
+                    <td><span className="datetime">{p.created_at}</span></td>
+                    <td><span className="datetime">{p.closes_at}</span></td>
+                    <td><span className="datetime">{p.closed_at}</span></td>
+                    <td>
+                      <Button size="sm" className="action" onClick={() => this.showResults(p.id)}>
+                        Results
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Polls</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["poll"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {content}
+      {this.renderResults()}
+    </>;
+  }
+}
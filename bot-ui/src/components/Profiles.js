@@ -0,0 +1,232 @@
+import React from "react";
+import {Form, Button, Alert, Table, Row, Col} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+
+const EMPTY_PROFILE = {
+  name: "",
+  keys: "",
+};
+
+export default class Profiles extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      success: null,
+      profiles: null,
+      edit: Object.assign({}, EMPTY_PROFILE),
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of profiles.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let profiles = await this.api.profiles();
+
+      this.setState({
+        loading: false,
+        error: null,
+        profiles,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request profiles: ${e}`,
+        profiles: null,
+      });
+    }
+  }
+
+  /**
+   * Snapshot the current settings into the profile being edited.
+   */
+  async save(e) {
+    e.preventDefault();
+
+    let edit = this.state.edit;
+
+    let keys = edit.keys
+      .split(/[\s,]+/)
+      .map(key => key.trim())
+      .filter(key => key !== "");
+
+    try {
+      await this.api.profilesEdit(edit.name, keys);
+
+      this.setState({
+        edit: Object.assign({}, EMPTY_PROFILE),
+        success: `Saved profile ${edit.name.toLowerCase()}`,
+      });
+
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to save profile: ${e}`,
+        success: null,
+      });
+    }
+  }
+
+  /**
+   * Start editing the given profile.
+   */
+  edit(profile) {
+    this.setState({
+      edit: {
+        name: profile.name,
+        keys: profile.settings.map(s => s.key).join("\n"),
+      },
+    });
+  }
+
+  /**
+   * Switch to the given profile.
+   *
+   * @param {string} name name of the profile
+   */
+  async apply(name) {
+    try {
+      await this.api.profilesApply(name);
+
+      this.setState({
+        error: null,
+        success: `Switched to profile ${name}`,
+      });
+    } catch(e) {
+      this.setState({
+        error: `failed to switch profile: ${e}`,
+        success: null,
+      });
+    }
+  }
+
+  /**
+   * Delete the given profile.
+   *
+   * @param {string} name name of the profile
+   */
+  async delete(name) {
+    try {
+      await this.api.profilesDelete(name);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to delete profile: ${e}`,
+        success: null,
+      });
+    }
+  }
+
+  renderProfiles() {
+    if (!this.state.profiles) {
+      return null;
+    }
+
+    if (this.state.profiles.length === 0) {
+      return (
+        <Alert variant="info">
+          No Profiles!
+        </Alert>
+      );
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>Name</th>
+            <th className="table-fill">Settings</th>
+            <th></th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.profiles.map(profile => {
+            return (
+              <tr key={profile.name}>
+                <td><code>{profile.name}</code></td>
+                <td>
+                  {profile.settings.map(s => (
+                    <div key={s.key}>
+                      <code>{s.key}</code> = <code>{s.value === null ? "default" : JSON.stringify(s.value)}</code>
+                    </div>
+                  ))}
+                </td>
+                <td>
+                  <Button size="sm" variant="success" className="action" onClick={() => this.apply(profile.name)}>
+                    Switch
+                  </Button>
+                  <Button size="sm" className="action" onClick={() => this.edit(profile)}>
+                    Edit
+                  </Button>
+                  <Button size="sm" variant="danger" className="action" onClick={() => this.delete(profile.name)}>
+                    Delete
+                  </Button>
+                </td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  renderForm() {
+    let edit = this.state.edit;
+    let set = (key, value) => this.setState({edit: Object.assign({}, edit, {[key]: value})});
+
+    return (
+      <Form className="mt-4 mb-4" onSubmit={e => this.save(e)}>
+        <Row>
+          <Col xs="3">
+            <Form.Control value={edit.name} placeholder="Name" onChange={e => set("name", e.target.value)} />
+          </Col>
+          <Col>
+            <Form.Control as="textarea" rows="3" value={edit.keys} placeholder={"song/enabled\nspeedrun"} onChange={e => set("keys", e.target.value)} />
+            <Form.Text>
+              Settings or prefixes of settings to store in the profile, one per line.
+              Saving stores the <b>current</b> value of every listed setting.
+            </Form.Text>
+          </Col>
+          <Col xs="auto">
+            <Button variant="primary" type="submit" disabled={!edit.name || !edit.keys}>Save</Button>
+          </Col>
+        </Row>
+      </Form>
+    );
+  }
+
+  render() {
+    let success = null;
+
+    if (!!this.state.success) {
+      success = <Alert variant="info">{this.state.success}</Alert>;
+    }
+
+    return <>
+      <p>
+        Profiles are named snapshots of a subset of settings which can be switched between with a single click,
+        or with <code>!admin profile &lt;name&gt;</code> in chat.
+      </p>
+
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+      {success}
+
+      {this.renderForm()}
+      {this.renderProfiles()}
+    </>;
+  }
+}
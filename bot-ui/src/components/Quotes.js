@@ -0,0 +1,126 @@
+import React from "react";
+import {Button, Alert, Table} from "react-bootstrap";
+import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+export default class Quotes extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      data: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of quotes.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.quotes(this.props.current.channel);
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request quotes: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Delete the given quote.
+   *
+   * @param {number} id quote id to delete
+   */
+  async delete(id) {
+    try {
+      await this.api.quotesDelete(this.props.current.channel, id);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to delete quote: ${e}`,
+      });
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Quotes!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>#</th>
+                <th>Author</th>
+                <th className="table-fill">Quote</th>
+                <th>Game</th>
+                <th>Added</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(q => {
+                return (
+                  <tr key={q.id}>
+                    <td>{q.id}</td>
+                    <td>{q.author}</td>
+                    <td><code>{q.text}</code></td>
+                    <td>{q.game}</td>
+                    <td>
+                      <span className="datetime">{q.added_at}</span> by <b>{q.added_by}</b>
+                    </td>
+                    <td>
+                      <Button size="sm" variant="danger" className="action" onClick={() => this.delete(q.id)}>
+                        <FontAwesomeIcon icon="trash" />
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Quotes</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["quote"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {content}
+    </>;
+  }
+}
@@ -0,0 +1,265 @@
+import React from "react";
+import {Form, Button, Alert, Table, Row, Col} from "react-bootstrap";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+const EMPTY_ACTION = {
+  reward: "",
+  action: "command",
+  argument: "",
+  amount: 0,
+  fulfill: true,
+  refund: true,
+  disabled: false,
+};
+
+/**
+ * Placeholders for the argument of each kind of action.
+ */
+const ARGUMENTS = {
+  command: "Command, like: !song skip",
+  gtav: "!gtav command, like: punish enemy 1",
+  theme: "Name of theme to play",
+};
+
+/**
+ * Describe the given action.
+ *
+ * @param {object} action the action to describe
+ */
+function describe(action) {
+  switch (action.action) {
+    case "command":
+      return <>Run <code>{action.argument}</code></>;
+    case "currency":
+      return <>Give {action.amount} currency</>;
+    case "gtav":
+      return <>Run <code>!gtav {action.argument}</code></>;
+    case "theme":
+      return <>Play theme <code>{action.argument}</code></>;
+    default:
+      return <em>unknown action <code>{action.action}</code></em>;
+  }
+}
+
+export default class Redemptions extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      actions: null,
+      edit: Object.assign({}, EMPTY_ACTION),
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of actions.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let actions = await this.api.redemptions(this.props.current.channel);
+
+      this.setState({
+        loading: false,
+        error: null,
+        actions,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request redemptions: ${e}`,
+        actions: null,
+      });
+    }
+  }
+
+  /**
+   * Save the action currently being edited.
+   */
+  async save(e) {
+    e.preventDefault();
+
+    let edit = this.state.edit;
+
+    let action = {
+      action: edit.action,
+      argument: edit.action === "currency" ? "" : edit.argument,
+      amount: edit.action === "currency" ? (parseInt(edit.amount, 10) || 0) : 0,
+      fulfill: edit.fulfill,
+      refund: edit.refund,
+      disabled: edit.disabled,
+    };
+
+    try {
+      await this.api.redemptionsEdit(this.props.current.channel, edit.reward, action);
+      this.setState({edit: Object.assign({}, EMPTY_ACTION)});
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to save redemption: ${e}`,
+      });
+    }
+  }
+
+  /**
+   * Start editing the given action.
+   */
+  edit(action) {
+    this.setState({
+      edit: {
+        reward: action.reward,
+        action: action.action,
+        argument: action.argument,
+        amount: action.amount,
+        fulfill: action.fulfill,
+        refund: action.refund,
+        disabled: action.disabled,
+      },
+    });
+  }
+
+  /**
+   * Delete the action for the given reward.
+   *
+   * @param {string} reward title or id of the reward
+   */
+  async delete(reward) {
+    try {
+      await this.api.redemptionsDelete(this.props.current.channel, reward);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to delete redemption: ${e}`,
+      });
+    }
+  }
+
+  renderActions() {
+    if (!this.state.actions) {
+      return null;
+    }
+
+    if (this.state.actions.length === 0) {
+      return (
+        <Alert variant="info">
+          No Redemptions!
+        </Alert>
+      );
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>Reward</th>
+            <th className="table-fill">Action</th>
+            <th>Fulfill</th>
+            <th>Refund</th>
+            <th></th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.actions.map(action => {
+            return (
+              <tr key={action.reward} className={action.disabled ? "text-muted" : null}>
+                <td><code>{action.reward}</code></td>
+                <td>{describe(action)}</td>
+                <td>{action.fulfill ? "yes" : "no"}</td>
+                <td>{action.refund ? "yes" : "no"}</td>
+                <td>
+                  <Button size="sm" className="action" onClick={() => this.edit(action)}>
+                    Edit
+                  </Button>
+                  <Button size="sm" variant="danger" className="action" onClick={() => this.delete(action.reward)}>
+                    Delete
+                  </Button>
+                </td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  renderForm() {
+    let edit = this.state.edit;
+    let set = (key, value) => this.setState({edit: Object.assign({}, edit, {[key]: value})});
+
+    let argument = null;
+
+    if (edit.action === "currency") {
+      argument = <Form.Control type="number" value={edit.amount} placeholder="Amount" onChange={e => set("amount", e.target.value)} />;
+    } else {
+      argument = <Form.Control value={edit.argument} placeholder={ARGUMENTS[edit.action]} onChange={e => set("argument", e.target.value)} />;
+    }
+
+    return (
+      <Form className="mt-4 mb-4" onSubmit={e => this.save(e)}>
+        <Row>
+          <Col>
+            <Form.Control value={edit.reward} placeholder="Reward title or id" onChange={e => set("reward", e.target.value)} />
+          </Col>
+          <Col xs="auto">
+            <Form.Control as="select" value={edit.action} onChange={e => set("action", e.target.value)}>
+              <option value="command">Run a command</option>
+              <option value="currency">Give currency</option>
+              <option value="gtav">ChaosMod command</option>
+              <option value="theme">Play a theme</option>
+            </Form.Control>
+          </Col>
+          <Col>
+            {argument}
+          </Col>
+        </Row>
+        <Row className="mt-2">
+          <Col xs="auto">
+            <Form.Check type="checkbox" label="Fulfill on success" checked={edit.fulfill} onChange={e => set("fulfill", e.target.checked)} />
+          </Col>
+          <Col xs="auto">
+            <Form.Check type="checkbox" label="Refund on failure" checked={edit.refund} onChange={e => set("refund", e.target.checked)} />
+          </Col>
+          <Col xs="auto">
+            <Form.Check type="checkbox" label="Disabled" checked={edit.disabled} onChange={e => set("disabled", e.target.checked)} />
+          </Col>
+          <Col xs="auto">
+            <Button variant="primary" type="submit" disabled={!edit.reward}>Save</Button>
+          </Col>
+        </Row>
+        <Form.Text>
+          Commands are templates which can use <code>{"{{user}}"}</code>, <code>{"{{name}}"}</code>,
+          and <code>{"{{input}}"}</code> for the text entered when redeeming.
+          ChaosMod commands are subject to the same settings and cooldowns as <code>!gtav</code>.
+          Only rewards which are created by OxidizeBot's client id can be fulfilled or refunded.
+        </Form.Text>
+      </Form>
+    );
+  }
+
+  render() {
+    return <>
+      <h1 className='oxi-page-title'>Redemptions</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["redemptions"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {this.renderForm()}
+      {this.renderActions()}
+    </>;
+  }
+}
@@ -0,0 +1,296 @@
+import React from "react";
+import {Form, Button, Alert, Table, Row, Col} from "react-bootstrap";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+const EMPTY_ITEM = {
+  name: "",
+  description: "",
+  price: 0,
+  stock: "",
+  cooldown: 0,
+  command: "",
+  disabled: false,
+};
+
+export default class Shop extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      items: null,
+      pending: null,
+      edit: Object.assign({}, EMPTY_ITEM),
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of items and pending purchases.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let channel = this.props.current.channel;
+      let [items, pending] = await Promise.all([
+        this.api.shopItems(channel),
+        this.api.shopPending(channel),
+      ]);
+
+      this.setState({
+        loading: false,
+        error: null,
+        items,
+        pending,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request shop: ${e}`,
+        items: null,
+        pending: null,
+      });
+    }
+  }
+
+  /**
+   * Save the item currently being edited.
+   */
+  async save(e) {
+    e.preventDefault();
+
+    let edit = this.state.edit;
+
+    let item = {
+      description: edit.description || null,
+      price: parseInt(edit.price, 10) || 0,
+      stock: edit.stock === "" ? null : parseInt(edit.stock, 10),
+      cooldown: parseInt(edit.cooldown, 10) || 0,
+      command: edit.command || null,
+      disabled: edit.disabled,
+    };
+
+    try {
+      await this.api.shopItemsEdit(this.props.current.channel, edit.name, item);
+      this.setState({edit: Object.assign({}, EMPTY_ITEM)});
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to save item: ${e}`,
+      });
+    }
+  }
+
+  /**
+   * Start editing the given item.
+   */
+  edit(item) {
+    this.setState({
+      edit: {
+        name: item.name,
+        description: item.description || "",
+        price: item.price,
+        stock: item.stock === null ? "" : item.stock,
+        cooldown: item.cooldown,
+        command: item.command || "",
+        disabled: item.disabled,
+      },
+    });
+  }
+
+  /**
+   * Delete the given item.
+   *
+   * @param {string} name name of the item to delete
+   */
+  async delete(name) {
+    try {
+      await this.api.shopItemsDelete(this.props.current.channel, name);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to delete item: ${e}`,
+      });
+    }
+  }
+
+  /**
+   * Mark the given purchase as fulfilled.
+   *
+   * @param {number} id id of the purchase
+   */
+  async fulfill(id) {
+    try {
+      await this.api.shopFulfill(this.props.current.channel, id);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to fulfill purchase: ${e}`,
+      });
+    }
+  }
+
+  renderItems() {
+    if (!this.state.items) {
+      return null;
+    }
+
+    if (this.state.items.length === 0) {
+      return (
+        <Alert variant="info">
+          No Items!
+        </Alert>
+      );
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>Name</th>
+            <th className="table-fill">Description</th>
+            <th>Price</th>
+            <th>Stock</th>
+            <th>Cooldown</th>
+            <th>Command</th>
+            <th></th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.items.map(item => {
+            return (
+              <tr key={item.name} className={item.disabled ? "text-muted" : null}>
+                <td><code>{item.name}</code></td>
+                <td>{item.description}</td>
+                <td>{item.price}</td>
+                <td>{item.stock === null ? "unlimited" : item.stock}</td>
+                <td>{item.cooldown}s</td>
+                <td><code>{item.command}</code></td>
+                <td>
+                  <Button size="sm" className="action" onClick={() => this.edit(item)}>
+                    Edit
+                  </Button>
+                  <Button size="sm" variant="danger" className="action" onClick={() => this.delete(item.name)}>
+                    Delete
+                  </Button>
+                </td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  renderPending() {
+    if (!this.state.pending) {
+      return null;
+    }
+
+    if (this.state.pending.length === 0) {
+      return (
+        <Alert variant="info">
+          No purchases waiting to be fulfilled!
+        </Alert>
+      );
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>#</th>
+            <th>When</th>
+            <th>Item</th>
+            <th className="table-fill">User</th>
+            <th></th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.pending.map(p => {
+            return (
+              <tr key={p.id}>
+                <td>{p.id}</td>
+                <td><span className="datetime">{p.purchased_at}</span></td>
+                <td><code>{p.item}</code></td>
+                <td>{p.user}</td>
+                <td>
+                  <Button size="sm" variant="success" className="action" onClick={() => this.fulfill(p.id)}>
+                    Fulfill
+                  </Button>
+                </td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  renderForm() {
+    let edit = this.state.edit;
+    let set = (key, value) => this.setState({edit: Object.assign({}, edit, {[key]: value})});
+
+    return (
+      <Form className="mt-4 mb-4" onSubmit={e => this.save(e)}>
+        <Row>
+          <Col>
+            <Form.Control value={edit.name} placeholder="Name" onChange={e => set("name", e.target.value)} />
+          </Col>
+          <Col>
+            <Form.Control type="number" value={edit.price} placeholder="Price" onChange={e => set("price", e.target.value)} />
+          </Col>
+          <Col>
+            <Form.Control type="number" value={edit.stock} placeholder="Stock (empty for unlimited)" onChange={e => set("stock", e.target.value)} />
+          </Col>
+          <Col>
+            <Form.Control type="number" value={edit.cooldown} placeholder="Cooldown (seconds)" onChange={e => set("cooldown", e.target.value)} />
+          </Col>
+        </Row>
+        <Row className="mt-2">
+          <Col>
+            <Form.Control value={edit.description} placeholder="Description" onChange={e => set("description", e.target.value)} />
+          </Col>
+          <Col>
+            <Form.Control value={edit.command} placeholder="Command, like: !song skip (optional)" onChange={e => set("command", e.target.value)} />
+          </Col>
+          <Col xs="auto">
+            <Form.Check type="checkbox" label="Disabled" checked={edit.disabled} onChange={e => set("disabled", e.target.checked)} />
+          </Col>
+          <Col xs="auto">
+            <Button variant="primary" type="submit" disabled={!edit.name}>Save</Button>
+          </Col>
+        </Row>
+      </Form>
+    );
+  }
+
+  render() {
+    return <>
+      <h1 className='oxi-page-title'>Shop</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["shop"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      <h2>Fulfilment Queue</h2>
+      {this.renderPending()}
+
+      <h2>Items</h2>
+      {this.renderForm()}
+      {this.renderItems()}
+    </>;
+  }
+}
@@ -0,0 +1,233 @@
+import React from "react";
+import {Form, Button, Alert, Table, Row, Col} from "react-bootstrap";
+import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+const KINDS = ["countdown", "stopwatch", "break"];
+
+const EMPTY_TIMER = {
+  name: "",
+  kind: "countdown",
+  duration: "",
+  template: "",
+};
+
+/**
+ * Format the given number of seconds like a clock.
+ *
+ * @param {number} seconds the number of seconds to format
+ */
+function digital(seconds) {
+  let pad = n => String(n).padStart(2, "0");
+
+  let hours = Math.floor(seconds / 3600);
+  let minutes = Math.floor((seconds % 3600) / 60);
+  let parts = [pad(minutes), pad(seconds % 60)];
+
+  if (hours > 0) {
+    parts.unshift(pad(hours));
+  }
+
+  return parts.join(":");
+}
+
+export default class Timers extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      data: null,
+      edit: Object.assign({}, EMPTY_TIMER),
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of timers.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.timers(this.props.current.channel);
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request timers: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Start the timer currently being edited.
+   */
+  async start(e) {
+    e.preventDefault();
+
+    let edit = this.state.edit;
+    let timer = {kind: edit.kind};
+
+    if (edit.kind !== "stopwatch") {
+      timer.duration = edit.duration;
+    }
+
+    if (!!edit.template) {
+      timer.template = edit.template;
+    }
+
+    try {
+      await this.api.timersStart(this.props.current.channel, edit.name, timer);
+      this.setState({edit: Object.assign({}, EMPTY_TIMER)});
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to start timer: ${e}`,
+      });
+    }
+  }
+
+  /**
+   * Run the given action on a timer and refresh the list.
+   *
+   * @param {string} what description of the action
+   * @param {function} action the action to run
+   */
+  async action(what, action) {
+    try {
+      await action(this.props.current.channel);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to ${what} timer: ${e}`,
+      });
+    }
+  }
+
+  renderForm() {
+    let edit = this.state.edit;
+    let set = (key, value) => this.setState({edit: Object.assign({}, edit, {[key]: value})});
+    let needsDuration = edit.kind !== "stopwatch";
+
+    return (
+      <Form className="mt-4 mb-4" onSubmit={e => this.start(e)}>
+        <Row>
+          <Col xs="auto">
+            <Form.Control value={edit.name} placeholder="Name, like: countdown" onChange={e => set("name", e.target.value)} />
+          </Col>
+          <Col xs="auto">
+            <Form.Control as="select" value={edit.kind} onChange={e => set("kind", e.target.value)}>
+              {KINDS.map(kind => <option key={kind} value={kind}>{kind}</option>)}
+            </Form.Control>
+          </Col>
+          <Col xs="auto">
+            <Form.Control value={edit.duration} placeholder="Duration, like: 5m" disabled={!needsDuration} onChange={e => set("duration", e.target.value)} />
+          </Col>
+          <Col>
+            <Form.Control value={edit.template} placeholder="Template, like: Starting in {{remaining}}" onChange={e => set("template", e.target.value)} />
+          </Col>
+          <Col xs="auto">
+            <Button variant="primary" type="submit" disabled={!edit.name || (needsDuration && !edit.duration)}>Start</Button>
+          </Col>
+        </Row>
+      </Form>
+    );
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Timers!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>Name</th>
+                <th>Kind</th>
+                <th>Elapsed</th>
+                <th>Remaining</th>
+                <th className="table-fill">Template</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(t => {
+                let toggle = null;
+
+                if (t.running) {
+                  toggle = (
+                    <Button size="sm" variant="secondary" className="action" onClick={() => this.action("pause", c => this.api.timersPause(c, t.name))}>
+                      <FontAwesomeIcon icon="pause" />
+                    </Button>
+                  );
+                } else {
+                  toggle = (
+                    <Button size="sm" variant="secondary" className="action" onClick={() => this.action("resume", c => this.api.timersResume(c, t.name))}>
+                      <FontAwesomeIcon icon="play" />
+                    </Button>
+                  );
+                }
+
+                return (
+                  <tr key={t.name}>
+                    <td><code>{t.name}</code></td>
+                    <td>{t.kind}</td>
+                    <td>{digital(t.elapsed)}</td>
+                    <td>{t.remaining !== null ? digital(t.remaining) : null}</td>
+                    <td>{t.template !== null ? <code>{t.template}</code> : <em>default</em>}</td>
+                    <td>
+                      {toggle}
+                      <Button size="sm" variant="secondary" className="action" onClick={() => this.action("reset", c => this.api.timersReset(c, t.name))}>
+                        <FontAwesomeIcon icon="sync" />
+                      </Button>
+                      <Button size="sm" variant="danger" className="action" onClick={() => this.action("delete", c => this.api.timersDelete(c, t.name))}>
+                        <FontAwesomeIcon icon="trash" />
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Timers</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["countdown"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {this.renderForm()}
+      {content}
+    </>;
+  }
+}
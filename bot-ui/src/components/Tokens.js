@@ -0,0 +1,241 @@
+import React from "react";
+import {Form, Button, Alert, Table, Row, Col} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+import * as utils from "../utils.js";
+
+const EMPTY_TOKEN = {
+  name: "",
+  access: "read",
+};
+
+/**
+ * Build the URL of the overlay using the given token.
+ *
+ * @param {string} token the token to use
+ */
+function overlayUrl(token) {
+  let loc = window.location;
+  return `${loc.protocol}//${loc.host}/overlay?token=${encodeURIComponent(token)}`;
+}
+
+export default class Tokens extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      success: null,
+      tokens: null,
+      edit: Object.assign({}, EMPTY_TOKEN),
+      current: utils.token() || "",
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of tokens.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let tokens = await this.api.tokens();
+
+      this.setState({
+        loading: false,
+        error: null,
+        tokens,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request tokens: ${e}`,
+        tokens: null,
+      });
+    }
+  }
+
+  /**
+   * Generate the token currently being edited.
+   */
+  async create(e) {
+    e.preventDefault();
+
+    let edit = this.state.edit;
+
+    try {
+      await this.api.tokensCreate(edit.name, edit.access);
+
+      this.setState({
+        edit: Object.assign({}, EMPTY_TOKEN),
+        error: null,
+        success: `Created token ${edit.name.toLowerCase()}`,
+      });
+
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to create token: ${e}`,
+        success: null,
+      });
+    }
+  }
+
+  /**
+   * Delete the given token.
+   *
+   * @param {string} name name of the token
+   */
+  async delete(name) {
+    try {
+      await this.api.tokensDelete(name);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to delete token: ${e}`,
+        success: null,
+      });
+    }
+  }
+
+  /**
+   * Store the token used by this browser.
+   */
+  useToken(e) {
+    e.preventDefault();
+    utils.setToken(this.state.current);
+
+    this.setState({
+      error: null,
+      success: !!this.state.current ? "This browser now uses the given token" : "This browser no longer uses a token",
+    });
+  }
+
+  renderTokens() {
+    if (!this.state.tokens) {
+      return null;
+    }
+
+    if (this.state.tokens.length === 0) {
+      return (
+        <Alert variant="info">
+          No Tokens!
+        </Alert>
+      );
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>Name</th>
+            <th>Access</th>
+            <th className="table-fill">Token</th>
+            <th>Created</th>
+            <th></th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.tokens.map(token => {
+            let usage = null;
+
+            if (token.access === "read") {
+              usage = <div><small>Overlay: <code>{overlayUrl(token.token)}</code></small></div>;
+            }
+
+            return (
+              <tr key={token.name}>
+                <td><code>{token.name}</code></td>
+                <td>{token.access}</td>
+                <td>
+                  <code>{token.token}</code>
+                  {usage}
+                </td>
+                <td>{token.created_at}</td>
+                <td>
+                  <Button size="sm" variant="danger" className="action" onClick={() => this.delete(token.name)}>
+                    Delete
+                  </Button>
+                </td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  renderForm() {
+    let edit = this.state.edit;
+    let set = (key, value) => this.setState({edit: Object.assign({}, edit, {[key]: value})});
+
+    return (
+      <Form className="mt-4 mb-4" onSubmit={e => this.create(e)}>
+        <Row>
+          <Col>
+            <Form.Control value={edit.name} placeholder="Name, like: obs-overlay" onChange={e => set("name", e.target.value)} />
+          </Col>
+          <Col xs="auto">
+            <Form.Control as="select" value={edit.access} onChange={e => set("access", e.target.value)}>
+              <option value="read">Read-only (overlays)</option>
+              <option value="admin">Admin</option>
+            </Form.Control>
+          </Col>
+          <Col xs="auto">
+            <Button variant="primary" type="submit" disabled={!edit.name}>Generate</Button>
+          </Col>
+        </Row>
+      </Form>
+    );
+  }
+
+  renderCurrent() {
+    return (
+      <Form className="mt-4 mb-4" onSubmit={e => this.useToken(e)}>
+        <Row>
+          <Col>
+            <Form.Control value={this.state.current} placeholder="Admin token" onChange={e => this.setState({current: e.target.value})} />
+            <Form.Text>
+              The token used by the web UI in this browser.
+              Leave empty to stop using a token.
+            </Form.Text>
+          </Col>
+          <Col xs="auto">
+            <Button variant="primary" type="submit">Use in this browser</Button>
+          </Col>
+        </Row>
+      </Form>
+    );
+  }
+
+  render() {
+    let success = null;
+
+    if (!!this.state.success) {
+      success = <Alert variant="info">{this.state.success}</Alert>;
+    }
+
+    return <>
+      <p>
+        Tokens grant access to the web UI and overlays once <code>web/auth/enabled</code> is set.
+        Admin tokens can use the whole web UI, while read-only tokens can only be used by overlays by adding <code>?token=&lt;token&gt;</code> to their URL.
+        If <code>web/auth/allow-local</code> is enabled, the machine running the bot never needs a token.
+      </p>
+
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+      {success}
+
+      {this.renderCurrent()}
+      {this.renderForm()}
+      {this.renderTokens()}
+    </>;
+  }
+}
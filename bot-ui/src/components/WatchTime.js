@@ -0,0 +1,104 @@
+import React from "react";
+import {Alert, Table} from "react-bootstrap";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+import * as utils from "../utils.js";
+
+/**
+ * Number of users to show in the leaderboard.
+ */
+const LIMIT = 100;
+
+export default class WatchTime extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      top: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the leaderboard.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let top = await this.api.watchTime(this.props.current.channel, LIMIT);
+
+      this.setState({
+        loading: false,
+        error: null,
+        top,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request watch time: ${e}`,
+        top: null,
+      });
+    }
+  }
+
+  renderTop() {
+    if (!this.state.top) {
+      return null;
+    }
+
+    if (this.state.top.length === 0) {
+      return (
+        <Alert variant="info">
+          No watch time recorded yet!
+        </Alert>
+      );
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>#</th>
+            <th className="table-fill">User</th>
+            <th>Watch Time</th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.top.map((entry, index) => {
+            return (
+              <tr key={entry.user}>
+                <td>{index + 1}</td>
+                <td>{entry.user}</td>
+                <td>{utils.formatSeconds(entry.seconds)}</td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  render() {
+    return <>
+      <h1 className='oxi-page-title'>Watch Time</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["watch-time"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {this.renderTop()}
+    </>;
+  }
+}
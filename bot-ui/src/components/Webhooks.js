@@ -0,0 +1,351 @@
+import React from "react";
+import {Form, Button, Alert, Table, Row, Col} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+
+/// Number of deliveries to show.
+const DELIVERIES_LIMIT = 100;
+
+const EMPTY_WEBHOOK = {
+  name: "",
+  url: "",
+  secret: "",
+  events: [],
+  enabled: true,
+};
+
+/**
+ * Split the stored comma-separated list of events.
+ *
+ * @param {string} events the stored events
+ */
+function splitEvents(events) {
+  return events.split(",").map(e => e.trim()).filter(e => e !== "");
+}
+
+export default class Webhooks extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      success: null,
+      webhooks: null,
+      events: [],
+      deliveries: null,
+      filter: null,
+      edit: Object.assign({}, EMPTY_WEBHOOK),
+    };
+  }
+
+  async componentDidMount() {
+    try {
+      let events = await this.api.webhookEvents();
+      this.setState({events});
+    } catch(e) {
+      this.setState({error: `failed to request events: ${e}`});
+    }
+
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of webhooks and deliveries.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let [webhooks, deliveries] = await Promise.all([
+        this.api.webhooks(),
+        this.api.webhookDeliveries(this.state.filter, DELIVERIES_LIMIT),
+      ]);
+
+      this.setState({
+        loading: false,
+        error: null,
+        webhooks,
+        deliveries,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request webhooks: ${e}`,
+        webhooks: null,
+        deliveries: null,
+      });
+    }
+  }
+
+  /**
+   * Only show deliveries for the given webhook.
+   *
+   * @param {string | null} filter the webhook to show deliveries for
+   */
+  async filter(filter) {
+    this.setState({filter}, () => this.list());
+  }
+
+  /**
+   * Save the webhook currently being edited.
+   */
+  async save(e) {
+    e.preventDefault();
+
+    let edit = this.state.edit;
+
+    let webhook = {
+      url: edit.url,
+      events: edit.events,
+      enabled: edit.enabled,
+    };
+
+    if (!!edit.secret) {
+      webhook.secret = edit.secret;
+    }
+
+    try {
+      await this.api.webhooksEdit(edit.name, webhook);
+
+      this.setState({
+        edit: Object.assign({}, EMPTY_WEBHOOK),
+        error: null,
+        success: `Saved webhook ${edit.name.toLowerCase()}`,
+      });
+
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to save webhook: ${e}`,
+        success: null,
+      });
+    }
+  }
+
+  /**
+   * Delete the given webhook.
+   *
+   * @param {string} name name of the webhook
+   */
+  async delete(name) {
+    try {
+      await this.api.webhooksDelete(name);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to delete webhook: ${e}`,
+        success: null,
+      });
+    }
+  }
+
+  /**
+   * Start editing the given webhook.
+   *
+   * @param {object} webhook the webhook to edit
+   */
+  edit(webhook) {
+    this.setState({
+      edit: {
+        name: webhook.name,
+        url: webhook.url,
+        secret: "",
+        events: splitEvents(webhook.events),
+        enabled: webhook.enabled,
+      },
+    });
+  }
+
+  renderWebhooks() {
+    if (!this.state.webhooks) {
+      return null;
+    }
+
+    if (this.state.webhooks.length === 0) {
+      return (
+        <Alert variant="info">
+          No Webhooks!
+        </Alert>
+      );
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>Name</th>
+            <th className="table-fill">URL</th>
+            <th>Events</th>
+            <th>Secret</th>
+            <th>Enabled</th>
+            <th></th>
+          </tr>
+        </thead>
+        <tbody>
+          {this.state.webhooks.map(webhook => {
+            let events = splitEvents(webhook.events);
+
+            return (
+              <tr key={webhook.name}>
+                <td><code>{webhook.name}</code></td>
+                <td><code>{webhook.url}</code></td>
+                <td>{events.length === 0 ? <em>all</em> : events.map(e => <div key={e}><code>{e}</code></div>)}</td>
+                <td><code>{webhook.secret}</code></td>
+                <td>{webhook.enabled ? "yes" : "no"}</td>
+                <td>
+                  <Button size="sm" className="action" onClick={() => this.edit(webhook)}>
+                    Edit
+                  </Button>
+                  <Button size="sm" variant="secondary" className="action" onClick={() => this.filter(webhook.name)}>
+                    Deliveries
+                  </Button>
+                  <Button size="sm" variant="danger" className="action" onClick={() => this.delete(webhook.name)}>
+                    Delete
+                  </Button>
+                </td>
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  renderForm() {
+    let edit = this.state.edit;
+    let set = (key, value) => this.setState({edit: Object.assign({}, edit, {[key]: value})});
+
+    let toggle = (event, checked) => {
+      let events = edit.events.filter(e => e !== event);
+
+      if (checked) {
+        events.push(event);
+      }
+
+      set("events", events);
+    };
+
+    return (
+      <Form className="mt-4 mb-4" onSubmit={e => this.save(e)}>
+        <Row>
+          <Col xs="auto">
+            <Form.Control value={edit.name} placeholder="Name, like: discord" onChange={e => set("name", e.target.value)} />
+          </Col>
+          <Col>
+            <Form.Control value={edit.url} placeholder="https://example.com/hook" onChange={e => set("url", e.target.value)} />
+          </Col>
+          <Col xs="auto">
+            <Form.Control value={edit.secret} placeholder="Secret (generated if empty)" onChange={e => set("secret", e.target.value)} />
+          </Col>
+        </Row>
+        <Row className="mt-2">
+          <Col>
+            {this.state.events.map(event => {
+              return <Form.Check inline key={event} id={`event-${event}`} label={event} type="checkbox" checked={edit.events.includes(event)} onChange={e => toggle(event, e.target.checked)} />;
+            })}
+            <Form.Text>
+              Events to deliver, all events are delivered if none are selected.
+            </Form.Text>
+          </Col>
+          <Col xs="auto">
+            <Form.Check id="webhook-enabled" label="Enabled" type="checkbox" checked={edit.enabled} onChange={e => set("enabled", e.target.checked)} />
+          </Col>
+          <Col xs="auto">
+            <Button variant="primary" type="submit" disabled={!edit.name || !edit.url}>Save</Button>
+          </Col>
+        </Row>
+      </Form>
+    );
+  }
+
+  renderDeliveries() {
+    if (!this.state.deliveries) {
+      return null;
+    }
+
+    let filter = null;
+
+    if (!!this.state.filter) {
+      filter = (
+        <p>
+          Showing deliveries to <code>{this.state.filter}</code>.{" "}
+          <Button size="sm" variant="secondary" onClick={() => this.filter(null)}>Show all</Button>
+        </p>
+      );
+    }
+
+    let deliveries = null;
+
+    if (this.state.deliveries.length === 0) {
+      deliveries = (
+        <Alert variant="info">
+          No Deliveries!
+        </Alert>
+      );
+    } else {
+      deliveries = (
+        <Table responsive="sm" size="sm">
+          <thead>
+            <tr>
+              <th>When</th>
+              <th>Webhook</th>
+              <th>Event</th>
+              <th>Attempts</th>
+              <th>Status</th>
+              <th className="table-fill">Payload</th>
+            </tr>
+          </thead>
+          <tbody>
+            {this.state.deliveries.map(d => {
+              let status = d.error === null ? <span className="text-success">{d.status}</span> : <span className="text-danger">{d.error}</span>;
+
+              return (
+                <tr key={d.id}>
+                  <td>{d.delivered_at}</td>
+                  <td><code>{d.webhook}</code></td>
+                  <td><code>{d.event}</code></td>
+                  <td>{d.attempts}</td>
+                  <td>{status}</td>
+                  <td><small><code>{d.payload}</code></small></td>
+                </tr>
+              );
+            })}
+          </tbody>
+        </Table>
+      );
+    }
+
+    return <>
+      <h4>Deliveries</h4>
+      {filter}
+      {deliveries}
+    </>;
+  }
+
+  render() {
+    let success = null;
+
+    if (!!this.state.success) {
+      success = <Alert variant="info">{this.state.success}</Alert>;
+    }
+
+    return <>
+      <p>
+        Webhooks receive a JSON <code>POST</code> whenever one of the selected events happens.
+        Each request is signed with the secret of the webhook, and the signature is sent in the <code>X-Oxidize-Signature</code> header as <code>sha256=&lt;hex HMAC-SHA256 of the body&gt;</code>.
+        Failed deliveries are retried up to <code>webhooks/max-attempts</code> times.
+      </p>
+
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+      {success}
+
+      {this.renderForm()}
+      {this.renderWebhooks()}
+      {this.renderDeliveries()}
+    </>;
+  }
+}
@@ -9,14 +9,30 @@ import Connections from "./components/Connections.js";
 import Devices from "./components/Devices.js";
 import AfterStreams from "./components/AfterStreams.js";
 import Overlay from "./components/Overlay.js";
+import AlertsOverlay from "./components/AlertsOverlay.js";
+import CountdownOverlay from "./components/CountdownOverlay.js";
 import Settings from "./components/Settings.js";
 import Cache from "./components/Cache";
+import Profiles from "./components/Profiles";
+import History from "./components/History";
+import Tokens from "./components/Tokens";
+import Webhooks from "./components/Webhooks";
 import Modules from "./components/Modules.js";
 import ImportExport from "./components/ImportExport.js";
 import Commands from "./components/Commands.js";
 import Promotions from "./components/Promotions";
 import Aliases from "./components/Aliases";
+import Quotes from "./components/Quotes";
+import Counters from "./components/Counters";
+import Giveaways from "./components/Giveaways";
+import Polls from "./components/Polls";
+import Bets from "./components/Bets";
+import Ledger from "./components/Ledger";
+import Redemptions from "./components/Redemptions";
+import Shop from "./components/Shop";
+import WatchTime from "./components/WatchTime";
 import Themes from "./components/Themes";
+import Timers from "./components/Timers";
 import YouTube from "./components/YouTube";
 import Chat from "./components/Chat";
 import Authorization from "./components/Authorization";
@@ -69,6 +85,74 @@ class SettingsPage extends React.Component {
   }
 }
 
+class ProfilesPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <h1 className="oxi-page-title">Profiles</h1>
+
+        <Profiles api={this.api} {...this.props} />
+      </RouteLayout>
+    );
+  }
+}
+
+class HistoryPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <h1 className="oxi-page-title">Settings History</h1>
+
+        <History api={this.api} {...this.props} />
+      </RouteLayout>
+    );
+  }
+}
+
+class TokensPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <h1 className="oxi-page-title">Tokens</h1>
+
+        <Tokens api={this.api} {...this.props} />
+      </RouteLayout>
+    );
+  }
+}
+
+class WebhooksPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <h1 className="oxi-page-title">Webhooks</h1>
+
+        <Webhooks api={this.api} {...this.props} />
+      </RouteLayout>
+    );
+  }
+}
+
 class CachePage extends React.Component {
   constructor(props) {
     super(props);
@@ -320,21 +404,63 @@ class Layout extends React.Component {
                 <NavDropdown.Item as={Link} active={path === "/aliases"} to="/aliases">
                   Aliases
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/bets"} to="/bets">
+                  Bets
+                </NavDropdown.Item>
                 <NavDropdown.Item as={Link} active={path === "/commands"} to="/commands">
                   Commands
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/counters"} to="/counters">
+                  Counters
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/giveaways"} to="/giveaways">
+                  Giveaways
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/ledger"} to="/ledger">
+                  Ledger
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/polls"} to="/polls">
+                  Polls
+                </NavDropdown.Item>
                 <NavDropdown.Item as={Link} active={path === "/promotions"} to="/promotions">
                   Promotions
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/quotes"} to="/quotes">
+                  Quotes
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/redemptions"} to="/redemptions">
+                  Redemptions
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/shop"} to="/shop">
+                  Shop
+                </NavDropdown.Item>
                 <NavDropdown.Item as={Link} active={path === "/themes"} to="/themes">
                   Themes
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/timers"} to="/timers">
+                  Timers
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/watch-time"} to="/watch-time">
+                  Watch Time
+                </NavDropdown.Item>
               </NavDropdown>
 
               <NavDropdown title="Advanced">
                 <NavDropdown.Item as={Link} active={path === "/settings"} to="/settings">
                   Settings
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/profiles"} to="/profiles">
+                  Profiles
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/history"} to="/history">
+                  History
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/tokens"} to="/tokens">
+                  Tokens
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/webhooks"} to="/webhooks">
+                  Webhooks
+                </NavDropdown.Item>
                 <NavDropdown.Item as={Link} active={path === "/cache"} to="/cache">
                   Cache
                 </NavDropdown.Item>
@@ -350,6 +476,12 @@ class Layout extends React.Component {
                 <NavDropdown.Item as={Link} active={path === "/overlay"} to="/overlay" target="overlay">
                   Overlay
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/alerts"} to="/alerts" target="alerts">
+                  Alerts Overlay
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/countdown"} to="/countdown" target="countdown">
+                  Countdown Overlay
+                </NavDropdown.Item>
                 <NavDropdown.Item as={Link} active={path === "/youtube"} to="/youtube" target="youtube">
                   YouTube Player
                 </NavDropdown.Item>
@@ -385,6 +517,10 @@ function AppRouter() {
       <Route path="/" exact component={IndexPage} />
       <Route path="/after-streams" exact component={AfterStreamsPage} />
       <Route path="/settings" exact component={SettingsPage} />
+      <Route path="/profiles" exact component={ProfilesPage} />
+      <Route path="/history" exact component={HistoryPage} />
+      <Route path="/tokens" exact component={TokensPage} />
+      <Route path="/webhooks" exact component={WebhooksPage} />
       <Route path="/cache" exact component={CachePage} />
       <Route path="/modules" component={ModulesPage} />
       <Route path="/authorization" exact component={props => (
@@ -400,10 +536,42 @@ function AppRouter() {
       <Route path="/promotions" exact render={props => (
         <AuthorizedPage><Promotions {...props} /></AuthorizedPage>
       )} />
+      <Route path="/counters" exact render={props => (
+        <AuthorizedPage><Counters {...props} /></AuthorizedPage>
+      )} />
+      <Route path="/giveaways" exact render={props => (
+        <AuthorizedPage><Giveaways {...props} /></AuthorizedPage>
+      )} />
+      <Route path="/polls" exact render={props => (
+        <AuthorizedPage><Polls {...props} /></AuthorizedPage>
+      )} />
+      <Route path="/bets" exact render={props => (
+        <AuthorizedPage><Bets {...props} /></AuthorizedPage>
+      )} />
+      <Route path="/ledger" exact render={props => (
+        <AuthorizedPage><Ledger {...props} /></AuthorizedPage>
+      )} />
+      <Route path="/redemptions" exact render={props => (
+        <AuthorizedPage><Redemptions {...props} /></AuthorizedPage>
+      )} />
+      <Route path="/shop" exact render={props => (
+        <AuthorizedPage><Shop {...props} /></AuthorizedPage>
+      )} />
+      <Route path="/watch-time" exact render={props => (
+        <AuthorizedPage><WatchTime {...props} /></AuthorizedPage>
+      )} />
+      <Route path="/quotes" exact render={props => (
+        <AuthorizedPage><Quotes {...props} /></AuthorizedPage>
+      )} />
       <Route path="/themes" exact render={props => (
         <AuthorizedPage><Themes {...props} /></AuthorizedPage>
       )} />
+      <Route path="/timers" exact render={props => (
+        <AuthorizedPage><Timers {...props} /></AuthorizedPage>
+      )} />
       <Route path="/overlay/" component={Overlay} />
+      <Route path="/alerts" component={AlertsOverlay} />
+      <Route path="/countdown" component={CountdownOverlay} />
       <Route path="/youtube" component={YouTube} />
       <Route path="/chat" component={Chat} />
     </Router>
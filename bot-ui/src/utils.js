@@ -62,6 +62,22 @@ export function formatDuration(duration) {
   return zeroPad(minutes, 2) + ":" + zeroPad(seconds, 2);
 }
 
+/**
+ * Format the given number of seconds as a compact duration.
+ *
+ * @param {number} seconds number of seconds to format
+ */
+export function formatSeconds(seconds) {
+  let hours = Math.floor(seconds / 3600);
+  let minutes = Math.floor((seconds % 3600) / 60);
+
+  if (hours > 0) {
+    return `${hours}h ${minutes}m`;
+  }
+
+  return `${minutes}m`;
+}
+
 /**
  * Pad a number with zeroes.
  *
@@ -92,6 +108,51 @@ export function percentage(part, total) {
   return Math.round((part / total) * 10000) / 100;
 }
 
+/**
+ * Key in local storage where the access token is stored.
+ */
+const TOKEN_KEY = "oxidize-token";
+
+/**
+ * Get the token used to access the bot, if any.
+ *
+ * A token in the `token` query parameter takes precedence, which is how
+ * overlays are given read-only access.
+ */
+export function token() {
+  let search = new URLSearchParams(window.location.search);
+  return search.get("token") || window.localStorage.getItem(TOKEN_KEY);
+}
+
+/**
+ * Store the token used to access the bot in this browser.
+ *
+ * @param {string | null} token the token to store, or null to forget it
+ */
+export function setToken(token) {
+  if (token) {
+    window.localStorage.setItem(TOKEN_KEY, token);
+  } else {
+    window.localStorage.removeItem(TOKEN_KEY);
+  }
+}
+
+/**
+ * Append the current token to the given URL, if any.
+ *
+ * @param {string} url the url to append the token to
+ */
+export function withToken(url) {
+  let t = token();
+
+  if (!t) {
+    return url;
+  }
+
+  let sep = url.indexOf("?") === -1 ? "?" : "&";
+  return `${url}${sep}token=${encodeURIComponent(t)}`;
+}
+
 /**
  * Get the current URL to connect to.
  */
@@ -103,7 +164,34 @@ export function websocketUrl(path) {
     scheme = "wss";
   }
 
-  return `${scheme}://${loc.host}/${path}`;
+  return withToken(`${scheme}://${loc.host}/${path}`);
+}
+
+/**
+ * Get the URL of the multiplexed websocket, subscribed to the given topics.
+ *
+ * Messages received are of the form `{type: "message", topic, snapshot, data}`.
+ *
+ * @param {string[]} topics topics to subscribe to, like `song` or `chat`
+ */
+export function topicsUrl(topics) {
+  return websocketUrl(`ws/topics?topics=${topics.map(encodeURIComponent).join(",")}`);
+}
+
+/**
+ * Get the URL of an image or sound used in alerts.
+ *
+ * Full URLs are used as-is, anything else is a file in the configured assets
+ * directory.
+ *
+ * @param {string} asset the configured asset
+ */
+export function alertAssetUrl(asset) {
+  if (asset.indexOf("://") !== -1) {
+    return asset;
+  }
+
+  return withToken(`/alerts/assets/${encodeURIComponent(asset)}`);
 }
 
 /**
@@ -338,6 +338,25 @@ async fn try_main(
         .update(db::Promotions::load(db.clone()).await?)
         .await;
     injector.update(db::Themes::load(db.clone()).await?).await;
+    injector.update(db::Quotes::load(db.clone()).await?).await;
+    injector.update(db::Counters::load(db.clone()).await?).await;
+    injector.update(db::Timers::load(db.clone()).await?).await;
+    injector
+        .update(db::Giveaways::load(db.clone()).await?)
+        .await;
+    injector.update(db::Polls::load(db.clone()).await?).await;
+    injector.update(db::Bets::load(db.clone()).await?).await;
+    injector.update(db::Shop::load(db.clone()).await?).await;
+    injector
+        .update(db::WatchTime::load(db.clone()).await?)
+        .await;
+    injector
+        .update(db::Redemptions::load(db.clone()).await?)
+        .await;
+    injector
+        .update(db::WebTokens::load(db.clone()).await?)
+        .await;
+    injector.update(db::Webhooks::load(db.clone()).await?).await;
 
     let message_bus = bus::Bus::new();
     injector.update(message_bus.clone()).await;
@@ -347,6 +366,8 @@ async fn try_main(
     injector.update(youtube_bus.clone()).await;
     let command_bus = bus::Bus::new();
     injector.update(command_bus.clone()).await;
+    let event_bus = bus::Bus::new();
+    injector.update(event_bus.clone()).await;
 
     let system_loop_future = system_loop(settings.scoped("system"), system.clone());
 
@@ -369,6 +390,7 @@ async fn try_main(
         youtube_bus.clone(),
         command_bus.clone(),
         auth.clone(),
+        settings.clone(),
         latest.clone(),
     )
     .await?;
@@ -447,11 +469,17 @@ async fn try_main(
         settings.clone(),
     );
 
-    let song_file_future =
-        crate::song_file::setup(injector.clone(), settings.scoped("player/song-file"));
+    let song_file_future = crate::song_file::setup(
+        injector.clone(),
+        settings.scoped("player/song-file"),
+        global_bus.clone(),
+    );
 
     let setbac_future = crate::setbac::run(&settings, &injector, global_bus.clone()).await?;
 
+    let webhooks_future =
+        crate::webhooks::run(&settings, &injector, global_bus.clone(), event_bus.clone()).await?;
+
     let (stream_state_tx, stream_state_rx) = mpsc::channel(64);
 
     let mut chat = chat::Configuration::new(
@@ -484,9 +512,23 @@ async fn try_main(
     chat.module(module::auth::Module);
     chat.module(module::poll::Module);
     chat.module(module::weather::Module);
+    chat.module(module::quote::Module);
+    chat.module(module::counter::Module);
+    chat.module(module::giveaway::Module);
+    chat.module(module::bet::Module);
+    chat.module(module::shop::Module);
+    chat.module(module::watch_time::Module);
+    chat.module(module::games::Module);
+    chat.module(module::redemptions::Module);
+    chat.module(module::alerts::Module);
     chat.module(module::help::Module);
 
-    let notify_after_streams = notify_after_streams(&injector, stream_state_rx, system.clone());
+    let notify_after_streams = notify_after_streams(
+        &injector,
+        stream_state_rx,
+        event_bus.clone(),
+        system.clone(),
+    );
 
     let chat_future = chat.run();
 
@@ -509,6 +551,7 @@ async fn try_main(
         song_file_future,
         player_future,
         setbac_future,
+        webhooks_future,
         notify_after_streams,
         chat_future,
     );
@@ -542,6 +585,7 @@ async fn try_main(
 async fn notify_after_streams(
     injector: &Injector,
     mut rx: mpsc::Receiver<stream_info::StreamState>,
+    event_bus: bus::Bus<bus::Event>,
     system: sys::System,
 ) -> Result<()> {
     let (mut after_streams_stream, mut after_streams) = injector.stream::<db::AfterStreams>().await;
@@ -555,8 +599,11 @@ async fn notify_after_streams(
                 match update {
                     stream_info::StreamState::Started => {
                         tracing::info!("Stream started");
+                        event_bus.send(bus::Event::StreamStarted).await;
                     }
                     stream_info::StreamState::Stopped => {
+                        event_bus.send(bus::Event::StreamStopped).await;
+
                         let after_streams = match after_streams.as_ref() {
                             Some(after_streams) => after_streams,
                             None => continue,
@@ -115,3 +115,4 @@ mod song_file;
 mod sys;
 mod tracing;
 mod updater;
+mod webhooks;
@@ -4,6 +4,11 @@ use async_trait::async_trait;
 use chat::command;
 use chat::module;
 
+/// Number of changes shown by `!admin history`.
+const HISTORY_LIMIT: usize = 5;
+/// Maximum number of changes which can be undone at once.
+const UNDO_LIMIT: usize = 10;
+
 /// Handler for the !admin command.
 pub(crate) struct Handler {
     settings: settings::Settings<::auth::Scope>,
@@ -102,7 +107,7 @@ impl command::Handler for Handler {
                     .unwrap_or_default();
 
                 values.push(value);
-                self.settings.set(&key, values).await?;
+                self.settings_for(ctx).set(&key, values).await?;
                 chat::respond!(ctx, "Updated the {} setting", key);
             }
             // Delete a value from a setting.
@@ -117,7 +122,7 @@ impl command::Handler for Handler {
                     .unwrap_or_default();
 
                 values.retain(|v| v != &value);
-                self.settings.set(&key, values).await?;
+                self.settings_for(ctx).set(&key, values).await?;
                 chat::respond!(ctx, "Updated the {} setting", key);
             }
             Some("toggle") => {
@@ -169,6 +174,74 @@ impl command::Handler for Handler {
 
                 chat::respond!(ctx, "Disabled group {}", group);
             }
+            // Switch to a settings profile.
+            Some("profile") => match ctx.next() {
+                Some(name) => {
+                    if !self.settings_for(ctx).profile_apply(&name).await? {
+                        chat::respond_bail!("No profile named `{}`", name);
+                    }
+
+                    chat::respond!(ctx, "Switched to profile {}", name.to_lowercase());
+                }
+                None => {
+                    let profiles = self.settings.profiles().await?;
+
+                    if profiles.is_empty() {
+                        chat::respond!(ctx, "No profiles, they can be created in the web UI");
+                    } else {
+                        let names = profiles
+                            .into_iter()
+                            .map(|p| p.name)
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        chat::respond!(ctx, "Available profiles: {}", names);
+                    }
+                }
+            },
+            // Show the most recent changes to settings.
+            Some("history") => {
+                let history = self.settings.history(HISTORY_LIMIT, 0).await?;
+
+                if history.is_empty() {
+                    chat::respond_bail!("No settings have been changed");
+                }
+
+                let changes = history
+                    .iter()
+                    .map(describe_change)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                chat::respond!(ctx, "Recent changes: {}", changes);
+            }
+            // Undo the most recent changes to settings.
+            Some("undo") => {
+                let count = match ctx.next() {
+                    Some(count) => match str::parse::<usize>(&count) {
+                        Ok(count) if (1..=UNDO_LIMIT).contains(&count) => count,
+                        _ => chat::respond_bail!(
+                            "Expected a number of changes between 1 and {}",
+                            UNDO_LIMIT
+                        ),
+                    },
+                    None => 1,
+                };
+
+                let undone = self.settings_for(ctx).undo(count).await?;
+
+                if undone.is_empty() {
+                    chat::respond_bail!("No changes to undo");
+                }
+
+                let changes = undone
+                    .iter()
+                    .map(describe_change)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                chat::respond!(ctx, "Undid: {}", changes);
+            }
             // Get or set settings.
             Some("settings") => {
                 let key = key(ctx)?;
@@ -214,7 +287,7 @@ impl command::Handler for Handler {
                         }
 
                         let value_string = serde_json::to_string(&value)?;
-                        self.settings.set_json(&key, value).await?;
+                        self.settings_for(ctx).set_json(&key, value).await?;
                         chat::respond!(ctx, "Updated setting {} = {}", key, value_string);
                     }
                 }
@@ -227,7 +300,10 @@ impl command::Handler for Handler {
                      refresh-vips, \
                      version, \
                      shutdown, \
-                     settings.",
+                     settings, \
+                     profile, \
+                     history, \
+                     undo.",
                 );
             }
         }
@@ -237,6 +313,16 @@ impl command::Handler for Handler {
 }
 
 impl Handler {
+    /// Settings which attribute changes to the user running the command.
+    fn settings_for(&self, ctx: &command::Context<'_>) -> settings::Settings<::auth::Scope> {
+        let settings = self.settings.with_source(settings::Source::Chat);
+
+        match ctx.user.name() {
+            Some(name) => settings.with_user(name),
+            None => settings,
+        }
+    }
+
     /// Handler for the toggle command.
     async fn toggle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
         let key = key(ctx)?;
@@ -279,7 +365,7 @@ impl Handler {
         };
 
         let value_string = serde_json::to_string(&toggled)?;
-        self.settings.set_json(&key, toggled).await?;
+        self.settings_for(ctx).set_json(&key, toggled).await?;
         chat::respond!(ctx, "Updated setting {} = {}", key, value_string);
         Ok(())
     }
@@ -327,6 +413,14 @@ impl Handler {
     }
 }
 
+/// Describe a change to a setting for chat.
+fn describe_change(entry: &settings::HistoryEntry) -> String {
+    match &entry.user {
+        Some(user) => format!("{} ({} by {})", entry.key, entry.source, user),
+        None => format!("{} ({})", entry.key, entry.source),
+    }
+}
+
 /// Extract a settings key from the context.
 fn key(ctx: &mut command::Context<'_>) -> Result<String> {
     let key = ctx.next().ok_or(chat::respond_err!("Expected <key>"))?;
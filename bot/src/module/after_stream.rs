@@ -11,6 +11,7 @@ pub(crate) struct AfterStream {
     pub(crate) enabled: settings::Var<bool>,
     pub(crate) cooldown: settings::Var<Cooldown>,
     pub(crate) after_streams: async_injector::Ref<db::AfterStreams>,
+    pub(crate) event_bus: async_injector::Ref<bus::Bus<bus::Event>>,
 }
 
 #[async_trait]
@@ -54,6 +55,16 @@ impl command::Handler for AfterStream {
         after_streams
             .push(ctx.channel(), user.login(), ctx.rest())
             .await?;
+
+        if let Some(event_bus) = self.event_bus.load().await {
+            event_bus
+                .send(bus::Event::AfterStream {
+                    user: user.login().to_owned(),
+                    text: ctx.rest().to_owned(),
+                })
+                .await;
+        }
+
         chat::respond!(ctx, "Reminder added.");
         Ok(())
     }
@@ -87,6 +98,7 @@ impl chat::Module for Module {
                     .var("cooldown", Cooldown::from_duration(Duration::seconds(30)))
                     .await?,
                 after_streams: injector.var().await,
+                event_bus: injector.var().await,
             },
         );
 
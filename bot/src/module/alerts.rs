@@ -0,0 +1,503 @@
+//! Alerts for subscriptions, raids, cheers and redemptions which are shown
+//! in overlays.
+//!
+//! Alerts are queued up and shown one at a time, and alerts which have
+//! already been seen recently are ignored.
+
+use std::collections::{HashSet, VecDeque};
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Context as _, Result};
+use api::twitch::pubsub;
+use async_fuse::Fuse;
+use async_injector::Injector;
+use async_trait::async_trait;
+use chat::command;
+use chat::module;
+use common::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+/// Number of alert ids remembered for de-duplication.
+const RECENT_LIMIT: usize = 256;
+/// Maximum number of alerts waiting to be shown.
+const QUEUE_LIMIT: usize = 64;
+/// Kinds of alerts which are supported.
+const KINDS: &[&str] = &["sub", "subgift", "raid", "bits", "redemption"];
+
+/// Get the default template for the given kind of alert.
+fn default_template(kind: &str) -> &'static str {
+    match kind {
+        "sub" => "{{user}} just subscribed!{{#if amount}} ({{amount}} months){{/if}}",
+        "subgift" => "{{user}} gifted {{amount}} subs!",
+        "raid" => "{{user}} is raiding with {{amount}} viewers!",
+        "bits" => "{{user}} cheered {{amount}} bits!",
+        "redemption" => "{{user}} redeemed {{title}}!",
+        _ => "{{user}}!",
+    }
+}
+
+/// Configuration for a single kind of alert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KindConfig {
+    kind: String,
+    #[serde(default)]
+    enabled: Option<bool>,
+    #[serde(default)]
+    template: Option<template::Template>,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    sound: Option<String>,
+    #[serde(default)]
+    duration: Option<Duration>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct KindsConfig(Vec<KindConfig>);
+
+impl KindsConfig {
+    /// Get the configuration for the given kind of alert.
+    fn get(&self, kind: &str) -> Option<&KindConfig> {
+        self.0.iter().find(|c| c.kind == kind)
+    }
+}
+
+/// A single alert, the fields of which are available to templates.
+#[derive(Debug, Clone, Serialize)]
+struct Alert {
+    id: String,
+    kind: String,
+    user: String,
+    amount: Option<i64>,
+    title: Option<String>,
+    message: Option<String>,
+}
+
+impl Alert {
+    /// Convert an event into an alert.
+    fn from_event(event: bus::Event) -> Option<Self> {
+        match event {
+            bus::Event::Alert {
+                id,
+                kind,
+                user,
+                amount,
+                title,
+                message,
+            } => Some(Alert {
+                id,
+                kind,
+                user,
+                amount,
+                title,
+                message,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Convert a redemption into an alert.
+    fn from_redemption(redemption: pubsub::Redemption) -> Self {
+        Alert {
+            id: redemption.id,
+            kind: String::from("redemption"),
+            user: redemption.user.display_name,
+            amount: Some(redemption.reward.cost),
+            title: Some(redemption.reward.title),
+            message: redemption.user_input,
+        }
+    }
+}
+
+/// Queue of alerts waiting to be shown.
+#[derive(Default)]
+struct Queue {
+    pending: VecDeque<Alert>,
+    recent: VecDeque<String>,
+    seen: HashSet<String>,
+}
+
+impl Queue {
+    /// Push an alert to the back of the queue.
+    ///
+    /// Returns `false` if the alert has been seen recently or if the queue
+    /// is full.
+    fn push(&mut self, alert: Alert) -> bool {
+        if self.pending.len() >= QUEUE_LIMIT || !self.seen.insert(alert.id.clone()) {
+            return false;
+        }
+
+        self.recent.push_back(alert.id.clone());
+
+        while self.recent.len() > RECENT_LIMIT {
+            if let Some(id) = self.recent.pop_front() {
+                self.seen.remove(&id);
+            }
+        }
+
+        self.pending.push_back(alert);
+        true
+    }
+
+    /// Pop the next alert to show.
+    fn pop(&mut self) -> Option<Alert> {
+        self.pending.pop_front()
+    }
+
+    /// Clear all pending alerts.
+    fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+enum Event {
+    /// Queue an alert.
+    Push(Alert),
+    /// Skip the alert currently being shown.
+    Skip,
+    /// Clear all pending alerts.
+    Clear,
+}
+
+/// Handler for the `!alert` command.
+pub(crate) struct Handler {
+    sender: mpsc::UnboundedSender<Event>,
+    enabled: settings::Var<bool>,
+    test_counter: AtomicUsize,
+}
+
+#[async_trait]
+impl command::Handler for Handler {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Alert)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let event = match ctx.next().as_deref() {
+            Some("test") => {
+                let kind = ctx.next().unwrap_or_else(|| String::from("sub"));
+
+                if !KINDS.contains(&kind.as_str()) {
+                    chat::respond!(ctx, "Expected one of: {}", KINDS.join(", "));
+                    return Ok(());
+                }
+
+                let id = self.test_counter.fetch_add(1, Ordering::SeqCst);
+
+                let user = match ctx.user.display_name() {
+                    Some(user) => user.to_owned(),
+                    None => String::from("test"),
+                };
+
+                Event::Push(Alert {
+                    id: format!("test-{id}"),
+                    kind,
+                    user,
+                    amount: Some(5),
+                    title: Some(String::from("Test")),
+                    message: Some(String::from("This is a test alert")),
+                })
+            }
+            Some("skip") => Event::Skip,
+            Some("clear") => Event::Clear,
+            _ => {
+                chat::respond!(
+                    ctx,
+                    "Expected: !alert test [kind], !alert skip, or !alert clear"
+                );
+                return Ok(());
+            }
+        };
+
+        if self.sender.send(event).is_err() {
+            chat::respond!(ctx, "Alerts are not available right now :(");
+            return Ok(());
+        }
+
+        chat::respond!(ctx, "Done!");
+        Ok(())
+    }
+}
+
+/// Task which queues up alerts and shows them one at a time.
+async fn task(
+    injector: Injector,
+    settings: settings::Settings<::auth::Scope>,
+    enabled: settings::Var<bool>,
+    mut enabled_stream: settings::Stream<bool>,
+    mut receiver: mpsc::UnboundedReceiver<Event>,
+) -> Result<()> {
+    let (mut duration_stream, mut duration) = settings
+        .stream("duration")
+        .or_with(Duration::seconds(8))
+        .await?;
+    let (mut kinds_stream, mut kinds) =
+        settings.stream::<KindsConfig>("kinds").or_default().await?;
+    let (mut pubsub_stream, pubsub) = injector.stream::<pubsub::TwitchPubSub>().await;
+
+    let event_bus = injector
+        .get::<bus::Bus<bus::Event>>()
+        .await
+        .context("event bus not available")?;
+
+    let global_bus = injector
+        .get::<bus::Bus<bus::Global>>()
+        .await
+        .context("global bus not available")?;
+
+    let mut event_rx = event_bus.subscribe();
+
+    let mut state = State {
+        enabled: enabled.load().await,
+        pubsub,
+        redemptions_stream: Fuse::empty(),
+    };
+
+    state.build();
+
+    let mut queue = Queue::default();
+    let mut showing = pin!(Fuse::empty());
+
+    loop {
+        tokio::select! {
+            update = enabled_stream.recv() => {
+                *enabled.write().await = update;
+                state.enabled = update;
+                state.build();
+
+                if !update {
+                    queue.clear();
+                    showing.set(Fuse::empty());
+                }
+            }
+            update = duration_stream.recv() => {
+                duration = update;
+            }
+            update = kinds_stream.recv() => {
+                kinds = update;
+            }
+            pubsub = pubsub_stream.recv() => {
+                state.pubsub = pubsub;
+                state.build();
+            }
+            Some(redemption) = state.redemptions_stream.next() => {
+                queue.push(Alert::from_redemption(redemption));
+            }
+            event = event_rx.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!("Alerts skipped {n} events");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                };
+
+                if !state.enabled {
+                    continue;
+                }
+
+                if let Some(alert) = Alert::from_event(event) {
+                    queue.push(alert);
+                }
+            }
+            Some(event) = receiver.recv() => {
+                match event {
+                    Event::Push(alert) => {
+                        queue.push(alert);
+                    }
+                    Event::Skip => {
+                        showing.set(Fuse::empty());
+                    }
+                    Event::Clear => {
+                        queue.clear();
+                    }
+                }
+            }
+            _ = showing.as_mut() => {
+            }
+        }
+
+        if !showing.is_empty() {
+            continue;
+        }
+
+        while let Some(alert) = queue.pop() {
+            let config = kinds.get(&alert.kind);
+
+            if config.and_then(|c| c.enabled) == Some(false) {
+                continue;
+            }
+
+            let text = match render(config, &alert) {
+                Ok(text) => text,
+                Err(e) => {
+                    common::log_error!(e, "Failed to render alert `{}`", alert.kind);
+                    continue;
+                }
+            };
+
+            let duration = config.and_then(|c| c.duration).unwrap_or(duration);
+
+            global_bus
+                .send(bus::Global::Alert {
+                    id: alert.id,
+                    kind: alert.kind,
+                    user: alert.user,
+                    text,
+                    image: config.and_then(|c| c.image.clone()),
+                    sound: config.and_then(|c| c.sound.clone()),
+                    duration: duration.as_std().as_secs(),
+                })
+                .await;
+
+            showing.set(Fuse::new(tokio::time::sleep(duration.as_std())));
+            break;
+        }
+    }
+}
+
+/// Render the text of an alert.
+fn render(config: Option<&KindConfig>, alert: &Alert) -> Result<String> {
+    if let Some(template) = config.and_then(|c| c.template.as_ref()) {
+        return template.render_to_string(alert);
+    }
+
+    template::Template::compile(default_template(&alert.kind))?.render_to_string(alert)
+}
+
+struct State {
+    enabled: bool,
+    pubsub: Option<pubsub::TwitchPubSub>,
+    redemptions_stream: Fuse<pubsub::TwitchStream<pubsub::Redemption>>,
+}
+
+impl State {
+    fn build(&mut self) {
+        let pubsub = match (self.pubsub.as_ref(), self.enabled) {
+            (Some(pubsub), true) => pubsub,
+            _ => {
+                self.redemptions_stream.clear();
+                return;
+            }
+        };
+
+        self.redemptions_stream.set(pubsub.redemptions());
+    }
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "alerts"
+    }
+
+    /// Set up the `!alert` command and the task which shows alerts.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            tasks,
+            settings,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("alerts");
+
+        let (enabled_stream, enabled) = settings.stream("enabled").or_default().await?;
+        let enabled = settings::Var::new(enabled);
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        handlers.insert(
+            "alert",
+            Handler {
+                sender,
+                enabled: enabled.clone(),
+                test_counter: AtomicUsize::new(0),
+            },
+        );
+
+        tasks.push(Box::pin(task(
+            injector.clone(),
+            settings,
+            enabled,
+            enabled_stream,
+            receiver,
+        )));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_template, Alert, Queue, KINDS, QUEUE_LIMIT, RECENT_LIMIT};
+
+    fn alert(id: &str) -> Alert {
+        Alert {
+            id: id.to_owned(),
+            kind: String::from("raid"),
+            user: String::from("turtle"),
+            amount: Some(10),
+            title: None,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_queue() {
+        let mut queue = Queue::default();
+
+        assert!(queue.push(alert("a")));
+        assert!(queue.push(alert("b")));
+        assert!(!queue.push(alert("a")));
+
+        assert_eq!(queue.pop().map(|a| a.id), Some(String::from("a")));
+        assert_eq!(queue.pop().map(|a| a.id), Some(String::from("b")));
+        assert!(queue.pop().is_none());
+
+        // Already shown alerts are still de-duplicated.
+        assert!(!queue.push(alert("b")));
+
+        for n in 0..QUEUE_LIMIT {
+            assert!(queue.push(alert(&format!("full-{n}"))));
+        }
+
+        assert!(!queue.push(alert("c")));
+        queue.clear();
+
+        for n in 0..RECENT_LIMIT {
+            assert!(queue.push(alert(&format!("recent-{n}"))));
+            queue.pop();
+        }
+
+        // Old alerts are eventually forgotten.
+        assert!(queue.push(alert("a")));
+    }
+
+    #[test]
+    fn test_default_templates() {
+        for kind in KINDS {
+            let template = template::Template::compile(default_template(kind)).unwrap();
+            assert!(template.render_to_string(alert("a")).is_ok());
+        }
+
+        let text = template::Template::compile(default_template("raid"))
+            .unwrap()
+            .render_to_string(alert("a"))
+            .unwrap();
+
+        assert_eq!(text, "turtle is raiding with 10 viewers!");
+    }
+}
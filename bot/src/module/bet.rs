@@ -0,0 +1,673 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chat::command;
+use chat::module;
+use chrono::Utc;
+use common::Duration;
+use tokio::sync::Mutex;
+
+/// How frequently to check for bets which should be locked or settled.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Handler for the `!bet` command.
+pub(crate) struct Handler {
+    enabled: settings::Var<bool>,
+    duration: settings::Var<Duration>,
+    min_wager: settings::Var<u32>,
+    max_wager: settings::Var<u32>,
+    bets: async_injector::Ref<db::Bets>,
+    currency: async_injector::Ref<currency::Currency>,
+    streamer: api::TwitchAndUser,
+    settle_lock: Arc<Mutex<()>>,
+}
+
+impl Handler {
+    /// Open a new bet.
+    async fn open(&self, ctx: &mut command::Context<'_>, bets: &db::Bets) -> Result<()> {
+        let title = ctx.next_str("<title> <outcomes...>")?;
+
+        let mut outcomes = Vec::new();
+
+        for outcome in ctx.by_ref() {
+            let (key, description) = match outcome.find('=') {
+                Some(i) => {
+                    let (keyword, description) = outcome.split_at(i);
+                    (keyword.to_string(), Some(description[1..].to_string()))
+                }
+                None => (outcome, None),
+            };
+
+            outcomes.push((key.to_lowercase(), description));
+        }
+
+        if outcomes.len() < 2 {
+            chat::respond!(ctx, "Expected at least two outcomes to bet on");
+            return Ok(());
+        }
+
+        if let Some(latest) = bets.latest(ctx.channel()).await? {
+            if latest.resolved_at.is_none() {
+                chat::respond!(
+                    ctx,
+                    "Bet #{} is still running, resolve or cancel it first",
+                    latest.id
+                );
+                return Ok(());
+            }
+        }
+
+        let duration = self.duration.load().await;
+
+        let locks_at = if duration.is_empty() {
+            None
+        } else {
+            Some((Utc::now() + duration.as_chrono()).naive_utc())
+        };
+
+        let keys = outcomes
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let bet = bets.open(ctx.channel(), &title, outcomes, locks_at).await?;
+
+        if duration.is_empty() {
+            ctx.privmsg(format!(
+                "Bet #{} is open: {}! Wager with !bet <outcome> <amount>, outcomes: {}",
+                bet.id, title, keys
+            ))
+            .await;
+        } else {
+            ctx.privmsg(format!(
+                "Bet #{} is open for {}: {}! Wager with !bet <outcome> <amount>, outcomes: {}",
+                bet.id, duration, title, keys
+            ))
+            .await;
+        }
+
+        Ok(())
+    }
+
+    /// Place a wager on the given outcome of the running bet.
+    async fn wager(
+        &self,
+        ctx: &mut command::Context<'_>,
+        bets: &db::Bets,
+        outcome: &str,
+    ) -> Result<()> {
+        let amount = ctx.next_parse::<i64, _>("<outcome> <amount>")?;
+
+        let user = match ctx.user.real() {
+            Some(user) => user,
+            None => {
+                chat::respond!(ctx, "Only real users can place wagers");
+                return Ok(());
+            }
+        };
+
+        let bet = match bets.latest(ctx.channel()).await? {
+            Some(bet) if bet.resolved_at.is_none() => bet,
+            _ => {
+                chat::respond!(ctx, "There is no bet running");
+                return Ok(());
+            }
+        };
+
+        let now = Utc::now().naive_utc();
+
+        if bet.locked_at.is_some() || matches!(bet.locks_at, Some(locks_at) if locks_at <= now) {
+            chat::respond!(ctx, "Bet #{} is no longer accepting wagers", bet.id);
+            return Ok(());
+        }
+
+        let outcome = outcome.to_lowercase();
+
+        if !bets
+            .outcomes(bet.id)
+            .await?
+            .iter()
+            .any(|o| o.key == outcome)
+        {
+            chat::respond!(ctx, "`{}` is not an outcome of bet #{}", outcome, bet.id);
+            return Ok(());
+        }
+
+        let existing = bets.wager(bet.id, user.login()).await?;
+
+        if let Some(existing) = &existing {
+            if existing.outcome != outcome {
+                chat::respond!(
+                    ctx,
+                    "You've already wagered on `{}`, you can't change sides",
+                    existing.outcome
+                );
+                return Ok(());
+            }
+        }
+
+        let min = self.min_wager.load().await as i64;
+        let max = self.max_wager.load().await as i64;
+
+        if amount <= 0 || amount < min {
+            chat::respond!(ctx, "You must wager at least {}", min.max(1));
+            return Ok(());
+        }
+
+        let already = existing.map(|w| w.amount).unwrap_or_default();
+
+        if max > 0 && already.saturating_add(amount) > max {
+            chat::respond!(
+                ctx,
+                "You can wager at most {} in total, and you've already wagered {}",
+                max,
+                already
+            );
+            return Ok(());
+        }
+
+        let currency = match self.currency.load().await {
+            Some(currency) => currency,
+            None => {
+                chat::respond!(ctx, "No currency configured for stream, sorry :(");
+                return Ok(());
+            }
+        };
+
+        let bank = &self.streamer.user.login;
+
+        let result = currency
+            .balance_transfer(
+                ctx.channel(),
+                user.login(),
+                bank,
+                amount,
+                false,
+                currency::Reason::Bet,
+            )
+            .await;
+
+        match result {
+            Ok(()) => {}
+            Err(currency::BalanceTransferError::NoBalance) => {
+                chat::respond!(ctx, "Not enough {} to wager {}", currency.name, amount);
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let max = (max > 0).then_some(max);
+
+        let now = Utc::now().naive_utc();
+
+        let result = bets
+            .place(bet.id, user.login(), &outcome, amount, now, max)
+            .await;
+
+        if !matches!(result, Ok(db::WagerPlacement::Placed(..))) {
+            let refund = currency
+                .balance_transfer(
+                    ctx.channel(),
+                    bank,
+                    user.login(),
+                    amount,
+                    true,
+                    currency::Reason::Bet,
+                )
+                .await;
+
+            if let Err(e) = refund {
+                common::log_error!(
+                    e,
+                    "Failed to refund wager of {} to {}",
+                    amount,
+                    user.login()
+                );
+            }
+        }
+
+        match result? {
+            db::WagerPlacement::Placed(wager) => {
+                chat::respond!(
+                    ctx,
+                    "You've wagered {} {} on `{}`",
+                    wager.amount,
+                    currency.name,
+                    outcome
+                );
+            }
+            db::WagerPlacement::Closed => {
+                chat::respond!(ctx, "Bet #{} is no longer accepting wagers", bet.id);
+            }
+            db::WagerPlacement::OtherSide(existing) => {
+                chat::respond!(
+                    ctx,
+                    "You've already wagered on `{}`, you can't change sides",
+                    existing
+                );
+            }
+            db::WagerPlacement::Exceeded(already) => {
+                chat::respond!(
+                    ctx,
+                    "You can wager at most {} in total, and you've already wagered {}",
+                    max.unwrap_or_default(),
+                    already
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve or cancel the running bet and pay out all wagers.
+    async fn resolve(
+        &self,
+        ctx: &mut command::Context<'_>,
+        bets: &db::Bets,
+        bet: db::Bet,
+        winner: Option<&str>,
+    ) -> Result<()> {
+        let currency = match self.currency.load().await {
+            Some(currency) => currency,
+            None => {
+                chat::respond!(ctx, "No currency configured for stream, sorry :(");
+                return Ok(());
+            }
+        };
+
+        if !bets.resolve(bet.id, winner).await? {
+            chat::respond!(ctx, "Bet #{} has already been resolved", bet.id);
+            return Ok(());
+        }
+
+        let bet = db::Bet {
+            winner: winner.map(str::to_string),
+            cancelled: winner.is_none(),
+            ..bet
+        };
+
+        let paid = settle(
+            &self.settle_lock,
+            bets,
+            &currency,
+            &self.streamer.user.login,
+            &bet,
+        )
+        .await?;
+
+        let winner = match winner {
+            Some(winner) => winner,
+            None => {
+                ctx.privmsg(format!(
+                    "Bet #{} has been cancelled, all wagers have been refunded",
+                    bet.id
+                ))
+                .await;
+                return Ok(());
+            }
+        };
+
+        if paid.is_empty() {
+            ctx.privmsg(format!(
+                "`{}` won bet #{}, but nobody wagered on it. All wagers have been refunded",
+                winner, bet.id
+            ))
+            .await;
+            return Ok(());
+        }
+
+        let total = paid.iter().map(|(_, amount)| amount).sum::<i64>();
+
+        let mut top = paid;
+        top.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let top = top
+            .iter()
+            .take(3)
+            .map(|(user, amount)| format!("@{user} ({amount})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        ctx.privmsg(format!(
+            "`{}` won bet #{}! {} {} was paid out, top winners: {}",
+            winner, bet.id, total, currency.name, top
+        ))
+        .await;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl command::Handler for Handler {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Bet)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let bets = match self.bets.load().await {
+            Some(bets) => bets,
+            None => return Ok(()),
+        };
+
+        match ctx.next().as_deref() {
+            Some("open") => {
+                ctx.check_scope(auth::Scope::BetAdmin).await?;
+                self.open(ctx, &bets).await?;
+            }
+            Some("lock") => {
+                ctx.check_scope(auth::Scope::BetAdmin).await?;
+
+                let bet = bets
+                    .latest(ctx.channel())
+                    .await?
+                    .ok_or(chat::respond_err!("No bet to lock"))?;
+
+                if bets.lock(bet.id).await? {
+                    ctx.privmsg(format!("Bet #{} is now locked, no more wagers!", bet.id))
+                        .await;
+                } else {
+                    chat::respond!(ctx, "Bet #{} is already locked", bet.id);
+                }
+            }
+            Some("resolve") => {
+                ctx.check_scope(auth::Scope::BetAdmin).await?;
+
+                let outcome = ctx.next_str("<outcome>")?.to_lowercase();
+
+                let bet = bets
+                    .latest(ctx.channel())
+                    .await?
+                    .ok_or(chat::respond_err!("No bet to resolve"))?;
+
+                if !bets
+                    .outcomes(bet.id)
+                    .await?
+                    .iter()
+                    .any(|o| o.key == outcome)
+                {
+                    chat::respond!(ctx, "`{}` is not an outcome of bet #{}", outcome, bet.id);
+                    return Ok(());
+                }
+
+                self.resolve(ctx, &bets, bet, Some(&outcome)).await?;
+            }
+            Some("cancel") => {
+                ctx.check_scope(auth::Scope::BetAdmin).await?;
+
+                let bet = bets
+                    .latest(ctx.channel())
+                    .await?
+                    .ok_or(chat::respond_err!("No bet to cancel"))?;
+
+                self.resolve(ctx, &bets, bet, None).await?;
+            }
+            Some(outcome) => {
+                let outcome = outcome.to_string();
+                self.wager(ctx, &bets, &outcome).await?;
+            }
+            None => match bets.latest(ctx.channel()).await? {
+                Some(bet) if bet.resolved_at.is_none() => {
+                    let outcomes = bets.outcomes(bet.id).await?;
+                    let wagers = bets.wagers(bet.id).await?;
+                    let pool = wagers.iter().map(|w| w.amount).sum::<i64>();
+
+                    let outcomes = outcomes
+                        .iter()
+                        .map(|o| match &o.description {
+                            Some(description) => format!("{} ({})", o.key, description),
+                            None => o.key.clone(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    let state = if bet.locked_at.is_some() {
+                        "locked"
+                    } else {
+                        "open"
+                    };
+
+                    chat::respond!(
+                        ctx,
+                        "Bet #{} is {}: {} -> {}. {} wager(s) with a pool of {}",
+                        bet.id,
+                        state,
+                        bet.title,
+                        outcomes,
+                        wagers.len(),
+                        pool
+                    );
+                }
+                _ => {
+                    chat::respond!(ctx, "No bet is currently running");
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Calculate how much each wager pays out.
+///
+/// Winners split the whole pool proportionally to how much they wagered.
+/// If the bet was cancelled or nobody wagered on the winning outcome,
+/// everyone is refunded.
+fn payouts(wagers: &[(&str, i64)], winner: Option<&str>) -> Vec<i64> {
+    let winner = match winner {
+        Some(winner) => winner,
+        None => return wagers.iter().map(|(_, amount)| *amount).collect(),
+    };
+
+    let pool = wagers
+        .iter()
+        .map(|(_, amount)| *amount as i128)
+        .sum::<i128>();
+
+    let winning = wagers
+        .iter()
+        .filter(|(outcome, _)| *outcome == winner)
+        .map(|(_, amount)| *amount as i128)
+        .sum::<i128>();
+
+    if winning == 0 {
+        return wagers.iter().map(|(_, amount)| *amount).collect();
+    }
+
+    wagers
+        .iter()
+        .map(|(outcome, amount)| {
+            if *outcome == winner {
+                (*amount as i128 * pool / winning) as i64
+            } else {
+                0
+            }
+        })
+        .collect()
+}
+
+/// Pay out all unpaid wagers of a resolved bet.
+///
+/// Each wager is marked as paid as soon as it has been transferred, so
+/// settling can safely be resumed if it's interrupted.
+///
+/// Returns the payouts of all winning wagers.
+async fn settle(
+    settle_lock: &Mutex<()>,
+    bets: &db::Bets,
+    currency: &currency::Currency,
+    bank: &str,
+    bet: &db::Bet,
+) -> Result<Vec<(String, i64)>> {
+    let _guard = settle_lock.lock().await;
+
+    let wagers = bets.wagers(bet.id).await?;
+    let winner = if bet.cancelled {
+        None
+    } else {
+        bet.winner.as_deref()
+    };
+
+    let stakes = wagers
+        .iter()
+        .map(|w| (w.outcome.as_str(), w.amount))
+        .collect::<Vec<_>>();
+
+    let payouts = payouts(&stakes, winner);
+    let mut paid = Vec::new();
+
+    for (wager, payout) in wagers.iter().zip(payouts) {
+        if payout > 0 && winner == Some(wager.outcome.as_str()) {
+            paid.push((wager.user.clone(), payout));
+        }
+
+        if wager.paid {
+            continue;
+        }
+
+        if payout > 0 {
+            currency
+                .balance_transfer(
+                    &bet.channel,
+                    bank,
+                    &wager.user,
+                    payout,
+                    true,
+                    currency::Reason::Bet,
+                )
+                .await?;
+        }
+
+        bets.mark_paid(bet.id, &wager.user).await?;
+    }
+
+    bets.settle(bet.id).await?;
+    Ok(paid)
+}
+
+/// Lock bets whose timer has run out, and finish paying out bets which
+/// were interrupted while settling.
+async fn check(
+    bets: &db::Bets,
+    currency: Option<&currency::Currency>,
+    sender: &chat::Sender,
+    bank: &str,
+    settle_lock: &Mutex<()>,
+) -> Result<()> {
+    let now = Utc::now().naive_utc();
+
+    for bet in bets.expired(sender.channel(), now).await? {
+        if bets.lock(bet.id).await? {
+            sender
+                .privmsg(format!(
+                    "Bet #{} is now locked, no more wagers! {}",
+                    bet.id, bet.title
+                ))
+                .await;
+        }
+    }
+
+    let currency = match currency {
+        Some(currency) => currency,
+        None => return Ok(()),
+    };
+
+    for bet in bets.unsettled(sender.channel()).await? {
+        tracing::info!("Settling interrupted bet #{}", bet.id);
+        settle(settle_lock, bets, currency, bank, &bet).await?;
+    }
+
+    Ok(())
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "bet"
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            tasks,
+            sender,
+            settings,
+            streamer,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("bet");
+        let enabled = settings.var("enabled", false).await?;
+        let bets = injector.var().await;
+        let currency = injector.var().await;
+        let settle_lock = Arc::new(Mutex::new(()));
+
+        handlers.insert(
+            "bet",
+            Handler {
+                enabled: enabled.clone(),
+                duration: settings.var("duration", Duration::seconds(2 * 60)).await?,
+                min_wager: settings.var("min-wager", 1).await?,
+                max_wager: settings.var("max-wager", 0).await?,
+                bets: bets.clone(),
+                currency: currency.clone(),
+                streamer: streamer.clone(),
+                settle_lock: settle_lock.clone(),
+            },
+        );
+
+        let sender = sender.clone();
+        let bank = streamer.user.login.clone();
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+        let future = async move {
+            loop {
+                interval.tick().await;
+
+                if !enabled.load().await {
+                    continue;
+                }
+
+                let bets = match bets.load().await {
+                    Some(bets) => bets,
+                    None => continue,
+                };
+
+                let currency = currency.load().await;
+
+                if let Err(e) = check(&bets, currency.as_ref(), &sender, &bank, &settle_lock).await
+                {
+                    tracing::error!("Failed to check bets: {}", e);
+                }
+            }
+        };
+
+        tasks.push(Box::pin(future));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::payouts;
+
+    #[test]
+    fn test_payouts() {
+        let wagers = [("yes", 100), ("no", 50), ("yes", 300), ("no", 150)];
+        assert_eq!(payouts(&wagers, Some("yes")), vec![150, 0, 450, 0]);
+        assert_eq!(payouts(&wagers, Some("no")), vec![0, 150, 0, 450]);
+        assert_eq!(payouts(&wagers, None), vec![100, 50, 300, 150]);
+        assert_eq!(payouts(&wagers, Some("maybe")), vec![100, 50, 300, 150]);
+    }
+}
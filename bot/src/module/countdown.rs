@@ -1,30 +1,66 @@
+//! Named timers, like countdowns, stopwatches and break timers.
+//!
+//! Timers are stored in the database so that they survive restarts, and the
+//! state of all timers is published on the global bus every second while any
+//! of them are running. If `countdown/path` is set, their text is also written
+//! to that file.
+
 use std::fs;
-use std::path::PathBuf;
-use std::pin::pin;
-use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::path::{Path, PathBuf};
 use std::time;
 
-use anyhow::Result;
-use async_fuse::Fuse;
+use anyhow::{Context as _, Result};
 use async_trait::async_trait;
 use chat::command;
 use chat::module;
-use common::stream::Stream;
-use common::Duration;
+use chrono::{NaiveDateTime, Utc};
+use common::{Channel, Duration, OwnedChannel};
+use db::TimerKind;
 use serde::Serialize;
-use tokio::sync::mpsc;
 
-enum Event {
-    /// Set the countdown.
-    Set(Duration, template::Template),
-    /// Clear the countdown.
-    Clear,
+/// The name of the timer used when no name is given.
+const DEFAULT_NAME: &str = "countdown";
+/// The name of the break timer.
+const BREAK_NAME: &str = "break";
+
+/// Get the template used by timers without one.
+fn default_template(kind: &str) -> &'static str {
+    match kind {
+        "stopwatch" => "{{elapsed}}",
+        "break" => "Back in {{remaining}}",
+        _ => "{{remaining}}",
+    }
 }
 
 pub(crate) struct Handler {
-    sender: mpsc::UnboundedSender<Event>,
     enabled: settings::Var<bool>,
+    timers: async_injector::Ref<db::Timers>,
+}
+
+impl Handler {
+    /// Start a timer using the rest of the command as its template.
+    async fn start(
+        &self,
+        ctx: &mut command::Context<'_>,
+        timers: &db::Timers,
+        name: &str,
+        kind: TimerKind,
+        duration: Option<Duration>,
+    ) -> Result<()> {
+        let template = match ctx.rest().trim() {
+            "" => None,
+            template => Some(template::Template::compile(template)?.source().to_owned()),
+        };
+
+        let duration = duration.map(|d| d.as_std().as_secs() as i64);
+
+        timers
+            .start(ctx.channel(), name, kind, duration, template)
+            .await?;
+
+        chat::respond!(ctx, "Started {} `{}`!", kind, name);
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -38,36 +74,102 @@ impl command::Handler for Handler {
             return Ok(());
         }
 
+        let Some(timers) = self.timers.load().await else {
+            chat::respond!(ctx, "Timers are not available right now :(");
+            return Ok(());
+        };
+
         match ctx.next().as_deref() {
             Some("set") => {
                 let duration = ctx.next_parse("<duration> <template>")?;
-                let template = ctx.rest_parse("<duration> <template>")?;
 
-                match self.sender.send(Event::Set(duration, template)) {
-                    Ok(()) => {
-                        chat::respond!(ctx, "Countdown set!");
-                    }
-                    Err(_) => {
-                        chat::respond!(ctx, "Could not set countdown :(");
-                        return Ok(());
-                    }
+                if ctx.rest().trim().is_empty() {
+                    chat::respond!(ctx, "Expected <duration> <template>");
+                    return Ok(());
+                }
+
+                self.start(
+                    ctx,
+                    &timers,
+                    DEFAULT_NAME,
+                    TimerKind::Countdown,
+                    Some(duration),
+                )
+                .await?;
+            }
+            Some("start") => {
+                let name = ctx.next_str("<name> <duration> [template]")?;
+                let duration = ctx.next_parse("<name> <duration> [template]")?;
+                self.start(ctx, &timers, &name, TimerKind::Countdown, Some(duration))
+                    .await?;
+            }
+            Some("stopwatch") => {
+                let name = ctx.next_str("<name> [template]")?;
+                self.start(ctx, &timers, &name, TimerKind::Stopwatch, None)
+                    .await?;
+            }
+            Some("break") => {
+                let duration = ctx.next_parse("<duration> [template]")?;
+                self.start(ctx, &timers, BREAK_NAME, TimerKind::Break, Some(duration))
+                    .await?;
+            }
+            Some("pause") => {
+                let name = ctx.next().unwrap_or_else(|| DEFAULT_NAME.to_owned());
+
+                match timers.pause(ctx.channel(), &name).await? {
+                    Some(..) => chat::respond!(ctx, "Paused `{}`!", name),
+                    None => chat::respond!(ctx, "No timer named `{}`", name),
                 }
             }
-            Some("clear") => match self.sender.send(Event::Clear) {
-                Ok(()) => {
-                    chat::respond!(ctx, "Countdown cleared!");
+            Some("resume") => {
+                let name = ctx.next().unwrap_or_else(|| DEFAULT_NAME.to_owned());
+
+                match timers.resume(ctx.channel(), &name).await? {
+                    Some(..) => chat::respond!(ctx, "Resumed `{}`!", name),
+                    None => chat::respond!(ctx, "No timer named `{}`", name),
                 }
-                Err(_) => {
-                    chat::respond!(ctx, "Could not clear countdown :(");
+            }
+            Some("reset") => {
+                let name = ctx.next().unwrap_or_else(|| DEFAULT_NAME.to_owned());
+
+                match timers.reset(ctx.channel(), &name).await? {
+                    Some(..) => chat::respond!(ctx, "Reset `{}`!", name),
+                    None => chat::respond!(ctx, "No timer named `{}`", name),
+                }
+            }
+            Some("clear") => {
+                let name = ctx.next().unwrap_or_else(|| DEFAULT_NAME.to_owned());
+
+                if timers.delete(ctx.channel(), &name).await? {
+                    chat::respond!(ctx, "Cleared `{}`!", name);
+                } else {
+                    chat::respond!(ctx, "No timer named `{}`", name);
+                }
+            }
+            Some("list") => {
+                let now = Utc::now().naive_utc();
+                let timers = timers.list(ctx.channel()).await?;
+
+                if timers.is_empty() {
+                    chat::respond!(ctx, "No timers running");
                     return Ok(());
                 }
-            },
+
+                let list = timers
+                    .iter()
+                    .map(|t| {
+                        let state = if t.is_running() { "" } else { ", paused" };
+                        format!("{} ({}{})", t.name, display(t, now), state)
+                    })
+                    .collect::<Vec<_>>();
+
+                chat::respond!(ctx, "Timers: {}", list.join(", "));
+            }
             _ => {
                 chat::respond!(
                     ctx,
-                    "Expected: !countdown set <duration> <template>, or !countdown clear"
+                    "Expected: set, start, stopwatch, break, pause, resume, reset, clear, or list"
                 );
-                return Ok(());
             }
         }
 
@@ -75,195 +177,240 @@ impl command::Handler for Handler {
     }
 }
 
-pub(crate) struct Module;
-
-#[async_trait]
-impl chat::Module for Module {
-    fn ty(&self) -> &'static str {
-        "countdown"
-    }
-
-    /// Set up command handlers for this module.
-    async fn hook(
-        &self,
-        module::HookContext {
-            handlers,
-            tasks,
-            settings,
-            ..
-        }: module::HookContext<'_, '_>,
-    ) -> Result<()> {
-        let settings = settings.scoped("countdown");
-
-        let (mut enabled_stream, enabled) = settings.stream("enabled").or_with(true).await?;
-        let enabled = settings::Var::new(enabled);
-
-        let (mut path_stream, path) = settings.stream::<PathBuf>("path").optional().await?;
+/// Variables available to timer templates.
+#[derive(Serialize)]
+struct Data<'a> {
+    name: &'a str,
+    remaining: String,
+    elapsed: String,
+    duration: String,
+}
 
-        let mut writer = FileWriter::default();
-        writer.path = path;
+/// A timer loaded from the database, with its compiled template.
+struct Loaded {
+    timer: db::Timer,
+    template: template::Template,
+}
 
-        let (sender, mut receiver) = mpsc::unbounded_channel();
+impl Loaded {
+    /// Convert into the state published on the bus.
+    fn state(&self, now: NaiveDateTime) -> Result<bus::Timer> {
+        let elapsed = self.timer.elapsed_at(now).max(0) as u64;
+        let remaining = self.timer.remaining_at(now).map(|r| r as u64);
+        let duration = self.timer.duration.map(|d| d.max(0) as u64);
+
+        let digital = |s: Option<u64>| s.map(|s| Duration::seconds(s).as_digital());
+
+        let text = self.template.render_to_string(Data {
+            name: &self.timer.name,
+            remaining: digital(remaining).unwrap_or_default(),
+            elapsed: Duration::seconds(elapsed).as_digital(),
+            duration: digital(duration).unwrap_or_default(),
+        })?;
+
+        Ok(bus::Timer {
+            name: self.timer.name.clone(),
+            kind: self.timer.kind.clone(),
+            running: self.timer.is_running(),
+            elapsed,
+            remaining,
+            duration,
+            text,
+        })
+    }
+}
 
-        handlers.insert(
-            "countdown",
-            Handler {
-                sender,
-                enabled: enabled.clone(),
-            },
-        );
+/// Display the time on a timer, like it's shown in chat.
+fn display(timer: &db::Timer, now: NaiveDateTime) -> String {
+    match timer.remaining_at(now) {
+        Some(remaining) => format!("{} left", Duration::seconds(remaining as u64).as_digital()),
+        None => Duration::seconds(timer.elapsed_at(now) as u64).as_digital(),
+    }
+}
 
-        let future = async move {
-            let mut timer = pin!(Fuse::empty());
+/// Load all timers in the channel and compile their templates.
+///
+/// Timers with a template which fails to compile are skipped.
+async fn load(timers: &db::Timers, channel: &Channel) -> Result<Vec<Loaded>> {
+    let mut loaded = Vec::new();
 
-            loop {
-                tokio::select! {
-                    update = path_stream.recv() => {
-                        writer.path = update;
-                    }
-                    update = enabled_stream.recv() => {
-                        if !update {
-                            timer.set(Fuse::empty());
-                            writer.clear_log();
-                        }
+    for timer in timers.list(channel).await? {
+        let source = match &timer.template {
+            Some(template) => template.as_str(),
+            None => default_template(&timer.kind),
+        };
 
-                        *enabled.write().await = update;
-                    }
-                    out = timer.as_mut().poll_stream(Stream::poll_next) => {
-                        match out {
-                            Some(()) => if let Some(timer) = timer.as_inner_ref() {
-                                writer.write_log(timer);
-                            },
-                            None => {
-                                writer.clear_log();
-                            },
-                        }
-                    },
-                    Some(event) = receiver.recv() => {
-                        match event {
-                            Event::Set(duration, template) => {
-                                let t = Timer {
-                                    duration,
-                                    elapsed: Default::default(),
-                                    interval: tokio::time::interval(time::Duration::from_secs(1)),
-                                };
-
-                                writer.template = Some(template);
-                                writer.write_log(&t);
-                                timer.set(Fuse::new(t));
-                            }
-                            Event::Clear => {
-                                timer.set(Fuse::empty());
-                                writer.clear_log();
-                            }
-                        }
-                    }
-                }
+        let template = match template::Template::compile(source) {
+            Ok(template) => template,
+            Err(e) => {
+                common::log_error!(e, "Bad template for timer `{}`", timer.name);
+                continue;
             }
         };
 
-        tasks.push(Box::pin(future));
-        Ok(())
+        loaded.push(Loaded { timer, template });
     }
-}
 
-#[derive(Default)]
-struct FileWriter {
-    path: Option<PathBuf>,
-    template: Option<template::Template>,
+    Ok(loaded)
 }
 
-impl FileWriter {
-    fn write(&self, timer: &Timer) -> Result<()> {
-        let path = match &self.path {
-            Some(path) => path,
-            None => return Ok(()),
-        };
-
-        let template = match &self.template {
-            Some(template) => template,
-            None => return Ok(()),
-        };
+/// Write the text of the given timers to a file, one per line.
+///
+/// The file is removed if there are no timers to show.
+fn write_file(path: &Path, states: &[bus::Timer]) -> Result<()> {
+    if states.is_empty() {
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
 
-        tracing::trace!("Writing to log: {}", path.display());
+        return Ok(());
+    }
 
-        let mut f = fs::File::create(path)?;
-        let remaining = timer.duration.saturating_sub(timer.elapsed);
-        let remaining = remaining.as_digital();
-        let elapsed = timer.elapsed.as_digital();
-        let duration = timer.duration.as_digital();
+    let text = states
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
 
-        template.render(
-            &mut f,
-            Data {
-                remaining,
-                elapsed,
-                duration,
-            },
-        )?;
+    fs::write(path, text)?;
+    Ok(())
+}
 
-        return Ok(());
+/// Task which publishes the state of all timers.
+async fn task(
+    timers: db::Timers,
+    global_bus: bus::Bus<bus::Global>,
+    channel: OwnedChannel,
+    enabled: settings::Var<bool>,
+    mut enabled_stream: settings::Stream<bool>,
+    mut path: Option<PathBuf>,
+    mut path_stream: settings::Stream<Option<PathBuf>>,
+) -> Result<()> {
+    let mut changed = timers.subscribe();
+    let mut interval = tokio::time::interval(time::Duration::from_secs(1));
+    let mut loaded = Vec::new();
+    let mut reload = true;
+
+    loop {
+        if reload {
+            reload = false;
+
+            match load(&timers, &channel).await {
+                Ok(update) => loaded = update,
+                Err(e) => common::log_error!(e, "Failed to load timers"),
+            }
+        } else {
+            tokio::select! {
+                update = enabled_stream.recv() => {
+                    *enabled.write().await = update;
+                }
+                update = path_stream.recv() => {
+                    path = update;
+                }
+                result = changed.changed() => {
+                    if result.is_err() {
+                        return Ok(());
+                    }
 
-        #[derive(Serialize)]
-        struct Data {
-            remaining: String,
-            elapsed: String,
-            duration: String,
+                    reload = true;
+                    continue;
+                }
+                _ = interval.tick() => {
+                    if !loaded.iter().any(|t| t.timer.is_running()) {
+                        continue;
+                    }
+                }
+            }
         }
-    }
 
-    fn clear(&self) -> Result<()> {
-        let path = match &self.path {
-            Some(path) => path,
-            None => return Ok(()),
-        };
+        let now = Utc::now().naive_utc();
+        let mut states = Vec::new();
 
-        tracing::trace!("Clearing log: {}", path.display());
+        if enabled.load().await {
+            for t in &loaded {
+                // NB: pausing the timer causes it to be reloaded.
+                if t.timer.is_running() && t.timer.is_finished_at(now) {
+                    if let Err(e) = timers.pause(&channel, &t.timer.name).await {
+                        common::log_error!(e, "Failed to stop timer `{}`", t.timer.name);
+                    }
+                }
 
-        if !path.is_file() {
-            return Ok(());
+                match t.state(now) {
+                    Ok(state) => states.push(state),
+                    Err(e) => {
+                        common::log_error!(e, "Failed to render timer `{}`", t.timer.name)
+                    }
+                }
+            }
         }
 
-        fs::remove_file(path)?;
-        Ok(())
-    }
-
-    /// Attempt to write an update and log on errors.
-    fn write_log(&self, timer: &Timer) {
-        if let Err(e) = self.write(timer) {
-            common::log_error!(e, "Failed to write");
+        if let Some(path) = &path {
+            if let Err(e) = write_file(path, &states) {
+                common::log_error!(e, "Failed to write timers to {}", path.display());
+            }
         }
-    }
 
-    /// Attempt to clear the file and log on errors.
-    fn clear_log(&self) {
-        if let Err(e) = self.clear() {
-            common::log_error!(e, "Failed to clear");
-        }
+        global_bus
+            .send(bus::Global::Timers { timers: states })
+            .await;
     }
 }
 
-struct Timer {
-    duration: Duration,
-    elapsed: Duration,
-    interval: tokio::time::Interval,
-}
+pub(crate) struct Module;
 
-impl Stream for Timer {
-    type Item = ();
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "countdown"
+    }
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match Pin::new(&mut self.as_mut().interval).poll_tick(cx) {
-            Poll::Pending => return Poll::Pending,
-            Poll::Ready(..) => (),
-        }
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            tasks,
+            settings,
+            sender,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("countdown");
 
-        self.as_mut().elapsed += Duration::seconds(1);
+        let (enabled_stream, enabled) = settings.stream("enabled").or_with(true).await?;
+        let enabled = settings::Var::new(enabled);
 
-        if self.as_ref().elapsed >= self.as_ref().duration {
-            return Poll::Ready(None);
-        }
+        let (path_stream, path) = settings.stream::<PathBuf>("path").optional().await?;
 
-        Poll::Ready(Some(()))
+        handlers.insert(
+            "countdown",
+            Handler {
+                enabled: enabled.clone(),
+                timers: injector.var().await,
+            },
+        );
+
+        let timers = injector
+            .get::<db::Timers>()
+            .await
+            .context("timers not available")?;
+
+        let global_bus = injector
+            .get::<bus::Bus<bus::Global>>()
+            .await
+            .context("global bus not available")?;
+
+        tasks.push(Box::pin(task(
+            timers,
+            global_bus,
+            sender.channel().to_owned(),
+            enabled,
+            enabled_stream,
+            path,
+            path_stream,
+        )));
+
+        Ok(())
     }
 }
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chat::command;
+use chat::module;
+use chat::stream_info;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+static DEFAULT_TEMPLATE: &str = "{{#each counters}}{{@key}}: {{this.stream}}\n{{/each}}";
+
+/// Values of a single counter.
+#[derive(Debug, Clone, Serialize)]
+struct Vars {
+    total: i64,
+    stream: i64,
+}
+
+/// Handler for the `!counter` command.
+pub(crate) struct Handler {
+    enabled: settings::Var<bool>,
+    counters: async_injector::Ref<db::Counters>,
+    global_bus: async_injector::Ref<bus::Bus<bus::Global>>,
+    stream_info: stream_info::StreamInfo,
+    sender: mpsc::UnboundedSender<BTreeMap<String, Vars>>,
+}
+
+impl Handler {
+    /// Apply the given operation and report the new value of the counter.
+    async fn modify(
+        &self,
+        ctx: &mut command::Context<'_>,
+        counters: &db::Counters,
+        name: &str,
+        op: db::CounterOp,
+    ) -> Result<()> {
+        let stream_id = self.stream_info.stream_id();
+
+        let counter = counters
+            .modify(ctx.channel(), name, stream_id.as_deref(), op)
+            .await?;
+
+        let stream = counter.stream(stream_id.as_deref());
+
+        chat::respond!(
+            ctx,
+            "{} is now {} this stream ({} in total).",
+            counter.name,
+            stream,
+            counter.count
+        );
+
+        if let Some(global_bus) = self.global_bus.load().await {
+            global_bus
+                .send(bus::Global::Counter {
+                    name: counter.name.clone(),
+                    total: counter.count,
+                    stream,
+                })
+                .await;
+        }
+
+        let mut snapshot = BTreeMap::new();
+
+        for counter in counters.list(ctx.channel()).await? {
+            let vars = Vars {
+                total: counter.count,
+                stream: counter.stream(stream_id.as_deref()),
+            };
+
+            snapshot.insert(counter.name, vars);
+        }
+
+        let _ = self.sender.send(snapshot);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl command::Handler for Handler {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Counter)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let counters = match self.counters.load().await {
+            Some(counters) => counters,
+            None => return Ok(()),
+        };
+
+        let first = match ctx.next() {
+            Some(first) => first,
+            None => {
+                chat::respond!(
+                    ctx,
+                    "Expected: !counter <name>, !counter +<n> <name>, !counter -<n> <name>, \
+                     !counter set <name> <n>, or !counter reset <name>"
+                );
+                return Ok(());
+            }
+        };
+
+        match first.as_str() {
+            "set" => {
+                ctx.check_scope(auth::Scope::CounterEdit).await?;
+                let name = ctx.next_str("<name> <n>")?.to_lowercase();
+                let n = ctx.next_parse("<name> <n>")?;
+                self.modify(ctx, &counters, &name, db::CounterOp::Set(n))
+                    .await?;
+            }
+            "reset" => {
+                ctx.check_scope(auth::Scope::CounterEdit).await?;
+                let name = ctx.next_str("<name>")?.to_lowercase();
+                self.modify(ctx, &counters, &name, db::CounterOp::Reset)
+                    .await?;
+            }
+            "delete" => {
+                ctx.check_scope(auth::Scope::CounterEdit).await?;
+                let name = ctx.next_str("<name>")?.to_lowercase();
+
+                if counters.delete(ctx.channel(), &name).await? {
+                    chat::respond!(ctx, "Deleted counter {}.", name);
+                } else {
+                    chat::respond!(ctx, "No counter named {}.", name);
+                }
+            }
+            delta if delta.starts_with('+') || delta.starts_with('-') => {
+                ctx.check_scope(auth::Scope::CounterEdit).await?;
+
+                let n = match str::parse::<i64>(delta) {
+                    Ok(n) => n,
+                    Err(..) => {
+                        chat::respond!(ctx, "Expected a number like +1 or -1, but got {}", delta);
+                        return Ok(());
+                    }
+                };
+
+                let name = ctx.next_str("<name>")?.to_lowercase();
+                self.modify(ctx, &counters, &name, db::CounterOp::Add(n))
+                    .await?;
+            }
+            name => {
+                let name = name.to_lowercase();
+
+                match counters.get(ctx.channel(), &name).await? {
+                    Some(counter) => {
+                        let stream_id = self.stream_info.stream_id();
+
+                        chat::respond!(
+                            ctx,
+                            "{} is {} this stream ({} in total).",
+                            counter.name,
+                            counter.stream(stream_id.as_deref()),
+                            counter.count
+                        );
+                    }
+                    None => {
+                        chat::respond!(ctx, "No counter named {}.", name);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "counter"
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            tasks,
+            settings,
+            stream_info,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("counter");
+
+        let (mut path_stream, path) = settings.stream::<PathBuf>("path").optional().await?;
+
+        let (mut template_stream, template) = settings
+            .stream("template")
+            .or_with(template::Template::compile(DEFAULT_TEMPLATE)?)
+            .await?;
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        handlers.insert(
+            "counter",
+            Handler {
+                enabled: settings.var("enabled", false).await?,
+                counters: injector.var().await,
+                global_bus: injector.var().await,
+                stream_info: stream_info.clone(),
+                sender,
+            },
+        );
+
+        let mut writer = FileWriter {
+            path,
+            template,
+            counters: None,
+        };
+
+        let future = async move {
+            loop {
+                tokio::select! {
+                    update = path_stream.recv() => {
+                        writer.path = update;
+                    }
+                    update = template_stream.recv() => {
+                        writer.template = update;
+                    }
+                    Some(counters) = receiver.recv() => {
+                        writer.counters = Some(counters);
+                    }
+                }
+
+                writer.write_log();
+            }
+        };
+
+        tasks.push(Box::pin(future));
+        Ok(())
+    }
+}
+
+struct FileWriter {
+    path: Option<PathBuf>,
+    template: template::Template,
+    /// Latest known snapshot of counters.
+    counters: Option<BTreeMap<String, Vars>>,
+}
+
+impl FileWriter {
+    fn write(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let counters = match &self.counters {
+            Some(counters) => counters,
+            None => return Ok(()),
+        };
+
+        tracing::trace!("Writing counters: {}", path.display());
+
+        let mut f = fs::File::create(path)?;
+        self.template.render(&mut f, Data { counters })?;
+        return Ok(());
+
+        #[derive(Serialize)]
+        struct Data<'a> {
+            counters: &'a BTreeMap<String, Vars>,
+        }
+    }
+
+    /// Attempt to write an update and log on errors.
+    fn write_log(&self) {
+        if let Err(e) = self.write() {
+            common::log_error!(e, "Failed to write counters");
+        }
+    }
+}
@@ -0,0 +1,865 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chat::command;
+use chat::module;
+use common::{display, Duration};
+use rand::Rng as _;
+use tokio::sync::Mutex;
+
+/// How frequently to check for expired duels and finished heists.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Scale the given amount by a percentage.
+fn scale(amount: i64, percentage: u32) -> i64 {
+    (amount as i128 * percentage as i128 / 100) as i64
+}
+
+/// Roll a number between 0 and 99 and test if it falls within the given
+/// percentage.
+fn roll(percentage: u32) -> (u32, bool) {
+    let roll = rand::thread_rng().gen_range(0..100);
+    (roll, roll < percentage)
+}
+
+/// Per-user cooldowns.
+#[derive(Default)]
+struct Cooldowns {
+    last: parking_lot::Mutex<HashMap<String, Instant>>,
+}
+
+impl Cooldowns {
+    /// Test how much time remains until the given user is allowed to play
+    /// again.
+    fn check(&self, user: &str, cooldown: Duration) -> Option<std::time::Duration> {
+        let last = *self.last.lock().get(user)?;
+        cooldown.as_std().checked_sub(last.elapsed())
+    }
+
+    /// Start the cooldown for the given user.
+    fn poke(&self, user: &str) {
+        let mut last = self.last.lock();
+        last.retain(|_, at| at.elapsed() < std::time::Duration::from_secs(60 * 60));
+        last.insert(user.to_string(), Instant::now());
+    }
+}
+
+/// Parse the amount to bet, which might be `all` to bet the whole balance.
+///
+/// Betting all is capped by the maximum bet.
+fn parse_amount(amount: &str, balance: i64, max: i64) -> Option<i64> {
+    if amount == "all" {
+        if max > 0 {
+            return Some(balance.min(max));
+        }
+
+        return Some(balance);
+    }
+
+    str::parse(amount).ok()
+}
+
+/// A pending duel challenge.
+struct Challenge {
+    challenger: String,
+    amount: i64,
+    expires_at: Instant,
+}
+
+/// Handler for the `!duel` command.
+pub(crate) struct Duel {
+    enabled: settings::Var<bool>,
+    cooldown: settings::Var<Duration>,
+    max_bet: settings::Var<u32>,
+    timeout: settings::Var<Duration>,
+    currency: async_injector::Ref<currency::Currency>,
+    cooldowns: Cooldowns,
+    /// Pending challenges, by the user being challenged.
+    challenges: Arc<Mutex<HashMap<String, Challenge>>>,
+}
+
+impl Duel {
+    /// Challenge another user to a duel.
+    async fn challenge(
+        &self,
+        ctx: &mut command::Context<'_>,
+        currency: &currency::Currency,
+        target: &str,
+    ) -> Result<()> {
+        let target = db::user_id(target);
+        let amount = ctx.next_parse::<i64, _>("<user> <amount>")?;
+
+        let user = match ctx.user.real() {
+            Some(user) => user,
+            None => {
+                chat::respond!(ctx, "Only real users can duel");
+                return Ok(());
+            }
+        };
+
+        if ctx.user.is(&target) {
+            chat::respond!(ctx, "You can't duel yourself");
+            return Ok(());
+        }
+
+        let max = self.max_bet.load().await as i64;
+
+        if amount <= 0 {
+            chat::respond!(ctx, "You have to bet at least 1 {}", currency.name);
+            return Ok(());
+        }
+
+        if max > 0 && amount > max {
+            chat::respond!(ctx, "You can bet at most {} {}", max, currency.name);
+            return Ok(());
+        }
+
+        if let Some(remaining) = self
+            .cooldowns
+            .check(user.login(), self.cooldown.load().await)
+        {
+            chat::respond!(
+                ctx,
+                "You can duel again in {}",
+                display::compact_duration(remaining)
+            );
+            return Ok(());
+        }
+
+        let balance = currency
+            .balance_of(ctx.channel(), user.login())
+            .await?
+            .unwrap_or_default();
+
+        if balance.balance < amount {
+            chat::respond!(ctx, "Not enough {} to bet {}", currency.name, amount);
+            return Ok(());
+        }
+
+        let timeout = self.timeout.load().await;
+
+        {
+            let mut challenges = self.challenges.lock().await;
+
+            if matches!(challenges.get(&target), Some(c) if c.expires_at > Instant::now()) {
+                chat::respond!(ctx, "{} has already been challenged to a duel", target);
+                return Ok(());
+            }
+
+            challenges.insert(
+                target.clone(),
+                Challenge {
+                    challenger: user.login().to_string(),
+                    amount,
+                    expires_at: Instant::now() + timeout.as_std(),
+                },
+            );
+        }
+
+        self.cooldowns.poke(user.login());
+
+        ctx.privmsg(format!(
+            "@{}, {} has challenged you to a duel for {} {}! \
+             Reply with !duel accept or !duel decline within {}",
+            target,
+            user.display_name(),
+            amount,
+            currency.name,
+            timeout
+        ))
+        .await;
+
+        Ok(())
+    }
+
+    /// Accept a pending challenge.
+    async fn accept(
+        &self,
+        ctx: &mut command::Context<'_>,
+        currency: &currency::Currency,
+    ) -> Result<()> {
+        let user = match ctx.user.real() {
+            Some(user) => user,
+            None => {
+                chat::respond!(ctx, "Only real users can duel");
+                return Ok(());
+            }
+        };
+
+        let challenge = match self.challenges.lock().await.remove(user.login()) {
+            Some(challenge) if challenge.expires_at > Instant::now() => challenge,
+            _ => {
+                chat::respond!(ctx, "Nobody has challenged you to a duel");
+                return Ok(());
+            }
+        };
+
+        for login in [challenge.challenger.as_str(), user.login()] {
+            let balance = currency
+                .balance_of(ctx.channel(), login)
+                .await?
+                .unwrap_or_default();
+
+            if balance.balance < challenge.amount {
+                chat::respond!(
+                    ctx,
+                    "{} doesn't have {} {} anymore, the duel is off",
+                    login,
+                    challenge.amount,
+                    currency.name
+                );
+                return Ok(());
+            }
+        }
+
+        let challenger_wins = rand::thread_rng().gen_bool(0.5);
+
+        let (winner, loser) = if !challenger_wins {
+            (user.login(), challenge.challenger.as_str())
+        } else {
+            (challenge.challenger.as_str(), user.login())
+        };
+
+        let result = currency
+            .balance_transfer(
+                ctx.channel(),
+                loser,
+                winner,
+                challenge.amount,
+                false,
+                currency::Reason::Minigame,
+            )
+            .await;
+
+        match result {
+            Ok(()) => {}
+            Err(currency::BalanceTransferError::NoBalance) => {
+                chat::respond!(
+                    ctx,
+                    "{} doesn't have {} {} anymore, the duel is off",
+                    loser,
+                    challenge.amount,
+                    currency.name
+                );
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        ctx.privmsg(format!(
+            "{} won the duel against {} and takes {} {}!",
+            winner, loser, challenge.amount, currency.name
+        ))
+        .await;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl command::Handler for Duel {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::GamesDuel)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let currency = match self.currency.load().await {
+            Some(currency) => currency,
+            None => {
+                chat::respond!(ctx, "No currency configured for stream, sorry :(");
+                return Ok(());
+            }
+        };
+
+        match ctx.next().as_deref() {
+            Some("accept") => {
+                self.accept(ctx, &currency).await?;
+            }
+            Some("decline") => {
+                let user = match ctx.user.real() {
+                    Some(user) => user,
+                    None => return Ok(()),
+                };
+
+                match self.challenges.lock().await.remove(user.login()) {
+                    Some(challenge) if challenge.expires_at > Instant::now() => {
+                        chat::respond!(ctx, "You declined the duel from {}", challenge.challenger);
+                    }
+                    _ => {
+                        chat::respond!(ctx, "Nobody has challenged you to a duel");
+                    }
+                }
+            }
+            Some(target) => {
+                let target = target.to_string();
+                self.challenge(ctx, &currency, &target).await?;
+            }
+            None => {
+                chat::respond!(ctx, "Expected: <user> <amount>, accept, or decline");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler for the `!gamble` command.
+pub(crate) struct Gamble {
+    enabled: settings::Var<bool>,
+    cooldown: settings::Var<Duration>,
+    max_bet: settings::Var<u32>,
+    win_chance: settings::Var<u32>,
+    currency: async_injector::Ref<currency::Currency>,
+    streamer: api::TwitchAndUser,
+    cooldowns: Cooldowns,
+}
+
+#[async_trait]
+impl command::Handler for Gamble {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::GamesGamble)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let amount = ctx.next_str("<amount|all>")?;
+
+        let user = match ctx.user.real() {
+            Some(user) => user,
+            None => {
+                chat::respond!(ctx, "Only real users can gamble");
+                return Ok(());
+            }
+        };
+
+        let currency = match self.currency.load().await {
+            Some(currency) => currency,
+            None => {
+                chat::respond!(ctx, "No currency configured for stream, sorry :(");
+                return Ok(());
+            }
+        };
+
+        if let Some(remaining) = self
+            .cooldowns
+            .check(user.login(), self.cooldown.load().await)
+        {
+            chat::respond!(
+                ctx,
+                "You can gamble again in {}",
+                display::compact_duration(remaining)
+            );
+            return Ok(());
+        }
+
+        let balance = currency
+            .balance_of(ctx.channel(), user.login())
+            .await?
+            .unwrap_or_default()
+            .balance;
+
+        let max = self.max_bet.load().await as i64;
+
+        let amount = match parse_amount(&amount, balance, max) {
+            Some(amount) if amount > 0 => amount,
+            _ => {
+                chat::respond!(ctx, "Expected a positive amount to gamble, or `all`");
+                return Ok(());
+            }
+        };
+
+        if max > 0 && amount > max {
+            chat::respond!(ctx, "You can gamble at most {} {}", max, currency.name);
+            return Ok(());
+        }
+
+        if balance < amount {
+            chat::respond!(ctx, "Not enough {} to gamble {}", currency.name, amount);
+            return Ok(());
+        }
+
+        self.cooldowns.poke(user.login());
+
+        let (roll, won) = roll(self.win_chance.load().await);
+        let bank = &self.streamer.user.login;
+
+        if won {
+            currency
+                .balance_transfer(
+                    ctx.channel(),
+                    bank,
+                    user.login(),
+                    amount,
+                    true,
+                    currency::Reason::Minigame,
+                )
+                .await?;
+
+            chat::respond!(
+                ctx,
+                "Rolled {} and won {} {}! You now have {}",
+                roll,
+                amount,
+                currency.name,
+                balance.saturating_add(amount)
+            );
+        } else {
+            let result = currency
+                .balance_transfer(
+                    ctx.channel(),
+                    user.login(),
+                    bank,
+                    amount,
+                    false,
+                    currency::Reason::Minigame,
+                )
+                .await;
+
+            match result {
+                Ok(()) => {}
+                Err(currency::BalanceTransferError::NoBalance) => {
+                    chat::respond!(ctx, "Not enough {} to gamble {}", currency.name, amount);
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            chat::respond!(
+                ctx,
+                "Rolled {} and lost {} {}. You now have {}",
+                roll,
+                amount,
+                currency.name,
+                balance.saturating_sub(amount)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// A heist being planned.
+struct Crew {
+    ends_at: Instant,
+    /// Members of the crew and their stakes.
+    members: Vec<(String, i64)>,
+}
+
+#[derive(Default)]
+struct HeistState {
+    running: Option<Crew>,
+    last_ended: Option<Instant>,
+    /// Payouts and refunds which couldn't be transferred yet.
+    owed: Vec<(String, i64)>,
+}
+
+/// Handler for the `!heist` command.
+pub(crate) struct Heist {
+    enabled: settings::Var<bool>,
+    cooldown: settings::Var<Duration>,
+    max_bet: settings::Var<u32>,
+    join_duration: settings::Var<Duration>,
+    currency: async_injector::Ref<currency::Currency>,
+    streamer: api::TwitchAndUser,
+    state: Arc<Mutex<HeistState>>,
+}
+
+#[async_trait]
+impl command::Handler for Heist {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::GamesHeist)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let amount = ctx.next_parse::<i64, _>("<amount>")?;
+
+        let user = match ctx.user.real() {
+            Some(user) => user,
+            None => {
+                chat::respond!(ctx, "Only real users can join a heist");
+                return Ok(());
+            }
+        };
+
+        let currency = match self.currency.load().await {
+            Some(currency) => currency,
+            None => {
+                chat::respond!(ctx, "No currency configured for stream, sorry :(");
+                return Ok(());
+            }
+        };
+
+        let max = self.max_bet.load().await as i64;
+
+        if amount <= 0 {
+            chat::respond!(ctx, "You have to bring at least 1 {}", currency.name);
+            return Ok(());
+        }
+
+        if max > 0 && amount > max {
+            chat::respond!(ctx, "You can bring at most {} {}", max, currency.name);
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().await;
+
+        let starting = match &state.running {
+            Some(heist) => {
+                if heist
+                    .members
+                    .iter()
+                    .any(|(member, _)| member == user.login())
+                {
+                    chat::respond!(ctx, "You're already part of the crew");
+                    return Ok(());
+                }
+
+                false
+            }
+            None => {
+                let cooldown = self.cooldown.load().await;
+
+                if let Some(remaining) = state
+                    .last_ended
+                    .and_then(|at| cooldown.as_std().checked_sub(at.elapsed()))
+                {
+                    chat::respond!(
+                        ctx,
+                        "The crew is laying low, try again in {}",
+                        display::compact_duration(remaining)
+                    );
+                    return Ok(());
+                }
+
+                true
+            }
+        };
+
+        let result = currency
+            .balance_transfer(
+                ctx.channel(),
+                user.login(),
+                &self.streamer.user.login,
+                amount,
+                false,
+                currency::Reason::Minigame,
+            )
+            .await;
+
+        match result {
+            Ok(()) => {}
+            Err(currency::BalanceTransferError::NoBalance) => {
+                chat::respond!(ctx, "Not enough {} to bring {}", currency.name, amount);
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        if starting {
+            let join_duration = self.join_duration.load().await;
+
+            state.running = Some(Crew {
+                ends_at: Instant::now() + join_duration.as_std(),
+                members: vec![(user.login().to_string(), amount)],
+            });
+
+            ctx.privmsg(format!(
+                "{} is planning a heist! Join the crew with !heist <amount> within {}",
+                user.display_name(),
+                join_duration
+            ))
+            .await;
+        } else if let Some(heist) = &mut state.running {
+            heist.members.push((user.login().to_string(), amount));
+
+            chat::respond!(
+                ctx,
+                "You joined the crew with {} {}, {} member(s) so far",
+                amount,
+                currency.name,
+                heist.members.len()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Announce expired duel challenges and carry out heists whose join window
+/// has ended.
+async fn check(
+    challenges: &Mutex<HashMap<String, Challenge>>,
+    heist: &Mutex<HeistState>,
+    currency: Option<&currency::Currency>,
+    sender: &chat::Sender,
+    bank: &str,
+    success_chance: u32,
+    payout: u32,
+) -> Result<()> {
+    let now = Instant::now();
+
+    let expired = {
+        let mut challenges = challenges.lock().await;
+        let mut expired = Vec::new();
+
+        challenges.retain(|target, c| {
+            if c.expires_at > now {
+                return true;
+            }
+
+            expired.push((c.challenger.clone(), target.clone()));
+            false
+        });
+
+        expired
+    };
+
+    for (challenger, target) in expired {
+        sender
+            .privmsg(format!(
+                "{target} didn't respond to the duel from {challenger} in time"
+            ))
+            .await;
+    }
+
+    let mut state = heist.lock().await;
+
+    if let Some(currency) = currency {
+        let owed = std::mem::take(&mut state.owed);
+        state.owed = pay(currency, sender, bank, owed).await;
+    }
+
+    if !matches!(&state.running, Some(heist) if heist.ends_at <= now) {
+        return Ok(());
+    }
+
+    let Some(crew) = state.running.take() else {
+        return Ok(());
+    };
+
+    state.last_ended = Some(now);
+
+    let Some(currency) = currency else {
+        sender
+            .privmsg("The heist was called off since there's no stream currency, stakes will be refunded")
+            .await;
+        state.owed.extend(crew.members);
+        return Ok(());
+    };
+
+    let total = crew.members.iter().map(|(_, stake)| *stake).sum::<i64>();
+    let (_, success) = roll(success_chance);
+
+    if !success {
+        sender
+            .privmsg(format!(
+                "The heist failed! The crew of {} lost {} {}",
+                crew.members.len(),
+                total,
+                currency.name
+            ))
+            .await;
+
+        return Ok(());
+    }
+
+    let payouts = crew
+        .members
+        .into_iter()
+        .map(|(member, stake)| (member, scale(stake, payout)))
+        .collect::<Vec<_>>();
+
+    let paid = payouts
+        .iter()
+        .map(|(member, amount)| format!("{member} ({amount})"))
+        .collect::<Vec<_>>();
+
+    // Members who couldn't be paid are retried on the next check.
+    let failed = pay(currency, sender, bank, payouts).await;
+    state.owed.extend(failed);
+
+    sender
+        .privmsg(format!(
+            "The heist succeeded! Payouts in {}: {}",
+            currency.name,
+            paid.join(", ")
+        ))
+        .await;
+
+    Ok(())
+}
+
+/// Transfer the given amounts from the bank to each user.
+///
+/// Returns the transfers which failed.
+async fn pay(
+    currency: &currency::Currency,
+    sender: &chat::Sender,
+    bank: &str,
+    owed: Vec<(String, i64)>,
+) -> Vec<(String, i64)> {
+    let mut failed = Vec::new();
+
+    for (user, amount) in owed {
+        let result = currency
+            .balance_transfer(
+                sender.channel(),
+                bank,
+                &user,
+                amount,
+                true,
+                currency::Reason::Minigame,
+            )
+            .await;
+
+        if let Err(e) = result {
+            common::log_error!(e, "Failed to pay {} to {} for heist", amount, user);
+            failed.push((user, amount));
+        }
+    }
+
+    failed
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "games"
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            tasks,
+            sender,
+            settings,
+            streamer,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("games");
+        let currency = injector.var().await;
+
+        let challenges = Arc::new(Mutex::new(HashMap::new()));
+
+        handlers.insert(
+            "duel",
+            Duel {
+                enabled: settings.var("duel/enabled", false).await?,
+                cooldown: settings.var("duel/cooldown", Duration::seconds(60)).await?,
+                max_bet: settings.var("duel/max-bet", 0).await?,
+                timeout: settings.var("duel/timeout", Duration::seconds(60)).await?,
+                currency: currency.clone(),
+                cooldowns: Cooldowns::default(),
+                challenges: challenges.clone(),
+            },
+        );
+
+        handlers.insert(
+            "gamble",
+            Gamble {
+                enabled: settings.var("gamble/enabled", false).await?,
+                cooldown: settings
+                    .var("gamble/cooldown", Duration::seconds(60))
+                    .await?,
+                max_bet: settings.var("gamble/max-bet", 0).await?,
+                win_chance: settings.var("gamble/win%", 45).await?,
+                currency: currency.clone(),
+                streamer: streamer.clone(),
+                cooldowns: Cooldowns::default(),
+            },
+        );
+
+        let heist_enabled = settings.var("heist/enabled", false).await?;
+        let success_chance = settings.var("heist/success%", 50).await?;
+        let payout = settings.var("heist/payout%", 200).await?;
+        let heist = Arc::new(Mutex::new(HeistState::default()));
+
+        handlers.insert(
+            "heist",
+            Heist {
+                enabled: heist_enabled.clone(),
+                cooldown: settings
+                    .var("heist/cooldown", Duration::seconds(10 * 60))
+                    .await?,
+                max_bet: settings.var("heist/max-bet", 0).await?,
+                join_duration: settings
+                    .var("heist/join-duration", Duration::seconds(60))
+                    .await?,
+                currency: currency.clone(),
+                streamer: streamer.clone(),
+                state: heist.clone(),
+            },
+        );
+
+        let sender = sender.clone();
+        let bank = streamer.user.login.clone();
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+        let future = async move {
+            loop {
+                interval.tick().await;
+
+                let currency = currency.load().await;
+                let success_chance = success_chance.load().await;
+                let payout = payout.load().await;
+
+                if let Err(e) = check(
+                    &challenges,
+                    &heist,
+                    currency.as_ref(),
+                    &sender,
+                    &bank,
+                    success_chance,
+                    payout,
+                )
+                .await
+                {
+                    tracing::error!("Failed to check games: {}", e);
+                }
+            }
+        };
+
+        tasks.push(Box::pin(future));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_amount, scale};
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(scale(100, 200), 200);
+        assert_eq!(scale(33, 150), 49);
+        assert_eq!(scale(100, 0), 0);
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("all", 500, 0), Some(500));
+        assert_eq!(parse_amount("all", 500, 100), Some(100));
+        assert_eq!(parse_amount("42", 500, 100), Some(42));
+        assert_eq!(parse_amount("lots", 500, 100), None);
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use chat::command;
+use chat::module;
+use chrono::{DateTime, Utc};
+use common::Duration;
+use tokio::sync::Mutex;
+
+/// The giveaway which is currently being tracked.
+#[derive(Default)]
+struct State {
+    /// The tracked giveaway.
+    giveaway_id: Option<i32>,
+    /// The keyword used to enter, if the giveaway is still open.
+    keyword: Option<String>,
+    /// When users were last seen chatting while the giveaway was open.
+    last_seen: HashMap<String, DateTime<Utc>>,
+}
+
+impl State {
+    /// Track the given giveaway.
+    fn track(&mut self, giveaway: &db::Giveaway) {
+        if self.giveaway_id != Some(giveaway.id) {
+            self.giveaway_id = Some(giveaway.id);
+            self.last_seen.clear();
+        }
+
+        self.keyword = match giveaway.closed_at {
+            Some(..) => None,
+            None => Some(giveaway.keyword.to_lowercase()),
+        };
+    }
+}
+
+/// Handler for the `!giveaway` command.
+pub(crate) struct Handler {
+    enabled: settings::Var<bool>,
+    ticket_price: settings::Var<u32>,
+    max_tickets: settings::Var<u32>,
+    subscriber_luck: settings::Var<u32>,
+    active_within: settings::Var<Duration>,
+    giveaways: async_injector::Ref<db::Giveaways>,
+    currency: async_injector::Ref<currency::Currency>,
+    streamer: api::TwitchAndUser,
+    state: Arc<Mutex<State>>,
+}
+
+impl Handler {
+    /// Stop accepting entries for the currently tracked giveaway.
+    async fn stop_entries(&self) {
+        self.state.lock().await.keyword = None;
+    }
+
+    /// Draw a winner among the eligible entrants of the given giveaway.
+    async fn draw(
+        &self,
+        giveaways: &db::Giveaways,
+        giveaway: &db::Giveaway,
+    ) -> Result<Option<String>> {
+        use rand::distributions::{Distribution as _, WeightedIndex};
+
+        let luck = self.subscriber_luck.load().await.max(1);
+        let active_within = self.active_within.load().await;
+
+        let last_seen = {
+            let state = self.state.lock().await;
+
+            if state.giveaway_id == Some(giveaway.id) {
+                state.last_seen.clone()
+            } else {
+                HashMap::new()
+            }
+        };
+
+        // Entrants need to have been seen recently relative to when the
+        // giveaway was closed.
+        let closed_at = giveaway
+            .closed_at
+            .map(|closed_at| DateTime::<Utc>::from_naive_utc_and_offset(closed_at, Utc))
+            .unwrap_or_else(Utc::now);
+
+        let cutoff = if active_within.is_empty() {
+            None
+        } else {
+            Some(closed_at - active_within.as_chrono())
+        };
+
+        let mut candidates = Vec::new();
+        let mut weights = Vec::new();
+
+        for entrant in giveaways.entrants(giveaway.id).await? {
+            if entrant.drawn || entrant.tickets <= 0 {
+                continue;
+            }
+
+            // NB: activity is only tracked in memory, so entrants we haven't
+            // seen since the bot was started are given the benefit of the
+            // doubt.
+            if let (Some(cutoff), Some(seen)) = (cutoff, last_seen.get(&entrant.user)) {
+                let entered_at =
+                    DateTime::<Utc>::from_naive_utc_and_offset(entrant.entered_at, Utc);
+
+                if (*seen).max(entered_at) < cutoff {
+                    continue;
+                }
+            }
+
+            let weight = if entrant.subscriber {
+                entrant.tickets as u64 * luck as u64
+            } else {
+                entrant.tickets as u64
+            };
+
+            weights.push(weight);
+            candidates.push(entrant.user);
+        }
+
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let index = WeightedIndex::new(&weights)?.sample(&mut rand::thread_rng());
+        let winner = candidates.swap_remove(index);
+        giveaways.set_winner(giveaway.id, &winner).await?;
+        Ok(Some(winner))
+    }
+}
+
+#[async_trait]
+impl command::Handler for Handler {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Giveaway)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let giveaways = match self.giveaways.load().await {
+            Some(giveaways) => giveaways,
+            None => return Ok(()),
+        };
+
+        let latest = giveaways.latest(ctx.channel()).await?;
+
+        if let Some(latest) = &latest {
+            self.state.lock().await.track(latest);
+        }
+
+        match ctx.next().as_deref() {
+            Some("open") => {
+                ctx.check_scope(auth::Scope::GiveawayAdmin).await?;
+
+                let keyword = ctx.next_str("<keyword>")?;
+                let giveaway = giveaways.open(ctx.channel(), &keyword).await?;
+                self.state.lock().await.track(&giveaway);
+
+                let price = self.ticket_price.load().await;
+
+                if price > 0 {
+                    ctx.privmsg(format!(
+                        "A giveaway has started! Type {keyword} to enter, or buy extra tickets \
+                         for {price} each with !giveaway tickets <n>"
+                    ))
+                    .await;
+                } else {
+                    ctx.privmsg(format!("A giveaway has started! Type {keyword} to enter"))
+                        .await;
+                }
+            }
+            Some("close") => {
+                ctx.check_scope(auth::Scope::GiveawayAdmin).await?;
+
+                let giveaway = latest.ok_or(chat::respond_err!("No giveaway to close"))?;
+                self.stop_entries().await;
+
+                if giveaways.close(giveaway.id).await? {
+                    let count = giveaways.entrants(giveaway.id).await?.len();
+                    ctx.privmsg(format!(
+                        "The giveaway is now closed with {count} entrant(s). Good luck!"
+                    ))
+                    .await;
+                } else {
+                    chat::respond!(ctx, "Giveaway is already closed");
+                }
+            }
+            Some(command @ ("draw" | "redraw")) => {
+                ctx.check_scope(auth::Scope::GiveawayAdmin).await?;
+
+                let giveaway = latest.ok_or(chat::respond_err!("No giveaway to draw from"))?;
+                self.stop_entries().await;
+                giveaways.close(giveaway.id).await?;
+
+                if command == "redraw" {
+                    if let Some(previous) = &giveaway.winner {
+                        ctx.privmsg(format!("Redrawing, @{previous} is out of luck..."))
+                            .await;
+                    }
+                }
+
+                match self.draw(&giveaways, &giveaway).await? {
+                    Some(winner) => {
+                        ctx.privmsg(format!(
+                            "The winner of the giveaway is @{winner}! Congratulations!"
+                        ))
+                        .await;
+                    }
+                    None => {
+                        chat::respond!(ctx, "There are no eligible entrants left to draw from");
+                    }
+                }
+            }
+            Some("tickets") => {
+                let n = ctx.next_parse::<u32, _>("<n>")?;
+
+                if n == 0 {
+                    chat::respond!(ctx, "Expected a positive number of tickets");
+                    return Ok(());
+                }
+
+                let user = match ctx.user.real() {
+                    Some(user) => user,
+                    None => {
+                        chat::respond!(ctx, "Only real users can buy tickets");
+                        return Ok(());
+                    }
+                };
+
+                let giveaway = match latest {
+                    Some(giveaway) if giveaway.closed_at.is_none() => giveaway,
+                    _ => {
+                        chat::respond!(ctx, "There is no open giveaway");
+                        return Ok(());
+                    }
+                };
+
+                let price = self.ticket_price.load().await;
+
+                if price == 0 {
+                    chat::respond!(ctx, "Tickets are not for sale in this giveaway");
+                    return Ok(());
+                }
+
+                let currency = match self.currency.load().await {
+                    Some(currency) => currency,
+                    None => {
+                        chat::respond!(ctx, "No currency configured for stream, sorry :(");
+                        return Ok(());
+                    }
+                };
+
+                let max = self.max_tickets.load().await;
+
+                let bought = match giveaways.entrant(giveaway.id, user.login()).await? {
+                    Some(entrant) => entrant.tickets.saturating_sub(1).max(0) as u32,
+                    None => 0,
+                };
+
+                if max > 0 && bought.saturating_add(n) > max {
+                    chat::respond!(
+                        ctx,
+                        "You can buy at most {} ticket(s), and you already have {}",
+                        max,
+                        bought
+                    );
+                    return Ok(());
+                }
+
+                let cost = price as i64 * n as i64;
+
+                let result = currency
+                    .balance_transfer(
+                        ctx.channel(),
+                        user.login(),
+                        &self.streamer.user.login,
+                        cost,
+                        false,
+                        currency::Reason::Giveaway,
+                    )
+                    .await;
+
+                match result {
+                    Ok(()) => {}
+                    Err(currency::BalanceTransferError::NoBalance) => {
+                        chat::respond!(
+                            ctx,
+                            "Not enough {} to buy {} ticket(s) for {}",
+                            currency.name,
+                            n,
+                            cost
+                        );
+                        return Ok(());
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+
+                let subscriber = user.roles().contains(&auth::Role::Subscriber);
+                let max = (max > 0).then_some(max as i32);
+
+                let result = giveaways
+                    .buy_tickets(giveaway.id, user.login(), n as i32, subscriber, max)
+                    .await;
+
+                if !matches!(result, Ok(db::TicketPurchase::Bought)) {
+                    let refund = currency
+                        .balance_transfer(
+                            ctx.channel(),
+                            &self.streamer.user.login,
+                            user.login(),
+                            cost,
+                            true,
+                            currency::Reason::Giveaway,
+                        )
+                        .await;
+
+                    if let Err(e) = refund {
+                        common::log_error!(
+                            e,
+                            "Failed to refund {} for giveaway tickets to {}",
+                            cost,
+                            user.login()
+                        );
+                    }
+                }
+
+                match result? {
+                    db::TicketPurchase::Bought => {
+                        chat::respond!(
+                            ctx,
+                            "Bought {} ticket(s) for {} {}!",
+                            n,
+                            cost,
+                            currency.name
+                        );
+                    }
+                    db::TicketPurchase::Closed => {
+                        chat::respond!(ctx, "The giveaway was closed, no tickets were bought");
+                    }
+                    db::TicketPurchase::Exceeded(bought) => {
+                        chat::respond!(
+                            ctx,
+                            "You can buy at most {} ticket(s), and you already have {}",
+                            max.unwrap_or_default(),
+                            bought
+                        );
+                    }
+                }
+            }
+            None => match latest {
+                Some(giveaway) if giveaway.closed_at.is_none() => {
+                    let count = giveaways.entrants(giveaway.id).await?.len();
+
+                    chat::respond!(
+                        ctx,
+                        "A giveaway is running with {} entrant(s), type {} to enter!",
+                        count,
+                        giveaway.keyword
+                    );
+                }
+                _ => {
+                    chat::respond!(ctx, "No giveaway is currently running");
+                }
+            },
+            Some(..) => {
+                chat::respond!(
+                    ctx,
+                    "Expected: !giveaway, !giveaway tickets <n>, or one of open, close, draw, redraw"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Hook which enters users who type the keyword of the open giveaway.
+struct EntryHook {
+    enabled: settings::Var<bool>,
+    giveaways: db::Giveaways,
+    state: Arc<Mutex<State>>,
+}
+
+#[async_trait]
+impl command::MessageHook for EntryHook {
+    async fn peek(&self, user: &chat::User, m: &str) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let user = match user.real() {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let giveaway_id = {
+            let mut state = self.state.lock().await;
+
+            let (Some(giveaway_id), Some(keyword)) = (state.giveaway_id, &state.keyword) else {
+                return Ok(());
+            };
+
+            let entered = common::words::trimmed(m)
+                .next()
+                .map_or(false, |first| first.to_lowercase() == *keyword);
+
+            state.last_seen.insert(user.login().to_string(), Utc::now());
+
+            if !entered {
+                return Ok(());
+            }
+
+            giveaway_id
+        };
+
+        let subscriber = user.roles().contains(&auth::Role::Subscriber);
+        self.giveaways
+            .enter(giveaway_id, user.login(), subscriber)
+            .await?;
+        Ok(())
+    }
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "giveaway"
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            hooks,
+            settings,
+            sender,
+            streamer,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("giveaway");
+        let enabled = settings.var("enabled", false).await?;
+
+        let giveaways = injector
+            .get::<db::Giveaways>()
+            .await
+            .context("giveaways not available")?;
+
+        // Keep accepting entries for a giveaway which was left open across
+        // restarts.
+        let mut state = State::default();
+
+        if let Some(giveaway) = giveaways.latest(sender.channel()).await? {
+            state.track(&giveaway);
+        }
+
+        let state = Arc::new(Mutex::new(state));
+
+        hooks
+            .insert(EntryHook {
+                enabled: enabled.clone(),
+                giveaways,
+                state: state.clone(),
+            })
+            .await;
+
+        handlers.insert(
+            "giveaway",
+            Handler {
+                enabled,
+                ticket_price: settings.var("ticket-price", 0).await?,
+                max_tickets: settings.var("max-tickets", 10).await?,
+                subscriber_luck: settings.var("subscriber-luck", 2).await?,
+                active_within: settings
+                    .var("active-within", Duration::seconds(10 * 60))
+                    .await?,
+                giveaways: injector.var().await,
+                currency: injector.var().await,
+                streamer: streamer.clone(),
+                state,
+            },
+        );
+
+        Ok(())
+    }
+}
@@ -894,8 +894,10 @@ impl Handler {
 #[async_trait]
 impl command::Handler for Handler {
     async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        // NB: commands which aren't sent to ChaosMod fail, so that
+        // redemptions running them can be refunded.
         if !self.enabled.load().await {
-            return Ok(());
+            return Err(chat::respond_err!().into());
         }
 
         let currency = self.currency.load().await.ok_or(chat::respond_err!(
@@ -926,13 +928,13 @@ impl command::Handler for Handler {
                     other - To do other kinds of modifications.",
                 );
 
-                return Ok(());
+                return Err(chat::respond_err!().into());
             }
         };
 
         let (command, percentage) = match result {
             Some((command, percentage)) => (command, percentage),
-            None => return Ok(()),
+            None => return Err(chat::respond_err!().into()),
         };
 
         let mut cost = command.cost();
@@ -942,7 +944,7 @@ impl command::Handler for Handler {
 
             if let Some(setting) = per_command_configs.get(command.command_name()) {
                 if !setting.enabled {
-                    return Ok(());
+                    return Err(chat::respond_err!().into());
                 }
 
                 if let Some(c) = setting.cost {
@@ -957,14 +959,12 @@ impl command::Handler for Handler {
             if let Some((what, remaining)) =
                 self.check_cooldown(ctx, &command, category_cooldown).await
             {
-                chat::respond!(
-                    ctx,
+                return Err(chat::respond_err!(
                     "{} cooldown in effect, please wait at least {}!",
                     what,
                     display::compact_duration(remaining),
-                );
-
-                return Ok(());
+                )
+                .into());
             }
         }
 
@@ -1000,11 +1000,16 @@ impl command::Handler for Handler {
                     balance = balance,
                 );
 
-                return Ok(());
+                return Err(chat::respond_err!().into());
             }
 
             currency
-                .balance_add(ctx.channel(), real.login(), -(cost as i64))
+                .balance_add(
+                    ctx.channel(),
+                    real.login(),
+                    -(cost as i64),
+                    currency::Reason::Gtav,
+                )
                 .await?;
         }
 
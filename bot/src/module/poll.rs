@@ -1,19 +1,119 @@
-use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chat::command;
 use chat::module;
-use chrono::{DateTime, Utc};
-use std::collections::{HashMap, HashSet};
+use chrono::{NaiveDateTime, Utc};
+use common::Duration;
 use tokio::sync::Mutex;
 
-/// Handler for the !poll command.
-pub(crate) struct Poll {
+/// How frequently to check for timed polls which should be closed.
+const CLOSE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A poll which is accepting votes.
+struct OpenPoll {
+    question: String,
+    closes_at: Option<NaiveDateTime>,
+    keys: HashSet<String>,
+}
+
+impl OpenPoll {
+    /// Construct from a persisted poll and its options.
+    fn new(poll: &db::Poll, options: Vec<db::PollOption>) -> Self {
+        Self {
+            question: poll.question.clone(),
+            closes_at: poll.closes_at,
+            keys: options.into_iter().map(|o| o.key).collect(),
+        }
+    }
+}
+
+type OpenPolls = Arc<Mutex<HashMap<i32, OpenPoll>>>;
+
+/// Handler for the `!poll` command.
+pub(crate) struct Handler {
     enabled: settings::Var<bool>,
-    polls: Mutex<HashMap<command::HookId, ActivePoll>>,
+    polls: async_injector::Ref<db::Polls>,
+    global_bus: async_injector::Ref<bus::Bus<bus::Global>>,
+    open: OpenPolls,
+}
+
+impl Handler {
+    /// Start a new poll, optionally closing after the given duration.
+    async fn start(
+        &self,
+        ctx: &mut command::Context<'_>,
+        polls: &db::Polls,
+        duration: Option<Duration>,
+    ) -> Result<()> {
+        let question = ctx.next_str("<question> <options...>")?;
+
+        let mut options = Vec::new();
+
+        for option in ctx.by_ref() {
+            let (key, description) = match option.find('=') {
+                Some(i) => {
+                    let (keyword, description) = option.split_at(i);
+                    (keyword.to_string(), Some(description[1..].to_string()))
+                }
+                None => (option, None),
+            };
+
+            options.push((key.to_lowercase(), description));
+        }
+
+        if options.is_empty() {
+            chat::respond!(ctx, "Expected: <question> <options...>");
+            return Ok(());
+        }
+
+        let closes_at = duration.map(|d| (Utc::now() + d.as_chrono()).naive_utc());
+
+        let poll = polls
+            .create(ctx.channel(), &question, options, closes_at)
+            .await?;
+
+        let options = polls.options(poll.id).await?;
+        self.open
+            .lock()
+            .await
+            .insert(poll.id, OpenPoll::new(&poll, options));
+
+        publish(&self.global_bus, polls, poll.id, &poll.question, false).await?;
+
+        match duration {
+            Some(duration) => {
+                chat::respond!(
+                    ctx,
+                    "Started poll `{}` (id: {}), closing in {}",
+                    question,
+                    poll.id,
+                    duration
+                );
+            }
+            None => {
+                chat::respond!(ctx, "Started poll `{}` (id: {})", question, poll.id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse an optional poll id from the command.
+    fn next_id(ctx: &mut command::Context<'_>) -> Result<Option<i32>> {
+        match ctx.next() {
+            Some(id) => Ok(Some(
+                str::parse::<i32>(&id).map_err(|_| chat::respond_err!("Bad id `{}`", id))?,
+            )),
+            None => Ok(None),
+        }
+    }
 }
 
 #[async_trait]
-impl command::Handler for Poll {
+impl command::Handler for Handler {
     fn scope(&self) -> Option<auth::Scope> {
         Some(auth::Scope::Poll)
     }
@@ -23,81 +123,70 @@ impl command::Handler for Poll {
             return Ok(());
         }
 
+        let polls = match self.polls.load().await {
+            Some(polls) => polls,
+            None => return Ok(()),
+        };
+
         match ctx.next().as_deref() {
             Some("run") => {
-                let question = ctx.next_str("<question> <options...>")?;
-
-                let mut options = HashMap::new();
-
-                for option in ctx.by_ref() {
-                    let (key, description) = match option.find('=') {
-                        Some(i) => {
-                            let (keyword, description) = option.split_at(i);
-                            (keyword.to_string(), Some(description[1..].to_string()))
-                        }
-                        None => (option, None),
-                    };
+                self.start(ctx, &polls, None).await?;
+            }
+            Some("timed") => {
+                let duration =
+                    ctx.next_parse::<Duration, _>("<duration> <question> <options...>")?;
 
-                    options.insert(key.to_lowercase(), description);
+                if duration.is_empty() {
+                    chat::respond!(ctx, "Expected a non-empty duration");
+                    return Ok(());
                 }
 
-                let poll = ActivePoll {
-                    question: question.clone(),
-                    created_at: Utc::now(),
-                    options,
-                    inner: settings::Var::new(Inner {
-                        voted: Default::default(),
-                        votes: Default::default(),
-                    }),
-                };
-
-                let hook_id = ctx.insert_hook(poll.clone()).await;
-                self.polls.lock().await.insert(hook_id, poll);
-                ctx.respond(format!("Started poll `{question}` (id: {hook_id})"))
-                    .await;
+                self.start(ctx, &polls, Some(duration)).await?;
             }
             Some("close") => {
-                let mut polls = self.polls.lock().await;
-
-                let id = match ctx.next() {
-                    Some(id) => str::parse::<command::HookId>(&id)
-                        .map_err(|_| chat::respond_err!("Bad id `{}`", id))?,
-                    None => {
-                        *polls
-                            .iter()
-                            .max_by_key(|e| e.1.created_at)
-                            .ok_or(chat::respond_err!("No running polls"))?
-                            .0
-                    }
+                let poll = match Self::next_id(ctx)? {
+                    Some(id) => polls
+                        .get(ctx.channel(), id)
+                        .await?
+                        .ok_or(chat::respond_err!("No poll with id `{}`!", id))?,
+                    None => polls
+                        .open(ctx.channel())
+                        .await?
+                        .into_iter()
+                        .next()
+                        .ok_or(chat::respond_err!("No running polls"))?,
                 };
 
-                let poll = polls
-                    .remove(&id)
-                    .ok_or(chat::respond_err!("No poll with id `{}`!", id))?;
-
-                ctx.remove_hook(id).await;
-                let results = poll.close().await;
+                self.open.lock().await.remove(&poll.id);
 
-                let total = results.iter().map(|(_, c)| c).sum::<u32>();
+                if !polls.close(poll.id).await? {
+                    chat::respond!(ctx, "Poll #{} is already closed", poll.id);
+                    return Ok(());
+                }
 
-                let mut formatted = Vec::new();
+                let results =
+                    publish(&self.global_bus, &polls, poll.id, &poll.question, true).await?;
+                ctx.respond(format_results(&poll.question, results)).await;
+            }
+            Some("results") => {
+                let id = Self::next_id(ctx)?.ok_or(chat::respond_err!("Expected: <id>"))?;
 
-                for (key, votes) in results {
-                    let p = common::percentage(votes, total);
+                let poll = polls
+                    .get(ctx.channel(), id)
+                    .await?
+                    .ok_or(chat::respond_err!("No poll with id `{}`!", id))?;
 
-                    let votes = match votes {
-                        0 => "no votes".to_string(),
-                        1 => "one vote".to_string(),
-                        n => format!("{n} votes"),
-                    };
+                let results = polls.results(poll.id).await?;
+                let formatted = format_results(&poll.question, results);
 
-                    formatted.push(format!("{key} = {votes} ({p})"));
+                if poll.closed_at.is_some() {
+                    chat::respond!(ctx, "#{}: {}", poll.id, formatted);
+                } else {
+                    chat::respond!(ctx, "#{} (still open): {}", poll.id, formatted);
                 }
-
-                chat::respond!(ctx, "{} -> {}.", poll.question, formatted.join(", "));
             }
             _ => {
-                ctx.respond("Expected: run, close.").await;
+                ctx.respond("Expected: run, timed, close, results.").await;
             }
         }
 
@@ -105,60 +194,138 @@ impl command::Handler for Poll {
     }
 }
 
-struct Inner {
-    voted: HashSet<String>,
-    votes: HashMap<String, u32>,
+/// Publish the current results of the given poll on the global bus.
+async fn publish(
+    global_bus: &async_injector::Ref<bus::Bus<bus::Global>>,
+    polls: &db::Polls,
+    id: i32,
+    question: &str,
+    closed: bool,
+) -> Result<Vec<db::PollResult>> {
+    let results = polls.results(id).await?;
+
+    if let Some(global_bus) = global_bus.load().await {
+        let options = results
+            .iter()
+            .map(|r| bus::PollOption {
+                key: r.key.clone(),
+                description: r.description.clone(),
+                votes: r.votes,
+            })
+            .collect();
+
+        global_bus
+            .send(bus::Global::Poll {
+                id,
+                question: question.to_string(),
+                options,
+                closed,
+            })
+            .await;
+    }
+
+    Ok(results)
 }
 
-#[derive(Clone)]
-struct ActivePoll {
-    question: String,
-    created_at: DateTime<Utc>,
-    options: HashMap<String, Option<String>>,
-    inner: settings::Var<Inner>,
+/// Format the results of a poll, most popular option first.
+fn format_results(question: &str, mut results: Vec<db::PollResult>) -> String {
+    results.sort_by(|a, b| b.votes.cmp(&a.votes));
+
+    let total = results.iter().map(|r| r.votes).sum::<u32>();
+
+    let mut formatted = Vec::new();
+
+    for result in results {
+        let p = common::percentage(result.votes, total);
+
+        let votes = match result.votes {
+            0 => "no votes".to_string(),
+            1 => "one vote".to_string(),
+            n => format!("{n} votes"),
+        };
+
+        formatted.push(format!("{} = {votes} ({p})", result.name()));
+    }
+
+    format!("{} -> {}.", question, formatted.join(", "))
 }
 
-impl ActivePoll {
-    /// Close the poll.
-    pub(crate) async fn close(&self) -> Vec<(String, u32)> {
-        let inner = self.inner.read().await;
+/// Close all timed polls which have run out and announce their results.
+async fn close_expired(
+    polls: &db::Polls,
+    sender: &chat::Sender,
+    global_bus: &async_injector::Ref<bus::Bus<bus::Global>>,
+    open: &OpenPolls,
+) -> Result<()> {
+    let now = Utc::now().naive_utc();
 
-        let mut results = Vec::new();
+    for poll in polls.expired(sender.channel(), now).await? {
+        open.lock().await.remove(&poll.id);
 
-        for (o, description) in &self.options {
-            results.push((
-                description.clone().unwrap_or_else(|| o.to_string()),
-                inner.votes.get(o).cloned().unwrap_or_default(),
-            ));
+        if !polls.close(poll.id).await? {
+            continue;
         }
 
-        results.sort_by_key(|v| v.1);
-        results
+        let results = publish(global_bus, polls, poll.id, &poll.question, true).await?;
+        sender
+            .privmsg(format!(
+                "Poll #{} has closed! {}",
+                poll.id,
+                format_results(&poll.question, results)
+            ))
+            .await;
     }
+
+    Ok(())
+}
+
+/// Hook which records votes for all open polls.
+struct VoteHook {
+    enabled: settings::Var<bool>,
+    polls: db::Polls,
+    global_bus: async_injector::Ref<bus::Bus<bus::Global>>,
+    open: OpenPolls,
 }
 
 #[async_trait]
-impl command::MessageHook for ActivePoll {
+impl command::MessageHook for VoteHook {
     async fn peek(&self, user: &chat::User, m: &str) -> Result<()> {
-        let mut inner = self.inner.write().await;
+        if !self.enabled.load().await {
+            return Ok(());
+        }
 
         let user = match user.real() {
             Some(user) => user,
             None => return Ok(()),
         };
 
-        if inner.voted.contains(user.login()) {
-            return Ok(());
+        let now = Utc::now().naive_utc();
+        let mut votes = Vec::new();
+
+        {
+            let open = self.open.lock().await;
+
+            for (id, poll) in open.iter() {
+                if matches!(poll.closes_at, Some(closes_at) if closes_at <= now) {
+                    continue;
+                }
+
+                let key = common::words::trimmed(m)
+                    .map(|word| word.to_lowercase())
+                    .find(|word| poll.keys.contains(word));
+
+                if let Some(key) = key {
+                    votes.push((*id, poll.question.clone(), key));
+                }
+            }
         }
 
-        for word in common::words::trimmed(m) {
-            if !self.options.contains_key(&word.to_lowercase()) {
+        for (id, question, key) in votes {
+            if !self.polls.vote(id, user.login(), &key).await? {
                 continue;
             }
 
-            *inner.votes.entry(word.to_string()).or_default() += 1;
-            inner.voted.insert(user.login().to_string());
-            break;
+            publish(&self.global_bus, &self.polls, id, &question, false).await?;
         }
 
         Ok(())
@@ -177,17 +344,83 @@ impl chat::Module for Module {
     async fn hook(
         &self,
         module::HookContext {
-            handlers, settings, ..
+            injector,
+            handlers,
+            hooks,
+            tasks,
+            sender,
+            settings,
+            ..
         }: module::HookContext<'_, '_>,
     ) -> Result<()> {
+        let settings = settings.scoped("poll");
+        let enabled = settings.var("enabled", false).await?;
+        let global_bus = injector.var().await;
+
+        let polls = injector
+            .get::<db::Polls>()
+            .await
+            .context("polls not available")?;
+
+        // Keep collecting votes for polls which were left open across
+        // restarts.
+        let mut open = HashMap::new();
+
+        for poll in polls.open(sender.channel()).await? {
+            let options = polls.options(poll.id).await?;
+            open.insert(poll.id, OpenPoll::new(&poll, options));
+        }
+
+        let open: OpenPolls = Arc::new(Mutex::new(open));
+
+        hooks
+            .insert(VoteHook {
+                enabled: enabled.clone(),
+                polls,
+                global_bus: global_bus.clone(),
+                open: open.clone(),
+            })
+            .await;
+
         handlers.insert(
             "poll",
-            Poll {
-                polls: Mutex::new(Default::default()),
-                enabled: settings.var("poll/enabled", false).await?,
+            Handler {
+                enabled: enabled.clone(),
+                polls: injector.var().await,
+                global_bus: global_bus.clone(),
+                open: open.clone(),
             },
         );
 
+        let (mut polls_stream, mut polls) = injector.stream::<db::Polls>().await;
+        let sender = sender.clone();
+        let mut interval = tokio::time::interval(CLOSE_INTERVAL);
+
+        let future = async move {
+            loop {
+                tokio::select! {
+                    update = polls_stream.recv() => {
+                        polls = update;
+                    }
+                    _ = interval.tick() => {
+                        if !enabled.load().await {
+                            continue;
+                        }
+
+                        let polls = match polls.as_ref() {
+                            Some(polls) => polls,
+                            None => continue,
+                        };
+
+                        if let Err(e) = close_expired(polls, &sender, &global_bus, &open).await {
+                            tracing::error!("Failed to close expired polls: {}", e);
+                        }
+                    }
+                }
+            }
+        };
+
+        tasks.push(Box::pin(future));
         Ok(())
     }
 }
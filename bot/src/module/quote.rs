@@ -0,0 +1,227 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chat::command;
+use chat::module;
+use chat::stream_info;
+use common::{Cooldown, Duration};
+
+/// Maximum number of search results to show in chat.
+const SEARCH_LIMIT: i64 = 5;
+
+/// The quote to show.
+#[derive(Debug, PartialEq, Eq)]
+enum Lookup {
+    /// A random quote.
+    Random,
+    /// The quote with the given id.
+    Id(i32),
+}
+
+impl Lookup {
+    /// Parse the argument given to `!quote`, like `random` or `#12`.
+    fn parse(arg: Option<&str>) -> Option<Self> {
+        match arg {
+            None | Some("random") => Some(Lookup::Random),
+            Some(arg) => str::parse::<i32>(arg.trim_start_matches('#'))
+                .ok()
+                .map(Lookup::Id),
+        }
+    }
+}
+
+/// Handler for the `!quote` command.
+pub(crate) struct Quote {
+    enabled: settings::Var<bool>,
+    cooldown: settings::Var<Cooldown>,
+    quotes: async_injector::Ref<db::Quotes>,
+    stream_info: stream_info::StreamInfo,
+    streamer: api::TwitchAndUser,
+}
+
+impl Quote {
+    /// Display the given quote.
+    fn display(quote: &db::Quote) -> String {
+        let date = quote.added_at.format("%Y-%m-%d");
+
+        match &quote.game {
+            Some(game) => format!(
+                "#{}: \"{}\" - {} ({}, {})",
+                quote.id, quote.text, quote.author, game, date
+            ),
+            None => format!(
+                "#{}: \"{}\" - {} ({})",
+                quote.id, quote.text, quote.author, date
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl command::Handler for Quote {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Quote)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let quotes = match self.quotes.load().await {
+            Some(quotes) => quotes,
+            None => return Ok(()),
+        };
+
+        match ctx.next().as_deref() {
+            Some("add") => {
+                ctx.check_scope(auth::Scope::QuoteAdd).await?;
+
+                let user = match ctx.user.real() {
+                    Some(user) => user,
+                    None => {
+                        chat::respond!(ctx, "Only real users can add quotes");
+                        return Ok(());
+                    }
+                };
+
+                let rest = ctx.rest().trim();
+
+                let (author, text) = match rest.strip_prefix('@') {
+                    Some(rest) => match rest.split_once(' ') {
+                        Some((author, text)) => (author.to_string(), text.trim()),
+                        None => (rest.to_string(), ""),
+                    },
+                    None => (self.streamer.user.display_name.to_string(), rest),
+                };
+
+                if text.is_empty() {
+                    chat::respond!(
+                        ctx,
+                        "You add a quote by calling !quote add [@author] <text>, \
+                         like \"!quote add @{} I never miss\"",
+                        self.streamer.user.login
+                    );
+                    return Ok(());
+                }
+
+                let game = self.stream_info.data.read().game.clone();
+
+                let id = quotes
+                    .insert(ctx.channel(), user.login(), &author, game.as_deref(), text)
+                    .await?;
+
+                chat::respond!(ctx, "Added quote #{}.", id);
+            }
+            Some("search") => {
+                let search = ctx.rest().trim();
+
+                if search.is_empty() {
+                    chat::respond!(ctx, "Expected: !quote search <text>");
+                    return Ok(());
+                }
+
+                let results = quotes.search(ctx.channel(), search, SEARCH_LIMIT).await?;
+                let results = results.iter().map(Self::display);
+                ctx.respond_lines(results, "No matching quotes found").await;
+            }
+            Some("delete") => {
+                ctx.check_scope(auth::Scope::QuoteEdit).await?;
+
+                let id = ctx.next_parse::<i32, _>("<id>")?;
+
+                if quotes.delete(ctx.channel(), id).await? {
+                    chat::respond!(ctx, "Deleted quote #{}.", id);
+                } else {
+                    chat::respond!(ctx, "No quote with id #{}.", id);
+                }
+            }
+            arg => {
+                if !self.cooldown.write().await.is_open() {
+                    chat::respond!(ctx, "A quote was shown recently, please wait a bit.");
+                    return Ok(());
+                }
+
+                let quote = match Lookup::parse(arg) {
+                    Some(Lookup::Id(id)) => match quotes.get(ctx.channel(), id).await? {
+                        Some(quote) => quote,
+                        None => {
+                            chat::respond!(ctx, "No quote with id #{}.", id);
+                            return Ok(());
+                        }
+                    },
+                    Some(Lookup::Random) => match quotes.random(ctx.channel()).await? {
+                        Some(quote) => quote,
+                        None => {
+                            chat::respond!(ctx, "There are no quotes yet.");
+                            return Ok(());
+                        }
+                    },
+                    None => {
+                        chat::respond!(
+                            ctx,
+                            "Expected: !quote [id], !quote random, !quote add, !quote search, or !quote delete"
+                        );
+                        return Ok(());
+                    }
+                };
+
+                chat::respond!(ctx, Self::display(&quote));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "quote"
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            settings,
+            stream_info,
+            streamer,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("quote");
+
+        handlers.insert(
+            "quote",
+            Quote {
+                enabled: settings.var("enabled", false).await?,
+                cooldown: settings
+                    .var("cooldown", Cooldown::from_duration(Duration::seconds(10)))
+                    .await?,
+                quotes: injector.var().await,
+                stream_info: stream_info.clone(),
+                streamer: streamer.clone(),
+            },
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lookup;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(Lookup::parse(None), Some(Lookup::Random));
+        assert_eq!(Lookup::parse(Some("random")), Some(Lookup::Random));
+        assert_eq!(Lookup::parse(Some("12")), Some(Lookup::Id(12)));
+        assert_eq!(Lookup::parse(Some("#12")), Some(Lookup::Id(12)));
+        assert_eq!(Lookup::parse(Some("foo")), None);
+    }
+}
@@ -0,0 +1,310 @@
+use anyhow::{anyhow, bail, Result};
+use api::twitch::pubsub;
+use async_fuse::Fuse;
+use async_injector::Injector;
+use async_trait::async_trait;
+use chat::module;
+
+/// How long to wait for a command run by a redemption to be handled.
+const COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Variables available to command templates.
+#[derive(serde::Serialize)]
+struct Vars<'a> {
+    user: &'a str,
+    name: &'a str,
+    input: &'a str,
+}
+
+/// An action performed when a reward is redeemed.
+enum Action {
+    /// Run a chat command.
+    Command(String),
+    /// Give currency to the redeeming user.
+    Currency(i64),
+    /// Run a `!gtav` command.
+    Gtav(String),
+    /// Play a theme song.
+    Theme(String),
+}
+
+impl Action {
+    /// Convert from the stored representation of an action.
+    fn from_db(action: &db::RedemptionAction) -> Result<Self> {
+        Ok(match action.action.as_str() {
+            "command" => Action::Command(action.argument.clone()),
+            "currency" => Action::Currency(action.amount),
+            "gtav" => Action::Gtav(action.argument.clone()),
+            "theme" => Action::Theme(action.argument.clone()),
+            other => bail!("unsupported action `{}`", other),
+        })
+    }
+}
+
+/// Task used to run the actions mapped to redemptions.
+async fn task(
+    sender: chat::Sender,
+    injector: Injector,
+    settings: settings::Settings<::auth::Scope>,
+    streamer: api::TwitchAndUser,
+) -> Result<()> {
+    let (mut pubsub_stream, pubsub) = injector.stream::<pubsub::TwitchPubSub>().await;
+    let (mut enabled_stream, enabled) = settings.stream("enabled").or_default().await?;
+
+    let mut state = State {
+        sender,
+        streamer,
+        enabled,
+        pubsub,
+        redemptions: injector.var().await,
+        currency: injector.var().await,
+        player: injector.var().await,
+        command_bus: injector.var().await,
+        redemptions_stream: Fuse::empty(),
+    };
+
+    state.build();
+
+    loop {
+        tokio::select! {
+            enabled = enabled_stream.recv() => {
+                state.enabled = enabled;
+                state.build();
+            }
+            pubsub = pubsub_stream.recv() => {
+                state.pubsub = pubsub;
+                state.build();
+            }
+            Some(redemption) = state.redemptions_stream.next() => {
+                state.process_redemption(redemption).await;
+            }
+        }
+    }
+}
+
+struct State {
+    sender: chat::Sender,
+    streamer: api::TwitchAndUser,
+    enabled: bool,
+    pubsub: Option<pubsub::TwitchPubSub>,
+    redemptions: async_injector::Ref<db::Redemptions>,
+    currency: async_injector::Ref<currency::Currency>,
+    player: async_injector::Ref<player::Player>,
+    command_bus: async_injector::Ref<bus::Bus<bus::Command>>,
+    redemptions_stream: Fuse<pubsub::TwitchStream<pubsub::Redemption>>,
+}
+
+impl State {
+    fn build(&mut self) {
+        let pubsub = match (self.pubsub.as_ref(), self.enabled) {
+            (Some(pubsub), true) => pubsub,
+            _ => {
+                self.redemptions_stream.clear();
+                return;
+            }
+        };
+
+        self.redemptions_stream.set(pubsub.redemptions());
+    }
+
+    /// Process a single incoming redemption.
+    async fn process_redemption(&mut self, redemption: pubsub::Redemption) {
+        let Some(redemptions) = self.redemptions.load().await else {
+            return;
+        };
+
+        let action = redemptions
+            .find(
+                self.sender.channel(),
+                &redemption.reward.id,
+                &redemption.reward.title,
+            )
+            .await;
+
+        let action = match action {
+            Ok(Some(action)) => action,
+            Ok(None) => return,
+            Err(e) => {
+                common::log_error!(e, "Failed to look up redemption action");
+                return;
+            }
+        };
+
+        tracing::trace!(?action, "Running redemption action");
+
+        let display_name = &redemption.user.display_name;
+
+        let status = match self.run(&action, &redemption).await {
+            Ok(()) if action.fulfill => Some(pubsub::Status::Fulfilled),
+            Ok(()) => None,
+            Err(e) => {
+                let refunded = if action.refund {
+                    ", your points have been refunded"
+                } else {
+                    ""
+                };
+
+                self.sender
+                    .privmsg(chat::respond(
+                        display_name,
+                        format!(
+                            "Failed to redeem {}: {}{}",
+                            redemption.reward.title, e, refunded
+                        ),
+                    ))
+                    .await;
+
+                action.refund.then_some(pubsub::Status::Canceled)
+            }
+        };
+
+        let Some(status) = status else {
+            return;
+        };
+
+        let result = self
+            .streamer
+            .client
+            .patch_redemptions(&self.streamer.user.id, &redemption, status)
+            .await;
+
+        if let Err(e) = result {
+            common::log_error!(
+                e,
+                "failed to update status of reward `{}`",
+                redemption.reward.id
+            );
+        }
+    }
+
+    /// Run the action for a redemption.
+    async fn run(
+        &self,
+        action: &db::RedemptionAction,
+        redemption: &pubsub::Redemption,
+    ) -> Result<()> {
+        let channel = self.sender.channel();
+        let user = &redemption.user;
+
+        match Action::from_db(action)? {
+            Action::Command(command) => {
+                let command = template::Template::compile(&command)?.render_to_string(Vars {
+                    user: &user.login,
+                    name: &user.display_name,
+                    input: redemption.user_input.as_deref().unwrap_or_default(),
+                })?;
+
+                // Run the command as the redeeming user, so that costs,
+                // cooldowns and scopes apply to them rather than the bot.
+                let caller = bus::Caller {
+                    login: user.login.clone(),
+                    display_name: user.display_name.clone(),
+                };
+
+                self.run_command(command, Some(caller)).await?;
+            }
+            Action::Currency(amount) => {
+                let currency = self
+                    .currency
+                    .load()
+                    .await
+                    .ok_or_else(|| anyhow!("no currency configured"))?;
+
+                currency
+                    .balance_add(channel, &user.login, amount, currency::Reason::Redemption)
+                    .await?;
+
+                self.sender
+                    .privmsg(chat::respond(
+                        &user.display_name,
+                        format!("You got {} {}!", amount, currency.name),
+                    ))
+                    .await;
+            }
+            Action::Gtav(command) => {
+                // NB: go through the `!gtav` command so that its settings and
+                // cooldowns apply.
+                self.run_command(format!("!gtav {command}"), None).await?;
+            }
+            Action::Theme(name) => {
+                let player = self
+                    .player
+                    .load()
+                    .await
+                    .ok_or_else(|| anyhow!("player not configured"))?;
+
+                match player.play_theme(channel, &name).await {
+                    Ok(()) => (),
+                    Err(player::PlayThemeError::NoSuchTheme) => {
+                        bail!("no theme named `{}`", name);
+                    }
+                    Err(player::PlayThemeError::NotConfigured) => {
+                        bail!("themes are not configured");
+                    }
+                    Err(player::PlayThemeError::MissingAuth) => {
+                        bail!("missing authentication to play theme `{}`", name);
+                    }
+                    Err(player::PlayThemeError::Error(e)) => return Err(e),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run the given command and wait for it to be handled.
+    ///
+    /// If `user` is specified, the command is run as that user.
+    async fn run_command(&self, command: String, user: Option<bus::Caller>) -> Result<()> {
+        let Some(bus) = self.command_bus.load().await else {
+            bail!("commands are not available");
+        };
+
+        let (done, rx) = bus::Done::new();
+        bus.send(bus::Command::Run {
+            command,
+            user,
+            done,
+        })
+        .await;
+
+        match tokio::time::timeout(COMMAND_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(..)) => bail!("the command was not run"),
+            Err(..) => bail!("timed out waiting for the command"),
+        }
+    }
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "redemptions"
+    }
+
+    /// Set up the task which handles redemptions.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            tasks,
+            settings,
+            streamer,
+            sender,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("redemptions");
+
+        tasks.push(Box::pin(task(
+            sender.clone(),
+            injector.clone(),
+            settings,
+            streamer.clone(),
+        )));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,398 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chat::command;
+use chat::module;
+use chrono::Utc;
+use common::display;
+use tokio::sync::Mutex;
+
+/// Maximum number of pending purchases to show in chat.
+const QUEUE_LIMIT: usize = 5;
+
+/// Variables available to item command templates.
+#[derive(serde::Serialize)]
+struct Vars<'a> {
+    user: &'a str,
+    item: &'a str,
+}
+
+/// Handler for the `!shop` command.
+pub(crate) struct Shop {
+    enabled: settings::Var<bool>,
+    shop: async_injector::Ref<db::Shop>,
+    currency: async_injector::Ref<currency::Currency>,
+    streamer: api::TwitchAndUser,
+}
+
+#[async_trait]
+impl command::Handler for Shop {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Shop)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let shop = match self.shop.load().await {
+            Some(shop) => shop,
+            None => return Ok(()),
+        };
+
+        match ctx.next().as_deref() {
+            Some("info") => {
+                let name = ctx.next_str("<item>")?;
+
+                let item = match shop.item(ctx.channel(), &name).await? {
+                    Some(item) if !item.disabled => item,
+                    _ => {
+                        chat::respond!(ctx, "No item named `{}` in the shop", name);
+                        return Ok(());
+                    }
+                };
+
+                let mut parts = vec![format!("costs {}", item.price)];
+
+                if let Some(stock) = item.stock {
+                    parts.push(format!("{} left", stock));
+                }
+
+                if item.cooldown > 0 {
+                    let cooldown = std::time::Duration::from_secs(item.cooldown as u64);
+                    parts.push(format!("{} cooldown", display::compact_duration(cooldown)));
+                }
+
+                match &item.description {
+                    Some(description) => {
+                        chat::respond!(ctx, "{}: {} ({})", item.name, description, parts.join(", "))
+                    }
+                    None => chat::respond!(ctx, "{} {}", item.name, parts.join(", ")),
+                }
+            }
+            Some("queue") => {
+                ctx.check_scope(auth::Scope::ShopAdmin).await?;
+
+                let pending = shop.pending(ctx.channel()).await?;
+
+                if pending.is_empty() {
+                    chat::respond!(ctx, "No purchases waiting to be fulfilled");
+                    return Ok(());
+                }
+
+                let purchases = pending
+                    .iter()
+                    .take(QUEUE_LIMIT)
+                    .map(|p| format!("#{} {} for @{}", p.id, p.item, p.user))
+                    .collect::<Vec<_>>();
+
+                let more = pending.len().saturating_sub(QUEUE_LIMIT);
+
+                if more > 0 {
+                    chat::respond!(ctx, "{} ... and {} more", purchases.join(", "), more);
+                } else {
+                    chat::respond!(ctx, "{}", purchases.join(", "));
+                }
+            }
+            Some("fulfill") => {
+                ctx.check_scope(auth::Scope::ShopAdmin).await?;
+
+                let id = ctx.next_parse::<i32, _>("<id>")?;
+                let by = ctx.user.real().map(|u| u.login().to_string());
+
+                if shop.fulfill(ctx.channel(), id, by.as_deref()).await? {
+                    chat::respond!(ctx, "Purchase #{} fulfilled", id);
+                } else {
+                    chat::respond!(ctx, "No purchase #{} waiting to be fulfilled", id);
+                }
+            }
+            Some("refund") => {
+                ctx.check_scope(auth::Scope::ShopAdmin).await?;
+
+                let id = ctx.next_parse::<i32, _>("<id>")?;
+
+                let currency = match self.currency.load().await {
+                    Some(currency) => currency,
+                    None => {
+                        chat::respond!(ctx, "No currency configured for stream, sorry :(");
+                        return Ok(());
+                    }
+                };
+
+                let purchase = match shop.refund(ctx.channel(), id).await? {
+                    Some(purchase) => purchase,
+                    None => {
+                        chat::respond!(ctx, "No unfulfilled purchase #{} to refund", id);
+                        return Ok(());
+                    }
+                };
+
+                let result = currency
+                    .balance_transfer(
+                        ctx.channel(),
+                        &self.streamer.user.login,
+                        &purchase.user,
+                        purchase.price,
+                        true,
+                        currency::Reason::Shop,
+                    )
+                    .await;
+
+                if let Err(e) = result {
+                    // The purchase can be refunded again once the currency
+                    // is reachable.
+                    shop.revert_refund(ctx.channel(), id).await?;
+                    return Err(e.into());
+                }
+
+                chat::respond!(
+                    ctx,
+                    "Refunded {} {} to @{} for {}",
+                    purchase.price,
+                    currency.name,
+                    purchase.user,
+                    purchase.item
+                );
+            }
+            None => {
+                let items = shop
+                    .items(ctx.channel())
+                    .await?
+                    .into_iter()
+                    .filter(|item| !item.disabled)
+                    .map(|item| match item.stock {
+                        Some(0) => format!("{} (sold out)", item.name),
+                        _ => format!("{} ({})", item.name, item.price),
+                    })
+                    .collect::<Vec<_>>();
+
+                if items.is_empty() {
+                    chat::respond!(ctx, "The shop is empty");
+                } else {
+                    chat::respond!(ctx, "For sale: {}. Buy with !buy <item>", items.join(", "));
+                }
+            }
+            _ => {
+                chat::respond!(ctx, "Expected: info, queue, fulfill, or refund");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler for the `!buy` command.
+pub(crate) struct Buy {
+    enabled: settings::Var<bool>,
+    shop: async_injector::Ref<db::Shop>,
+    currency: async_injector::Ref<currency::Currency>,
+    command_bus: async_injector::Ref<bus::Bus<bus::Command>>,
+    streamer: api::TwitchAndUser,
+    /// Serializes purchases so that stock and cooldowns are respected.
+    lock: Mutex<()>,
+}
+
+#[async_trait]
+impl command::Handler for Buy {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Shop)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let name = ctx.next_str("<item>")?;
+
+        let user = match ctx.user.real() {
+            Some(user) => user,
+            None => {
+                chat::respond!(ctx, "Only real users can buy items");
+                return Ok(());
+            }
+        };
+
+        let shop = match self.shop.load().await {
+            Some(shop) => shop,
+            None => return Ok(()),
+        };
+
+        let currency = match self.currency.load().await {
+            Some(currency) => currency,
+            None => {
+                chat::respond!(ctx, "No currency configured for stream, sorry :(");
+                return Ok(());
+            }
+        };
+
+        let _guard = self.lock.lock().await;
+
+        let item = match shop.item(ctx.channel(), &name).await? {
+            Some(item) if !item.disabled => item,
+            _ => {
+                chat::respond!(ctx, "No item named `{}` in the shop", name);
+                return Ok(());
+            }
+        };
+
+        if item.stock == Some(0) {
+            chat::respond!(ctx, "{} is sold out", item.name);
+            return Ok(());
+        }
+
+        if let Some(last) = item.last_purchased_at {
+            let ready = last + chrono::Duration::seconds(i64::from(item.cooldown));
+            let now = Utc::now().naive_utc();
+
+            if ready > now {
+                let remaining = (ready - now).to_std().unwrap_or_default();
+
+                chat::respond!(
+                    ctx,
+                    "{} is on cooldown, try again in {}",
+                    item.name,
+                    display::compact_duration(remaining)
+                );
+
+                return Ok(());
+            }
+        }
+
+        // Render the command up front so that a broken template doesn't cost
+        // the user anything.
+        let command = match &item.command {
+            Some(command) => Some(template::Template::compile(command)?.render_to_string(
+                Vars {
+                    user: user.login(),
+                    item: &item.name,
+                },
+            )?),
+            None => None,
+        };
+
+        let result = currency
+            .balance_transfer(
+                ctx.channel(),
+                user.login(),
+                &self.streamer.user.login,
+                item.price,
+                false,
+                currency::Reason::Shop,
+            )
+            .await;
+
+        match result {
+            Ok(()) => {}
+            Err(currency::BalanceTransferError::NoBalance) => {
+                chat::respond!(
+                    ctx,
+                    "Not enough {} to buy {} for {}",
+                    currency.name,
+                    item.name,
+                    item.price
+                );
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let purchase = match shop
+            .purchase(ctx.channel(), &item.name, user.login())
+            .await?
+        {
+            Some(purchase) => purchase,
+            None => {
+                currency
+                    .balance_transfer(
+                        ctx.channel(),
+                        &self.streamer.user.login,
+                        user.login(),
+                        item.price,
+                        true,
+                        currency::Reason::Shop,
+                    )
+                    .await?;
+
+                chat::respond!(ctx, "{} is sold out", item.name);
+                return Ok(());
+            }
+        };
+
+        match command {
+            Some(command) => {
+                if let Some(bus) = self.command_bus.load().await {
+                    bus.send(bus::Command::Raw { command }).await;
+                }
+
+                chat::respond!(
+                    ctx,
+                    "Bought {} for {} {}!",
+                    item.name,
+                    item.price,
+                    currency.name
+                );
+            }
+            None => {
+                chat::respond!(
+                    ctx,
+                    "Bought {} for {} {}! A moderator will fulfill purchase #{} shortly",
+                    item.name,
+                    item.price,
+                    currency.name,
+                    purchase.id
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "shop"
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            settings,
+            streamer,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("shop");
+        let enabled = settings.var("enabled", false).await?;
+
+        handlers.insert(
+            "shop",
+            Shop {
+                enabled: enabled.clone(),
+                shop: injector.var().await,
+                currency: injector.var().await,
+                streamer: streamer.clone(),
+            },
+        );
+
+        handlers.insert(
+            "buy",
+            Buy {
+                enabled,
+                shop: injector.var().await,
+                currency: injector.var().await,
+                command_bus: injector.var().await,
+                streamer: streamer.clone(),
+                lock: Mutex::new(()),
+            },
+        );
+
+        Ok(())
+    }
+}
@@ -38,7 +38,7 @@ impl Handler {
             Some(user) => user,
             None => {
                 ctx.respond("Only real users can request songs").await;
-                return Ok(());
+                return Err(chat::respond_err!().into());
             }
         };
 
@@ -57,25 +57,31 @@ impl Handler {
             Ok(outcome) => {
                 chat::respond!(user, "{}", outcome)
             }
-            Err(e) => match e {
-                requester::RequestError::BadRequest(reason) => {
-                    self.request_help(ctx, reason.as_deref()).await;
-                }
-                requester::RequestError::AddTrackError(e) => match e {
-                    player::AddTrackError::Error(e) => {
+            Err(e) => {
+                // NB: refusals are reported as errors, so that callers like
+                // redemptions know that the request wasn't added.
+                match e {
+                    requester::RequestError::BadRequest(reason) => {
+                        self.request_help(ctx, reason.as_deref()).await;
+                    }
+                    requester::RequestError::AddTrackError(e) => match e {
+                        player::AddTrackError::Error(e) => {
+                            return Err(e);
+                        }
+                        e => {
+                            chat::respond!(user, "{}", e);
+                        }
+                    },
+                    requester::RequestError::Error(e) => {
                         return Err(e);
                     }
                     e => {
                         chat::respond!(user, "{}", e);
                     }
-                },
-                requester::RequestError::Error(e) => {
-                    return Err(e);
                 }
-                e => {
-                    chat::respond!(user, "{}", e);
-                }
-            },
+
+                return Err(chat::respond_err!().into());
+            }
         }
 
         Ok(())
@@ -112,7 +118,7 @@ impl command::Handler for Handler {
 
     async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
         if !self.enabled.load().await {
-            return Ok(());
+            return Err(chat::respond_err!().into());
         }
 
         let player = self
@@ -180,7 +180,12 @@ impl SongRequester {
         };
 
         currency
-            .balance_add(&channel, user, request_reward as i64)
+            .balance_add(
+                &channel,
+                user,
+                request_reward as i64,
+                currency::Reason::SongRequest,
+            )
             .await
             .map_err(RequestError::Error)?;
 
@@ -66,10 +66,15 @@ impl command::Handler for Handler {
         let total_reward = reward * users.len() as i64;
 
         currency
-            .balance_add(ctx.channel(), &self.streamer.user.login, -total_reward)
+            .balance_add(
+                ctx.channel(),
+                &self.streamer.user.login,
+                -total_reward,
+                currency::Reason::Swearjar,
+            )
             .await?;
         currency
-            .balances_increment(ctx.channel(), users, reward, 0)
+            .balances_increment(ctx.channel(), users, reward, 0, currency::Reason::Swearjar)
             .await?;
 
         user.sender().privmsg(format!(
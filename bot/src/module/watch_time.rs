@@ -0,0 +1,238 @@
+use anyhow::Result;
+use async_fuse::Fuse;
+use async_trait::async_trait;
+use chat::command;
+use chat::module;
+use chat::stream_info;
+use common::display;
+use common::{Channel, Duration};
+
+/// Number of users to show in the leaderboard.
+const TOP_LIMIT: i64 = 5;
+
+/// Handler for the `!watchtime` command.
+pub(crate) struct Handler {
+    enabled: settings::Var<bool>,
+    watch_time: async_injector::Ref<db::WatchTime>,
+    stream_info: stream_info::StreamInfo,
+}
+
+#[async_trait]
+impl command::Handler for Handler {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::WatchTime)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context<'_>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let watch_time = match self.watch_time.load().await {
+            Some(watch_time) => watch_time,
+            None => return Ok(()),
+        };
+
+        let stream_id = self.stream_info.stream_id();
+
+        match ctx.next().as_deref() {
+            Some("top") => {
+                let stream_id = match ctx.next().as_deref() {
+                    None => None,
+                    Some("stream") => match stream_id {
+                        Some(stream_id) => Some(stream_id),
+                        None => {
+                            chat::respond!(ctx, "Stream is not live right now");
+                            return Ok(());
+                        }
+                    },
+                    Some(..) => {
+                        chat::respond!(ctx, "Expected: top [stream]");
+                        return Ok(());
+                    }
+                };
+
+                let top = watch_time
+                    .top(ctx.channel(), stream_id.as_deref(), TOP_LIMIT, 0)
+                    .await?;
+
+                if top.is_empty() {
+                    chat::respond!(ctx, "No watch time recorded yet");
+                    return Ok(());
+                }
+
+                let top = top
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (user, seconds))| {
+                        format!("#{} {} ({})", i + 1, user, format_seconds(seconds))
+                    })
+                    .collect::<Vec<_>>();
+
+                chat::respond!(ctx, "{}", top.join(", "));
+            }
+            Some(user) => {
+                let user = user.trim_start_matches('@').to_lowercase();
+                let of = watch_time
+                    .get(ctx.channel(), stream_id.as_deref(), &user)
+                    .await?;
+                respond_watch_time(ctx, &user, of, stream_id.is_some()).await;
+            }
+            None => {
+                let user = match ctx.user.real() {
+                    Some(user) => user,
+                    None => {
+                        chat::respond!(ctx, "Only real users have watch time");
+                        return Ok(());
+                    }
+                };
+
+                let of = watch_time
+                    .get(ctx.channel(), stream_id.as_deref(), user.login())
+                    .await?;
+
+                let login = user.login().to_string();
+                respond_watch_time(ctx, &login, of, stream_id.is_some()).await;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Respond with the watch time of the given user.
+async fn respond_watch_time(
+    ctx: &mut command::Context<'_>,
+    user: &str,
+    of: db::WatchTimeOf,
+    live: bool,
+) {
+    if of.total == 0 {
+        chat::respond!(ctx, "{} hasn't watched the stream yet", user);
+        return;
+    }
+
+    if live {
+        chat::respond!(
+            ctx,
+            "{} has watched for {} ({} this stream)",
+            user,
+            format_seconds(of.total),
+            format_seconds(of.stream)
+        );
+    } else {
+        chat::respond!(ctx, "{} has watched for {}", user, format_seconds(of.total));
+    }
+}
+
+/// Format the given number of seconds for chat.
+fn format_seconds(seconds: i64) -> String {
+    display::compact_duration(std::time::Duration::from_secs(seconds.max(0) as u64))
+}
+
+/// Record watch time for everyone currently in chat.
+async fn record(
+    watch_time: &db::WatchTime,
+    streamer: &api::TwitchAndUser,
+    channel: &Channel,
+    stream_id: &str,
+    seconds: i64,
+) -> Result<()> {
+    let users = currency::chatters(streamer).await?;
+
+    watch_time
+        .record(
+            channel,
+            Some(stream_id),
+            users.into_iter().collect(),
+            seconds,
+        )
+        .await
+}
+
+pub(crate) struct Module;
+
+#[async_trait]
+impl chat::Module for Module {
+    fn ty(&self) -> &'static str {
+        "watch-time"
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            tasks,
+            settings,
+            streamer,
+            stream_info,
+            sender,
+            ..
+        }: module::HookContext<'_, '_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("watch-time");
+        let enabled = settings.var("enabled", false).await?;
+
+        let (mut interval_stream, interval) = settings
+            .stream("interval")
+            .or_with_else(|| Duration::seconds(60))
+            .await?;
+
+        handlers.insert(
+            "watchtime",
+            Handler {
+                enabled: enabled.clone(),
+                watch_time: injector.var().await,
+                stream_info: stream_info.clone(),
+            },
+        );
+
+        let (mut watch_time_stream, mut watch_time) = injector.stream::<db::WatchTime>().await;
+        let stream_info = stream_info.clone();
+        let streamer = streamer.clone();
+        let channel = sender.channel().to_owned();
+        let mut seconds = interval.num_seconds() as i64;
+
+        let new_timer = |interval: &Duration| {
+            if interval.is_empty() {
+                Fuse::empty()
+            } else {
+                Fuse::new(tokio::time::interval(interval.as_std()))
+            }
+        };
+
+        let mut timer = new_timer(&interval);
+
+        let future = async move {
+            loop {
+                tokio::select! {
+                    update = watch_time_stream.recv() => {
+                        watch_time = update;
+                    }
+                    update = interval_stream.recv() => {
+                        seconds = update.num_seconds() as i64;
+                        timer = new_timer(&update);
+                    }
+                    _ = timer.as_pin_mut().poll_inner(|mut i, cx| i.poll_tick(cx)) => {
+                        if !enabled.load().await {
+                            continue;
+                        }
+
+                        let (Some(watch_time), Some(stream_id)) = (watch_time.as_ref(), stream_info.stream_id()) else {
+                            continue;
+                        };
+
+                        if let Err(error) = record(watch_time, &streamer, &channel, &stream_id, seconds).await {
+                            common::log_error!(error, "Failed to record watch time");
+                        }
+                    }
+                }
+            }
+        };
+
+        tasks.push(Box::pin(future));
+        Ok(())
+    }
+}
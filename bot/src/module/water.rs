@@ -97,7 +97,12 @@ impl command::Handler for Handler {
                 .await;
 
                 if let Err(e) = currency
-                    .balance_add(ctx.channel(), &reward.user, -reward.amount)
+                    .balance_add(
+                        ctx.channel(),
+                        &reward.user,
+                        -reward.amount,
+                        currency::Reason::Water,
+                    )
                     .await
                 {
                     tracing::error!("Failed to undo water from database: {}", e);
@@ -138,7 +143,7 @@ impl command::Handler for Handler {
                 );
 
                 if let Err(error) = currency
-                    .balance_add(ctx.channel(), user.login(), amount)
+                    .balance_add(ctx.channel(), user.login(), amount, currency::Reason::Water)
                     .await
                 {
                     common::log_error!(error, "Failed to appply water balance");
@@ -8,6 +8,7 @@ use common::models::{Song, State, TrackId};
 use common::{display, Duration};
 use serde::Serialize;
 use template::Template;
+use tokio::sync::broadcast::error::RecvError;
 
 static DEFAULT_CURRENT_SONG_TEMPLATE: &str = "Song: {{name}}{{#if artists}} by {{artists}}{{/if}}{{#if paused}} (Paused){{/if}} ({{duration}})\n{{#if user~}}Request by: @{{user~}}{{/if}}";
 static DEFAULT_CURRENT_SONG_STOPPED_TEMPLATE: &str = "Not Playing";
@@ -16,7 +17,11 @@ static DEFAULT_CURRENT_SONG_STOPPED_TEMPLATE: &str = "Not Playing";
 pub(crate) async fn setup(
     injector: Injector,
     settings: settings::Settings<::auth::Scope>,
+    global_bus: bus::Bus<bus::Global>,
 ) -> Result<()> {
+    let mut global_rx = global_bus.subscribe();
+    let mut queue = latest_queue(&global_bus).await;
+
     let (mut song_stream, mut song) = injector.stream::<Song>().await;
     let (mut state_stream, mut state) = injector.stream::<State>().await;
     let (mut path_stream, path) = settings.stream("path").optional().await?;
@@ -40,6 +45,9 @@ pub(crate) async fn setup(
         .or_with(Duration::seconds(1))
         .await?;
 
+    let (mut queue_length_stream, mut queue_length) =
+        settings.stream("queue-length").or_with(3usize).await?;
+
     let (mut enabled_stream, enabled) = settings.stream("enabled").or_default().await?;
 
     let mut song_file = Fuse::empty();
@@ -83,10 +91,24 @@ pub(crate) async fn setup(
             update = state_stream.recv() => {
                 state = update;
             }
+            update = queue_length_stream.recv() => {
+                queue_length = update;
+            }
+            message = global_rx.recv() => {
+                match message {
+                    Ok(bus::Global::SongQueue { items }) => {
+                        queue = items;
+                    }
+                    Ok(..) | Err(RecvError::Lagged(..)) => continue,
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            }
         }
 
         if let Some(song_file) = song_file.as_inner_mut() {
-            song_file.update_song(song.as_ref(), state).await;
+            song_file
+                .update_song(song.as_ref(), state, up_next(&queue, queue_length))
+                .await;
         }
     }
 }
@@ -153,13 +175,18 @@ pub(crate) struct SongFile {
 
 impl SongFile {
     /// Write current song. Log any errors.
-    async fn update_song(&self, song: Option<&Song>, state: Option<State>) {
+    async fn update_song(
+        &self,
+        song: Option<&Song>,
+        state: Option<State>,
+        queue: &[bus::QueueItem],
+    ) {
         tracing::trace!("Updating song: {:?} {:?}", song, state);
 
         let state = state.unwrap_or_default();
 
         let result = match song {
-            Some(song) => self.write(song, state),
+            Some(song) => self.write(song, state, queue),
             None => self.blank(),
         };
 
@@ -192,9 +219,9 @@ impl SongFile {
     }
 
     /// Write the current song to a path.
-    pub(crate) fn write(&self, song: &Song, state: State) -> Result<()> {
+    pub(crate) fn write(&self, song: &Song, state: State, queue: &[bus::QueueItem]) -> Result<()> {
         let mut f = self.create_or_truncate()?;
-        let data = data(song, state)?;
+        let data = data(song, state, queue)?;
         self.template.render(&mut f, &data)?;
         Ok(())
     }
@@ -207,10 +234,41 @@ impl SongFile {
     }
 }
 
+/// Get the songs which are up next, limited to `length` of them.
+fn up_next<T>(queue: &[T], length: usize) -> &[T] {
+    &queue[..queue.len().min(length)]
+}
+
+/// Get the last queue snapshot sent on the bus.
+async fn latest_queue(global_bus: &bus::Bus<bus::Global>) -> Vec<bus::QueueItem> {
+    for message in global_bus.latest().await {
+        if let bus::Global::SongQueue { items } = message {
+            return items;
+        }
+    }
+
+    Vec::new()
+}
+
 /// Get serializable data for this item.
-pub(crate) fn data(song: &Song, state: State) -> Result<CurrentData<'_>> {
+pub(crate) fn data<'a>(
+    song: &'a Song,
+    state: State,
+    queue: &'a [bus::QueueItem],
+) -> Result<CurrentData<'a>> {
     let artists = song.item().track().artists();
 
+    let queue = queue
+        .iter()
+        .map(|item| QueueData {
+            name: &item.name,
+            artists: item.artists.as_deref(),
+            user: item.user.as_deref(),
+            duration: display::digital_duration(std::time::Duration::from_secs(item.duration)),
+            image_url: item.image_url.as_deref(),
+        })
+        .collect();
+
     Ok(CurrentData {
         paused: state != State::Playing,
         track_id: song.item().track_id(),
@@ -219,6 +277,8 @@ pub(crate) fn data(song: &Song, state: State) -> Result<CurrentData<'_>> {
         user: song.item().user().map(String::as_str),
         duration: display::digital_duration(song.item().duration()),
         elapsed: display::digital_duration(song.elapsed()),
+        image_url: song.item().track().image_url(),
+        queue,
     })
 }
 
@@ -231,4 +291,31 @@ pub(crate) struct CurrentData<'a> {
     user: Option<&'a str>,
     duration: String,
     elapsed: String,
+    image_url: Option<String>,
+    /// The next songs in the queue.
+    queue: Vec<QueueData<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct QueueData<'a> {
+    name: &'a str,
+    artists: Option<&'a str>,
+    user: Option<&'a str>,
+    duration: String,
+    image_url: Option<&'a str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::up_next;
+
+    #[test]
+    fn test_up_next() {
+        let queue = [1, 2, 3, 4, 5];
+
+        assert_eq!(up_next(&queue, 3), &[1, 2, 3]);
+        assert_eq!(up_next(&queue, 10), &queue);
+        assert!(up_next(&queue, 0).is_empty());
+        assert!(up_next::<i32>(&[], 3).is_empty());
+    }
 }
@@ -0,0 +1,268 @@
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_injector::Injector;
+use chrono::Utc;
+use common::models::TrackId;
+use common::stream::StreamExt;
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::Instrument;
+use url::Url;
+
+/// Delay before the first retry of a failed delivery.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Run the loop which delivers bot events to configured webhooks.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn run<S>(
+    settings: &settings::Settings<S>,
+    injector: &Injector,
+    global_bus: bus::Bus<bus::Global>,
+    event_bus: bus::Bus<bus::Event>,
+) -> Result<impl Future<Output = Result<()>>>
+where
+    S: settings::Scope,
+{
+    let settings = settings.scoped("webhooks");
+
+    let (mut max_attempts_stream, mut max_attempts) =
+        settings.stream("max-attempts").or_with(5u32).await?;
+    let (mut webhooks_stream, mut webhooks) = injector.stream::<db::Webhooks>().await;
+
+    let client = api::Webhook::new(crate::USER_AGENT);
+    let mut global_rx = global_bus.subscribe();
+    let mut event_rx = event_bus.subscribe();
+
+    let future = async move {
+        let mut current_song = None;
+        let mut deliveries = common::Futures::<'static, ()>::default();
+
+        loop {
+            let (event, data) = tokio::select! {
+                update = max_attempts_stream.recv() => {
+                    max_attempts = update;
+                    continue;
+                }
+                update = webhooks_stream.recv() => {
+                    webhooks = update;
+                    continue;
+                }
+                Some(()) = deliveries.next() => {
+                    continue;
+                }
+                event = global_rx.recv() => {
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!("Webhooks skipped {n} global events");
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return Ok(()),
+                    };
+
+                    match global_event(&event, &mut current_song) {
+                        Some(event) => event,
+                        None => continue,
+                    }
+                }
+                event = event_rx.recv() => {
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!("Webhooks skipped {n} events");
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return Ok(()),
+                    };
+
+                    match into_payload(&event) {
+                        Some(event) => event,
+                        None => continue,
+                    }
+                }
+            };
+
+            let Some(webhooks) = webhooks.as_ref() else {
+                continue;
+            };
+
+            let body = serde_json::to_vec(&Payload {
+                event: &event,
+                sent_at: Utc::now().to_rfc3339(),
+                data: &data,
+            })?;
+
+            let list = match webhooks.list().await {
+                Ok(list) => list,
+                Err(e) => {
+                    common::log_error!(e, "Failed to list webhooks");
+                    continue;
+                }
+            };
+
+            for webhook in list {
+                if !webhook.enabled || !webhook.accepts(&event) {
+                    continue;
+                }
+
+                deliveries.push(Box::pin(deliver(
+                    client.clone(),
+                    webhooks.clone(),
+                    webhook,
+                    event.clone(),
+                    body.clone(),
+                    max_attempts.max(1),
+                )));
+            }
+        }
+    };
+
+    Ok(future.in_current_span())
+}
+
+/// The body delivered to webhooks.
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: &'a str,
+    sent_at: String,
+    data: &'a serde_json::Value,
+}
+
+/// Deliver a single event to a webhook and record the outcome.
+async fn deliver(
+    client: api::Webhook,
+    webhooks: db::Webhooks,
+    webhook: db::Webhook,
+    event: String,
+    body: Vec<u8>,
+    max_attempts: u32,
+) {
+    let url = match str::parse::<Url>(&webhook.url) {
+        Ok(url) => url,
+        Err(e) => {
+            tracing::warn!("Webhook `{}` has a bad url: {}", webhook.name, e);
+            return;
+        }
+    };
+
+    let payload = String::from_utf8_lossy(&body).into_owned();
+
+    let delivery = client
+        .deliver(
+            &url,
+            &webhook.secret,
+            &event,
+            body.into(),
+            common::backoff::Exponential::new(INITIAL_BACKOFF),
+            max_attempts,
+        )
+        .await;
+
+    if let Some(error) = &delivery.error {
+        tracing::warn!(
+            "Failed to deliver `{}` to webhook `{}` after {} attempt(s): {}",
+            event,
+            webhook.name,
+            delivery.attempts,
+            error
+        );
+    }
+
+    let outcome = db::WebhookOutcome {
+        attempts: delivery.attempts as i32,
+        status: delivery.status.map(i32::from),
+        error: delivery.error,
+    };
+
+    if let Err(e) = webhooks
+        .record(&webhook.name, &event, &payload, outcome)
+        .await
+    {
+        common::log_error!(e, "Failed to record webhook delivery");
+    }
+}
+
+/// Convert a message on the global bus into a webhook event.
+///
+/// Only changes to the current song and finished polls are of interest.
+fn global_event(
+    message: &bus::Global,
+    current_song: &mut Option<Option<TrackId>>,
+) -> Option<(String, serde_json::Value)> {
+    match message {
+        bus::Global::SongCurrent { track_id, .. } => {
+            if current_song.as_ref() == Some(track_id) {
+                return None;
+            }
+
+            *current_song = Some(track_id.clone());
+            into_payload(message)
+        }
+        bus::Global::Poll { closed: true, .. } => {
+            let (_, data) = into_payload(message)?;
+            Some((String::from("poll/finished"), data))
+        }
+        _ => None,
+    }
+}
+
+/// Serialize a bus message into the name of the event and its data.
+///
+/// Bus messages are tagged with their `type`, which is used as the name of
+/// the event.
+fn into_payload<T>(message: &T) -> Option<(String, serde_json::Value)>
+where
+    T: Serialize,
+{
+    let mut data = serde_json::to_value(message).ok()?;
+
+    let event = match data.as_object_mut()?.remove("type")? {
+        serde_json::Value::String(event) => event,
+        _ => return None,
+    };
+
+    Some((event, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{global_event, into_payload};
+
+    #[test]
+    fn test_events() {
+        let (event, data) = into_payload(&bus::Event::AfterStream {
+            user: String::from("setbac"),
+            text: String::from("hello"),
+        })
+        .unwrap();
+
+        assert_eq!(event, "after-stream");
+        assert_eq!(data, serde_json::json!({"user": "setbac", "text": "hello"}));
+
+        let (event, data) = into_payload(&bus::Event::StreamStarted).unwrap();
+        assert_eq!(event, "stream/started");
+        assert_eq!(data, serde_json::json!({}));
+
+        let mut current = None;
+        let song = bus::Global::song(None).unwrap();
+        let (event, _) = global_event(&song, &mut current).unwrap();
+        assert_eq!(event, "song/current");
+        // The same song is only delivered once.
+        assert!(global_event(&song, &mut current).is_none());
+
+        let poll = |closed| bus::Global::Poll {
+            id: 1,
+            question: String::from("Best fruit?"),
+            options: Vec::new(),
+            closed,
+        };
+
+        assert!(global_event(&poll(false), &mut current).is_none());
+        let (event, data) = global_event(&poll(true), &mut current).unwrap();
+        assert_eq!(event, "poll/finished");
+        assert_eq!(data["question"], "Best fruit?");
+        assert!(global_event(&bus::Global::Ping, &mut current).is_none());
+    }
+}
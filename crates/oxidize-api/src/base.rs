@@ -219,6 +219,11 @@ pub(crate) struct Response<B> {
 }
 
 impl Response<Bytes> {
+    /// Get the status of the response.
+    pub(crate) fn status(&self) -> StatusCode {
+        self.status
+    }
+
     /// Expect a successful response.
     pub(crate) fn ok(self) -> Result<()> {
         if self.status.is_success() {
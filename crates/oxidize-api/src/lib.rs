@@ -38,5 +38,8 @@ pub use self::token::Token;
 pub mod twitch;
 pub use self::twitch::Twitch;
 
+pub mod webhook;
+pub use self::webhook::Webhook;
+
 pub mod youtube;
 pub use self::youtube::YouTube;
@@ -0,0 +1,263 @@
+//! Client for delivering events to webhooks.
+
+use bytes::Bytes;
+use common::backoff;
+use reqwest::header::{self, HeaderName};
+use reqwest::{Client, Method, StatusCode, Url};
+use ring::hmac;
+
+use crate::base::RequestBuilder;
+
+/// Header containing the HMAC-SHA256 signature of the delivered body.
+pub const SIGNATURE_HEADER: &str = "x-oxidize-signature";
+/// Header containing the name of the delivered event.
+pub const EVENT_HEADER: &str = "x-oxidize-event";
+
+/// All events which can be delivered to webhooks.
+pub const EVENTS: &[&str] = &[
+    "song/current",
+    "stream/started",
+    "stream/stopped",
+    "after-stream",
+    "currency/given",
+    "poll/finished",
+    "moderation",
+    "alert",
+];
+
+/// The outcome of delivering an event.
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    /// Number of attempts made.
+    pub attempts: u32,
+    /// The status of the last response, if any.
+    pub status: Option<u16>,
+    /// The error of the last attempt, if the delivery failed.
+    pub error: Option<String>,
+}
+
+/// API integration.
+#[derive(Clone, Debug)]
+pub struct Webhook {
+    user_agent: &'static str,
+    client: Client,
+}
+
+impl Webhook {
+    /// Create a new API integration.
+    pub fn new(user_agent: &'static str) -> Self {
+        Self {
+            user_agent,
+            client: Client::new(),
+        }
+    }
+
+    /// Deliver the given event to a webhook.
+    ///
+    /// Failed attempts are retried using the given backoff, up until
+    /// `max_attempts` have been made. Client errors are not retried, except
+    /// for when we are being rate limited.
+    pub async fn deliver(
+        &self,
+        url: &Url,
+        secret: &str,
+        event: &str,
+        body: Bytes,
+        mut backoff: backoff::Exponential,
+        max_attempts: u32,
+    ) -> Delivery {
+        let signature = signature(secret, &body);
+
+        let mut delivery = Delivery {
+            attempts: 0,
+            status: None,
+            error: None,
+        };
+
+        loop {
+            delivery.attempts += 1;
+
+            let mut req =
+                RequestBuilder::new(&self.client, self.user_agent, Method::POST, url.clone());
+
+            req.header(header::CONTENT_TYPE, "application/json")
+                .header(HeaderName::from_static(SIGNATURE_HEADER), &signature)
+                .header(HeaderName::from_static(EVENT_HEADER), event)
+                .body(body.clone());
+
+            let retry = match req.execute().await {
+                Ok(res) => {
+                    let status = res.status();
+                    delivery.status = Some(status.as_u16());
+
+                    if status.is_success() {
+                        delivery.error = None;
+                        return delivery;
+                    }
+
+                    delivery.error = Some(format!("bad response status: {status}"));
+                    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => {
+                    delivery.status = None;
+                    delivery.error = Some(format!("{e:#}"));
+                    true
+                }
+            };
+
+            if !retry || delivery.attempts >= max_attempts {
+                return delivery;
+            }
+
+            tokio::time::sleep(backoff.failed()).await;
+        }
+    }
+}
+
+/// Calculate the signature of a body, as it's sent in the
+/// [`SIGNATURE_HEADER`].
+///
+/// This is the hex-encoded HMAC-SHA256 of the body using the secret of the
+/// webhook as key, prefixed with `sha256=`.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, body);
+    format!("sha256={}", hex::encode(tag.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::backoff;
+    use reqwest::Url;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    use super::{signature, Webhook, EVENT_HEADER, SIGNATURE_HEADER};
+
+    /// Spawn a local HTTP server which answers one request with each of the
+    /// given statuses and returns the raw requests it received.
+    async fn stand_in(statuses: Vec<u16>) -> (Url, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+
+                // Read until we have the headers and the full body.
+                loop {
+                    let mut chunk = [0u8; 1024];
+                    let n = stream.read(&mut chunk).await.unwrap();
+
+                    if n == 0 {
+                        break;
+                    }
+
+                    buf.extend_from_slice(&chunk[..n]);
+
+                    let request = String::from_utf8_lossy(&buf).to_lowercase();
+
+                    let Some(end) = request.find("\r\n\r\n") else {
+                        continue;
+                    };
+
+                    let length = request
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length:"))
+                        .and_then(|l| l.trim().parse::<usize>().ok())
+                        .unwrap_or_default();
+
+                    if buf.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.shutdown().await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf).into_owned());
+            }
+
+            requests
+        });
+
+        (str::parse(&url).unwrap(), handle)
+    }
+
+    fn backoff() -> backoff::Exponential {
+        backoff::Exponential::new(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn test_signature() {
+        // Test case 2 from RFC 4231.
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries() {
+        let (url, server) = stand_in(vec![500, 200]).await;
+        let body = r#"{"event":"stream/started"}"#;
+
+        let delivery = Webhook::new("test")
+            .deliver(&url, "secret", "stream/started", body.into(), backoff(), 3)
+            .await;
+
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.status, Some(200));
+        assert!(delivery.error.is_none());
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+
+        let expected = format!(
+            "{SIGNATURE_HEADER}: {}",
+            signature("secret", body.as_bytes())
+        );
+
+        for request in requests {
+            assert!(request.starts_with("POST /hook "));
+            assert!(request.to_lowercase().contains(&expected));
+            assert!(request
+                .to_lowercase()
+                .contains(&format!("{EVENT_HEADER}: stream/started")));
+            assert!(request.ends_with(body));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deliver_gives_up() {
+        let (url, server) = stand_in(vec![400]).await;
+
+        let delivery = Webhook::new("test")
+            .deliver(&url, "secret", "moderation", "{}".into(), backoff(), 3)
+            .await;
+
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.status, Some(400));
+        assert!(delivery.error.is_some());
+        assert_eq!(server.await.unwrap().len(), 1);
+
+        let (url, server) = stand_in(vec![503, 503]).await;
+
+        let delivery = Webhook::new("test")
+            .deliver(&url, "secret", "moderation", "{}".into(), backoff(), 2)
+            .await;
+
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.status, Some(503));
+        assert!(delivery.error.is_some());
+        assert_eq!(server.await.unwrap().len(), 2);
+    }
+}
@@ -630,12 +630,30 @@ scopes! {
     (CurrencyShow, "currency/show"),
     (CurrencyBoost, "currency/boost"),
     (CurrencyWindfall, "currency/windfall"),
+    (CurrencyHistory, "currency/history"),
+    (CurrencyReverse, "currency/reverse"),
     (WaterUndo, "water/undo"),
     (AuthPermit, "auth/permit"),
     (ChatBypassUrlWhitelist, "chat/bypass-url-whitelist"),
     (Time, "time"),
     (Poll, "poll"),
     (Weather, "weather"),
+    (Quote, "quote"),
+    (QuoteAdd, "quote/add"),
+    (QuoteEdit, "quote/edit"),
+    (Counter, "counter"),
+    (CounterEdit, "counter/edit"),
+    (Giveaway, "giveaway"),
+    (GiveawayAdmin, "giveaway/admin"),
+    (Bet, "bet"),
+    (BetAdmin, "bet/admin"),
+    (Shop, "shop"),
+    (ShopAdmin, "shop/admin"),
+    (WatchTime, "watch-time"),
+    (GamesDuel, "games/duel"),
+    (GamesGamble, "games/gamble"),
+    (GamesHeist, "games/heist"),
+    (Alert, "alert"),
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -667,6 +685,7 @@ roles! {
     (Moderator, "@moderator"),
     (Subscriber, "@subscriber"),
     (Vip, "@vip"),
+    (Regular, "@regular"),
     (Everyone, "@everyone"),
 }
 
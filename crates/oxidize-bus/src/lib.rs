@@ -1,17 +1,25 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
-use common::models::{Song, State, Track, TrackId};
+use common::models::{Item, Song, State, Track, TrackId};
 use serde::Serialize;
 use tokio::sync::broadcast;
+use tokio::sync::oneshot;
 use tokio::sync::RwLock;
 
 pub trait Message: 'static + Clone + Send + Sync + Serialize {
     /// The ID of a bussed message.
-    fn id(&self) -> Option<&'static str> {
+    fn id(&self) -> Option<Cow<'static, str>> {
         None
     }
+
+    /// If the message is the last one with its ID, in which case it removes
+    /// the cached message instead of replacing it.
+    fn is_last(&self) -> bool {
+        false
+    }
 }
 
 /// Reader side of the bus.
@@ -23,7 +31,7 @@ where
 {
     subs: broadcast::Sender<T>,
     /// Latest instances of all messages.
-    latest: RwLock<HashMap<&'static str, T>>,
+    latest: RwLock<HashMap<Cow<'static, str>, T>>,
 }
 
 /// Bus system.
@@ -56,7 +64,12 @@ where
     {
         if let Some(key) = m.id() {
             let mut latest = self.inner.latest.write().await;
-            latest.insert(key, m.clone());
+
+            if m.is_last() {
+                latest.remove(&key);
+            } else {
+                latest.insert(key, m.clone());
+            }
         }
 
         let _ = self.inner.subs.send(m);
@@ -126,16 +139,67 @@ pub enum YouTube {
 
 impl Message for YouTube {
     /// Whether a message should be cached or not and under what key.
-    fn id(&self) -> Option<&'static str> {
+    fn id(&self) -> Option<Cow<'static, str>> {
         use self::YouTube::*;
 
         match *self {
-            YouTubeCurrent { .. } => Some("youtube/current"),
-            YouTubeVolume { .. } => Some("youtube/volume"),
+            YouTubeCurrent { .. } => Some(Cow::Borrowed("youtube/current")),
+            YouTubeVolume { .. } => Some(Cow::Borrowed("youtube/volume")),
         }
     }
 }
 
+/// The current number of votes for a single poll option.
+#[derive(Debug, Clone, Serialize)]
+pub struct PollOption {
+    pub key: String,
+    pub description: Option<String>,
+    pub votes: u32,
+}
+
+/// A single item in the song queue.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueItem {
+    pub track_id: TrackId,
+    pub name: String,
+    pub artists: Option<String>,
+    /// The user who requested the song.
+    pub user: Option<String>,
+    /// Duration of the song in seconds.
+    pub duration: u64,
+    /// URL to the album art or thumbnail of the song.
+    pub image_url: Option<String>,
+}
+
+impl From<&Item> for QueueItem {
+    fn from(item: &Item) -> Self {
+        Self {
+            track_id: item.track_id().clone(),
+            name: item.track().name(),
+            artists: item.track().artists(),
+            user: item.user().cloned(),
+            duration: item.duration().as_secs(),
+            image_url: item.track().image_url(),
+        }
+    }
+}
+
+/// The current state of a single named timer.
+#[derive(Debug, Clone, Serialize)]
+pub struct Timer {
+    pub name: String,
+    pub kind: String,
+    pub running: bool,
+    /// Seconds elapsed.
+    pub elapsed: u64,
+    /// Seconds remaining, if the timer has a duration.
+    pub remaining: Option<u64>,
+    /// The duration of the timer in seconds, if it has one.
+    pub duration: Option<u64>,
+    /// The timer rendered with its template.
+    pub text: String,
+}
+
 /// Messages that go on the global bus.
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type")]
@@ -158,22 +222,67 @@ pub enum Global {
         is_playing: bool,
         elapsed: u64,
         duration: u64,
+        image_url: Option<String>,
     },
     #[serde(rename = "song/modified")]
     SongModified,
+    /// Snapshot of the songs queued up after the current song.
+    #[serde(rename = "song/queue")]
+    SongQueue { items: Vec<QueueItem> },
+    /// A named counter was modified.
+    #[serde(rename = "counter")]
+    Counter {
+        name: String,
+        total: i64,
+        stream: i64,
+    },
+    /// The live results of a poll.
+    #[serde(rename = "poll")]
+    Poll {
+        id: i32,
+        question: String,
+        options: Vec<PollOption>,
+        closed: bool,
+    },
+    /// An alert to show in overlays.
+    #[serde(rename = "alert")]
+    Alert {
+        id: String,
+        kind: String,
+        user: String,
+        text: String,
+        image: Option<String>,
+        sound: Option<String>,
+        duration: u64,
+    },
+    /// The state of all named timers.
+    #[serde(rename = "timers")]
+    Timers { timers: Vec<Timer> },
 }
 
 impl Message for Global {
     /// Whether a message should be cached or not and under what key.
-    fn id(&self) -> Option<&'static str> {
+    ///
+    /// Counters and polls are cached by name and id, so that the latest
+    /// state of each one is available.
+    fn id(&self) -> Option<Cow<'static, str>> {
         use self::Global::*;
 
-        match *self {
-            SongProgress { .. } => Some("song/progress"),
-            SongCurrent { .. } => Some("song/current"),
+        match self {
+            SongProgress { .. } => Some(Cow::Borrowed("song/progress")),
+            SongCurrent { .. } => Some(Cow::Borrowed("song/current")),
+            SongQueue { .. } => Some(Cow::Borrowed("song/queue")),
+            Timers { .. } => Some(Cow::Borrowed("timers")),
+            Counter { name, .. } => Some(Cow::Owned(format!("counter/{}", name))),
+            Poll { id, .. } => Some(Cow::Owned(format!("poll/{}", id))),
             _ => None,
         }
     }
+
+    /// Closed polls are no longer cached.
+    fn is_last(&self) -> bool {
+        matches!(self, Global::Poll { closed: true, .. })
+    }
 }
 
 impl Global {
@@ -209,6 +318,7 @@ impl Global {
                     is_playing: false,
                     elapsed: 0,
                     duration: 0,
+                    image_url: None,
                 });
             }
         };
@@ -220,10 +330,67 @@ impl Global {
             is_playing: song.state() == State::Playing,
             elapsed: song.elapsed().as_secs(),
             duration: song.item().duration().as_secs(),
+            image_url: song.item().track().image_url(),
         })
     }
+
+    /// Construct a snapshot of the given song queue.
+    pub fn song_queue<'a, I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Item>,
+    {
+        Global::SongQueue {
+            items: items.into_iter().map(QueueItem::from).collect(),
+        }
+    }
+}
+
+/// Things happening in the bot which might be of interest to external
+/// services, like webhooks.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+#[non_exhaustive]
+pub enum Event {
+    /// The stream went live.
+    #[serde(rename = "stream/started")]
+    StreamStarted,
+    /// The stream went offline.
+    #[serde(rename = "stream/stopped")]
+    StreamStopped,
+    /// An after-stream note was added.
+    #[serde(rename = "after-stream")]
+    AfterStream { user: String, text: String },
+    /// Currency was given to a user.
+    #[serde(rename = "currency/given")]
+    CurrencyGiven {
+        from: String,
+        to: String,
+        amount: i64,
+        currency: String,
+        reason: String,
+    },
+    /// A moderation action was performed by the bot.
+    #[serde(rename = "moderation")]
+    Moderation {
+        action: String,
+        user: Option<String>,
+        reason: String,
+    },
+    /// Something which should be shown as an alert happened, like a
+    /// subscription or a raid.
+    #[serde(rename = "alert")]
+    Alert {
+        id: String,
+        kind: String,
+        user: String,
+        amount: Option<i64>,
+        title: Option<String>,
+        message: Option<String>,
+    },
 }
 
+impl Message for Event {}
+
 /// Events for running commands externally.
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type")]
@@ -231,11 +398,58 @@ pub enum Command {
     /// Run a raw command.
     #[serde(rename = "raw")]
     Raw { command: String },
+    /// Run a raw command, and report back once it has been handled.
+    #[serde(rename = "run")]
+    Run {
+        command: String,
+        /// The user to run the command as, instead of the bot.
+        user: Option<Caller>,
+        #[serde(skip)]
+        done: Done,
+    },
+}
+
+/// A user on whose behalf a [Command::Run] is run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Caller {
+    /// The login of the user.
+    pub login: String,
+    /// The display name of the user.
+    pub display_name: String,
+}
+
+/// Handle used to report the outcome of a [Command::Run].
+#[derive(Debug, Clone)]
+pub struct Done {
+    tx: Arc<Mutex<Option<oneshot::Sender<Result<()>>>>>,
+}
+
+impl Done {
+    /// Construct a new handle, and the receiver of its outcome.
+    pub fn new() -> (Self, oneshot::Receiver<Result<()>>) {
+        let (tx, rx) = oneshot::channel();
+        let tx = Arc::new(Mutex::new(Some(tx)));
+        (Self { tx }, rx)
+    }
+
+    /// Report the outcome of the command.
+    ///
+    /// Only the first outcome reported is used.
+    pub fn complete(&self, result: Result<()>) {
+        let tx = match self.tx.lock() {
+            Ok(mut tx) => tx.take(),
+            Err(..) => return,
+        };
+
+        if let Some(tx) = tx {
+            let _ = tx.send(result);
+        }
+    }
 }
 
 impl Message for Command {
     /// Whether a message should be cached or not and under what key.
-    fn id(&self) -> Option<&'static str> {
+    fn id(&self) -> Option<Cow<'static, str>> {
         None
     }
 }
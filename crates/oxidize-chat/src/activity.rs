@@ -0,0 +1,47 @@
+//! Tracking of which users have chatted recently.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+#[derive(Clone, Default)]
+pub(crate) struct Activity {
+    /// If activity is being recorded.
+    enabled: Arc<AtomicBool>,
+    /// Logins of users who have chatted since the last time we checked.
+    seen: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Activity {
+    /// Set if activity should be recorded.
+    ///
+    /// Nothing takes the recorded activity while this is disabled, so any
+    /// users seen so far are forgotten.
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+
+        if !enabled {
+            self.seen.lock().clear();
+        }
+    }
+
+    /// Indicate that the given user has chatted.
+    pub(crate) fn seen(&self, login: &str) {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut seen = self.seen.lock();
+
+        if !seen.contains(login) {
+            seen.insert(login.to_string());
+        }
+    }
+
+    /// Take all users who have chatted since the last call.
+    pub(crate) fn take(&self) -> HashSet<String> {
+        std::mem::take(&mut *self.seen.lock())
+    }
+}
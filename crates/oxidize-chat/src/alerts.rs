@@ -0,0 +1,173 @@
+//! Alerts derived from chat, like subscriptions, raids and cheers.
+
+use std::collections::HashMap;
+
+use common::irc::Tags;
+
+/// Convert the tags and message of a USERNOTICE into an alert.
+///
+/// Returns `None` for notices which shouldn't be alerted on.
+pub(crate) fn from_user_notice<I, K, V>(tags: I, message: Option<&str>) -> Option<bus::Event>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let tags = tags
+        .into_iter()
+        .map(|(k, v)| (k.as_ref().to_owned(), v.as_ref().to_owned()))
+        .collect::<HashMap<_, _>>();
+
+    let tag = |key: &str| tags.get(key).filter(|v| !v.is_empty()).cloned();
+    let number = |key: &str| tags.get(key).and_then(|v| str::parse::<i64>(v).ok());
+
+    let id = tag("id")?;
+    let user = tag("display-name").or_else(|| tag("login"))?;
+    let message = message.filter(|m| !m.is_empty()).map(str::to_owned);
+
+    let (kind, user, amount, title, message) = match tag("msg-id")?.as_str() {
+        "sub" | "resub" => (
+            "sub",
+            user,
+            number("msg-param-cumulative-months"),
+            tag("msg-param-sub-plan-name"),
+            message,
+        ),
+        // NB: gifts which are part of a community gift are covered by the
+        // `submysterygift` notice sent before them.
+        "subgift" if tag("msg-param-community-gift-id").is_some() => return None,
+        "subgift" => (
+            "subgift",
+            user,
+            Some(1),
+            tag("msg-param-recipient-display-name"),
+            None,
+        ),
+        "submysterygift" => (
+            "subgift",
+            user,
+            number("msg-param-mass-gift-count"),
+            None,
+            None,
+        ),
+        "raid" => (
+            "raid",
+            tag("msg-param-displayName").unwrap_or(user),
+            number("msg-param-viewerCount"),
+            None,
+            None,
+        ),
+        _ => return None,
+    };
+
+    Some(bus::Event::Alert {
+        id,
+        kind: kind.to_owned(),
+        user,
+        amount,
+        title,
+        message,
+    })
+}
+
+/// Convert a chat message into an alert if bits were cheered with it.
+pub(crate) fn from_cheer(tags: &Tags, login: &str, message: &str) -> Option<bus::Event> {
+    let bits = tags.bits.filter(|bits| *bits > 0)?;
+
+    Some(bus::Event::Alert {
+        id: tags.id.clone()?,
+        kind: String::from("bits"),
+        user: tags
+            .display_name
+            .clone()
+            .unwrap_or_else(|| login.to_owned()),
+        amount: Some(bits),
+        title: None,
+        message: Some(message.to_owned()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use common::irc::Tags;
+
+    use super::{from_cheer, from_user_notice};
+
+    fn alert(event: Option<bus::Event>) -> Option<(String, String, Option<i64>, Option<String>)> {
+        match event? {
+            bus::Event::Alert {
+                kind,
+                user,
+                amount,
+                title,
+                ..
+            } => Some((kind, user, amount, title)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_user_notice() {
+        let resub = [
+            ("id", "1"),
+            ("msg-id", "resub"),
+            ("display-name", "Turtle"),
+            ("login", "turtle"),
+            ("msg-param-cumulative-months", "7"),
+            ("msg-param-sub-plan-name", "Tier 1"),
+        ];
+
+        assert_eq!(
+            alert(from_user_notice(resub, Some("hello"))),
+            Some((
+                String::from("sub"),
+                String::from("Turtle"),
+                Some(7),
+                Some(String::from("Tier 1"))
+            ))
+        );
+
+        let raid = [
+            ("id", "2"),
+            ("msg-id", "raid"),
+            ("login", "hare"),
+            ("msg-param-displayName", "Hare"),
+            ("msg-param-viewerCount", "42"),
+        ];
+
+        assert_eq!(
+            alert(from_user_notice(raid, None)),
+            Some((String::from("raid"), String::from("Hare"), Some(42), None))
+        );
+
+        let community = [
+            ("id", "3"),
+            ("msg-id", "subgift"),
+            ("login", "hare"),
+            ("msg-param-community-gift-id", "1234"),
+        ];
+
+        assert_eq!(alert(from_user_notice(community, None)), None);
+
+        let ritual = [("id", "4"), ("msg-id", "ritual"), ("login", "hare")];
+        assert_eq!(alert(from_user_notice(ritual, None)), None);
+    }
+
+    #[test]
+    fn test_cheer() {
+        let tags = Tags::from_tags([("id", "5"), ("bits", "100")]);
+
+        assert_eq!(
+            alert(from_cheer(&tags, "turtle", "cheer100")),
+            Some((
+                String::from("bits"),
+                String::from("turtle"),
+                Some(100),
+                None
+            ))
+        );
+
+        let tags = Tags::from_tags([("id", "6")]);
+        assert_eq!(alert(from_cheer(&tags, "turtle", "hello")), None);
+    }
+}
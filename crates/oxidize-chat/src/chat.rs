@@ -10,17 +10,19 @@ use auth::{Auth, Role, Scope};
 use common::backoff;
 use common::irc::Tags;
 use common::stream::{Stream, StreamExt};
-use common::{tags, Channel, Cooldown};
+use common::{tags, Channel, Cooldown, Duration, OwnedChannel};
 use irc::client::{self, Client};
 use irc::proto::command::Command;
 use irc::proto::message::{Message, Tag};
 use irc::proto::Prefix;
 use notify::{recommended_watcher, RecommendedWatcher, Watcher};
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use tokio::sync::{mpsc, Notify};
 
+use crate::activity;
+use crate::alerts;
 use crate::chat_log;
 use crate::command;
 use crate::currency_admin;
@@ -140,6 +142,8 @@ struct Setup {
     #[dependency]
     global_bus: bus::Bus<bus::Global>,
     #[dependency]
+    event_bus: bus::Bus<bus::Event>,
+    #[dependency]
     settings: settings::Settings<::auth::Scope>,
 }
 
@@ -166,6 +170,7 @@ impl ChatLoop<'_> {
             message_log,
             command_bus,
             global_bus,
+            event_bus,
             settings,
         } = setup;
 
@@ -210,6 +215,7 @@ impl ChatLoop<'_> {
         let sender_ty = chat_settings.var("sender-type", sender::Type::Chat).await?;
         let threshold = chat_settings.var("idle-detection/threshold", 5).await?;
         let idle = idle::Idle::new(threshold);
+        let activity = activity::Activity::default();
 
         let nightbot = injector.var::<api::NightBot>().await;
 
@@ -227,6 +233,17 @@ impl ChatLoop<'_> {
 
         let refresh_roles_future = refresh_roles(context_inner.clone(), streamer.clone());
 
+        let regular_threshold = settings
+            .var("watch-time/regular-threshold", Duration::hours(10))
+            .await?;
+
+        let refresh_regulars_future = refresh_regulars(
+            context_inner.clone(),
+            injector.clone(),
+            Channel::from_string(&streamer.user.login).into_owned(),
+            regular_threshold,
+        );
+
         let mut handlers = module::Handlers::default();
 
         let channel = Channel::from_string(&streamer.user.login);
@@ -252,6 +269,7 @@ impl ChatLoop<'_> {
         };
 
         let mut hook_futures = Vec::new();
+        let hooks = command::Hooks::new(context_inner.clone());
 
         for module in modules {
             tracing::trace!("Initializing module: {}", module.ty());
@@ -264,6 +282,7 @@ impl ChatLoop<'_> {
                     idle: &idle,
                     streamer: &streamer,
                     sender: &sender,
+                    hooks: &hooks,
                     settings: &settings,
                     injector,
                 })
@@ -278,6 +297,9 @@ impl ChatLoop<'_> {
             streamer.clone(),
             sender.clone(),
             idle.clone(),
+            activity.clone(),
+            stream_info.clone(),
+            context_inner.clone(),
             injector.clone(),
             chat_settings.clone(),
             settings.clone(),
@@ -306,6 +328,8 @@ impl ChatLoop<'_> {
 
         let (mut commands_stream, commands) = injector.stream().await;
         let (mut aliases_stream, aliases) = injector.stream().await;
+        let (mut counters_stream, counters) = injector.stream().await;
+        let (mut watch_time_stream, watch_time) = injector.stream().await;
 
         let mut pong_timeout = Fuse::empty();
 
@@ -316,6 +340,7 @@ impl ChatLoop<'_> {
             futures =>
             stream_info_future,
             refresh_roles_future,
+            refresh_regulars_future,
             reward_loop_future,
             messages_future
         }
@@ -337,12 +362,16 @@ impl ChatLoop<'_> {
             commands,
             bad_words: &bad_words,
             global_bus: &global_bus,
+            event_bus: &event_bus,
             aliases,
+            counters,
+            watch_time,
             api_url: Arc::new(api_url),
             moderator_cooldown,
             handlers: &handlers,
             scripts: &mut scripts,
             idle: &idle,
+            activity: &activity,
             pong_timeout: &mut pong_timeout,
             bot: &bot,
             handler_shutdown: false,
@@ -398,7 +427,15 @@ impl ChatLoop<'_> {
                         bus::Command::Raw { command } => {
                             tracing::trace!("Raw command: {}", command);
 
-                            if let Err(e) = handler.raw(command, &mut pending, &mut hooks).await {
+                            if let Err(e) = handler.raw(command, None, None, &mut pending, &mut hooks).await {
+                                common::log_error!(e, "Failed to handle message");
+                            }
+                        }
+                        bus::Command::Run { command, user, done } => {
+                            tracing::trace!("Run command: {}", command);
+
+                            if let Err(e) = handler.raw(command, user, Some(done.clone()), &mut pending, &mut hooks).await {
+                                done.complete(Err(anyhow!("{}", e)));
                                 common::log_error!(e, "Failed to handle message");
                             }
                         }
@@ -427,6 +464,12 @@ impl ChatLoop<'_> {
                 aliases = aliases_stream.recv() => {
                     handler.aliases = aliases;
                 }
+                counters = counters_stream.recv() => {
+                    handler.counters = counters;
+                }
+                watch_time = watch_time_stream.recv() => {
+                    handler.watch_time = watch_time;
+                }
                 chat_log = chat_log_builder.update() => {
                     handler.chat_log = chat_log?;
                 }
@@ -512,8 +555,14 @@ struct Handler<'a> {
     bad_words: &'a db::Words,
     /// For sending notifications.
     global_bus: &'a bus::Bus<bus::Global>,
+    /// For sending events, like moderation actions.
+    event_bus: &'a bus::Bus<bus::Event>,
     /// Aliases.
     aliases: Option<db::Aliases>,
+    /// Named counters.
+    counters: Option<db::Counters>,
+    /// Watch time of users.
+    watch_time: Option<db::WatchTime>,
     /// Configured API URL.
     api_url: Arc<Option<String>>,
     /// Active moderator cooldown.
@@ -524,6 +573,8 @@ struct Handler<'a> {
     scripts: &'a mut script::Scripts,
     /// Build idle detection.
     idle: &'a idle::Idle,
+    /// Users who have chatted since the last viewer reward.
+    activity: &'a activity::Activity,
     /// Pong timeout currently running.
     pong_timeout: &'a mut Fuse<Pin<Box<tokio::time::Sleep>>>,
     /// OAuth 2.0 Token used to authenticate with Chat.
@@ -606,11 +657,16 @@ async fn process_command<'a>(
     handlers: &'a module::Handlers,
     scripts: &script::Scripts,
     pending: &mut common::Futures<'a, PendingOutput<'a>>,
+    done: &mut Option<bus::Done>,
 ) -> Result<()> {
     match command {
         "ping" => {
             respond!(ctx, "What do you want?");
             global_bus.send(bus::Global::Ping).await;
+
+            if let Some(done) = done.take() {
+                done.complete(Ok(()));
+            }
         }
         other => {
             tracing::trace!("Testing command: {}", other);
@@ -647,12 +703,26 @@ async fn process_command<'a>(
                             ctx.respond(m).await;
                         }
 
+                        if let Some(done) = done.take() {
+                            done.complete(Err(anyhow!("not allowed to run `{}`", other)));
+                        }
+
                         return Ok(());
                     }
                 }
 
+                let done = done.take();
+
                 pending.push(Box::pin(async move {
                     let result = handler.handle(&mut ctx).await;
+
+                    if let Some(done) = done {
+                        done.complete(match &result {
+                            Ok(()) => Ok(()),
+                            Err(e) => Err(anyhow!("{}", e)),
+                        });
+                    }
+
                     (result, ctx)
                 }));
 
@@ -660,9 +730,18 @@ async fn process_command<'a>(
             }
 
             if let Some(handler) = scripts.get(other) {
-                if let Err(e) = handler.call(ctx.clone()).await {
-                    ctx.respond("Sorry, something went wrong :(").await;
-                    common::log_error!(e, "Error when processing command");
+                let result = match handler.call(ctx.clone()).await {
+                    Ok(()) => Ok(()),
+                    Err(e) => {
+                        ctx.respond("Sorry, something went wrong :(").await;
+                        let message = e.to_string();
+                        common::log_error!(e, "Error when processing command");
+                        Err(anyhow!("{}", message))
+                    }
+                };
+
+                if let Some(done) = done.take() {
+                    done.complete(result);
                 }
 
                 return Ok(());
@@ -686,11 +765,11 @@ impl<'a> Handler<'a> {
         Ok(())
     }
 
-    /// Test if the message should be deleted.
-    async fn should_be_deleted(&self, user: &User, message: &str) -> bool {
+    /// Test if the message should be deleted, and if so why.
+    async fn deletion_reason(&self, user: &User, message: &str) -> Option<&'static str> {
         // Moderators can say whatever they want.
         if user.is_moderator() {
-            return false;
+            return None;
         }
 
         if self.bad_words_enabled.load().await {
@@ -711,7 +790,7 @@ impl<'a> Handler<'a> {
                     }
                 }
 
-                return true;
+                return Some("bad-word");
             }
         }
 
@@ -719,10 +798,10 @@ impl<'a> Handler<'a> {
             && self.url_whitelist_enabled.load().await
             && self.has_bad_link(message)
         {
-            return true;
+            return Some("bad-link");
         }
 
-        false
+        None
     }
 
     /// Test the message for bad words.
@@ -762,10 +841,14 @@ impl<'a> Handler<'a> {
     }
 
     /// Process the given command.
+    ///
+    /// If `done` is specified, it's completed once the command has been
+    /// handled.
     pub(crate) async fn process_message(
         &mut self,
         user: &User,
         mut message: Arc<String>,
+        mut done: Option<bus::Done>,
         pending: &mut common::Futures<'a, PendingOutput<'a>>,
         hooks: &mut common::Futures<'a, HookOutput<'a>>,
     ) -> Result<()> {
@@ -793,6 +876,10 @@ impl<'a> Handler<'a> {
             self.idle.seen();
         }
 
+        if let Some(user) = user.real() {
+            self.activity.seen(user.login());
+        }
+
         // NB: declared here to be in scope.
         let mut seen = HashSet::new();
         let mut path = Vec::new();
@@ -814,6 +901,11 @@ impl<'a> Handler<'a> {
                         "Recursion found in alias expansion: {} :(",
                         path.join(" -> ")
                     );
+
+                    if let Some(done) = done {
+                        done.complete(Err(anyhow!("recursion found in alias expansion")));
+                    }
+
                     return Ok(());
                 }
 
@@ -834,15 +926,41 @@ impl<'a> Handler<'a> {
                     commands.increment(&command).await?;
                 }
 
+                let counters = match self.counters.as_ref() {
+                    Some(counters) if command.has_var("counter") => Some(
+                        counter_vars(counters, user.sender().channel(), self.stream_info).await?,
+                    ),
+                    _ => None,
+                };
+
+                let watch_time = match (self.watch_time.as_ref(), user.real()) {
+                    (Some(watch_time), Some(real)) if command.has_var("watch_time") => {
+                        let stream_id = self.stream_info.stream_id();
+
+                        let of = watch_time
+                            .get(user.sender().channel(), stream_id.as_deref(), real.login())
+                            .await?;
+
+                        Some(WatchTimeVars::from(of))
+                    }
+                    _ => None,
+                };
+
                 let vars = CommandVars {
                     name: user.display_name(),
                     target: &self.streamer.user.login,
                     count: command.count(),
+                    counters,
+                    watch_time,
                     captures,
                 };
 
                 let response = command.render(&vars)?;
                 self.sender.privmsg(response).await;
+
+                if let Some(done) = done.take() {
+                    done.complete(Ok(()));
+                }
             }
         }
 
@@ -864,6 +982,7 @@ impl<'a> Handler<'a> {
                     self.handlers,
                     self.scripts,
                     pending,
+                    &mut done,
                 )
                 .await;
 
@@ -873,27 +992,55 @@ impl<'a> Handler<'a> {
             }
         }
 
-        if self.should_be_deleted(user, &message).await {
+        if let Some(done) = done {
+            done.complete(Err(anyhow!("no such command")));
+        }
+
+        if let Some(reason) = self.deletion_reason(user, &message).await {
             self.delete_message(user)?;
+
+            self.event_bus
+                .send(bus::Event::Moderation {
+                    action: String::from("delete"),
+                    user: user.real().map(|u| u.login().to_owned()),
+                    reason: reason.to_owned(),
+                })
+                .await;
         }
 
         Ok(())
     }
 
     /// Run the given raw command.
+    ///
+    /// If `caller` is specified, the command is run as that user. Otherwise
+    /// it's run as the bot.
     pub(crate) async fn raw(
         &mut self,
         message: String,
+        caller: Option<bus::Caller>,
+        done: Option<bus::Done>,
         pending: &mut common::Futures<'a, PendingOutput<'a>>,
         hook: &mut common::Futures<'a, HookOutput<'a>>,
     ) -> Result<()> {
-        let tags = Tags::default();
+        let mut tags = Tags::default();
+
+        let principal = match caller {
+            Some(caller) => {
+                tags.display_name = Some(caller.display_name);
+
+                Principal::User {
+                    login: caller.login.into(),
+                }
+            }
+            None => Principal::Injected,
+        };
 
         let user = User {
             inner: Arc::new(UserInner {
                 tags,
                 sender: self.sender.clone(),
-                principal: Principal::Injected,
+                principal,
                 streamer_login: self.streamer.user.login.clone(),
                 stream_info: self.stream_info.clone(),
                 auth: self.auth.clone(),
@@ -901,7 +1048,7 @@ impl<'a> Handler<'a> {
             }),
         };
 
-        self.process_message(&user, Arc::new(message), pending, hook)
+        self.process_message(&user, Arc::new(message), done, pending, hook)
             .await?;
         Ok(())
     }
@@ -931,6 +1078,10 @@ impl<'a> Handler<'a> {
 
                 let login = Box::<str>::from(login);
 
+                if let Some(alert) = alerts::from_cheer(&tags, &login, &message) {
+                    self.event_bus.send(alert).await;
+                }
+
                 if let Some(chat_log) = self.chat_log.as_ref().cloned() {
                     let tags = tags.clone();
                     let user = self.streamer.user.clone();
@@ -955,7 +1106,8 @@ impl<'a> Handler<'a> {
                     }),
                 };
 
-                self.process_message(&user, message, pending, hooks).await?;
+                self.process_message(&user, message, None, pending, hooks)
+                    .await?;
             }
             Command::JOIN(channel, _, _) => {
                 let user = match &m.prefix {
@@ -1003,6 +1155,20 @@ impl<'a> Handler<'a> {
                         }
                     }
                 }
+                "USERNOTICE" => {
+                    let tags = m.tags.iter().flat_map(|tag| {
+                        tag.iter().flat_map(|tag| match tag {
+                            Tag(key, Some(value)) => Some((key, value)),
+                            _ => None,
+                        })
+                    });
+
+                    if let Some(alert) =
+                        alerts::from_user_notice(tags, tail.get(1).map(String::as_str))
+                    {
+                        self.event_bus.send(alert).await;
+                    }
+                }
                 "CLEARCHAT" => {
                     if let Some(chat_log) = self.chat_log.as_ref() {
                         match tail.first() {
@@ -1084,6 +1250,11 @@ impl<'a> RealUser<'a> {
         self.context.vips.read().contains(self.login)
     }
 
+    /// Test if regular.
+    fn is_regular(&self) -> bool {
+        self.context.regulars.read().contains(self.login)
+    }
+
     /// Get a list of all roles the current requester belongs to.
     pub fn roles(&self) -> smallvec::SmallVec<[Role; 4]> {
         let mut roles = smallvec::SmallVec::new();
@@ -1104,6 +1275,10 @@ impl<'a> RealUser<'a> {
             roles.push(Role::Vip);
         }
 
+        if self.is_regular() {
+            roles.push(Role::Regular);
+        }
+
         roles.push(Role::Everyone);
         roles
     }
@@ -1244,6 +1419,7 @@ impl User {
                 roles.push(Role::Moderator);
                 roles.push(Role::Subscriber);
                 roles.push(Role::Vip);
+                roles.push(Role::Regular);
                 roles
             }
         }
@@ -1381,10 +1557,66 @@ pub(crate) struct CommandVars<'a> {
     name: Option<&'a str>,
     target: &'a str,
     count: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    counters: Option<HashMap<String, CounterVars>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    watch_time: Option<WatchTimeVars>,
     #[serde(flatten)]
     captures: db::Captures<'a>,
 }
 
+/// Values of a named counter as exposed to templates.
+#[derive(Serialize)]
+pub(crate) struct CounterVars {
+    total: i64,
+    stream: i64,
+}
+
+/// Watch time of the user running a command as exposed to templates.
+#[derive(Serialize)]
+pub(crate) struct WatchTimeVars {
+    total: String,
+    stream: String,
+    total_seconds: i64,
+    stream_seconds: i64,
+}
+
+impl From<db::WatchTimeOf> for WatchTimeVars {
+    fn from(of: db::WatchTimeOf) -> Self {
+        let format = |seconds: i64| {
+            common::display::compact_duration(time::Duration::from_secs(seconds.max(0) as u64))
+        };
+
+        Self {
+            total: format(of.total),
+            stream: format(of.stream),
+            total_seconds: of.total,
+            stream_seconds: of.stream,
+        }
+    }
+}
+
+/// Collect the values of all counters in the given channel.
+async fn counter_vars(
+    counters: &db::Counters,
+    channel: &Channel,
+    stream_info: &stream_info::StreamInfo,
+) -> Result<HashMap<String, CounterVars>> {
+    let stream_id = stream_info.stream_id();
+    let mut out = HashMap::new();
+
+    for counter in counters.list(channel).await? {
+        let vars = CounterVars {
+            total: counter.count,
+            stream: counter.stream(stream_id.as_deref()),
+        };
+
+        out.insert(counter.name, vars);
+    }
+
+    Ok(out)
+}
+
 // Future to populate moderators and VIPs.
 #[tracing::instrument(skip_all)]
 async fn refresh_roles(
@@ -1453,3 +1685,43 @@ async fn refresh_roles(
         }
     }
 }
+
+// Future to populate regulars based on their watch time.
+#[tracing::instrument(skip_all)]
+async fn refresh_regulars(
+    context: Arc<command::ContextInner>,
+    injector: Injector,
+    channel: OwnedChannel,
+    threshold: settings::Var<Duration>,
+) -> Result<()> {
+    let (mut watch_time_stream, mut watch_time) = injector.stream::<db::WatchTime>().await;
+    let mut interval = tokio::time::interval(time::Duration::from_secs(60 * 5));
+
+    loop {
+        tokio::select! {
+            update = watch_time_stream.recv() => {
+                watch_time = update;
+            }
+            _ = interval.tick() => {
+                let threshold = threshold.load().await;
+
+                let regulars = match &watch_time {
+                    Some(watch_time) if !threshold.is_empty() => {
+                        let seconds = threshold.num_seconds() as i64;
+
+                        match watch_time.users_above(&channel, seconds).await {
+                            Ok(regulars) => regulars,
+                            Err(error) => {
+                                common::log_error!(error, "Failed to update regulars");
+                                continue;
+                            }
+                        }
+                    }
+                    _ => HashSet::new(),
+                };
+
+                *context.regulars.write() = regulars;
+            }
+        }
+    }
+}
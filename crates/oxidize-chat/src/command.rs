@@ -91,6 +91,8 @@ pub(crate) struct ContextInner {
     pub(crate) moderators: parking_lot::RwLock<HashSet<String>>,
     /// Logins for VIPs.
     pub(crate) vips: parking_lot::RwLock<HashSet<String>>,
+    /// Logins for regulars, based on their watch time.
+    pub(crate) regulars: parking_lot::RwLock<HashSet<String>>,
     /// Notifications that can be sent to the context.
     notify: ContextNotify,
 }
@@ -107,6 +109,7 @@ impl ContextInner {
             message_hooks: Default::default(),
             moderators: Default::default(),
             vips: Default::default(),
+            regulars: Default::default(),
             notify: ContextNotify::new(restart),
         }
     }
@@ -115,6 +118,51 @@ impl ContextInner {
     pub(crate) fn notify(&self) -> &ContextNotify {
         &self.notify
     }
+
+    /// Setup the specified hook.
+    async fn insert_hook<H>(&self, hook: H) -> HookId
+    where
+        H: MessageHook,
+    {
+        let mut hooks = self.message_hooks.write().await;
+        let len = hooks.insert(Box::new(hook));
+        HookId(len)
+    }
+
+    /// Remove the specified hook.
+    async fn remove_hook(&self, id: HookId) {
+        let mut hooks = self.message_hooks.write().await;
+
+        if hooks.contains(id.0) {
+            let _ = hooks.remove(id.0);
+        }
+    }
+}
+
+/// Handle used to install message hooks outside of a command invocation,
+/// like when a module is being set up.
+#[derive(Clone)]
+pub struct Hooks {
+    inner: Arc<ContextInner>,
+}
+
+impl Hooks {
+    pub(crate) fn new(inner: Arc<ContextInner>) -> Self {
+        Self { inner }
+    }
+
+    /// Setup the specified hook.
+    pub async fn insert<H>(&self, hook: H) -> HookId
+    where
+        H: MessageHook,
+    {
+        self.inner.insert_hook(hook).await
+    }
+
+    /// Remove the specified hook.
+    pub async fn remove(&self, id: HookId) {
+        self.inner.remove_hook(id).await
+    }
 }
 
 /// Context for a single command invocation.
@@ -153,18 +201,12 @@ impl Context<'_> {
     where
         H: MessageHook,
     {
-        let mut hooks = self.inner.message_hooks.write().await;
-        let len = hooks.insert(Box::new(hook));
-        HookId(len)
+        self.inner.insert_hook(hook).await
     }
 
-    /// Setup the specified hook.
+    /// Remove the specified hook.
     pub async fn remove_hook(&self, id: HookId) {
-        let mut hooks = self.inner.message_hooks.write().await;
-
-        if hooks.contains(id.0) {
-            let _ = hooks.remove(id.0);
-        }
+        self.inner.remove_hook(id).await
     }
 
     /// Verify that the current user has the associated scope.
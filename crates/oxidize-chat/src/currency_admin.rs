@@ -8,9 +8,15 @@ use common::display;
 
 use crate::command;
 
+/// Default number of users to show in the leaderboard.
+const DEFAULT_TOP: i64 = 5;
+/// Maximum number of users to show in the leaderboard.
+const MAX_TOP: i64 = 10;
+
 /// Handler for the !admin command.
 pub(crate) struct Handler {
     pub(crate) currency: async_injector::Ref<currency::Currency>,
+    pub(crate) event_bus: async_injector::Ref<bus::Bus<bus::Event>>,
 }
 
 impl Handler {
@@ -21,6 +27,28 @@ impl Handler {
             _ => None,
         }
     }
+
+    /// Notify that currency was given to a user.
+    async fn currency_given(
+        &self,
+        from: &str,
+        to: &str,
+        amount: i64,
+        currency: &currency::Currency,
+        reason: &str,
+    ) {
+        if let Some(event_bus) = self.event_bus.load().await {
+            event_bus
+                .send(bus::Event::CurrencyGiven {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                    amount,
+                    currency: currency.name.to_string(),
+                    reason: reason.to_owned(),
+                })
+                .await;
+        }
+    }
 }
 
 #[async_trait]
@@ -114,6 +142,7 @@ impl command::Handler for Handler {
                         &taker,
                         amount,
                         user.is_streamer(),
+                        currency::Reason::Give,
                     )
                     .await;
 
@@ -126,6 +155,9 @@ impl command::Handler for Handler {
                             amount = amount,
                             currency = currency.name
                         );
+
+                        self.currency_given(user.login(), &taker, amount, &currency, "give")
+                            .await;
                     }
                     Err(currency::BalanceTransferError::NoBalance) => {
                         respond!(
@@ -160,9 +192,19 @@ impl command::Handler for Handler {
                 }
 
                 currency
-                    .balance_add(ctx.channel(), &boosted_user, amount)
+                    .balance_add(
+                        ctx.channel(),
+                        &boosted_user,
+                        amount,
+                        currency::Reason::Boost,
+                    )
                     .await?;
 
+                if let Some(booster) = ctx.user.real() {
+                    self.currency_given(booster.login(), &boosted_user, amount, &currency, "boost")
+                        .await;
+                }
+
                 if amount >= 0 {
                     respond!(
                         ctx,
@@ -186,7 +228,9 @@ impl command::Handler for Handler {
 
                 let amount: i64 = ctx.next_parse("<amount>")?;
 
-                currency.add_channel_all(ctx.channel(), amount, 0).await?;
+                currency
+                    .add_channel_all(ctx.channel(), amount, 0, currency::Reason::Windfall)
+                    .await?;
 
                 if amount >= 0 {
                     ctx.privmsg(format!(
@@ -204,10 +248,143 @@ impl command::Handler for Handler {
                     .await;
                 }
             }
+            Some("top") => {
+                let limit = match ctx.next() {
+                    Some(n) => match n.parse::<i64>() {
+                        Ok(n) if n > 0 => n.min(MAX_TOP),
+                        _ => {
+                            respond!(ctx, "Expected: top [n], where n is a positive number");
+                            return Ok(());
+                        }
+                    },
+                    None => DEFAULT_TOP,
+                };
+
+                let top = currency.top(ctx.channel(), limit).await?;
+
+                if top.is_empty() {
+                    respond!(
+                        ctx,
+                        "Nobody has any {currency} yet",
+                        currency = currency.name
+                    );
+                    return Ok(());
+                }
+
+                let top = top
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| format!("#{} {} ({})", i + 1, e.user, e.balance))
+                    .collect::<Vec<_>>();
+
+                respond!(ctx, "{top}", top = top.join(", "));
+            }
+            Some("rank") => {
+                let user = match ctx.next() {
+                    Some(user) => db::user_id(&user),
+                    None => ctx
+                        .user
+                        .real()
+                        .ok_or(respond_err!("Only real users can check their rank"))?
+                        .login()
+                        .to_string(),
+                };
+
+                match currency.rank(ctx.channel(), &user).await? {
+                    Some(rank) => {
+                        respond!(
+                            ctx,
+                            "{user} is ranked #{rank} out of {total} with {balance} {currency}.",
+                            user = user,
+                            rank = rank.rank,
+                            total = rank.total,
+                            balance = rank.balance,
+                            currency = currency.name,
+                        );
+                    }
+                    None => {
+                        respond!(
+                            ctx,
+                            "{user} doesn't have any {currency} yet",
+                            user = user,
+                            currency = currency.name
+                        );
+                    }
+                }
+            }
+            Some("history") => {
+                ctx.check_scope(Scope::CurrencyHistory).await?;
+
+                let user = ctx.next_str("<user>")?;
+                let entries = currency
+                    .history(ctx.channel(), Some(user.as_str()), 5, 0)
+                    .await?;
+
+                if entries.is_empty() {
+                    respond!(ctx, "No transactions for {user}", user = user);
+                    return Ok(());
+                }
+
+                let entries = entries
+                    .iter()
+                    .map(|e| {
+                        let reversed = if e.reversed { ", reversed" } else { "" };
+
+                        match &e.counterparty {
+                            Some(counterparty) => format!(
+                                "#{} {:+} ({} with {}{})",
+                                e.transaction_id, e.amount, e.reason, counterparty, reversed
+                            ),
+                            None => format!(
+                                "#{} {:+} ({}{})",
+                                e.transaction_id, e.amount, e.reason, reversed
+                            ),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                respond!(
+                    ctx,
+                    "Latest transactions for {user}: {entries}",
+                    user = user,
+                    entries = entries.join(", ")
+                );
+            }
+            Some("reverse") => {
+                ctx.check_scope(Scope::CurrencyReverse).await?;
+
+                let id: i32 = ctx.next_parse("<transaction>")?;
+
+                match currency.reverse(ctx.channel(), id).await {
+                    Ok(reversal) => {
+                        respond!(
+                            ctx,
+                            "Reversed transaction #{id} (recorded as #{reversal})",
+                            id = id,
+                            reversal = reversal
+                        );
+                    }
+                    Err(currency::ReverseError::NotFound) => {
+                        respond!(ctx, "No transaction with id #{id}", id = id);
+                    }
+                    Err(currency::ReverseError::AlreadyReversed) => {
+                        respond!(ctx, "Transaction #{id} has already been reversed", id = id);
+                    }
+                    Err(currency::ReverseError::Reversal) => {
+                        respond!(ctx, "Transaction #{id} is a reversal itself", id = id);
+                    }
+                    Err(e) => {
+                        respond!(ctx, "Failed to reverse transaction, sorry :(");
+                        common::log_error!(e, "Failed to reverse transaction");
+                    }
+                }
+            }
             Some(..) => {
                 let mut alts = Vec::new();
 
                 alts.push("give");
+                alts.push("top");
+                alts.push("rank");
 
                 if ctx.user.has_scope(Scope::CurrencyBoost).await {
                     alts.push("boost");
@@ -227,6 +404,18 @@ impl command::Handler for Handler {
                     alts.push("show 🛇");
                 }
 
+                if ctx.user.has_scope(Scope::CurrencyHistory).await {
+                    alts.push("history");
+                } else {
+                    alts.push("history 🛇");
+                }
+
+                if ctx.user.has_scope(Scope::CurrencyReverse).await {
+                    alts.push("reverse");
+                } else {
+                    alts.push("reverse 🛇");
+                }
+
                 respond!(ctx, "Expected: {alts}", alts = alts.join(", "));
             }
         }
@@ -237,6 +426,10 @@ impl command::Handler for Handler {
 
 pub(crate) async fn setup(injector: &Injector) -> Result<Arc<Handler>, Error> {
     let currency = injector.var::<currency::Currency>().await;
-    let handler = Handler { currency };
+    let event_bus = injector.var::<bus::Bus<bus::Event>>().await;
+    let handler = Handler {
+        currency,
+        event_bus,
+    };
     Ok(Arc::new(handler))
 }
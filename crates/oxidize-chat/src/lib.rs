@@ -1,6 +1,8 @@
 #[macro_use]
 mod macros;
 
+mod activity;
+mod alerts;
 mod idle;
 
 mod chat;
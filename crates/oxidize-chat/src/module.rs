@@ -37,6 +37,7 @@ pub struct HookContext<'a, 'task> {
     pub idle: &'a idle::Idle,
     pub streamer: &'a api::TwitchAndUser,
     pub sender: &'a sender::Sender,
+    pub hooks: &'a command::Hooks,
     pub settings: &'a settings::Settings<::auth::Scope>,
     pub handlers: &'a mut Handlers,
     pub tasks: &'a mut Vec<BoxFuture<'task, Result<()>>>,
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -6,14 +7,81 @@ use async_injector::Injector;
 use common::Duration;
 use tokio::time;
 
+use crate::activity;
+use crate::command;
 use crate::idle;
 use crate::sender;
+use crate::stream_info;
+
+/// Reward multipliers for different kinds of viewers, in percent.
+#[derive(Debug, Clone)]
+struct Multipliers {
+    tier1: u32,
+    tier2: u32,
+    tier3: u32,
+    vip: u32,
+    moderator: u32,
+    active: u32,
+    custom: HashMap<String, u32>,
+}
+
+impl Multipliers {
+    /// Calculate the reward for the given viewer.
+    ///
+    /// The highest multiplier which applies to the viewer is used, and the
+    /// activity bonus is applied on top of it for viewers who have chatted.
+    fn reward(&self, base: i64, viewer: &Viewer<'_>) -> i64 {
+        let mut multiplier = 100;
+
+        match viewer.tier {
+            Some(3) => multiplier = multiplier.max(self.tier3),
+            Some(2) => multiplier = multiplier.max(self.tier2),
+            Some(_) => multiplier = multiplier.max(self.tier1),
+            None => {}
+        }
+
+        if viewer.vip {
+            multiplier = multiplier.max(self.vip);
+        }
+
+        if viewer.moderator {
+            multiplier = multiplier.max(self.moderator);
+        }
+
+        if let Some(custom) = self.custom.get(viewer.login) {
+            multiplier = multiplier.max(*custom);
+        }
+
+        let mut reward = base * multiplier as i64 / 100;
+
+        if viewer.active {
+            reward = reward * self.active as i64 / 100;
+        }
+
+        reward
+    }
+}
+
+/// Information on a single viewer being rewarded.
+#[derive(Debug, Default)]
+struct Viewer<'a> {
+    login: &'a str,
+    /// Subscription tier, if subscribed.
+    tier: Option<u8>,
+    vip: bool,
+    moderator: bool,
+    /// If the viewer has chatted since the last reward.
+    active: bool,
+}
 
 /// Set up a reward loop.
 pub async fn setup(
     streamer: api::TwitchAndUser,
     sender: sender::Sender,
     idle: idle::Idle,
+    activity: activity::Activity,
+    stream_info: stream_info::StreamInfo,
+    context: Arc<command::ContextInner>,
     injector: Injector,
     chat_settings: settings::Settings<::auth::Scope>,
     settings: settings::Settings<::auth::Scope>,
@@ -24,6 +92,9 @@ pub async fn setup(
         streamer,
         sender,
         idle,
+        activity,
+        stream_info,
+        context,
         injector,
         chat_settings,
         settings,
@@ -41,12 +112,61 @@ struct Task {
     streamer: api::TwitchAndUser,
     sender: sender::Sender,
     idle: idle::Idle,
+    activity: activity::Activity,
+    stream_info: stream_info::StreamInfo,
+    context: Arc<command::ContextInner>,
     injector: Injector,
     chat_settings: settings::Settings<::auth::Scope>,
     settings: settings::Settings<::auth::Scope>,
 }
 
 impl Task {
+    /// Calculate the reward for every user currently in chat.
+    fn rewards(
+        &self,
+        base: i64,
+        multipliers: &Multipliers,
+        chatters: impl IntoIterator<Item = String>,
+    ) -> HashMap<String, i64> {
+        let active = self.activity.take();
+
+        let tiers = self
+            .stream_info
+            .data
+            .read()
+            .subs
+            .iter()
+            .map(|s| {
+                let tier = match s.tier.as_str() {
+                    "3000" => 3,
+                    "2000" => 2,
+                    _ => 1,
+                };
+
+                (s.user_name.to_lowercase(), tier)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let moderators = self.context.moderators.read();
+        let vips = self.context.vips.read();
+
+        chatters
+            .into_iter()
+            .map(|login| {
+                let viewer = Viewer {
+                    login: &login,
+                    tier: tiers.get(&login).copied(),
+                    vip: vips.contains(&login),
+                    moderator: moderators.contains(&login),
+                    active: active.contains(&login),
+                };
+
+                let reward = multipliers.reward(base, &viewer);
+                (login, reward)
+            })
+            .collect()
+    }
+
     async fn run(&self) -> Result<()> {
         let Task {
             streamer,
@@ -55,6 +175,7 @@ impl Task {
             injector,
             chat_settings,
             settings,
+            ..
         } = &self;
 
         let reward = 10;
@@ -66,7 +187,16 @@ impl Task {
             .await?;
 
         let reward_percentage = chat_settings.var("viewer-reward%", 100).await?;
-        let (mut viewer_reward_stream, viewer_reward) = chat_settings
+        let tier1_percentage = chat_settings.var("viewer-reward/tier1%", 100).await?;
+        let tier2_percentage = chat_settings.var("viewer-reward/tier2%", 100).await?;
+        let tier3_percentage = chat_settings.var("viewer-reward/tier3%", 100).await?;
+        let vip_percentage = chat_settings.var("viewer-reward/vip%", 100).await?;
+        let moderator_percentage = chat_settings.var("viewer-reward/moderator%", 100).await?;
+        let active_percentage = chat_settings.var("viewer-reward/active%", 100).await?;
+        let custom_percentage = chat_settings
+            .var("viewer-reward/custom%", HashMap::<String, u32>::new())
+            .await?;
+        let (mut viewer_reward_stream, mut viewer_reward) = chat_settings
             .stream("viewer-reward/enabled")
             .or_with(false)
             .await?;
@@ -89,8 +219,34 @@ impl Task {
             .stream("currency/mysql/schema")
             .or_default()
             .await?;
+        let (mut postgres_url_stream, postgres_url) =
+            settings.stream("currency/postgres/url").optional().await?;
+        let (mut postgres_schema_stream, postgres_schema) = settings
+            .stream("currency/postgres/schema")
+            .or_default()
+            .await?;
+
+        let (mut max_balance_stream, max_balance) = settings
+            .stream("currency/max-balance")
+            .or_with(0i64)
+            .await?;
+        let (mut stream_earn_cap_stream, stream_earn_cap) = settings
+            .stream("currency/stream-earn-cap")
+            .or_with(0i64)
+            .await?;
+
+        let decay_percentage = settings.var("currency/decay%", 0u32).await?;
+        let decay_inactive = settings
+            .var("currency/decay/inactive", Duration::hours(24 * 30))
+            .await?;
+        let (mut decay_interval_stream, decay_interval) = settings
+            .stream("currency/decay/interval")
+            .or_with(Duration::hours(24))
+            .await?;
+        let watch_time_enabled = settings.var("watch-time/enabled", false).await?;
 
         let (mut db_stream, db) = injector.stream::<db::Database>().await;
+        let (mut watch_time_stream, mut watch_time) = injector.stream::<db::WatchTime>().await;
 
         let mut builder =
             currency::CurrencyBuilder::new(streamer.clone(), mysql_schema, injector.clone());
@@ -101,11 +257,21 @@ impl Task {
         builder.command_enabled = command_enabled;
         builder.name = name.map(Arc::new);
         builder.mysql_url = mysql_url;
+        builder.postgres_url = postgres_url;
+        builder.postgres_schema = postgres_schema;
+        builder.max_balance = max_balance;
+        builder.stream_earn_cap = stream_earn_cap;
 
         let mut currency = builder.build_and_inject().await;
 
         let new_timer = |interval: &Duration, viewer_reward: bool| {
-            if viewer_reward && !interval.is_empty() {
+            let enabled = viewer_reward && !interval.is_empty();
+
+            // Only record who's been chatting while there are rewards to
+            // give out.
+            self.activity.set_enabled(enabled);
+
+            if enabled {
                 Fuse::new(tokio::time::interval(interval.as_std()))
             } else {
                 Fuse::empty()
@@ -114,6 +280,19 @@ impl Task {
 
         let mut timer = new_timer(&reward_interval, viewer_reward);
 
+        // NB: decay shouldn't run immediately on startup, or every time the
+        // interval is changed.
+        let new_decay_timer = |interval: &Duration| {
+            if interval.is_empty() {
+                Fuse::empty()
+            } else {
+                let period = interval.as_std();
+                Fuse::new(time::interval_at(time::Instant::now() + period, period))
+            }
+        };
+
+        let mut decay_timer = new_decay_timer(&decay_interval);
+
         loop {
             tokio::select! {
                 update = interval_stream.recv() => {
@@ -127,6 +306,39 @@ impl Task {
                     builder.db = update;
                     currency = builder.build_and_inject().await;
                 }
+                update = watch_time_stream.recv() => {
+                    watch_time = update;
+                }
+                update = max_balance_stream.recv() => {
+                    builder.max_balance = update;
+                    currency = builder.build_and_inject().await;
+                }
+                update = stream_earn_cap_stream.recv() => {
+                    builder.stream_earn_cap = update;
+                    currency = builder.build_and_inject().await;
+                }
+                update = decay_interval_stream.recv() => {
+                    decay_timer = new_decay_timer(&update);
+                }
+                _ = decay_timer.as_pin_mut().poll_inner(|mut i, cx| i.poll_tick(cx)) => {
+                    let percentage = decay_percentage.load().await;
+
+                    // Decay relies on watch time to know when users were last
+                    // seen.
+                    if percentage == 0 || !watch_time_enabled.load().await {
+                        continue;
+                    }
+
+                    let (Some(currency), Some(watch_time)) = (currency.as_ref(), watch_time.as_ref()) else {
+                        continue;
+                    };
+
+                    let inactive = decay_inactive.load().await.num_seconds() as i64;
+                    let users = watch_time.inactive(sender.channel(), inactive).await?;
+                    let total = currency.decay(sender.channel(), users, percentage).await?;
+
+                    tracing::info!(?total, "Decayed balances of inactive users");
+                }
                 enabled = enabled_stream.recv() => {
                     builder.enabled = enabled;
                     currency = builder.build_and_inject().await;
@@ -147,11 +359,20 @@ impl Task {
                     builder.mysql_schema = update;
                     currency = builder.build_and_inject().await;
                 }
+                postgres_url = postgres_url_stream.recv() => {
+                    builder.postgres_url = postgres_url;
+                    currency = builder.build_and_inject().await;
+                }
+                update = postgres_schema_stream.recv() => {
+                    builder.postgres_schema = update;
+                    currency = builder.build_and_inject().await;
+                }
                 command_enabled = command_enabled_stream.recv() => {
                     builder.command_enabled = command_enabled;
                     currency = builder.build_and_inject().await;
                 }
-                viewer_reward = viewer_reward_stream.recv() => {
+                update = viewer_reward_stream.recv() => {
+                    viewer_reward = update;
                     timer = new_timer(&reward_interval, viewer_reward);
                 }
                 _ = timer.as_pin_mut().poll_inner(|mut i, cx| i.poll_tick(cx)) => {
@@ -165,18 +386,93 @@ impl Task {
                     tracing::trace!("Running reward loop");
 
                     let reward = (reward * reward_percentage.load().await as i64) / 100i64;
-                    let count = currency
-                        .add_channel_all(sender.channel(), reward, seconds)
+
+                    let multipliers = Multipliers {
+                        tier1: tier1_percentage.load().await,
+                        tier2: tier2_percentage.load().await,
+                        tier3: tier3_percentage.load().await,
+                        vip: vip_percentage.load().await,
+                        moderator: moderator_percentage.load().await,
+                        active: active_percentage.load().await,
+                        custom: custom_percentage.load().await,
+                    };
+
+                    let chatters = currency.chatters().await?;
+                    let mut rewards = self.rewards(reward, &multipliers, chatters);
+
+                    if let Some(stream_id) = self.stream_info.stream_id() {
+                        currency.cap_earnings(&stream_id, &mut rewards);
+                    }
+
+                    let count = rewards.len();
+                    let bonus = rewards.values().filter(|r| **r > reward).count();
+
+                    tracing::trace!(?count, ?bonus, "Rewarding viewers");
+
+                    currency
+                        .balances_reward(
+                            sender.channel(),
+                            rewards,
+                            seconds,
+                            currency::Reason::RewardLoop,
+                        )
                         .await?;
 
                     if notify_rewards && count > 0 && !idle.is_idle().await {
-                        sender.privmsg(format!(
-                            "/me has given {} {} to all viewers!",
-                            reward, currency.name
-                        )).await;
+                        if bonus > 0 {
+                            sender.privmsg(format!(
+                                "/me has given {} {} to all viewers, with a bonus for {} loyal viewer(s)!",
+                                reward, currency.name, bonus
+                            )).await;
+                        } else {
+                            sender.privmsg(format!(
+                                "/me has given {} {} to all viewers!",
+                                reward, currency.name
+                            )).await;
+                        }
                     }
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Multipliers, Viewer};
+
+    fn multipliers() -> Multipliers {
+        Multipliers {
+            tier1: 150,
+            tier2: 200,
+            tier3: 300,
+            vip: 120,
+            moderator: 100,
+            active: 200,
+            custom: HashMap::from([(String::from("turtle"), 500)]),
+        }
+    }
+
+    #[test]
+    fn test_reward() {
+        let m = multipliers();
+
+        let viewer = |login, tier, vip, active| Viewer {
+            login,
+            tier,
+            vip,
+            active,
+            ..Viewer::default()
+        };
+
+        assert_eq!(10, m.reward(10, &viewer("lurker", None, false, false)));
+        assert_eq!(20, m.reward(10, &viewer("chatter", None, false, true)));
+        assert_eq!(15, m.reward(10, &viewer("sub", Some(1), false, false)));
+        assert_eq!(30, m.reward(10, &viewer("sub", Some(3), true, false)));
+        assert_eq!(12, m.reward(10, &viewer("vip", None, true, false)));
+        assert_eq!(40, m.reward(10, &viewer("sub", Some(2), false, true)));
+        assert_eq!(50, m.reward(10, &viewer("turtle", Some(1), false, false)));
+    }
+}
@@ -36,6 +36,11 @@ impl StreamInfo {
         self.data.read().subs_set.contains(name)
     }
 
+    /// Get the identifier of the current stream, if we are live.
+    pub fn stream_id(&self) -> Option<String> {
+        self.data.read().stream.as_ref().map(|s| s.id.clone())
+    }
+
     /// Refresh the known list of subscribers.
     pub(crate) async fn refresh_subs(&self, streamer: &api::TwitchAndUser) -> Result<()> {
         let subs = {
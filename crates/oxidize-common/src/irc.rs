@@ -13,6 +13,8 @@ pub struct Tags {
     pub color: Option<String>,
     /// Emotes part of the message.
     pub emotes: Option<String>,
+    /// Number of bits cheered with the message.
+    pub bits: Option<i64>,
 }
 
 impl Tags {
@@ -29,6 +31,7 @@ impl Tags {
         let mut user_id = None;
         let mut color = None;
         let mut emotes = None;
+        let mut bits = None;
 
         for (key, value) in tags {
             match key.as_ref() {
@@ -38,6 +41,7 @@ impl Tags {
                 "user-id" => user_id = Some(value.as_ref().to_owned()),
                 "color" => color = Some(value.as_ref().to_owned()),
                 "emotes" => emotes = Some(value.as_ref().to_owned()),
+                "bits" => bits = str::parse(value.as_ref()).ok(),
                 key => {
                     tracing::trace!(key, value = value.as_ref(), "unsupported tag");
                 }
@@ -51,6 +55,7 @@ impl Tags {
             user_id,
             color,
             emotes,
+            bits,
         }
     }
 }
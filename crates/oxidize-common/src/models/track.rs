@@ -37,4 +37,96 @@ impl Track {
                 .to_string(),
         }
     }
+
+    /// Get the URL of the largest album art or thumbnail available for the
+    /// track.
+    pub fn image_url(&self) -> Option<String> {
+        match self {
+            Self::Spotify { track } => track
+                .album
+                .images
+                .iter()
+                .max_by_key(|i| i.width.unwrap_or_default())
+                .map(|i| i.url.clone()),
+            Self::YouTube { video } => video
+                .snippet
+                .as_ref()?
+                .thumbnails
+                .values()
+                .max_by_key(|t| t.width)
+                .map(|t| t.url.clone()),
+        }
+    }
+}
+
+#[test]
+fn test_spotify_image_url() {
+    let json = r#"{
+        "type": "spotify",
+        "track": {
+            "album": {
+                "artists": [],
+                "external_urls": {},
+                "images": [
+                    {"height": 300, "url": "https://i.scdn.co/image/medium", "width": 300},
+                    {"height": 640, "url": "https://i.scdn.co/image/large", "width": 640},
+                    {"url": "https://i.scdn.co/image/unknown"}
+                ],
+                "name": "Album",
+                "type": "album"
+            },
+            "artists": [],
+            "disc_number": 1,
+            "duration_ms": 1000,
+            "explicit": false,
+            "external_ids": {},
+            "external_urls": {},
+            "is_local": false,
+            "name": "Track",
+            "popularity": 0,
+            "track_number": 1,
+            "type": "track",
+            "uri": "spotify:track:test"
+        }
+    }"#;
+
+    let track: Track = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        track.image_url().as_deref(),
+        Some("https://i.scdn.co/image/large")
+    );
+}
+
+#[test]
+fn test_youtube_image_url() {
+    let json = r#"{
+        "type": "youtube",
+        "video": {
+            "kind": "youtube#video",
+            "etag": "etag",
+            "id": "test",
+            "snippet": {
+                "channelId": "channel",
+                "title": "Video",
+                "thumbnails": {
+                    "default": {"url": "https://i.ytimg.com/vi/test/default.jpg", "width": 120, "height": 90},
+                    "high": {"url": "https://i.ytimg.com/vi/test/hqdefault.jpg", "width": 480, "height": 360}
+                }
+            }
+        }
+    }"#;
+
+    let track: Track = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        track.image_url().as_deref(),
+        Some("https://i.ytimg.com/vi/test/hqdefault.jpg")
+    );
+
+    let json = r#"{
+        "type": "youtube",
+        "video": {"kind": "youtube#video", "etag": "etag", "id": "test"}
+    }"#;
+
+    let track: Track = serde_json::from_str(json).unwrap();
+    assert_eq!(track.image_url(), None);
 }
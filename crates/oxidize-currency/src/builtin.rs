@@ -5,7 +5,7 @@ use common::Channel;
 use db::{models, schema, user_id, Database};
 use diesel::prelude::*;
 
-use crate::{BalanceOf, BalanceTransferError};
+use crate::{BalanceOf, BalanceTransferError, LeaderboardEntry, Rank};
 
 pub(crate) struct Backend {
     db: Database,
@@ -141,6 +141,80 @@ impl Backend {
             .await
     }
 
+    /// Get the users with the highest balances.
+    pub(crate) async fn top(
+        &self,
+        channel: &Channel,
+        exclude: &str,
+        limit: i64,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        use self::schema::balances::dsl;
+
+        let channel = channel.to_owned();
+        let exclude = user_id(exclude);
+
+        self.db
+            .asyncify(move |c| {
+                let top = dsl::balances
+                    .select((dsl::user, dsl::amount))
+                    .filter(dsl::channel.eq(&channel).and(dsl::user.ne(&exclude)))
+                    .order((dsl::amount.desc(), dsl::user.asc()))
+                    .limit(limit)
+                    .load::<(String, i64)>(c)?;
+
+                Ok(top
+                    .into_iter()
+                    .map(|(user, balance)| LeaderboardEntry { user, balance })
+                    .collect())
+            })
+            .await
+    }
+
+    /// Get the rank of the given user.
+    pub(crate) async fn rank(
+        &self,
+        channel: &Channel,
+        exclude: &str,
+        user: &str,
+    ) -> Result<Option<Rank>> {
+        use self::schema::balances::dsl;
+
+        let channel = channel.to_owned();
+        let exclude = user_id(exclude);
+        let user = user_id(user);
+
+        self.db
+            .asyncify(move |c| {
+                let balance = dsl::balances
+                    .select(dsl::amount)
+                    .filter(dsl::channel.eq(&channel).and(dsl::user.eq(&user)))
+                    .first::<i64>(c)
+                    .optional()?;
+
+                let Some(balance) = balance else {
+                    return Ok(None);
+                };
+
+                let ranked =
+                    dsl::balances.filter(dsl::channel.eq(&channel).and(dsl::user.ne(&exclude)));
+
+                let above = ranked
+                    .clone()
+                    .filter(dsl::amount.gt(balance))
+                    .count()
+                    .get_result::<i64>(c)?;
+
+                let total = ranked.count().get_result::<i64>(c)?;
+
+                Ok(Some(Rank {
+                    rank: above + 1,
+                    balance,
+                    total,
+                }))
+            })
+            .await
+    }
+
     /// Add (or subtract) from the balance for a single user.
     pub async fn balance_add(&self, channel: &Channel, user: &str, amount: i64) -> Result<()> {
         let channel = channel.to_owned();
@@ -151,14 +225,56 @@ impl Backend {
             .await
     }
 
-    /// Add balance to users.
+    /// Decay the balance of a single user by the given percentage.
+    ///
+    /// Returns the amount which was taken.
+    pub(crate) async fn balance_decay(
+        &self,
+        channel: &Channel,
+        user: &str,
+        percentage: u32,
+    ) -> Result<i64> {
+        use self::schema::balances::dsl;
+
+        let channel = channel.to_owned();
+        let user = user_id(user);
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let filter =
+                        dsl::balances.filter(dsl::channel.eq(&channel).and(dsl::user.eq(&user)));
+
+                    let Some(balance) = filter.select(dsl::amount).first::<i64>(c).optional()?
+                    else {
+                        return Ok(0);
+                    };
+
+                    let amount = crate::decay_amount(balance, percentage);
+
+                    if amount > 0 {
+                        diesel::update(filter)
+                            .set(dsl::amount.eq(balance - amount))
+                            .execute(c)?;
+                    }
+
+                    Ok(amount)
+                })
+            })
+            .await
+    }
+
+    /// Add balance to users, without going over `max_balance`.
+    ///
+    /// Returns the amount which was actually added for each user.
     pub(crate) async fn balances_increment<I>(
         &self,
         channel: &Channel,
         users: I,
         amount: i64,
         watch_time: i64,
-    ) -> Result<()>
+        max_balance: Option<i64>,
+    ) -> Result<Vec<(String, i64)>>
     where
         I: IntoIterator<IntoIter: Send + 'static, Item = String>,
     {
@@ -169,6 +285,8 @@ impl Backend {
 
         self.db
             .asyncify(move |c| {
+                let mut applied = Vec::new();
+
                 for user in users {
                     let user = user_id(&user);
 
@@ -177,31 +295,37 @@ impl Backend {
 
                     let b = filter.first::<models::Balance>(c).optional()?;
 
-                    match b {
+                    let added = match b {
                         None => {
                             let balance = models::Balance {
                                 channel: channel.to_owned(),
                                 user: user.clone(),
-                                amount,
+                                amount: crate::capped(0, amount, max_balance),
                                 watch_time,
                             };
 
                             diesel::insert_into(dsl::balances)
                                 .values(&balance)
                                 .execute(c)?;
+
+                            balance.amount
                         }
                         Some(b) => {
-                            let value = b.amount.saturating_add(amount);
+                            let value = crate::capped(b.amount, amount, max_balance);
                             let watch_time = b.watch_time.saturating_add(watch_time);
 
                             diesel::update(filter)
                                 .set((dsl::amount.eq(value), dsl::watch_time.eq(watch_time)))
                                 .execute(c)?;
+
+                            value - b.amount
                         }
-                    }
+                    };
+
+                    applied.push((user, added));
                 }
 
-                Ok(())
+                Ok(applied)
             })
             .await
     }
@@ -1,6 +1,7 @@
 //! Stream currency configuration.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::pin::pin;
 use std::sync::Arc;
 
@@ -9,12 +10,13 @@ use async_injector::Injector;
 use common::stream::StreamExt;
 use common::{Channel, Duration};
 use db::models::Balance;
-use db::Database;
+use db::{Database, Ledger, LedgerUpdate};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 mod builtin;
 mod mysql;
+mod postgres;
 
 /// Balance of a single user.
 #[derive(Default)]
@@ -34,10 +36,122 @@ impl BalanceOf {
     }
 }
 
+/// A single entry in the currency leaderboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    pub user: String,
+    pub balance: i64,
+}
+
+/// The leaderboard rank of a single user.
+#[derive(Debug, Clone, Copy)]
+pub struct Rank {
+    /// The rank of the user, starting at 1. Users with the same balance share
+    /// the same rank.
+    pub rank: i64,
+    pub balance: i64,
+    /// The total number of ranked users.
+    pub total: i64,
+}
+
+/// Why the balance of a user was modified, as recorded in the ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Reason {
+    /// Periodic reward for watching the stream.
+    RewardLoop,
+    /// Reward handed out by the swear jar.
+    Swearjar,
+    /// Reward for requesting a song.
+    SongRequest,
+    /// A user giving currency to another user.
+    Give,
+    /// An admin boosting or taking currency from a user.
+    Boost,
+    /// An admin giving currency to everyone in the channel.
+    Windfall,
+    /// Buying tickets in a giveaway.
+    Giveaway,
+    /// Wagering in or getting paid out from a bet.
+    Bet,
+    /// Water reminder rewards.
+    Water,
+    /// Buying GTA V mod commands.
+    Gtav,
+    /// Buying an item in the shop, or getting refunded for one.
+    Shop,
+    /// Winning or losing a duel, gamble, or heist.
+    Minigame,
+    /// Balance decaying for inactive users.
+    Decay,
+    /// Redeeming a channel point reward.
+    Redemption,
+    /// Reversing an earlier transaction.
+    Reversal,
+}
+
+impl Reason {
+    /// Get the reason as stored in the ledger.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Reason::RewardLoop => "reward-loop",
+            Reason::Swearjar => "swearjar",
+            Reason::SongRequest => "song-request",
+            Reason::Give => "give",
+            Reason::Boost => "boost",
+            Reason::Windfall => "windfall",
+            Reason::Giveaway => "giveaway",
+            Reason::Bet => "bet",
+            Reason::Water => "water",
+            Reason::Gtav => "gtav",
+            Reason::Shop => "shop",
+            Reason::Minigame => "minigame",
+            Reason::Decay => "decay",
+            Reason::Redemption => "redemption",
+            Reason::Reversal => "reversal",
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// How much each user has earned from rewards during the current stream.
+#[derive(Debug, Default)]
+struct Earnings {
+    stream_id: Option<String>,
+    earned: HashMap<String, i64>,
+}
+
+impl Earnings {
+    /// Limit rewards so that no user earns more than `cap` during the given
+    /// stream, and record what they earned.
+    fn cap(&mut self, stream_id: &str, rewards: &mut HashMap<String, i64>, cap: i64) {
+        if self.stream_id.as_deref() != Some(stream_id) {
+            self.stream_id = Some(stream_id.to_string());
+            self.earned.clear();
+        }
+
+        for (user, reward) in rewards.iter_mut() {
+            if *reward <= 0 {
+                continue;
+            }
+
+            let earned = self.earned.entry(user.clone()).or_default();
+            *reward = (*reward).min(cap.saturating_sub(*earned).max(0));
+            *earned += *reward;
+        }
+    }
+}
+
 /// Helper struct to construct a currency.
 pub struct CurrencyBuilder {
     streamer: api::TwitchAndUser,
     pub mysql_schema: mysql::Schema,
+    pub postgres_schema: postgres::Schema,
     injector: Injector,
     pub ty: BackendType,
     pub enabled: bool,
@@ -45,6 +159,13 @@ pub struct CurrencyBuilder {
     pub name: Option<Arc<String>>,
     pub db: Option<Database>,
     pub mysql_url: Option<String>,
+    pub postgres_url: Option<String>,
+    /// The maximum balance users can reach from rewards. 0 for no limit.
+    pub max_balance: i64,
+    /// The maximum amount users can earn from rewards during a single stream.
+    /// 0 for no limit.
+    pub stream_earn_cap: i64,
+    earnings: Arc<parking_lot::Mutex<Earnings>>,
 }
 
 impl CurrencyBuilder {
@@ -57,6 +178,7 @@ impl CurrencyBuilder {
         Self {
             streamer,
             mysql_schema,
+            postgres_schema: Default::default(),
             injector,
             ty: Default::default(),
             enabled: Default::default(),
@@ -64,6 +186,10 @@ impl CurrencyBuilder {
             name: Default::default(),
             db: None,
             mysql_url: None,
+            postgres_url: None,
+            max_balance: 0,
+            stream_earn_cap: 0,
+            earnings: Default::default(),
         }
     }
 
@@ -129,6 +255,21 @@ impl CurrencyBuilder {
 
                 Backend::MySql(backend)
             }
+            BackendType::Postgres => {
+                let channel = Channel::new("#").to_owned();
+                let url = self.postgres_url.clone()?;
+                let schema = self.postgres_schema.clone();
+
+                let backend = match self::postgres::Backend::connect(channel, url, schema) {
+                    Ok(backend) => backend,
+                    Err(e) => {
+                        common::log_error!(e, "Failed to establish connection");
+                        return None;
+                    }
+                };
+
+                Backend::Postgres(backend)
+            }
         };
 
         Some(Currency {
@@ -136,7 +277,11 @@ impl CurrencyBuilder {
             command_enabled: self.command_enabled,
             inner: Arc::new(Inner {
                 backend,
+                ledger: self.db.clone().map(Ledger::new),
                 streamer: self.streamer.clone(),
+                max_balance: (self.max_balance > 0).then_some(self.max_balance),
+                stream_earn_cap: (self.stream_earn_cap > 0).then_some(self.stream_earn_cap),
+                earnings: self.earnings.clone(),
             }),
         })
     }
@@ -151,11 +296,14 @@ pub enum BackendType {
     Mysql,
     #[serde(rename = "honkos")]
     Honkos,
+    #[serde(rename = "postgres")]
+    Postgres,
 }
 
 enum Backend {
     BuiltIn(self::builtin::Backend),
     MySql(self::mysql::Backend),
+    Postgres(self::postgres::Backend),
 }
 
 impl Backend {
@@ -181,6 +329,11 @@ impl Backend {
                     .balance_transfer(channel, giver, taker, amount, override_balance)
                     .await
             }
+            Postgres(backend) => {
+                backend
+                    .balance_transfer(channel, giver, taker, amount, override_balance)
+                    .await
+            }
         }
     }
 
@@ -191,6 +344,7 @@ impl Backend {
         match self {
             BuiltIn(backend) => backend.export_balances().await,
             MySql(backend) => backend.export_balances().await,
+            Postgres(backend) => backend.export_balances().await,
         }
     }
 
@@ -201,6 +355,7 @@ impl Backend {
         match self {
             BuiltIn(backend) => backend.import_balances(balances).await,
             MySql(backend) => backend.import_balances(balances).await,
+            Postgres(backend) => backend.import_balances(balances).await,
         }
     }
 
@@ -211,6 +366,34 @@ impl Backend {
         match self {
             BuiltIn(backend) => backend.balance_of(channel, user).await,
             MySql(backend) => backend.balance_of(channel, user).await,
+            Postgres(backend) => backend.balance_of(channel, user).await,
+        }
+    }
+
+    /// Get the users with the highest balances.
+    async fn top(
+        &self,
+        channel: &Channel,
+        exclude: &str,
+        limit: i64,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        use self::Backend::*;
+
+        match self {
+            BuiltIn(backend) => backend.top(channel, exclude, limit).await,
+            MySql(backend) => backend.top(channel, exclude, limit).await,
+            Postgres(backend) => backend.top(channel, exclude, limit).await,
+        }
+    }
+
+    /// Get the rank of the given user.
+    async fn rank(&self, channel: &Channel, exclude: &str, user: &str) -> Result<Option<Rank>> {
+        use self::Backend::*;
+
+        match self {
+            BuiltIn(backend) => backend.rank(channel, exclude, user).await,
+            MySql(backend) => backend.rank(channel, exclude, user).await,
+            Postgres(backend) => backend.rank(channel, exclude, user).await,
         }
     }
 
@@ -221,10 +404,29 @@ impl Backend {
         match self {
             BuiltIn(backend) => backend.balance_add(channel, user, amount).await,
             MySql(backend) => backend.balance_add(channel, user, amount).await,
+            Postgres(backend) => backend.balance_add(channel, user, amount).await,
+        }
+    }
+
+    /// Decay the balance of a single user by the given percentage.
+    ///
+    /// Returns the amount which was taken.
+    async fn balance_decay(&self, channel: &Channel, user: &str, percentage: u32) -> Result<i64> {
+        use self::Backend::*;
+
+        match self {
+            BuiltIn(backend) => backend.balance_decay(channel, user, percentage).await,
+            MySql(backend) => backend.balance_decay(channel, user, percentage).await,
+            Postgres(backend) => backend.balance_decay(channel, user, percentage).await,
         }
     }
 
     /// Add balance to users.
+    ///
+    /// If `max_balance` is specified, positive amounts won't increase
+    /// balances beyond it.
+    ///
+    /// Returns the amount which was actually added for each user.
     #[tracing::instrument(skip(self, users))]
     pub async fn balances_increment<I>(
         &self,
@@ -232,26 +434,60 @@ impl Backend {
         users: I,
         amount: i64,
         watch_time: i64,
-    ) -> Result<()>
+        max_balance: Option<i64>,
+    ) -> Result<Vec<(String, i64)>>
     where
         I: IntoIterator<IntoIter: Send + 'static, Item = String>,
     {
         use self::Backend::*;
 
+        let max_balance = max_balance.filter(|_| amount > 0);
+
         match self {
             BuiltIn(backend) => {
                 backend
-                    .balances_increment(channel, users, amount, watch_time)
+                    .balances_increment(channel, users, amount, watch_time, max_balance)
+                    .await
+            }
+            MySql(backend) => {
+                backend
+                    .balances_increment(channel, users, amount, max_balance)
+                    .await
+            }
+            Postgres(backend) => {
+                backend
+                    .balances_increment(channel, users, amount, watch_time, max_balance)
                     .await
             }
-            MySql(backend) => backend.balances_increment(channel, users, amount).await,
         }
     }
 }
 
+/// Add `amount` to `balance` without going over `max_balance`.
+///
+/// Balances which are already above the maximum are left as they are.
+fn capped(balance: i64, amount: i64, max_balance: Option<i64>) -> i64 {
+    let value = balance.saturating_add(amount);
+
+    match max_balance {
+        Some(max_balance) => value.min(max_balance.max(balance)),
+        None => value,
+    }
+}
+
+/// Calculate how much to take from the given balance when decaying it by
+/// `percentage`.
+fn decay_amount(balance: i64, percentage: u32) -> i64 {
+    (balance as i128 * percentage as i128 / 100) as i64
+}
+
 struct Inner {
     backend: Backend,
+    ledger: Option<Ledger>,
     streamer: api::TwitchAndUser,
+    max_balance: Option<i64>,
+    stream_earn_cap: Option<i64>,
+    earnings: Arc<parking_lot::Mutex<Earnings>>,
 }
 
 /// The currency being used.
@@ -262,7 +498,29 @@ pub struct Currency {
     inner: Arc<Inner>,
 }
 
+/// Get the logins of all users currently in the streamer's chat.
+pub async fn chatters(streamer: &api::TwitchAndUser) -> Result<HashSet<String>> {
+    tracing::trace!("Getting chatters");
+
+    let mut chatters = pin!(streamer
+        .client
+        .chatters(&streamer.user.id, &streamer.user.id));
+
+    let mut users = HashSet::new();
+
+    while let Some(chatter) = chatters.next().await.transpose()? {
+        users.insert(chatter.user_login);
+    }
+
+    Ok(users)
+}
+
 impl Currency {
+    /// Get the logins of all users currently in chat.
+    pub async fn chatters(&self) -> Result<HashSet<String>> {
+        chatters(&self.inner.streamer).await
+    }
+
     /// Reward all users.
     #[tracing::instrument(skip(self))]
     pub async fn add_channel_all(
@@ -270,26 +528,12 @@ impl Currency {
         channel: &Channel,
         reward: i64,
         watch_time: i64,
+        reason: Reason,
     ) -> Result<usize> {
-        tracing::trace!("Getting chatters");
-
-        let mut chatters = pin!(self
-            .inner
-            .streamer
-            .client
-            .chatters(&self.inner.streamer.user.id, &self.inner.streamer.user.id));
-
-        let mut users = HashSet::new();
-
-        while let Some(chatter) = chatters.next().await.transpose()? {
-            users.insert(chatter.user_login);
-        }
-
+        let users = self.chatters().await?;
         let len = users.len();
 
-        self.inner
-            .backend
-            .balances_increment(channel, users, reward, watch_time)
+        self.balances_increment(channel, users, reward, watch_time, reason)
             .await?;
 
         Ok(len)
@@ -303,11 +547,31 @@ impl Currency {
         taker: &str,
         amount: i64,
         override_balance: bool,
+        reason: Reason,
     ) -> Result<(), BalanceTransferError> {
         self.inner
             .backend
             .balance_transfer(channel, giver, taker, amount, override_balance)
-            .await
+            .await?;
+
+        let giver = db::user_id(giver);
+        let taker = db::user_id(taker);
+
+        let updates = vec![
+            LedgerUpdate {
+                user: giver.clone(),
+                amount: -amount,
+                counterparty: Some(taker.clone()),
+            },
+            LedgerUpdate {
+                user: taker,
+                amount,
+                counterparty: Some(giver),
+            },
+        ];
+
+        self.record(channel, reason, updates, None).await;
+        Ok(())
     }
 
     /// Get balances for all users.
@@ -316,6 +580,9 @@ impl Currency {
     }
 
     /// Import balances for all users.
+    ///
+    /// Imported balances replace existing ones, and are not recorded in the
+    /// ledger.
     pub async fn import_balances(&self, balances: Vec<Balance>) -> Result<()> {
         self.inner.backend.import_balances(balances).await
     }
@@ -325,9 +592,47 @@ impl Currency {
         self.inner.backend.balance_of(channel, user).await
     }
 
+    /// Get the users with the highest balances.
+    ///
+    /// The streamer is excluded, since they collect whatever viewers spend.
+    pub async fn top(&self, channel: &Channel, limit: i64) -> Result<Vec<LeaderboardEntry>> {
+        self.inner
+            .backend
+            .top(channel, &self.inner.streamer.user.login, limit)
+            .await
+    }
+
+    /// Get the leaderboard rank of the given user.
+    ///
+    /// Returns `None` if the user doesn't have a balance.
+    pub async fn rank(&self, channel: &Channel, user: &str) -> Result<Option<Rank>> {
+        self.inner
+            .backend
+            .rank(channel, &self.inner.streamer.user.login, user)
+            .await
+    }
+
     /// Add (or subtract) from the balance for a single user.
-    pub async fn balance_add(&self, channel: &Channel, user: &str, amount: i64) -> Result<()> {
-        self.inner.backend.balance_add(channel, user, amount).await
+    pub async fn balance_add(
+        &self,
+        channel: &Channel,
+        user: &str,
+        amount: i64,
+        reason: Reason,
+    ) -> Result<()> {
+        self.inner
+            .backend
+            .balance_add(channel, user, amount)
+            .await?;
+
+        let updates = vec![LedgerUpdate {
+            user: db::user_id(user),
+            amount,
+            counterparty: None,
+        }];
+
+        self.record(channel, reason, updates, None).await;
+        Ok(())
     }
 
     /// Add balance to users.
@@ -337,17 +642,280 @@ impl Currency {
         users: I,
         amount: i64,
         watch_time: i64,
+        reason: Reason,
     ) -> Result<()>
     where
-        I: IntoIterator<IntoIter: Send + 'static, Item = String>,
+        I: IntoIterator<Item = String>,
     {
-        self.inner
+        let users = users.into_iter().collect::<Vec<_>>();
+
+        let applied = self
+            .inner
             .backend
-            .balances_increment(channel, users, amount, watch_time)
+            .balances_increment(channel, users, amount, watch_time, self.inner.max_balance)
+            .await?;
+
+        // Only record what was actually added, since balances might've been
+        // capped by `max_balance`.
+        let updates = applied
+            .into_iter()
+            .filter(|(_, amount)| *amount != 0)
+            .map(|(user, amount)| LedgerUpdate {
+                user,
+                amount,
+                counterparty: None,
+            })
+            .collect::<Vec<_>>();
+
+        if !updates.is_empty() {
+            self.record(channel, reason, updates, None).await;
+        }
+
+        Ok(())
+    }
+
+    /// Reward each user with an individual amount, and add to their watch
+    /// time.
+    ///
+    /// All rewards are recorded in the ledger as a single transaction.
+    pub async fn balances_reward(
+        &self,
+        channel: &Channel,
+        rewards: HashMap<String, i64>,
+        watch_time: i64,
+        reason: Reason,
+    ) -> Result<()> {
+        let mut groups = HashMap::<i64, Vec<String>>::new();
+
+        for (user, amount) in rewards {
+            groups.entry(amount).or_default().push(user);
+        }
+
+        let mut updates = Vec::new();
+
+        for (amount, users) in groups {
+            let applied = self
+                .inner
+                .backend
+                .balances_increment(channel, users, amount, watch_time, self.inner.max_balance)
+                .await?;
+
+            updates.extend(applied.into_iter().filter(|(_, amount)| *amount != 0).map(
+                |(user, amount)| LedgerUpdate {
+                    user,
+                    amount,
+                    counterparty: None,
+                },
+            ));
+        }
+
+        if !updates.is_empty() {
+            self.record(channel, reason, updates, None).await;
+        }
+
+        Ok(())
+    }
+
+    /// Limit rewards according to the per-stream earning cap, if one is
+    /// configured.
+    pub fn cap_earnings(&self, stream_id: &str, rewards: &mut HashMap<String, i64>) {
+        if let Some(cap) = self.inner.stream_earn_cap {
+            self.inner.earnings.lock().cap(stream_id, rewards, cap);
+        }
+    }
+
+    /// Take the given percentage of the balance of each user.
+    ///
+    /// All deductions are recorded in the ledger as a single transaction.
+    /// Returns the total amount which was removed.
+    pub async fn decay(
+        &self,
+        channel: &Channel,
+        users: Vec<String>,
+        percentage: u32,
+    ) -> Result<i64> {
+        let mut updates = Vec::new();
+
+        for user in users {
+            let amount = self
+                .inner
+                .backend
+                .balance_decay(channel, &user, percentage)
+                .await?;
+
+            if amount <= 0 {
+                continue;
+            }
+
+            updates.push(LedgerUpdate {
+                user: db::user_id(&user),
+                amount: -amount,
+                counterparty: None,
+            });
+        }
+
+        let total = updates.iter().map(|u| -u.amount).sum::<i64>();
+
+        if !updates.is_empty() {
+            self.record(channel, Reason::Decay, updates, None).await;
+        }
+
+        Ok(total)
+    }
+
+    /// List the latest ledger entries, optionally limited to a single user.
+    pub async fn history(
+        &self,
+        channel: &Channel,
+        user: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<db::LedgerEntry>> {
+        match &self.inner.ledger {
+            Some(ledger) => ledger.list(channel, user, limit, offset).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Count the ledger entries, optionally limited to a single user.
+    pub async fn history_count(&self, channel: &Channel, user: Option<&str>) -> Result<i64> {
+        match &self.inner.ledger {
+            Some(ledger) => ledger.count(channel, user).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Reverse the given transaction by undoing all of its balance
+    /// modifications.
+    ///
+    /// If any step fails, the modifications applied so far are undone and the
+    /// transaction is left unreversed so that it can be retried.
+    ///
+    /// Returns the id of the transaction recording the reversal.
+    pub async fn reverse(&self, channel: &Channel, id: i32) -> Result<i32, ReverseError> {
+        let ledger = self.inner.ledger.as_ref().ok_or(ReverseError::NoLedger)?;
+
+        let entries = ledger.transaction(channel, id).await?;
+
+        let first = entries.first().ok_or(ReverseError::NotFound)?;
+
+        if first.reverses.is_some() {
+            return Err(ReverseError::Reversal);
+        }
+
+        if !ledger.mark_reversed(channel, id).await? {
+            return Err(ReverseError::AlreadyReversed);
+        }
+
+        let mut updates = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let result = self
+                .inner
+                .backend
+                .balance_add(channel, &entry.user, -entry.amount)
+                .await;
+
+            if let Err(e) = result {
+                self.rollback_reverse(channel, id, &updates).await;
+                return Err(e.into());
+            }
+
+            updates.push(LedgerUpdate {
+                user: entry.user,
+                amount: -entry.amount,
+                counterparty: entry.counterparty,
+            });
+        }
+
+        let result = ledger
+            .record(
+                channel,
+                Reason::Reversal.as_str(),
+                updates.clone(),
+                Some(id),
+            )
+            .await;
+
+        match result {
+            Ok(reversal) => Ok(reversal),
+            Err(e) => {
+                self.rollback_reverse(channel, id, &updates).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Undo the partially applied updates of a failed reversal and clear the
+    /// reversed flag so that it can be retried.
+    async fn rollback_reverse(&self, channel: &Channel, id: i32, applied: &[LedgerUpdate]) {
+        let ledger = match &self.inner.ledger {
+            Some(ledger) => ledger,
+            None => return,
+        };
+
+        for update in applied {
+            if let Err(e) = self
+                .inner
+                .backend
+                .balance_add(channel, &update.user, -update.amount)
+                .await
+            {
+                common::log_error!(
+                    e,
+                    "Failed to restore balance of {} after failed reversal",
+                    update.user
+                );
+                // leave the transaction flagged as reversed, since a retry
+                // would take back the unrestored balances a second time.
+                return;
+            }
+        }
+
+        if let Err(e) = ledger.clear_reversed(channel, id).await {
+            common::log_error!(e, "Failed to clear reversed flag of transaction {}", id);
+        }
+    }
+
+    /// Record the given updates in the ledger.
+    ///
+    /// Balances have already been modified at this point, so failing to
+    /// record them is logged rather than reported to the caller.
+    async fn record(
+        &self,
+        channel: &Channel,
+        reason: Reason,
+        updates: Vec<LedgerUpdate>,
+        reverses: Option<i32>,
+    ) {
+        let ledger = match &self.inner.ledger {
+            Some(ledger) => ledger,
+            None => return,
+        };
+
+        if let Err(e) = ledger
+            .record(channel, reason.as_str(), updates, reverses)
             .await
+        {
+            common::log_error!(e, "Failed to record currency transaction in ledger");
+        }
     }
 }
 
+#[derive(Debug, Error)]
+pub enum ReverseError {
+    #[error("no ledger available")]
+    NoLedger,
+    #[error("no such transaction")]
+    NotFound,
+    #[error("transaction has already been reversed")]
+    AlreadyReversed,
+    #[error("reversals can't be reversed")]
+    Reversal,
+    #[error("other error: {}", _0)]
+    Other(#[from] Error),
+}
+
 #[derive(Debug, Error)]
 pub enum BalanceTransferError {
     #[error("missing balance for transfer")]
@@ -356,8 +924,58 @@ pub enum BalanceTransferError {
     DieselError(#[from] diesel::result::Error),
     #[error("mysql error: {0}")]
     MysqlError(#[from] mysql_async::Error),
+    #[error("postgres error: {0}")]
+    PostgresError(#[from] tokio_postgres::Error),
     #[error("error joining: {0}")]
     JoinError(#[from] tokio::task::JoinError),
     #[error("other error: {}", _0)]
     Other(#[from] Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{capped, decay_amount, Earnings};
+
+    #[test]
+    fn test_capped() {
+        assert_eq!(capped(10, 5, None), 15);
+        assert_eq!(capped(10, 5, Some(12)), 12);
+        assert_eq!(capped(20, 5, Some(12)), 20);
+        assert_eq!(capped(0, 5, Some(12)), 5);
+        assert_eq!(capped(i64::MAX, 5, None), i64::MAX);
+    }
+
+    #[test]
+    fn test_decay_amount() {
+        assert_eq!(decay_amount(1000, 10), 100);
+        assert_eq!(decay_amount(25, 10), 2);
+        assert_eq!(decay_amount(5, 10), 0);
+        assert_eq!(decay_amount(i64::MAX, 100), i64::MAX);
+    }
+
+    #[test]
+    fn test_earnings_cap() {
+        let mut earnings = Earnings::default();
+
+        let mut rewards = HashMap::from([(String::from("turtle"), 40), (String::from("hare"), 80)]);
+        earnings.cap("1", &mut rewards, 100);
+        assert_eq!(rewards["turtle"], 40);
+        assert_eq!(rewards["hare"], 80);
+
+        let mut rewards = HashMap::from([(String::from("turtle"), 40), (String::from("hare"), 40)]);
+        earnings.cap("1", &mut rewards, 100);
+        assert_eq!(rewards["turtle"], 40);
+        assert_eq!(rewards["hare"], 20);
+
+        let mut rewards = HashMap::from([(String::from("hare"), 40)]);
+        earnings.cap("1", &mut rewards, 100);
+        assert_eq!(rewards["hare"], 0);
+
+        // A new stream resets what has been earned.
+        let mut rewards = HashMap::from([(String::from("hare"), 40)]);
+        earnings.cap("2", &mut rewards, 100);
+        assert_eq!(rewards["hare"], 40);
+    }
+}
@@ -17,7 +17,7 @@ use mysql::prelude::*;
 use mysql_async as mysql;
 use serde::{Deserialize, Serialize};
 
-use crate::{BalanceOf, BalanceTransferError};
+use crate::{BalanceOf, BalanceTransferError, LeaderboardEntry, Rank};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schema {
@@ -87,40 +87,166 @@ impl Queries {
         Ok(tx.exec_first(query.as_str(), params).await?)
     }
 
+    /// Select the given balance, locking it for the rest of the transaction.
+    #[tracing::instrument(skip(self, tx))]
+    async fn select_balance_for_update<Tx>(&self, tx: &mut Tx, user: &str) -> Result<Option<i32>>
+    where
+        Tx: Queryable,
+    {
+        tracing::trace!("Select balance for update");
+
+        let query = format!(
+            "SELECT `{balance_column}` \
+             FROM `{table}` \
+             WHERE `{user_column}` = :user \
+             LIMIT 1 \
+             FOR UPDATE",
+            table = self.schema.table,
+            balance_column = self.schema.balance_column,
+            user_column = self.schema.user_column,
+        );
+
+        let params = params! {
+            "user" => user,
+        };
+
+        Ok(tx.exec_first(query.as_str(), params).await?)
+    }
+
+    /// Select the users with the highest balances.
+    #[tracing::instrument(skip(self, tx))]
+    async fn select_top<Tx>(
+        &self,
+        tx: &mut Tx,
+        exclude: &str,
+        limit: i64,
+    ) -> Result<Vec<(String, i32)>>
+    where
+        Tx: Queryable,
+    {
+        tracing::trace!("Select top balances");
+
+        let query = format!(
+            "SELECT `{user_column}`, `{balance_column}` \
+             FROM `{table}` \
+             WHERE `{user_column}` <> :exclude \
+             ORDER BY `{balance_column}` DESC, `{user_column}` ASC \
+             LIMIT :limit",
+            table = self.schema.table,
+            balance_column = self.schema.balance_column,
+            user_column = self.schema.user_column,
+        );
+
+        let params = params! {
+            "exclude" => exclude,
+            "limit" => limit,
+        };
+
+        let results = tx
+            .exec_map(query.as_str(), params, mysql::from_row::<(String, i32)>)
+            .await?;
+        Ok(results)
+    }
+
+    /// Count the number of balances, optionally only the ones above the given
+    /// balance.
+    #[tracing::instrument(skip(self, tx))]
+    async fn count_balances<Tx>(
+        &self,
+        tx: &mut Tx,
+        exclude: &str,
+        above: Option<i32>,
+    ) -> Result<i64>
+    where
+        Tx: Queryable,
+    {
+        tracing::trace!("Count balances");
+
+        let condition = match above {
+            Some(..) => format!(" AND `{}` > :balance", self.schema.balance_column),
+            None => String::new(),
+        };
+
+        let query = format!(
+            "SELECT COUNT(*) \
+             FROM `{table}` \
+             WHERE `{user_column}` <> :exclude{condition}",
+            table = self.schema.table,
+            user_column = self.schema.user_column,
+        );
+
+        let params = match above {
+            Some(balance) => params! {
+                "exclude" => exclude,
+                "balance" => balance,
+            },
+            None => params! {
+                "exclude" => exclude,
+            },
+        };
+
+        let count: Option<i64> = tx.exec_first(query.as_str(), params).await?;
+        Ok(count.unwrap_or_default())
+    }
+
     /// Helper to insert or update a single balance.
     async fn modify_balance<Tx>(&self, tx: &mut Tx, user: &str, amount: i32) -> Result<()>
     where
         Tx: Queryable,
     {
         // TODO: when mysql_async moves to async/await we can probably remove this budged ownership.
-        self.upsert_balances(tx, vec![user.to_string()], amount)
+        self.upsert_balances(tx, vec![user.to_string()], amount, None)
             .await?;
         Ok(())
     }
 
     /// Update or insert a batch of balances.
+    ///
+    /// If `max_balance` is specified, balances won't be increased beyond it.
     #[tracing::instrument(skip_all)]
-    async fn upsert_balances<Tx, I>(&self, tx: &mut Tx, users: I, amount: i32) -> Result<()>
+    async fn upsert_balances<Tx, I>(
+        &self,
+        tx: &mut Tx,
+        users: I,
+        amount: i32,
+        max_balance: Option<i32>,
+    ) -> Result<()>
     where
         Tx: Queryable,
         I: IntoIterator<IntoIter: Send, Item = String>,
     {
         tracing::trace!("Upsert balances");
 
-        let query = format! {
-            "INSERT INTO `{table}` (`{user_column}`, `{balance_column}`) \
-            VALUES (:user, :amount) \
-            ON DUPLICATE KEY UPDATE  `{balance_column}` = `{balance_column}` + :amount",
-            table = self.schema.table,
-            user_column = self.schema.user_column,
-            balance_column = self.schema.balance_column,
+        let query = match max_balance {
+            Some(..) => format! {
+                "INSERT INTO `{table}` (`{user_column}`, `{balance_column}`) \
+                VALUES (:user, LEAST(:amount, :max_balance)) \
+                ON DUPLICATE KEY UPDATE `{balance_column}` = \
+                GREATEST(`{balance_column}`, LEAST(`{balance_column}` + :amount, :max_balance))",
+                table = self.schema.table,
+                user_column = self.schema.user_column,
+                balance_column = self.schema.balance_column,
+            },
+            None => format! {
+                "INSERT INTO `{table}` (`{user_column}`, `{balance_column}`) \
+                VALUES (:user, :amount) \
+                ON DUPLICATE KEY UPDATE  `{balance_column}` = `{balance_column}` + :amount",
+                table = self.schema.table,
+                user_column = self.schema.user_column,
+                balance_column = self.schema.balance_column,
+            },
         };
 
-        let params = users.into_iter().map(move |user| {
-            params! {
+        let params = users.into_iter().map(move |user| match max_balance {
+            Some(max_balance) => params! {
                 "user" => user,
                 "amount" => amount,
-            }
+                "max_balance" => max_balance,
+            },
+            None => params! {
+                "user" => user,
+                "amount" => amount,
+            },
         });
 
         tx.exec_batch(query.as_str(), params).await?;
@@ -298,6 +424,57 @@ impl Backend {
         }))
     }
 
+    /// Get the users with the highest balances.
+    pub(crate) async fn top(
+        &self,
+        _channel: &Channel,
+        exclude: &str,
+        limit: i64,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        let exclude = user_id(exclude);
+        let opts = mysql::TxOpts::new();
+        let mut tx = self.pool.start_transaction(opts).await?;
+
+        let top = self.queries.select_top(&mut tx, &exclude, limit).await?;
+
+        Ok(top
+            .into_iter()
+            .map(|(user, balance)| LeaderboardEntry {
+                user,
+                balance: i64::from(balance),
+            })
+            .collect())
+    }
+
+    /// Get the rank of the given user.
+    pub(crate) async fn rank(
+        &self,
+        _channel: &Channel,
+        exclude: &str,
+        user: &str,
+    ) -> Result<Option<Rank>> {
+        let exclude = user_id(exclude);
+        let user = user_id(user);
+        let opts = mysql::TxOpts::new();
+        let mut tx = self.pool.start_transaction(opts).await?;
+
+        let Some(balance) = self.queries.select_balance(&mut tx, &user).await? else {
+            return Ok(None);
+        };
+
+        let above = self
+            .queries
+            .count_balances(&mut tx, &exclude, Some(balance))
+            .await?;
+        let total = self.queries.count_balances(&mut tx, &exclude, None).await?;
+
+        Ok(Some(Rank {
+            rank: above + 1,
+            balance: i64::from(balance),
+            total,
+        }))
+    }
+
     /// Add (or subtract) from the balance for a single user.
     pub(crate) async fn balance_add(
         &self,
@@ -315,22 +492,95 @@ impl Backend {
         Ok(())
     }
 
-    /// Add balance to users.
+    /// Decay the balance of a single user by the given percentage.
+    ///
+    /// Returns the amount which was taken.
+    pub(crate) async fn balance_decay(
+        &self,
+        _channel: &Channel,
+        user: &str,
+        percentage: u32,
+    ) -> Result<i64> {
+        let user = user_id(user);
+
+        let opts = mysql::TxOpts::new();
+        let mut tx = self.pool.start_transaction(opts).await?;
+
+        let Some(balance) = self
+            .queries
+            .select_balance_for_update(&mut tx, &user)
+            .await?
+        else {
+            return Ok(0);
+        };
+
+        let amount = crate::decay_amount(i64::from(balance), percentage);
+
+        if amount > 0 {
+            let negated = i32::try_from(-amount)?;
+            self.queries.modify_balance(&mut tx, &user, negated).await?;
+        }
+
+        tx.commit().await?;
+        Ok(amount)
+    }
+
+    /// Add balance to users, without going over `max_balance`.
+    ///
+    /// Returns the amount which was actually added for each user.
     pub(crate) async fn balances_increment<I>(
         &self,
         _channel: &Channel,
         users: I,
         amount: i64,
-    ) -> Result<()>
+        max_balance: Option<i64>,
+    ) -> Result<Vec<(String, i64)>>
     where
         I: IntoIterator<IntoIter: Send, Item = String>,
     {
-        let amount = amount.try_into()?;
+        let amount: i32 = amount.try_into()?;
+        let max_balance = max_balance.map(|m| m.try_into().unwrap_or(i32::MAX));
         let opts = mysql::TxOpts::new();
         let mut tx = self.pool.start_transaction(opts).await?;
-        let users = users.into_iter().map(|u| user_id(&u));
-        self.queries.upsert_balances(&mut tx, users, amount).await?;
+        let users = users.into_iter().map(|u| user_id(&u)).collect::<Vec<_>>();
+
+        let applied = match max_balance {
+            Some(max_balance) => {
+                let mut applied = Vec::with_capacity(users.len());
+
+                // Balances are locked and updated one at a time so that we
+                // know how much was added after capping.
+                for user in users {
+                    let balance = self
+                        .queries
+                        .select_balance_for_update(&mut tx, &user)
+                        .await?
+                        .map(i64::from)
+                        .unwrap_or_default();
+
+                    self.queries
+                        .upsert_balances(&mut tx, [user.clone()], amount, Some(max_balance))
+                        .await?;
+
+                    let value = crate::capped(balance, amount.into(), Some(max_balance.into()));
+                    applied.push((user, value - balance));
+                }
+
+                applied
+            }
+            None => {
+                self.queries
+                    .upsert_balances(&mut tx, users.clone(), amount, None)
+                    .await?;
+
+                users
+                    .into_iter()
+                    .map(|user| (user, i64::from(amount)))
+                    .collect()
+            }
+        };
+
         tx.commit().await?;
-        Ok(())
+        Ok(applied)
     }
 }
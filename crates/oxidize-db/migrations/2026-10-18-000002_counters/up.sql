@@ -0,0 +1,8 @@
+CREATE TABLE counters (
+    channel VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    count BIGINT NOT NULL DEFAULT 0,
+    stream_count BIGINT NOT NULL DEFAULT 0,
+    stream_id VARCHAR,
+    PRIMARY KEY (channel, name)
+);
@@ -0,0 +1,2 @@
+DROP TABLE giveaway_entrants;
+DROP TABLE giveaways;
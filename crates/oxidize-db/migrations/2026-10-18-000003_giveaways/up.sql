@@ -0,0 +1,20 @@
+CREATE TABLE giveaways (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    channel VARCHAR NOT NULL,
+    keyword TEXT NOT NULL,
+    opened_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    closed_at TIMESTAMP,
+    winner TEXT
+);
+
+CREATE INDEX giveaways_channel ON giveaways (channel);
+
+CREATE TABLE giveaway_entrants (
+    giveaway_id INTEGER NOT NULL,
+    user TEXT NOT NULL,
+    entered_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    tickets INTEGER NOT NULL DEFAULT 1,
+    subscriber BOOLEAN NOT NULL DEFAULT FALSE,
+    drawn BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (giveaway_id, user)
+);
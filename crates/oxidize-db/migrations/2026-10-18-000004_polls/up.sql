@@ -0,0 +1,26 @@
+CREATE TABLE polls (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    channel VARCHAR NOT NULL,
+    question TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    closes_at TIMESTAMP,
+    closed_at TIMESTAMP
+);
+
+CREATE INDEX polls_channel ON polls (channel);
+
+CREATE TABLE poll_options (
+    poll_id INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    description TEXT,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (poll_id, key)
+);
+
+CREATE TABLE poll_votes (
+    poll_id INTEGER NOT NULL,
+    user TEXT NOT NULL,
+    option TEXT NOT NULL,
+    voted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (poll_id, user)
+);
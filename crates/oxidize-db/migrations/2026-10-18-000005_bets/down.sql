@@ -0,0 +1,3 @@
+DROP TABLE bet_wagers;
+DROP TABLE bet_outcomes;
+DROP TABLE bets;
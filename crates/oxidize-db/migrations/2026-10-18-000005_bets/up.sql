@@ -0,0 +1,32 @@
+CREATE TABLE bets (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    channel VARCHAR NOT NULL,
+    title TEXT NOT NULL,
+    opened_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    locks_at TIMESTAMP,
+    locked_at TIMESTAMP,
+    resolved_at TIMESTAMP,
+    winner TEXT,
+    cancelled BOOLEAN NOT NULL DEFAULT FALSE,
+    settled_at TIMESTAMP
+);
+
+CREATE INDEX bets_channel ON bets (channel);
+
+CREATE TABLE bet_outcomes (
+    bet_id INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    description TEXT,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (bet_id, key)
+);
+
+CREATE TABLE bet_wagers (
+    bet_id INTEGER NOT NULL,
+    user TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    placed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    paid BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (bet_id, user)
+);
@@ -0,0 +1,15 @@
+CREATE TABLE currency_ledger (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    transaction_id INTEGER NOT NULL,
+    channel VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    user TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    counterparty TEXT,
+    reverses INTEGER,
+    reversed BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX currency_ledger_transaction ON currency_ledger (transaction_id);
+CREATE INDEX currency_ledger_channel_user ON currency_ledger (channel, user);
@@ -0,0 +1,2 @@
+DROP TABLE shop_purchases;
+DROP TABLE shop_items;
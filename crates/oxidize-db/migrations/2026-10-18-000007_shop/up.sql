@@ -0,0 +1,26 @@
+CREATE TABLE shop_items (
+    channel VARCHAR NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    price BIGINT NOT NULL,
+    stock INTEGER,
+    cooldown INTEGER NOT NULL DEFAULT 0,
+    command TEXT,
+    disabled BOOLEAN NOT NULL DEFAULT FALSE,
+    last_purchased_at TIMESTAMP,
+    PRIMARY KEY (channel, name)
+);
+
+CREATE TABLE shop_purchases (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    channel VARCHAR NOT NULL,
+    item TEXT NOT NULL,
+    user TEXT NOT NULL,
+    price BIGINT NOT NULL,
+    purchased_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    fulfilled_at TIMESTAMP,
+    fulfilled_by TEXT,
+    refunded BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX shop_purchases_channel ON shop_purchases (channel);
@@ -0,0 +1,2 @@
+DROP TABLE watch_time_sessions;
+DROP TABLE watch_time;
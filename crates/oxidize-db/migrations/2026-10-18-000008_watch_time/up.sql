@@ -0,0 +1,15 @@
+CREATE TABLE watch_time (
+    channel VARCHAR NOT NULL,
+    user VARCHAR NOT NULL,
+    seconds BIGINT NOT NULL DEFAULT 0,
+    last_seen TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (channel, user)
+);
+
+CREATE TABLE watch_time_sessions (
+    channel VARCHAR NOT NULL,
+    stream_id VARCHAR NOT NULL,
+    user VARCHAR NOT NULL,
+    seconds BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (channel, stream_id, user)
+);
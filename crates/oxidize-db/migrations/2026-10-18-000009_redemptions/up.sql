@@ -0,0 +1,11 @@
+CREATE TABLE redemption_actions (
+    channel VARCHAR NOT NULL,
+    reward TEXT NOT NULL,
+    action VARCHAR NOT NULL,
+    argument TEXT NOT NULL DEFAULT '',
+    amount BIGINT NOT NULL DEFAULT 0,
+    fulfill BOOLEAN NOT NULL DEFAULT TRUE,
+    refund BOOLEAN NOT NULL DEFAULT TRUE,
+    disabled BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (channel, reward)
+);
@@ -0,0 +1,6 @@
+CREATE TABLE settings_profiles (
+    profile TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT,
+    PRIMARY KEY (profile, key)
+);
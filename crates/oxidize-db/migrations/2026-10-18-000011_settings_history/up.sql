@@ -0,0 +1,12 @@
+CREATE TABLE settings_history (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    key TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    source VARCHAR NOT NULL,
+    user TEXT,
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    undone BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX settings_history_key ON settings_history (key);
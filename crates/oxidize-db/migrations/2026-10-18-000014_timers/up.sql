@@ -0,0 +1,13 @@
+CREATE TABLE timers (
+    channel VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    -- Duration in seconds, not set for stopwatches.
+    duration BIGINT,
+    -- Seconds elapsed before the timer was last started.
+    elapsed BIGINT NOT NULL DEFAULT 0,
+    -- When the timer was last started, not set while paused.
+    started_at TIMESTAMP,
+    template TEXT,
+    PRIMARY KEY (channel, name)
+);
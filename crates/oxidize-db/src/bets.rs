@@ -0,0 +1,393 @@
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use common::Channel;
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::{Bet, BetOutcome, BetWager};
+
+/// The outcome of placing a wager.
+#[derive(Debug, Clone)]
+pub enum WagerPlacement {
+    /// The wager was placed, with the updated wager.
+    Placed(BetWager),
+    /// The bet is no longer accepting wagers.
+    Closed,
+    /// The user has already wagered on a different outcome.
+    OtherSide(String),
+    /// Placing the wager would exceed the maximum wager, with the amount
+    /// already wagered.
+    Exceeded(i64),
+}
+
+#[derive(Clone)]
+pub struct Bets {
+    db: crate::Database,
+}
+
+impl Bets {
+    /// Open the bets database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// Open a new bet with the given outcomes.
+    ///
+    /// Outcomes are provided as a keyword and an optional description.
+    /// Duplicate keywords are ignored.
+    pub async fn open(
+        &self,
+        channel: &Channel,
+        title: &str,
+        outcomes: Vec<(String, Option<String>)>,
+        locks_at: Option<NaiveDateTime>,
+    ) -> Result<Bet> {
+        use self::schema::bet_outcomes::dsl as o;
+        use self::schema::bets::dsl as b;
+
+        let bet = models::InsertBet {
+            channel: channel.to_owned(),
+            title: title.to_string(),
+            locks_at,
+        };
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(|c| {
+                    diesel::insert_into(b::bets).values(&bet).execute(c)?;
+
+                    let id = crate::last_insert_id(c)?;
+                    let created = b::bets.filter(b::id.eq(id)).first::<Bet>(c)?;
+
+                    let mut inserts = Vec::<BetOutcome>::new();
+
+                    for (key, description) in outcomes {
+                        if inserts.iter().any(|o| o.key == key) {
+                            continue;
+                        }
+
+                        inserts.push(BetOutcome {
+                            bet_id: created.id,
+                            key,
+                            description,
+                            position: inserts.len() as i32,
+                        });
+                    }
+
+                    diesel::insert_into(o::bet_outcomes)
+                        .values(&inserts)
+                        .execute(c)?;
+
+                    Ok(created)
+                })
+            })
+            .await
+    }
+
+    /// Get the latest bet in the given channel.
+    pub async fn latest(&self, channel: &Channel) -> Result<Option<Bet>> {
+        use self::schema::bets::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::bets
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::id.desc())
+                    .first::<Bet>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// List all bets in the given channel, newest first.
+    pub async fn list(&self, channel: &Channel) -> Result<Vec<Bet>> {
+        use self::schema::bets::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::bets
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::id.desc())
+                    .load::<Bet>(c)?)
+            })
+            .await
+    }
+
+    /// List all bets in the given channel which are still accepting wagers
+    /// but whose timer has run out.
+    pub async fn expired(&self, channel: &Channel, now: NaiveDateTime) -> Result<Vec<Bet>> {
+        use self::schema::bets::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::bets
+                    .filter(
+                        dsl::channel
+                            .eq(&channel)
+                            .and(dsl::locked_at.is_null())
+                            .and(dsl::resolved_at.is_null())
+                            .and(dsl::locks_at.le(now)),
+                    )
+                    .order(dsl::id.asc())
+                    .load::<Bet>(c)?)
+            })
+            .await
+    }
+
+    /// List all bets in the given channel which have been resolved or
+    /// cancelled, but where not all wagers have been paid out yet.
+    pub async fn unsettled(&self, channel: &Channel) -> Result<Vec<Bet>> {
+        use self::schema::bets::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::bets
+                    .filter(
+                        dsl::channel
+                            .eq(&channel)
+                            .and(dsl::resolved_at.is_not_null())
+                            .and(dsl::settled_at.is_null()),
+                    )
+                    .order(dsl::id.asc())
+                    .load::<Bet>(c)?)
+            })
+            .await
+    }
+
+    /// Get the outcomes of the given bet, in the order they were specified.
+    pub async fn outcomes(&self, bet_id: i32) -> Result<Vec<BetOutcome>> {
+        use self::schema::bet_outcomes::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::bet_outcomes
+                    .filter(dsl::bet_id.eq(bet_id))
+                    .order(dsl::position.asc())
+                    .load::<BetOutcome>(c)?)
+            })
+            .await
+    }
+
+    /// Get the wager placed by the given user.
+    pub async fn wager(&self, bet_id: i32, user: &str) -> Result<Option<BetWager>> {
+        use self::schema::bet_wagers::dsl;
+
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::bet_wagers
+                    .filter(dsl::bet_id.eq(bet_id).and(dsl::user.eq(&user)))
+                    .first::<BetWager>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// List all wagers placed in the given bet.
+    pub async fn wagers(&self, bet_id: i32) -> Result<Vec<BetWager>> {
+        use self::schema::bet_wagers::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::bet_wagers
+                    .filter(dsl::bet_id.eq(bet_id))
+                    .order(dsl::placed_at.asc())
+                    .load::<BetWager>(c)?)
+            })
+            .await
+    }
+
+    /// Place a wager for the given user, adding to any wager they've
+    /// already placed on the same outcome.
+    ///
+    /// The wager is only placed if the bet is still accepting wagers at
+    /// `now`, and if the user's total wager doesn't exceed `max`.
+    pub async fn place(
+        &self,
+        bet_id: i32,
+        user: &str,
+        outcome: &str,
+        amount: i64,
+        now: NaiveDateTime,
+        max: Option<i64>,
+    ) -> Result<WagerPlacement> {
+        use self::schema::bet_wagers::dsl as w;
+        use self::schema::bets::dsl as b;
+
+        let user = user.to_string();
+        let outcome = outcome.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(|c| {
+                    let open = b::bets
+                        .filter(
+                            b::id
+                                .eq(bet_id)
+                                .and(b::locked_at.is_null())
+                                .and(b::resolved_at.is_null())
+                                .and(b::locks_at.is_null().or(b::locks_at.gt(now))),
+                        )
+                        .count()
+                        .get_result::<i64>(c)?;
+
+                    if open == 0 {
+                        return Ok(WagerPlacement::Closed);
+                    }
+
+                    let filter = w::bet_wagers.filter(w::bet_id.eq(bet_id).and(w::user.eq(&user)));
+
+                    let existing = filter.first::<BetWager>(c).optional()?;
+
+                    if let Some(existing) = &existing {
+                        if existing.outcome != outcome {
+                            return Ok(WagerPlacement::OtherSide(existing.outcome.clone()));
+                        }
+                    }
+
+                    let already = existing.as_ref().map(|w| w.amount).unwrap_or_default();
+
+                    if matches!(max, Some(max) if already.saturating_add(amount) > max) {
+                        return Ok(WagerPlacement::Exceeded(already));
+                    }
+
+                    let wager = match existing {
+                        Some(mut wager) => {
+                            wager.amount = wager.amount.saturating_add(amount);
+
+                            diesel::update(filter)
+                                .set(w::amount.eq(wager.amount))
+                                .execute(c)?;
+
+                            wager
+                        }
+                        None => {
+                            let wager = BetWager {
+                                bet_id,
+                                user,
+                                outcome,
+                                amount,
+                                placed_at: Utc::now().naive_utc(),
+                                paid: false,
+                            };
+
+                            diesel::insert_into(w::bet_wagers)
+                                .values(&wager)
+                                .execute(c)?;
+
+                            wager
+                        }
+                    };
+
+                    Ok(WagerPlacement::Placed(wager))
+                })
+            })
+            .await
+    }
+
+    /// Stop accepting wagers for the given bet.
+    ///
+    /// Returns `true` if the bet was accepting wagers.
+    pub async fn lock(&self, id: i32) -> Result<bool> {
+        use self::schema::bets::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                let now = Utc::now().naive_utc();
+
+                let count = diesel::update(
+                    dsl::bets.filter(
+                        dsl::id
+                            .eq(id)
+                            .and(dsl::locked_at.is_null())
+                            .and(dsl::resolved_at.is_null()),
+                    ),
+                )
+                .set(dsl::locked_at.eq(now))
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// Resolve the given bet with a winning outcome, or cancel it if
+    /// `winner` is `None`.
+    ///
+    /// Returns `true` if the bet hadn't already been resolved.
+    pub async fn resolve(&self, id: i32, winner: Option<&str>) -> Result<bool> {
+        use self::schema::bets::dsl;
+
+        let winner = winner.map(str::to_string);
+
+        self.db
+            .asyncify(move |c| {
+                let now = Utc::now().naive_utc();
+                let cancelled = winner.is_none();
+
+                diesel::update(dsl::bets.filter(dsl::id.eq(id).and(dsl::locked_at.is_null())))
+                    .set(dsl::locked_at.eq(now))
+                    .execute(c)?;
+
+                let count = diesel::update(
+                    dsl::bets.filter(dsl::id.eq(id).and(dsl::resolved_at.is_null())),
+                )
+                .set((
+                    dsl::resolved_at.eq(now),
+                    dsl::winner.eq(winner),
+                    dsl::cancelled.eq(cancelled),
+                ))
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// Mark the wager of the given user as paid out.
+    pub async fn mark_paid(&self, bet_id: i32, user: &str) -> Result<()> {
+        use self::schema::bet_wagers::dsl;
+
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                diesel::update(
+                    dsl::bet_wagers.filter(dsl::bet_id.eq(bet_id).and(dsl::user.eq(&user))),
+                )
+                .set(dsl::paid.eq(true))
+                .execute(c)?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Mark the given bet as settled, after all wagers have been paid out.
+    pub async fn settle(&self, id: i32) -> Result<()> {
+        use self::schema::bets::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                let now = Utc::now().naive_utc();
+
+                diesel::update(dsl::bets.filter(dsl::id.eq(id)))
+                    .set(dsl::settled_at.eq(now))
+                    .execute(c)?;
+
+                Ok(())
+            })
+            .await
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::Result;
+use common::Channel;
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::Counter;
+
+impl Counter {
+    /// Get the value of the counter for the stream identified by `stream_id`.
+    ///
+    /// If the counter was last modified during a different stream, this is
+    /// zero.
+    pub fn stream(&self, stream_id: Option<&str>) -> i64 {
+        if self.stream_id.as_deref() == stream_id {
+            self.stream_count
+        } else {
+            0
+        }
+    }
+}
+
+/// An operation to apply to a counter.
+#[derive(Debug, Clone, Copy)]
+pub enum CounterOp {
+    /// Add the given value to the counter. Negative values decrement it.
+    Add(i64),
+    /// Set the per-stream value of the counter, adjusting the all-time value
+    /// by the same difference.
+    Set(i64),
+    /// Reset both the per-stream and all-time values of the counter.
+    Reset,
+}
+
+impl CounterOp {
+    /// Apply the operation to the all-time and per-stream values of a
+    /// counter, saturating instead of overflowing.
+    fn apply(self, count: i64, stream_count: i64) -> (i64, i64) {
+        match self {
+            CounterOp::Add(n) => (count.saturating_add(n), stream_count.saturating_add(n)),
+            CounterOp::Set(n) => (count.saturating_add(n.saturating_sub(stream_count)), n),
+            CounterOp::Reset => (0, 0),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Counters {
+    db: crate::Database,
+}
+
+impl Counters {
+    /// Open the counters database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// Get the counter with the given name.
+    pub async fn get(&self, channel: &Channel, name: &str) -> Result<Option<Counter>> {
+        use self::schema::counters::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::counters
+                    .filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name)))
+                    .first::<Counter>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// List all counters in the given channel.
+    pub async fn list(&self, channel: &Channel) -> Result<Vec<Counter>> {
+        use self::schema::counters::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::counters
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::name.asc())
+                    .load::<Counter>(c)?)
+            })
+            .await
+    }
+
+    /// Apply the given operation to a counter, creating it if it doesn't
+    /// exist.
+    ///
+    /// The `stream_id` identifies the current stream, and is used to reset
+    /// the per-stream value when a new stream starts.
+    pub async fn modify(
+        &self,
+        channel: &Channel,
+        name: &str,
+        stream_id: Option<&str>,
+        op: CounterOp,
+    ) -> Result<Counter> {
+        use self::schema::counters::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_string();
+        let stream_id = stream_id.map(str::to_string);
+
+        self.db
+            .asyncify(move |c| {
+                let filter =
+                    dsl::counters.filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name)));
+
+                let existing = filter.first::<Counter>(c).optional()?;
+
+                let (count, stream_count) = match &existing {
+                    Some(counter) => (counter.count, counter.stream(stream_id.as_deref())),
+                    None => (0, 0),
+                };
+
+                let (count, stream_count) = op.apply(count, stream_count);
+
+                let counter = Counter {
+                    channel: channel.clone(),
+                    name: name.clone(),
+                    count,
+                    stream_count,
+                    stream_id,
+                };
+
+                if existing.is_some() {
+                    diesel::update(filter)
+                        .set((
+                            dsl::count.eq(counter.count),
+                            dsl::stream_count.eq(counter.stream_count),
+                            dsl::stream_id.eq(&counter.stream_id),
+                        ))
+                        .execute(c)?;
+                } else {
+                    diesel::insert_into(dsl::counters)
+                        .values(&counter)
+                        .execute(c)?;
+                }
+
+                Ok(counter)
+            })
+            .await
+    }
+
+    /// Delete the counter with the given name.
+    pub async fn delete(&self, channel: &Channel, name: &str) -> Result<bool> {
+        use self::schema::counters::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                let count = diesel::delete(
+                    dsl::counters.filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name))),
+                )
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CounterOp;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(CounterOp::Add(2).apply(10, 3), (12, 5));
+        assert_eq!(CounterOp::Add(-2).apply(10, 3), (8, 1));
+        assert_eq!(CounterOp::Set(7).apply(10, 3), (14, 7));
+        assert_eq!(CounterOp::Set(0).apply(10, 3), (7, 0));
+        assert_eq!(CounterOp::Reset.apply(10, 3), (0, 0));
+
+        assert_eq!(
+            CounterOp::Add(i64::MAX).apply(i64::MAX, 1),
+            (i64::MAX, i64::MAX)
+        );
+        assert_eq!(
+            CounterOp::Set(i64::MAX).apply(0, i64::MIN),
+            (i64::MAX, i64::MAX)
+        );
+        assert_eq!(
+            CounterOp::Set(i64::MIN).apply(0, i64::MAX),
+            (i64::MIN, i64::MIN)
+        );
+        assert_eq!(
+            CounterOp::Set(i64::MIN).apply(i64::MIN, 1),
+            (i64::MIN, i64::MIN)
+        );
+    }
+}
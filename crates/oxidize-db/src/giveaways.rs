@@ -0,0 +1,293 @@
+use anyhow::Result;
+use chrono::Utc;
+use common::Channel;
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::{Giveaway, GiveawayEntrant};
+
+/// The outcome of buying tickets for a giveaway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TicketPurchase {
+    /// The tickets were bought.
+    Bought,
+    /// The giveaway is no longer open.
+    Closed,
+    /// Buying the tickets would exceed the maximum number of tickets, with
+    /// the number of tickets already bought.
+    Exceeded(i32),
+}
+
+#[derive(Clone)]
+pub struct Giveaways {
+    db: crate::Database,
+}
+
+impl Giveaways {
+    /// Open the giveaways database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// Open a new giveaway with the given keyword, closing any giveaways
+    /// which are still open in the channel.
+    pub async fn open(&self, channel: &Channel, keyword: &str) -> Result<Giveaway> {
+        use self::schema::giveaways::dsl;
+
+        let giveaway = models::InsertGiveaway {
+            channel: channel.to_owned(),
+            keyword: keyword.to_string(),
+        };
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(|c| {
+                    let now = Utc::now().naive_utc();
+
+                    diesel::update(
+                        dsl::giveaways.filter(
+                            dsl::channel
+                                .eq(&giveaway.channel)
+                                .and(dsl::closed_at.is_null()),
+                        ),
+                    )
+                    .set(dsl::closed_at.eq(now))
+                    .execute(c)?;
+
+                    diesel::insert_into(dsl::giveaways)
+                        .values(&giveaway)
+                        .execute(c)?;
+
+                    let id = crate::last_insert_id(c)?;
+
+                    Ok(dsl::giveaways.filter(dsl::id.eq(id)).first::<Giveaway>(c)?)
+                })
+            })
+            .await
+    }
+
+    /// Get the latest giveaway in the given channel.
+    pub async fn latest(&self, channel: &Channel) -> Result<Option<Giveaway>> {
+        use self::schema::giveaways::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::giveaways
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::id.desc())
+                    .first::<Giveaway>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// List all giveaways in the given channel, newest first.
+    pub async fn list(&self, channel: &Channel) -> Result<Vec<Giveaway>> {
+        use self::schema::giveaways::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::giveaways
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::id.desc())
+                    .load::<Giveaway>(c)?)
+            })
+            .await
+    }
+
+    /// Close the given giveaway for new entrants.
+    pub async fn close(&self, id: i32) -> Result<bool> {
+        use self::schema::giveaways::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                let now = Utc::now().naive_utc();
+
+                let count = diesel::update(
+                    dsl::giveaways.filter(dsl::id.eq(id).and(dsl::closed_at.is_null())),
+                )
+                .set(dsl::closed_at.eq(now))
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// Enter the given user into a giveaway.
+    ///
+    /// Returns `true` if the user wasn't already entered.
+    pub async fn enter(&self, giveaway_id: i32, user: &str, subscriber: bool) -> Result<bool> {
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(|c| {
+                    add_tickets(c, giveaway_id, &user, 0, subscriber)
+                })
+            })
+            .await
+    }
+
+    /// Buy tickets for the given user, entering them into the giveaway if
+    /// they haven't already.
+    ///
+    /// The giveaway must still be open, and if `max` is specified the user
+    /// can't hold more than that many bought tickets.
+    pub async fn buy_tickets(
+        &self,
+        giveaway_id: i32,
+        user: &str,
+        tickets: i32,
+        subscriber: bool,
+        max: Option<i32>,
+    ) -> Result<TicketPurchase> {
+        use self::schema::giveaway_entrants::dsl as e;
+        use self::schema::giveaways::dsl as g;
+
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(|c| {
+                    let open = g::giveaways
+                        .filter(g::id.eq(giveaway_id).and(g::closed_at.is_null()))
+                        .count()
+                        .get_result::<i64>(c)?;
+
+                    if open == 0 {
+                        return Ok(TicketPurchase::Closed);
+                    }
+
+                    if let Some(max) = max {
+                        let bought = e::giveaway_entrants
+                            .select(e::tickets)
+                            .filter(e::giveaway_id.eq(giveaway_id).and(e::user.eq(&user)))
+                            .first::<i32>(c)
+                            .optional()?
+                            .map(|tickets| tickets.saturating_sub(1).max(0))
+                            .unwrap_or_default();
+
+                        if bought.saturating_add(tickets) > max {
+                            return Ok(TicketPurchase::Exceeded(bought));
+                        }
+                    }
+
+                    add_tickets(c, giveaway_id, &user, tickets, subscriber)?;
+                    Ok(TicketPurchase::Bought)
+                })
+            })
+            .await
+    }
+
+    /// Get the entrant with the given name.
+    pub async fn entrant(&self, giveaway_id: i32, user: &str) -> Result<Option<GiveawayEntrant>> {
+        use self::schema::giveaway_entrants::dsl;
+
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::giveaway_entrants
+                    .filter(dsl::giveaway_id.eq(giveaway_id).and(dsl::user.eq(&user)))
+                    .first::<GiveawayEntrant>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// List all entrants of the given giveaway.
+    pub async fn entrants(&self, giveaway_id: i32) -> Result<Vec<GiveawayEntrant>> {
+        use self::schema::giveaway_entrants::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::giveaway_entrants
+                    .filter(dsl::giveaway_id.eq(giveaway_id))
+                    .order(dsl::entered_at.asc())
+                    .load::<GiveawayEntrant>(c)?)
+            })
+            .await
+    }
+
+    /// Mark the given user as the winner of the giveaway.
+    ///
+    /// The user is marked as drawn so that they are excluded from redraws.
+    pub async fn set_winner(&self, giveaway_id: i32, user: &str) -> Result<()> {
+        use self::schema::giveaway_entrants::dsl as e;
+        use self::schema::giveaways::dsl as g;
+
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                diesel::update(
+                    e::giveaway_entrants
+                        .filter(e::giveaway_id.eq(giveaway_id).and(e::user.eq(&user))),
+                )
+                .set(e::drawn.eq(true))
+                .execute(c)?;
+
+                diesel::update(g::giveaways.filter(g::id.eq(giveaway_id)))
+                    .set(g::winner.eq(&user))
+                    .execute(c)?;
+
+                Ok(())
+            })
+            .await
+    }
+}
+
+/// Add tickets for the given user, entering them into the giveaway if they
+/// haven't already.
+///
+/// Returns `true` if the user wasn't already entered.
+fn add_tickets(
+    c: &mut SqliteConnection,
+    giveaway_id: i32,
+    user: &str,
+    tickets: i32,
+    subscriber: bool,
+) -> Result<bool> {
+    use self::schema::giveaway_entrants::dsl;
+
+    let filter =
+        dsl::giveaway_entrants.filter(dsl::giveaway_id.eq(giveaway_id).and(dsl::user.eq(user)));
+
+    let existing = filter.first::<GiveawayEntrant>(c).optional()?;
+
+    match existing {
+        Some(existing) => {
+            diesel::update(filter)
+                .set((
+                    dsl::tickets.eq(existing.tickets.saturating_add(tickets)),
+                    dsl::subscriber.eq(existing.subscriber || subscriber),
+                ))
+                .execute(c)?;
+
+            Ok(false)
+        }
+        None => {
+            let entrant = GiveawayEntrant {
+                giveaway_id,
+                user: user.to_string(),
+                entered_at: Utc::now().naive_utc(),
+                tickets: tickets.saturating_add(1),
+                subscriber,
+                drawn: false,
+            };
+
+            diesel::insert_into(dsl::giveaway_entrants)
+                .values(&entrant)
+                .execute(c)?;
+
+            Ok(true)
+        }
+    }
+}
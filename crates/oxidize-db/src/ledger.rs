@@ -0,0 +1,208 @@
+use anyhow::Result;
+use chrono::Utc;
+use common::Channel;
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::LedgerEntry;
+
+/// A single balance modification to record in the ledger.
+#[derive(Debug, Clone)]
+pub struct LedgerUpdate {
+    /// The user whose balance was modified.
+    pub user: String,
+    /// How much the balance was modified by.
+    pub amount: i64,
+    /// The other user in a transfer.
+    pub counterparty: Option<String>,
+}
+
+/// Append-only ledger of all currency modifications.
+#[derive(Clone)]
+pub struct Ledger {
+    db: crate::Database,
+}
+
+impl Ledger {
+    /// Construct a new ledger.
+    pub fn new(db: crate::Database) -> Self {
+        Self { db }
+    }
+
+    /// Open the ledger database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self::new(db))
+    }
+
+    /// Record a transaction consisting of the given updates.
+    ///
+    /// Returns the id of the recorded transaction.
+    pub async fn record(
+        &self,
+        channel: &Channel,
+        reason: &str,
+        updates: Vec<LedgerUpdate>,
+        reverses: Option<i32>,
+    ) -> Result<i32> {
+        use self::schema::currency_ledger::dsl;
+
+        let channel = channel.to_owned();
+        let reason = reason.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let transaction_id = dsl::currency_ledger
+                        .select(diesel::dsl::max(dsl::transaction_id))
+                        .first::<Option<i32>>(c)?
+                        .unwrap_or_default()
+                        + 1;
+
+                    let created_at = Utc::now().naive_utc();
+
+                    let entries = updates
+                        .into_iter()
+                        .map(|u| models::InsertLedgerEntry {
+                            transaction_id,
+                            channel: channel.clone(),
+                            created_at,
+                            user: u.user,
+                            amount: u.amount,
+                            reason: reason.clone(),
+                            counterparty: u.counterparty,
+                            reverses,
+                        })
+                        .collect::<Vec<_>>();
+
+                    diesel::insert_into(dsl::currency_ledger)
+                        .values(&entries)
+                        .execute(c)?;
+
+                    Ok(transaction_id)
+                })
+            })
+            .await
+    }
+
+    /// List the latest entries in the given channel, optionally limited to
+    /// a single user. Newest entries come first, skipping the first `offset`
+    /// of them.
+    pub async fn list(
+        &self,
+        channel: &Channel,
+        user: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<LedgerEntry>> {
+        use self::schema::currency_ledger::dsl;
+
+        let channel = channel.to_owned();
+        let user = user.map(crate::user_id);
+
+        self.db
+            .asyncify(move |c| {
+                let mut query = dsl::currency_ledger
+                    .filter(dsl::channel.eq(&channel))
+                    .into_boxed();
+
+                if let Some(user) = &user {
+                    query = query.filter(dsl::user.eq(user));
+                }
+
+                Ok(query
+                    .order(dsl::id.desc())
+                    .limit(limit)
+                    .offset(offset)
+                    .load::<LedgerEntry>(c)?)
+            })
+            .await
+    }
+
+    /// Count the entries in the given channel, optionally limited to a single
+    /// user.
+    pub async fn count(&self, channel: &Channel, user: Option<&str>) -> Result<i64> {
+        use self::schema::currency_ledger::dsl;
+
+        let channel = channel.to_owned();
+        let user = user.map(crate::user_id);
+
+        self.db
+            .asyncify(move |c| {
+                let mut query = dsl::currency_ledger
+                    .filter(dsl::channel.eq(&channel))
+                    .into_boxed();
+
+                if let Some(user) = &user {
+                    query = query.filter(dsl::user.eq(user));
+                }
+
+                Ok(query.count().get_result::<i64>(c)?)
+            })
+            .await
+    }
+
+    /// Get all entries of the given transaction.
+    pub async fn transaction(&self, channel: &Channel, id: i32) -> Result<Vec<LedgerEntry>> {
+        use self::schema::currency_ledger::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::currency_ledger
+                    .filter(dsl::channel.eq(&channel).and(dsl::transaction_id.eq(id)))
+                    .order(dsl::id.asc())
+                    .load::<LedgerEntry>(c)?)
+            })
+            .await
+    }
+
+    /// Mark the given transaction as reversed.
+    ///
+    /// Returns `true` if the transaction wasn't already reversed.
+    pub async fn mark_reversed(&self, channel: &Channel, id: i32) -> Result<bool> {
+        use self::schema::currency_ledger::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                let count = diesel::update(
+                    dsl::currency_ledger.filter(
+                        dsl::channel
+                            .eq(&channel)
+                            .and(dsl::transaction_id.eq(id))
+                            .and(dsl::reversed.eq(false)),
+                    ),
+                )
+                .set(dsl::reversed.eq(true))
+                .execute(c)?;
+
+                Ok(count > 0)
+            })
+            .await
+    }
+
+    /// Clear the reversed flag of the given transaction, so that a reversal
+    /// which failed to apply can be retried.
+    pub async fn clear_reversed(&self, channel: &Channel, id: i32) -> Result<()> {
+        use self::schema::currency_ledger::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                diesel::update(
+                    dsl::currency_ledger
+                        .filter(dsl::channel.eq(&channel).and(dsl::transaction_id.eq(id))),
+                )
+                .set(dsl::reversed.eq(false))
+                .execute(c)?;
+
+                Ok(())
+            })
+            .await
+    }
+}
@@ -10,27 +10,63 @@ pub use self::after_streams::AfterStreams;
 mod aliases;
 pub use self::aliases::Aliases;
 
+mod bets;
+pub use self::bets::{Bet, BetOutcome, BetWager, Bets, WagerPlacement};
+
 pub mod commands;
 pub use self::commands::Commands;
 
+mod counters;
+pub use self::counters::{Counter, CounterOp, Counters};
+
+mod giveaways;
+pub use self::giveaways::{Giveaway, GiveawayEntrant, Giveaways, TicketPurchase};
+
+mod ledger;
+pub use self::ledger::{Ledger, LedgerEntry, LedgerUpdate};
+
 mod matcher;
 pub use self::matcher::{Captures, Key, Matchable, Matcher, Pattern};
 
 pub mod models;
 
+mod polls;
+pub use self::polls::{Poll, PollOption, PollResult, Polls};
+
 mod promotions;
 pub use self::promotions::{Promotion, Promotions};
 
+mod redemptions;
+pub use self::redemptions::{RedemptionAction, RedemptionActionEdit, Redemptions};
+
 #[cfg(feature = "scripting")]
 mod script_storage;
 #[cfg(feature = "scripting")]
 pub use self::script_storage::ScriptStorage;
 
+mod quotes;
+pub use self::quotes::{Quote, Quotes};
+
+mod shop;
+pub use self::shop::{Shop, ShopItem, ShopItemEdit, ShopPurchase};
+
 mod task;
 
 mod themes;
 pub use self::themes::Themes;
 
+mod timers;
+pub use self::timers::{Timer, TimerKind, Timers};
+
+mod watch_time;
+pub use self::watch_time::{WatchTime, WatchTimeOf};
+
+mod web_tokens;
+pub use self::web_tokens::{WebToken, WebTokens};
+
+mod webhooks;
+pub use self::webhooks::{Webhook, WebhookDelivery, WebhookEdit, WebhookOutcome, Webhooks};
+
 mod words;
 pub use self::words::{Word, Words};
 
@@ -243,6 +279,16 @@ impl Database {
     }
 }
 
+/// Get the id of the last row inserted through the given connection.
+///
+/// This should be called in the same transaction as the insert.
+pub(crate) fn last_insert_id(c: &mut SqliteConnection) -> QueryResult<i32> {
+    diesel::select(diesel::dsl::sql::<diesel::sql_types::Integer>(
+        "last_insert_rowid()",
+    ))
+    .get_result(c)
+}
+
 /// Convert a user display name into a user id.
 pub fn user_id(user: &str) -> String {
     user.trim_start_matches('@').to_lowercase()
@@ -5,7 +5,10 @@ use diesel::{Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 
 use crate::schema::{
-    after_streams, aliases, bad_words, balances, commands, promotions, script_keys, songs, themes,
+    after_streams, aliases, bad_words, balances, bet_outcomes, bet_wagers, bets, commands,
+    counters, currency_ledger, giveaway_entrants, giveaways, poll_options, poll_votes, polls,
+    promotions, quotes, redemption_actions, script_keys, shop_items, shop_purchases, songs, themes,
+    timers, watch_time, watch_time_sessions, web_tokens, webhook_deliveries, webhooks,
 };
 
 #[derive(Serialize, Deserialize, Queryable, Insertable)]
@@ -218,3 +221,416 @@ pub struct ScriptKey {
 pub struct SetScriptKeyValue<'a> {
     pub value: &'a [u8],
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+pub struct Quote {
+    /// The unique identifier of the quote.
+    pub id: i32,
+    /// The channel the quote belongs to.
+    pub channel: OwnedChannel,
+    /// When the quote was added.
+    pub added_at: NaiveDateTime,
+    /// The user that added the quote.
+    pub added_by: String,
+    /// The person being quoted.
+    pub author: String,
+    /// The game being played when the quote was added, if any.
+    pub game: Option<String>,
+    /// The text of the quote.
+    pub text: String,
+}
+
+/// Insert model for quotes.
+#[derive(Insertable)]
+#[diesel(table_name = quotes)]
+pub struct InsertQuote {
+    pub channel: OwnedChannel,
+    pub added_by: String,
+    pub author: String,
+    pub game: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = counters)]
+pub struct Counter {
+    /// The channel the counter belongs to.
+    pub channel: OwnedChannel,
+    /// The name of the counter.
+    pub name: String,
+    /// The all-time value of the counter.
+    pub count: i64,
+    /// The value of the counter during the stream identified by `stream_id`.
+    pub stream_count: i64,
+    /// The stream that `stream_count` was last modified in.
+    pub stream_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+pub struct Giveaway {
+    /// The unique identifier of the giveaway.
+    pub id: i32,
+    /// The channel the giveaway belongs to.
+    pub channel: OwnedChannel,
+    /// The keyword used to enter the giveaway.
+    pub keyword: String,
+    /// When the giveaway was opened.
+    pub opened_at: NaiveDateTime,
+    /// When the giveaway was closed for new entrants, if it has been.
+    pub closed_at: Option<NaiveDateTime>,
+    /// The last drawn winner of the giveaway.
+    pub winner: Option<String>,
+}
+
+/// Insert model for giveaways.
+#[derive(Insertable)]
+#[diesel(table_name = giveaways)]
+pub struct InsertGiveaway {
+    pub channel: OwnedChannel,
+    pub keyword: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = giveaway_entrants)]
+pub struct GiveawayEntrant {
+    /// The giveaway being entered.
+    pub giveaway_id: i32,
+    /// The user that entered.
+    pub user: String,
+    /// When the user entered.
+    pub entered_at: NaiveDateTime,
+    /// Number of tickets held by the user, including the free entry.
+    pub tickets: i32,
+    /// If the user was a subscriber when they entered.
+    pub subscriber: bool,
+    /// If the user has already been drawn.
+    pub drawn: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+pub struct Poll {
+    /// The unique identifier of the poll.
+    pub id: i32,
+    /// The channel the poll belongs to.
+    pub channel: OwnedChannel,
+    /// The question being asked.
+    pub question: String,
+    /// When the poll was created.
+    pub created_at: NaiveDateTime,
+    /// When the poll automatically closes, if it is timed.
+    pub closes_at: Option<NaiveDateTime>,
+    /// When the poll was closed, if it has been.
+    pub closed_at: Option<NaiveDateTime>,
+}
+
+/// Insert model for polls.
+#[derive(Insertable)]
+#[diesel(table_name = polls)]
+pub struct InsertPoll {
+    pub channel: OwnedChannel,
+    pub question: String,
+    pub closes_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = poll_options)]
+pub struct PollOption {
+    /// The poll the option belongs to.
+    pub poll_id: i32,
+    /// The keyword used to vote for the option.
+    pub key: String,
+    /// Optional description of the option.
+    pub description: Option<String>,
+    /// The position of the option in the poll.
+    pub position: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = poll_votes)]
+pub struct PollVote {
+    /// The poll being voted in.
+    pub poll_id: i32,
+    /// The user that voted.
+    pub user: String,
+    /// The key of the option voted for.
+    pub option: String,
+    /// When the vote was last cast or changed.
+    pub voted_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+pub struct Bet {
+    /// The unique identifier of the bet.
+    pub id: i32,
+    /// The channel the bet belongs to.
+    pub channel: OwnedChannel,
+    /// What is being bet on.
+    pub title: String,
+    /// When the bet was opened.
+    pub opened_at: NaiveDateTime,
+    /// When the bet automatically stops accepting wagers.
+    pub locks_at: Option<NaiveDateTime>,
+    /// When the bet stopped accepting wagers, if it has.
+    pub locked_at: Option<NaiveDateTime>,
+    /// When the bet was resolved or cancelled.
+    pub resolved_at: Option<NaiveDateTime>,
+    /// The winning outcome, if the bet has been resolved.
+    pub winner: Option<String>,
+    /// If the bet was cancelled and all wagers refunded.
+    pub cancelled: bool,
+    /// When all wagers were paid out or refunded.
+    pub settled_at: Option<NaiveDateTime>,
+}
+
+/// Insert model for bets.
+#[derive(Insertable)]
+#[diesel(table_name = bets)]
+pub struct InsertBet {
+    pub channel: OwnedChannel,
+    pub title: String,
+    pub locks_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = bet_outcomes)]
+pub struct BetOutcome {
+    /// The bet the outcome belongs to.
+    pub bet_id: i32,
+    /// The keyword used to wager on the outcome.
+    pub key: String,
+    /// Optional description of the outcome.
+    pub description: Option<String>,
+    /// The position of the outcome in the bet.
+    pub position: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = bet_wagers)]
+pub struct BetWager {
+    /// The bet being wagered on.
+    pub bet_id: i32,
+    /// The user that placed the wager.
+    pub user: String,
+    /// The key of the outcome wagered on.
+    pub outcome: String,
+    /// The amount of currency wagered.
+    pub amount: i64,
+    /// When the wager was first placed.
+    pub placed_at: NaiveDateTime,
+    /// If the wager has been paid out or refunded.
+    pub paid: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+pub struct LedgerEntry {
+    /// The unique identifier of the entry.
+    pub id: i32,
+    /// The transaction the entry is part of.
+    pub transaction_id: i32,
+    /// The channel whose currency was modified.
+    pub channel: OwnedChannel,
+    /// When the balance was modified.
+    pub created_at: NaiveDateTime,
+    /// The user whose balance was modified.
+    pub user: String,
+    /// How much the balance was modified by.
+    pub amount: i64,
+    /// Why the balance was modified.
+    pub reason: String,
+    /// The other user in a transfer.
+    pub counterparty: Option<String>,
+    /// The transaction this entry reverses.
+    pub reverses: Option<i32>,
+    /// If the transaction has been reversed.
+    pub reversed: bool,
+}
+
+/// Insert model for ledger entries.
+#[derive(Insertable)]
+#[diesel(table_name = currency_ledger)]
+pub struct InsertLedgerEntry {
+    pub transaction_id: i32,
+    pub channel: OwnedChannel,
+    pub created_at: NaiveDateTime,
+    pub user: String,
+    pub amount: i64,
+    pub reason: String,
+    pub counterparty: Option<String>,
+    pub reverses: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = shop_items)]
+pub struct ShopItem {
+    /// The channel the item is sold in.
+    pub channel: OwnedChannel,
+    /// The name of the item, used with `!buy`.
+    pub name: String,
+    /// Optional description of the item.
+    pub description: Option<String>,
+    /// The price of the item in stream currency.
+    pub price: i64,
+    /// How many of the item are left, or `None` if unlimited.
+    pub stock: Option<i32>,
+    /// Cooldown in seconds between purchases of the item.
+    pub cooldown: i32,
+    /// Command to run when the item is purchased. Items without a command
+    /// are fulfilled manually.
+    pub command: Option<String>,
+    /// If the item can't currently be bought.
+    pub disabled: bool,
+    /// When the item was last purchased.
+    pub last_purchased_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+pub struct ShopPurchase {
+    /// The unique identifier of the purchase.
+    pub id: i32,
+    /// The channel the purchase was made in.
+    pub channel: OwnedChannel,
+    /// The name of the item purchased.
+    pub item: String,
+    /// The user who made the purchase.
+    pub user: String,
+    /// The price paid for the item.
+    pub price: i64,
+    /// When the item was purchased.
+    pub purchased_at: NaiveDateTime,
+    /// When the purchase was fulfilled.
+    pub fulfilled_at: Option<NaiveDateTime>,
+    /// Who fulfilled the purchase, if it was fulfilled manually.
+    pub fulfilled_by: Option<String>,
+    /// If the purchase was refunded.
+    pub refunded: bool,
+}
+
+/// Insert model for shop purchases.
+#[derive(Insertable)]
+#[diesel(table_name = shop_purchases)]
+pub struct InsertShopPurchase {
+    pub channel: OwnedChannel,
+    pub item: String,
+    pub user: String,
+    pub price: i64,
+    pub purchased_at: NaiveDateTime,
+    pub fulfilled_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = watch_time)]
+pub struct WatchTimeTotal {
+    /// The channel the user watched.
+    pub channel: OwnedChannel,
+    /// The user who watched.
+    pub user: String,
+    /// Total watch time in seconds.
+    pub seconds: i64,
+    /// When the user was last seen watching.
+    pub last_seen: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = watch_time_sessions)]
+pub struct WatchTimeSession {
+    /// The channel the user watched.
+    pub channel: OwnedChannel,
+    /// The stream the user watched.
+    pub stream_id: String,
+    /// The user who watched.
+    pub user: String,
+    /// Watch time during the stream in seconds.
+    pub seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = redemption_actions)]
+pub struct RedemptionAction {
+    /// The channel the reward belongs to.
+    pub channel: OwnedChannel,
+    /// The title or id of the reward, in lowercase.
+    pub reward: String,
+    /// The kind of action to perform, like `command` or `currency`.
+    pub action: String,
+    /// Argument to the action, like the command to run or the theme to play.
+    pub argument: String,
+    /// Amount of currency to give, for actions that use it.
+    pub amount: i64,
+    /// If the redemption should be fulfilled once the action succeeds.
+    pub fulfill: bool,
+    /// If the redemption should be refunded when the action fails.
+    pub refund: bool,
+    /// If the action is disabled.
+    pub disabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+#[diesel(table_name = web_tokens)]
+pub struct WebToken {
+    pub id: i32,
+    /// Name of the token, like the overlay it's used by.
+    pub name: String,
+    /// The secret token itself.
+    pub token: String,
+    /// The access granted by the token, `read` or `admin`.
+    pub access: String,
+    /// When the token was created.
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+#[diesel(table_name = webhooks)]
+pub struct Webhook {
+    pub id: i32,
+    /// Name of the webhook.
+    pub name: String,
+    /// The URL events are posted to.
+    pub url: String,
+    /// Secret used to sign deliveries.
+    pub secret: String,
+    /// Comma-separated events the webhook is interested in. Empty for all.
+    pub events: String,
+    /// If the webhook is enabled.
+    pub enabled: bool,
+    /// When the webhook was created.
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    /// Name of the webhook the delivery was made to.
+    pub webhook: String,
+    /// The event which was delivered, like `song/current`.
+    pub event: String,
+    /// The JSON payload which was delivered.
+    pub payload: String,
+    /// Number of attempts made.
+    pub attempts: i32,
+    /// The last HTTP status received, if any.
+    pub status: Option<i32>,
+    /// The last error encountered, if the delivery failed.
+    pub error: Option<String>,
+    /// When the delivery finished.
+    pub delivered_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = timers)]
+pub struct Timer {
+    /// The channel the timer belongs to.
+    pub channel: OwnedChannel,
+    /// The name of the timer.
+    pub name: String,
+    /// The kind of the timer, like `countdown` or `stopwatch`.
+    pub kind: String,
+    /// The duration of the timer in seconds, not set for stopwatches.
+    pub duration: Option<i64>,
+    /// Seconds elapsed before the timer was last started.
+    pub elapsed: i64,
+    /// When the timer was last started, not set while it's paused.
+    pub started_at: Option<NaiveDateTime>,
+    /// Template used when rendering the timer.
+    pub template: Option<String>,
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use common::Channel;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::{Poll, PollOption};
+
+/// The number of votes cast for a single option of a poll.
+#[derive(Debug, Clone, Serialize)]
+pub struct PollResult {
+    /// The keyword used to vote for the option.
+    pub key: String,
+    /// Optional description of the option.
+    pub description: Option<String>,
+    /// Number of votes cast for the option.
+    pub votes: u32,
+}
+
+impl PollResult {
+    /// Get the display name of the option.
+    pub fn name(&self) -> &str {
+        self.description.as_deref().unwrap_or(&self.key)
+    }
+}
+
+#[derive(Clone)]
+pub struct Polls {
+    db: crate::Database,
+}
+
+impl Polls {
+    /// Open the polls database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// Create a new poll with the given options.
+    ///
+    /// Options are provided as a keyword and an optional description.
+    /// Duplicate keywords are ignored.
+    pub async fn create(
+        &self,
+        channel: &Channel,
+        question: &str,
+        options: Vec<(String, Option<String>)>,
+        closes_at: Option<NaiveDateTime>,
+    ) -> Result<Poll> {
+        use self::schema::poll_options::dsl as o;
+        use self::schema::polls::dsl as p;
+
+        let poll = models::InsertPoll {
+            channel: channel.to_owned(),
+            question: question.to_string(),
+            closes_at,
+        };
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(|c| {
+                    diesel::insert_into(p::polls).values(&poll).execute(c)?;
+
+                    let id = crate::last_insert_id(c)?;
+                    let created = p::polls.filter(p::id.eq(id)).first::<Poll>(c)?;
+
+                    let mut inserts = Vec::<PollOption>::new();
+
+                    for (key, description) in options {
+                        if inserts.iter().any(|o| o.key == key) {
+                            continue;
+                        }
+
+                        inserts.push(PollOption {
+                            poll_id: created.id,
+                            key,
+                            description,
+                            position: inserts.len() as i32,
+                        });
+                    }
+
+                    diesel::insert_into(o::poll_options)
+                        .values(&inserts)
+                        .execute(c)?;
+
+                    Ok(created)
+                })
+            })
+            .await
+    }
+
+    /// Get the poll with the given id.
+    pub async fn get(&self, channel: &Channel, id: i32) -> Result<Option<Poll>> {
+        use self::schema::polls::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::polls
+                    .filter(dsl::channel.eq(&channel).and(dsl::id.eq(id)))
+                    .first::<Poll>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// List all polls in the given channel, newest first.
+    pub async fn list(&self, channel: &Channel) -> Result<Vec<Poll>> {
+        use self::schema::polls::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::polls
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::id.desc())
+                    .load::<Poll>(c)?)
+            })
+            .await
+    }
+
+    /// List all polls in the given channel which have not been closed,
+    /// newest first.
+    pub async fn open(&self, channel: &Channel) -> Result<Vec<Poll>> {
+        use self::schema::polls::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::polls
+                    .filter(dsl::channel.eq(&channel).and(dsl::closed_at.is_null()))
+                    .order(dsl::id.desc())
+                    .load::<Poll>(c)?)
+            })
+            .await
+    }
+
+    /// List all open polls in the given channel whose timer has run out.
+    pub async fn expired(&self, channel: &Channel, now: NaiveDateTime) -> Result<Vec<Poll>> {
+        use self::schema::polls::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::polls
+                    .filter(
+                        dsl::channel
+                            .eq(&channel)
+                            .and(dsl::closed_at.is_null())
+                            .and(dsl::closes_at.le(now)),
+                    )
+                    .order(dsl::id.asc())
+                    .load::<Poll>(c)?)
+            })
+            .await
+    }
+
+    /// Get the options of the given poll, in the order they were specified.
+    pub async fn options(&self, poll_id: i32) -> Result<Vec<PollOption>> {
+        use self::schema::poll_options::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::poll_options
+                    .filter(dsl::poll_id.eq(poll_id))
+                    .order(dsl::position.asc())
+                    .load::<PollOption>(c)?)
+            })
+            .await
+    }
+
+    /// Cast a vote for the given user, replacing any vote they've already
+    /// cast in the poll.
+    ///
+    /// Returns `true` if the vote was recorded or changed.
+    pub async fn vote(&self, poll_id: i32, user: &str, option: &str) -> Result<bool> {
+        use self::schema::poll_votes::dsl;
+
+        let user = user.to_string();
+        let option = option.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                let filter =
+                    dsl::poll_votes.filter(dsl::poll_id.eq(poll_id).and(dsl::user.eq(&user)));
+
+                let existing = filter.first::<models::PollVote>(c).optional()?;
+                let now = Utc::now().naive_utc();
+
+                match existing {
+                    Some(existing) if existing.option == option => Ok(false),
+                    Some(..) => {
+                        diesel::update(filter)
+                            .set((dsl::option.eq(&option), dsl::voted_at.eq(now)))
+                            .execute(c)?;
+
+                        Ok(true)
+                    }
+                    None => {
+                        let vote = models::PollVote {
+                            poll_id,
+                            user,
+                            option,
+                            voted_at: now,
+                        };
+
+                        diesel::insert_into(dsl::poll_votes)
+                            .values(&vote)
+                            .execute(c)?;
+
+                        Ok(true)
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Tally the votes of the given poll, in the order options were
+    /// specified.
+    pub async fn results(&self, poll_id: i32) -> Result<Vec<PollResult>> {
+        use self::schema::poll_votes::dsl;
+
+        let options = self.options(poll_id).await?;
+
+        let votes = self
+            .db
+            .asyncify(move |c| {
+                Ok(dsl::poll_votes
+                    .filter(dsl::poll_id.eq(poll_id))
+                    .select(dsl::option)
+                    .load::<String>(c)?)
+            })
+            .await?;
+
+        let mut counts = HashMap::<String, u32>::new();
+
+        for option in votes {
+            *counts.entry(option).or_default() += 1;
+        }
+
+        let results = options
+            .into_iter()
+            .map(|o| PollResult {
+                votes: counts.get(&o.key).copied().unwrap_or_default(),
+                key: o.key,
+                description: o.description,
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Close the given poll.
+    ///
+    /// Returns `true` if the poll was open.
+    pub async fn close(&self, id: i32) -> Result<bool> {
+        use self::schema::polls::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                let now = Utc::now().naive_utc();
+
+                let count =
+                    diesel::update(dsl::polls.filter(dsl::id.eq(id).and(dsl::closed_at.is_null())))
+                        .set(dsl::closed_at.eq(now))
+                        .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+}
@@ -0,0 +1,252 @@
+use anyhow::Result;
+use common::Channel;
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::Quote;
+
+#[derive(Clone)]
+pub struct Quotes {
+    db: crate::Database,
+}
+
+impl Quotes {
+    /// Open the quotes database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// Insert a new quote and return its identifier.
+    pub async fn insert(
+        &self,
+        channel: &Channel,
+        added_by: &str,
+        author: &str,
+        game: Option<&str>,
+        text: &str,
+    ) -> Result<i32> {
+        use self::schema::quotes::dsl;
+
+        let quote = models::InsertQuote {
+            channel: channel.to_owned(),
+            added_by: added_by.to_string(),
+            author: author.to_string(),
+            game: game.map(str::to_string),
+            text: text.to_string(),
+        };
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(|c| {
+                    diesel::insert_into(dsl::quotes).values(&quote).execute(c)?;
+                    Ok(crate::last_insert_id(c)?)
+                })
+            })
+            .await
+    }
+
+    /// Get the quote with the given id.
+    pub async fn get(&self, channel: &Channel, id: i32) -> Result<Option<Quote>> {
+        use self::schema::quotes::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::quotes
+                    .filter(dsl::channel.eq(&channel).and(dsl::id.eq(id)))
+                    .first::<Quote>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// Get a random quote.
+    pub async fn random(&self, channel: &Channel) -> Result<Option<Quote>> {
+        use self::schema::quotes::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::quotes
+                    .filter(dsl::channel.eq(&channel))
+                    .order(diesel::dsl::sql::<diesel::sql_types::Integer>("RANDOM()"))
+                    .first::<Quote>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// Search for quotes whose text or author contains the given string.
+    pub async fn search(&self, channel: &Channel, search: &str, limit: i64) -> Result<Vec<Quote>> {
+        use self::schema::quotes::dsl;
+
+        let channel = channel.to_owned();
+        let pattern = format!("%{}%", escape_like(search));
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::quotes
+                    .filter(
+                        dsl::channel.eq(&channel).and(
+                            dsl::text
+                                .like(&pattern)
+                                .escape('\\')
+                                .or(dsl::author.like(&pattern).escape('\\')),
+                        ),
+                    )
+                    .order(dsl::id.asc())
+                    .limit(limit)
+                    .load::<Quote>(c)?)
+            })
+            .await
+    }
+
+    /// List all quotes in the given channel.
+    pub async fn list(&self, channel: &Channel) -> Result<Vec<Quote>> {
+        use self::schema::quotes::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::quotes
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::id.asc())
+                    .load::<Quote>(c)?)
+            })
+            .await
+    }
+
+    /// Edit the author and text of the given quote.
+    pub async fn edit(&self, channel: &Channel, id: i32, author: &str, text: &str) -> Result<bool> {
+        use self::schema::quotes::dsl;
+
+        let channel = channel.to_owned();
+        let author = author.to_string();
+        let text = text.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                let count = diesel::update(
+                    dsl::quotes.filter(dsl::channel.eq(&channel).and(dsl::id.eq(id))),
+                )
+                .set((dsl::author.eq(&author), dsl::text.eq(&text)))
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// Delete the quote with the given id.
+    pub async fn delete(&self, channel: &Channel, id: i32) -> Result<bool> {
+        use self::schema::quotes::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                let count = diesel::delete(
+                    dsl::quotes.filter(dsl::channel.eq(&channel).and(dsl::id.eq(id))),
+                )
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+}
+
+/// Escape the special characters of a `LIKE` pattern, using `\` as the escape
+/// character.
+fn escape_like(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            out.push('\\');
+        }
+
+        out.push(c);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use common::Channel;
+
+    use super::{escape_like, Quotes};
+
+    async fn quotes() -> anyhow::Result<Quotes> {
+        let db = crate::Database::open(Path::new(":memory:"))?;
+        Quotes::load(db).await
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("a_b\\c"), "a\\_b\\\\c");
+    }
+
+    #[tokio::test]
+    async fn test_search() -> anyhow::Result<()> {
+        let quotes = quotes().await?;
+        let channel = Channel::new("#setbac");
+
+        let a = quotes
+            .insert(channel, "setbac", "Turtle", None, "We're 100% done")
+            .await?;
+        let b = quotes
+            .insert(channel, "setbac", "Hare", None, "We're 1000 done")
+            .await?;
+        let c = quotes
+            .insert(channel, "setbac", "snake_case", None, "hiss")
+            .await?;
+        assert!(a < b && b < c);
+
+        let ids = |quotes: Vec<super::Quote>| quotes.into_iter().map(|q| q.id).collect::<Vec<_>>();
+
+        assert_eq!(ids(quotes.search(channel, "100%", 10).await?), vec![a]);
+        assert_eq!(ids(quotes.search(channel, "100", 10).await?), vec![a, b]);
+        assert_eq!(ids(quotes.search(channel, "e_c", 10).await?), vec![c]);
+        assert_eq!(ids(quotes.search(channel, "hare", 10).await?), vec![b]);
+        assert_eq!(ids(quotes.search(channel, "_", 10).await?), vec![c]);
+        assert_eq!(ids(quotes.search(channel, "100", 1).await?), vec![a]);
+        assert!(quotes
+            .search(Channel::new("#other"), "100", 10)
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_random() -> anyhow::Result<()> {
+        let quotes = quotes().await?;
+        let channel = Channel::new("#setbac");
+
+        assert!(quotes.random(channel).await?.is_none());
+
+        let a = quotes
+            .insert(channel, "setbac", "Turtle", None, "a")
+            .await?;
+        let b = quotes.insert(channel, "setbac", "Hare", None, "b").await?;
+        quotes
+            .insert(Channel::new("#other"), "setbac", "Snake", None, "c")
+            .await?;
+
+        for _ in 0..16 {
+            let quote = quotes.random(channel).await?.expect("quote");
+            assert!(quote.id == a || quote.id == b);
+        }
+
+        Ok(())
+    }
+}
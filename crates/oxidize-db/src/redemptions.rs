@@ -0,0 +1,168 @@
+use anyhow::Result;
+use common::Channel;
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::RedemptionAction;
+
+/// Editable fields of a redemption action.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RedemptionActionEdit {
+    pub action: String,
+    #[serde(default)]
+    pub argument: String,
+    #[serde(default)]
+    pub amount: i64,
+    #[serde(default = "default_true")]
+    pub fulfill: bool,
+    #[serde(default = "default_true")]
+    pub refund: bool,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Clone)]
+pub struct Redemptions {
+    db: crate::Database,
+}
+
+impl Redemptions {
+    /// Open the redemptions database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// List all redemption actions in the given channel, ordered by reward.
+    pub async fn list(&self, channel: &Channel) -> Result<Vec<RedemptionAction>> {
+        use self::schema::redemption_actions::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::redemption_actions
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::reward.asc())
+                    .load::<RedemptionAction>(c)?)
+            })
+            .await
+    }
+
+    /// Find the enabled action for a reward, matching either its id or its
+    /// title. Matches by id take precedence.
+    pub async fn find(
+        &self,
+        channel: &Channel,
+        id: &str,
+        title: &str,
+    ) -> Result<Option<RedemptionAction>> {
+        use self::schema::redemption_actions::dsl;
+
+        let channel = channel.to_owned();
+        let id = id.to_lowercase();
+        let title = title.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                let actions = dsl::redemption_actions
+                    .filter(
+                        dsl::channel
+                            .eq(&channel)
+                            .and(dsl::reward.eq(&id).or(dsl::reward.eq(&title)))
+                            .and(dsl::disabled.eq(false)),
+                    )
+                    .load::<RedemptionAction>(c)?;
+
+                let mut by_title = None;
+
+                for action in actions {
+                    if action.reward == id {
+                        return Ok(Some(action));
+                    }
+
+                    by_title = Some(action);
+                }
+
+                Ok(by_title)
+            })
+            .await
+    }
+
+    /// Insert or update the action for the given reward.
+    pub async fn edit(
+        &self,
+        channel: &Channel,
+        reward: &str,
+        edit: RedemptionActionEdit,
+    ) -> Result<()> {
+        use self::schema::redemption_actions::dsl;
+
+        let channel = channel.to_owned();
+        let reward = reward.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                let filter = dsl::redemption_actions
+                    .filter(dsl::channel.eq(&channel).and(dsl::reward.eq(&reward)));
+
+                let count = diesel::update(filter)
+                    .set((
+                        dsl::action.eq(&edit.action),
+                        dsl::argument.eq(&edit.argument),
+                        dsl::amount.eq(edit.amount),
+                        dsl::fulfill.eq(edit.fulfill),
+                        dsl::refund.eq(edit.refund),
+                        dsl::disabled.eq(edit.disabled),
+                    ))
+                    .execute(c)?;
+
+                if count == 0 {
+                    let action = models::RedemptionAction {
+                        channel,
+                        reward,
+                        action: edit.action,
+                        argument: edit.argument,
+                        amount: edit.amount,
+                        fulfill: edit.fulfill,
+                        refund: edit.refund,
+                        disabled: edit.disabled,
+                    };
+
+                    diesel::insert_into(dsl::redemption_actions)
+                        .values(&action)
+                        .execute(c)?;
+                }
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Delete the action for the given reward.
+    ///
+    /// Returns `true` if the action existed.
+    pub async fn delete(&self, channel: &Channel, reward: &str) -> Result<bool> {
+        use self::schema::redemption_actions::dsl;
+
+        let channel = channel.to_owned();
+        let reward = reward.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                let count = diesel::delete(
+                    dsl::redemption_actions
+                        .filter(dsl::channel.eq(&channel).and(dsl::reward.eq(&reward))),
+                )
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+}
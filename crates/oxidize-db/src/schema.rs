@@ -58,6 +58,27 @@ table! {
     }
 }
 
+table! {
+    settings_profiles (profile, key) {
+        profile -> Text,
+        key -> Text,
+        value -> Nullable<Text>,
+    }
+}
+
+table! {
+    settings_history (id) {
+        id -> Integer,
+        key -> Text,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+        source -> Text,
+        user -> Nullable<Text>,
+        changed_at -> Timestamp,
+        undone -> Bool,
+    }
+}
+
 table! {
     aliases (channel, name) {
         channel -> Text,
@@ -116,3 +137,232 @@ table! {
         value -> Binary,
     }
 }
+
+table! {
+    quotes (id) {
+        id -> Integer,
+        channel -> Text,
+        added_at -> Timestamp,
+        added_by -> Text,
+        author -> Text,
+        game -> Nullable<Text>,
+        text -> Text,
+    }
+}
+
+table! {
+    counters (channel, name) {
+        channel -> Text,
+        name -> Text,
+        count -> BigInt,
+        stream_count -> BigInt,
+        stream_id -> Nullable<Text>,
+    }
+}
+
+table! {
+    giveaways (id) {
+        id -> Integer,
+        channel -> Text,
+        keyword -> Text,
+        opened_at -> Timestamp,
+        closed_at -> Nullable<Timestamp>,
+        winner -> Nullable<Text>,
+    }
+}
+
+table! {
+    giveaway_entrants (giveaway_id, user) {
+        giveaway_id -> Integer,
+        user -> Text,
+        entered_at -> Timestamp,
+        tickets -> Integer,
+        subscriber -> Bool,
+        drawn -> Bool,
+    }
+}
+
+table! {
+    polls (id) {
+        id -> Integer,
+        channel -> Text,
+        question -> Text,
+        created_at -> Timestamp,
+        closes_at -> Nullable<Timestamp>,
+        closed_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    poll_options (poll_id, key) {
+        poll_id -> Integer,
+        key -> Text,
+        description -> Nullable<Text>,
+        position -> Integer,
+    }
+}
+
+table! {
+    poll_votes (poll_id, user) {
+        poll_id -> Integer,
+        user -> Text,
+        option -> Text,
+        voted_at -> Timestamp,
+    }
+}
+
+table! {
+    bets (id) {
+        id -> Integer,
+        channel -> Text,
+        title -> Text,
+        opened_at -> Timestamp,
+        locks_at -> Nullable<Timestamp>,
+        locked_at -> Nullable<Timestamp>,
+        resolved_at -> Nullable<Timestamp>,
+        winner -> Nullable<Text>,
+        cancelled -> Bool,
+        settled_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    bet_outcomes (bet_id, key) {
+        bet_id -> Integer,
+        key -> Text,
+        description -> Nullable<Text>,
+        position -> Integer,
+    }
+}
+
+table! {
+    bet_wagers (bet_id, user) {
+        bet_id -> Integer,
+        user -> Text,
+        outcome -> Text,
+        amount -> BigInt,
+        placed_at -> Timestamp,
+        paid -> Bool,
+    }
+}
+
+table! {
+    currency_ledger (id) {
+        id -> Integer,
+        transaction_id -> Integer,
+        channel -> Text,
+        created_at -> Timestamp,
+        user -> Text,
+        amount -> BigInt,
+        reason -> Text,
+        counterparty -> Nullable<Text>,
+        reverses -> Nullable<Integer>,
+        reversed -> Bool,
+    }
+}
+
+table! {
+    shop_items (channel, name) {
+        channel -> Text,
+        name -> Text,
+        description -> Nullable<Text>,
+        price -> BigInt,
+        stock -> Nullable<Integer>,
+        cooldown -> Integer,
+        command -> Nullable<Text>,
+        disabled -> Bool,
+        last_purchased_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    shop_purchases (id) {
+        id -> Integer,
+        channel -> Text,
+        item -> Text,
+        user -> Text,
+        price -> BigInt,
+        purchased_at -> Timestamp,
+        fulfilled_at -> Nullable<Timestamp>,
+        fulfilled_by -> Nullable<Text>,
+        refunded -> Bool,
+    }
+}
+
+table! {
+    watch_time (channel, user) {
+        channel -> Text,
+        user -> Text,
+        seconds -> BigInt,
+        last_seen -> Timestamp,
+    }
+}
+
+table! {
+    watch_time_sessions (channel, stream_id, user) {
+        channel -> Text,
+        stream_id -> Text,
+        user -> Text,
+        seconds -> BigInt,
+    }
+}
+
+table! {
+    redemption_actions (channel, reward) {
+        channel -> Text,
+        reward -> Text,
+        action -> Text,
+        argument -> Text,
+        amount -> BigInt,
+        fulfill -> Bool,
+        refund -> Bool,
+        disabled -> Bool,
+    }
+}
+
+table! {
+    web_tokens (id) {
+        id -> Integer,
+        name -> Text,
+        token -> Text,
+        access -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    webhooks (id) {
+        id -> Integer,
+        name -> Text,
+        url -> Text,
+        secret -> Text,
+        events -> Text,
+        enabled -> Bool,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    webhook_deliveries (id) {
+        id -> Integer,
+        webhook -> Text,
+        event -> Text,
+        payload -> Text,
+        attempts -> Integer,
+        status -> Nullable<Integer>,
+        error -> Nullable<Text>,
+        delivered_at -> Timestamp,
+    }
+}
+
+table! {
+    timers (channel, name) {
+        channel -> Text,
+        name -> Text,
+        kind -> Text,
+        duration -> Nullable<BigInt>,
+        elapsed -> BigInt,
+        started_at -> Nullable<Timestamp>,
+        template -> Nullable<Text>,
+    }
+}
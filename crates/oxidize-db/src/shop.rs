@@ -0,0 +1,386 @@
+use anyhow::Result;
+use chrono::Utc;
+use common::Channel;
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::{ShopItem, ShopPurchase};
+
+/// Editable fields of a shop item.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ShopItemEdit {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub price: i64,
+    #[serde(default)]
+    pub stock: Option<i32>,
+    #[serde(default)]
+    pub cooldown: i32,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+#[derive(Clone)]
+pub struct Shop {
+    db: crate::Database,
+}
+
+impl Shop {
+    /// Open the shop database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// List all items in the given channel, ordered by name.
+    pub async fn items(&self, channel: &Channel) -> Result<Vec<ShopItem>> {
+        use self::schema::shop_items::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::shop_items
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::name.asc())
+                    .load::<ShopItem>(c)?)
+            })
+            .await
+    }
+
+    /// Get the item with the given name.
+    pub async fn item(&self, channel: &Channel, name: &str) -> Result<Option<ShopItem>> {
+        use self::schema::shop_items::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::shop_items
+                    .filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name)))
+                    .first::<ShopItem>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// Insert or update the item with the given name.
+    pub async fn edit(&self, channel: &Channel, name: &str, edit: ShopItemEdit) -> Result<()> {
+        use self::schema::shop_items::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                let filter =
+                    dsl::shop_items.filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name)));
+
+                if filter.first::<ShopItem>(c).optional()?.is_some() {
+                    diesel::update(filter)
+                        .set((
+                            dsl::description.eq(edit.description),
+                            dsl::price.eq(edit.price),
+                            dsl::stock.eq(edit.stock),
+                            dsl::cooldown.eq(edit.cooldown),
+                            dsl::command.eq(edit.command),
+                            dsl::disabled.eq(edit.disabled),
+                        ))
+                        .execute(c)?;
+                } else {
+                    let item = ShopItem {
+                        channel,
+                        name,
+                        description: edit.description,
+                        price: edit.price,
+                        stock: edit.stock,
+                        cooldown: edit.cooldown,
+                        command: edit.command,
+                        disabled: edit.disabled,
+                        last_purchased_at: None,
+                    };
+
+                    diesel::insert_into(dsl::shop_items)
+                        .values(&item)
+                        .execute(c)?;
+                }
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Delete the item with the given name.
+    ///
+    /// Returns `true` if the item existed.
+    pub async fn delete(&self, channel: &Channel, name: &str) -> Result<bool> {
+        use self::schema::shop_items::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                let count = diesel::delete(
+                    dsl::shop_items.filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name))),
+                )
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// Record a purchase of the given item, taking one item out of stock.
+    ///
+    /// Items with a command are considered fulfilled immediately.
+    ///
+    /// Returns `None` if the item no longer exists or is out of stock.
+    pub async fn purchase(
+        &self,
+        channel: &Channel,
+        name: &str,
+        user: &str,
+    ) -> Result<Option<ShopPurchase>> {
+        use self::schema::shop_items::dsl as i;
+        use self::schema::shop_purchases::dsl as p;
+
+        let channel = channel.to_owned();
+        let name = name.to_lowercase();
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let filter =
+                        i::shop_items.filter(i::channel.eq(&channel).and(i::name.eq(&name)));
+
+                    let item = match filter.first::<ShopItem>(c).optional()? {
+                        Some(item) => item,
+                        None => return Ok(None),
+                    };
+
+                    let stock = match item.stock {
+                        Some(0) => return Ok(None),
+                        Some(stock) => Some(stock - 1),
+                        None => None,
+                    };
+
+                    let now = Utc::now().naive_utc();
+
+                    diesel::update(filter)
+                        .set((i::stock.eq(stock), i::last_purchased_at.eq(now)))
+                        .execute(c)?;
+
+                    let purchase = models::InsertShopPurchase {
+                        channel: channel.clone(),
+                        item: item.name,
+                        user,
+                        price: item.price,
+                        purchased_at: now,
+                        fulfilled_at: item.command.as_ref().map(|_| now),
+                    };
+
+                    diesel::insert_into(p::shop_purchases)
+                        .values(&purchase)
+                        .execute(c)?;
+
+                    let id = crate::last_insert_id(c)?;
+                    let purchase = p::shop_purchases
+                        .filter(p::id.eq(id))
+                        .first::<ShopPurchase>(c)?;
+
+                    Ok(Some(purchase))
+                })
+            })
+            .await
+    }
+
+    /// List the latest purchases in the given channel, newest first, skipping
+    /// the first `offset` of them.
+    pub async fn purchases(
+        &self,
+        channel: &Channel,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ShopPurchase>> {
+        use self::schema::shop_purchases::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::shop_purchases
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::id.desc())
+                    .limit(limit)
+                    .offset(offset)
+                    .load::<ShopPurchase>(c)?)
+            })
+            .await
+    }
+
+    /// Count the purchases in the given channel.
+    pub async fn count_purchases(&self, channel: &Channel) -> Result<i64> {
+        use self::schema::shop_purchases::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::shop_purchases
+                    .filter(dsl::channel.eq(&channel))
+                    .count()
+                    .get_result::<i64>(c)?)
+            })
+            .await
+    }
+
+    /// List all purchases waiting to be fulfilled, oldest first.
+    pub async fn pending(&self, channel: &Channel) -> Result<Vec<ShopPurchase>> {
+        use self::schema::shop_purchases::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::shop_purchases
+                    .filter(
+                        dsl::channel
+                            .eq(&channel)
+                            .and(dsl::fulfilled_at.is_null())
+                            .and(dsl::refunded.eq(false)),
+                    )
+                    .order(dsl::id.asc())
+                    .load::<ShopPurchase>(c)?)
+            })
+            .await
+    }
+
+    /// Mark the given purchase as fulfilled.
+    ///
+    /// Returns `true` if the purchase was waiting to be fulfilled.
+    pub async fn fulfill(&self, channel: &Channel, id: i32, by: Option<&str>) -> Result<bool> {
+        use self::schema::shop_purchases::dsl;
+
+        let channel = channel.to_owned();
+        let by = by.map(str::to_string);
+
+        self.db
+            .asyncify(move |c| {
+                let now = Utc::now().naive_utc();
+
+                let count = diesel::update(
+                    dsl::shop_purchases.filter(
+                        dsl::channel
+                            .eq(&channel)
+                            .and(dsl::id.eq(id))
+                            .and(dsl::fulfilled_at.is_null())
+                            .and(dsl::refunded.eq(false)),
+                    ),
+                )
+                .set((dsl::fulfilled_at.eq(now), dsl::fulfilled_by.eq(by)))
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// Mark the given purchase as refunded, putting the item back in stock.
+    ///
+    /// Returns the refunded purchase, or `None` if it doesn't exist, has
+    /// already been fulfilled, or has already been refunded.
+    pub async fn refund(&self, channel: &Channel, id: i32) -> Result<Option<ShopPurchase>> {
+        use self::schema::shop_items::dsl as i;
+        use self::schema::shop_purchases::dsl as p;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let filter = p::shop_purchases.filter(
+                        p::channel
+                            .eq(&channel)
+                            .and(p::id.eq(id))
+                            .and(p::fulfilled_at.is_null())
+                            .and(p::refunded.eq(false)),
+                    );
+
+                    let purchase = match filter.first::<ShopPurchase>(c).optional()? {
+                        Some(purchase) => purchase,
+                        None => return Ok(None),
+                    };
+
+                    diesel::update(filter)
+                        .set(p::refunded.eq(true))
+                        .execute(c)?;
+
+                    diesel::update(
+                        i::shop_items.filter(
+                            i::channel
+                                .eq(&channel)
+                                .and(i::name.eq(&purchase.item))
+                                .and(i::stock.is_not_null()),
+                        ),
+                    )
+                    .set(i::stock.eq(i::stock + 1))
+                    .execute(c)?;
+
+                    Ok(Some(purchase))
+                })
+            })
+            .await
+    }
+
+    /// Undo a refund of the given purchase, taking the item back out of
+    /// stock.
+    ///
+    /// This is used when the currency couldn't be given back to the user.
+    pub async fn revert_refund(&self, channel: &Channel, id: i32) -> Result<()> {
+        use self::schema::shop_items::dsl as i;
+        use self::schema::shop_purchases::dsl as p;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let filter = p::shop_purchases.filter(
+                        p::channel
+                            .eq(&channel)
+                            .and(p::id.eq(id))
+                            .and(p::refunded.eq(true)),
+                    );
+
+                    let purchase = match filter.first::<ShopPurchase>(c).optional()? {
+                        Some(purchase) => purchase,
+                        None => return Ok(()),
+                    };
+
+                    diesel::update(filter)
+                        .set(p::refunded.eq(false))
+                        .execute(c)?;
+
+                    diesel::update(
+                        i::shop_items.filter(
+                            i::channel
+                                .eq(&channel)
+                                .and(i::name.eq(&purchase.item))
+                                .and(i::stock.gt(0)),
+                        ),
+                    )
+                    .set(i::stock.eq(i::stock - 1))
+                    .execute(c)?;
+
+                    Ok(())
+                })
+            })
+            .await
+    }
+}
@@ -0,0 +1,375 @@
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use chrono::{NaiveDateTime, Utc};
+use common::Channel;
+use diesel::prelude::*;
+use tokio::sync::watch;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::Timer;
+
+/// The kind of a timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerKind {
+    /// Counts down from a duration.
+    Countdown,
+    /// Counts up from zero.
+    Stopwatch,
+    /// Counts down from a duration while the streamer is on a break.
+    Break,
+}
+
+impl TimerKind {
+    /// The name of the kind, as it's stored.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimerKind::Countdown => "countdown",
+            TimerKind::Stopwatch => "stopwatch",
+            TimerKind::Break => "break",
+        }
+    }
+
+    /// Test if timers of this kind count down from a duration.
+    pub fn has_duration(&self) -> bool {
+        !matches!(self, TimerKind::Stopwatch)
+    }
+}
+
+impl fmt::Display for TimerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl std::str::FromStr for TimerKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "countdown" => TimerKind::Countdown,
+            "stopwatch" => TimerKind::Stopwatch,
+            "break" => TimerKind::Break,
+            other => bail!("unsupported timer kind `{}`", other),
+        })
+    }
+}
+
+impl Timer {
+    /// Test if the timer is running.
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// Seconds elapsed on the timer at the given point in time.
+    pub fn elapsed_at(&self, now: NaiveDateTime) -> i64 {
+        let running = match self.started_at {
+            Some(started_at) => (now - started_at).num_seconds().max(0),
+            None => 0,
+        };
+
+        self.elapsed.saturating_add(running)
+    }
+
+    /// Seconds remaining on the timer at the given point in time, if it has a
+    /// duration.
+    pub fn remaining_at(&self, now: NaiveDateTime) -> Option<i64> {
+        let duration = self.duration?;
+        Some(duration.saturating_sub(self.elapsed_at(now)).max(0))
+    }
+
+    /// Test if the timer has run out at the given point in time.
+    pub fn is_finished_at(&self, now: NaiveDateTime) -> bool {
+        self.remaining_at(now) == Some(0)
+    }
+
+    /// Pause the timer at the given point in time.
+    fn pause_at(&mut self, now: NaiveDateTime) {
+        let elapsed = self.elapsed_at(now);
+        self.elapsed = self.duration.map_or(elapsed, |d| elapsed.min(d));
+        self.started_at = None;
+    }
+}
+
+/// Named timers, like countdowns and stopwatches.
+#[derive(Clone)]
+pub struct Timers {
+    db: crate::Database,
+    changed: Arc<watch::Sender<()>>,
+}
+
+impl Timers {
+    /// Open the timers database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self {
+            db,
+            changed: Arc::new(watch::channel(()).0),
+        })
+    }
+
+    /// Subscribe to changes made to any timer.
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.changed.subscribe()
+    }
+
+    /// List all timers in the given channel, ordered by name.
+    pub async fn list(&self, channel: &Channel) -> Result<Vec<Timer>> {
+        use self::schema::timers::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::timers
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::name.asc())
+                    .load::<Timer>(c)?)
+            })
+            .await
+    }
+
+    /// Get the timer with the given name.
+    pub async fn get(&self, channel: &Channel, name: &str) -> Result<Option<Timer>> {
+        use self::schema::timers::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::timers
+                    .filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name)))
+                    .first::<Timer>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// Create and start a timer, replacing any existing timer with the same
+    /// name.
+    ///
+    /// The `duration` is in seconds and is required for every kind of timer
+    /// except stopwatches.
+    pub async fn start(
+        &self,
+        channel: &Channel,
+        name: &str,
+        kind: TimerKind,
+        duration: Option<i64>,
+        template: Option<String>,
+    ) -> Result<Timer> {
+        use self::schema::timers::dsl;
+
+        let duration = match (kind.has_duration(), duration) {
+            (true, Some(duration)) if duration > 0 => Some(duration),
+            (true, _) => bail!("a {} requires a duration", kind),
+            (false, _) => None,
+        };
+
+        let timer = Timer {
+            channel: channel.to_owned(),
+            name: name.to_lowercase(),
+            kind: kind.as_str().to_owned(),
+            duration,
+            elapsed: 0,
+            started_at: Some(Utc::now().naive_utc()),
+            template,
+        };
+
+        let timer = self
+            .db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(|c| {
+                    diesel::delete(
+                        dsl::timers.filter(
+                            dsl::channel
+                                .eq(&timer.channel)
+                                .and(dsl::name.eq(&timer.name)),
+                        ),
+                    )
+                    .execute(c)?;
+
+                    diesel::insert_into(dsl::timers).values(&timer).execute(c)?;
+
+                    Ok(timer)
+                })
+            })
+            .await?;
+
+        self.changed.send_replace(());
+        Ok(timer)
+    }
+
+    /// Pause the given timer.
+    ///
+    /// Returns `None` if the timer doesn't exist.
+    pub async fn pause(&self, channel: &Channel, name: &str) -> Result<Option<Timer>> {
+        self.modify(channel, name, |timer, now| {
+            if timer.is_running() {
+                timer.pause_at(now);
+            }
+        })
+        .await
+    }
+
+    /// Resume the given timer.
+    ///
+    /// Returns `None` if the timer doesn't exist.
+    pub async fn resume(&self, channel: &Channel, name: &str) -> Result<Option<Timer>> {
+        self.modify(channel, name, |timer, now| {
+            if !timer.is_running() && !timer.is_finished_at(now) {
+                timer.started_at = Some(now);
+            }
+        })
+        .await
+    }
+
+    /// Reset the elapsed time of the given timer, keeping it running if it
+    /// was running.
+    ///
+    /// Returns `None` if the timer doesn't exist.
+    pub async fn reset(&self, channel: &Channel, name: &str) -> Result<Option<Timer>> {
+        self.modify(channel, name, |timer, now| {
+            timer.elapsed = 0;
+
+            if timer.is_running() {
+                timer.started_at = Some(now);
+            }
+        })
+        .await
+    }
+
+    /// Delete the given timer.
+    ///
+    /// Returns `true` if the timer existed.
+    pub async fn delete(&self, channel: &Channel, name: &str) -> Result<bool> {
+        use self::schema::timers::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_lowercase();
+
+        let deleted = self
+            .db
+            .asyncify(move |c| {
+                let count = diesel::delete(
+                    dsl::timers.filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name))),
+                )
+                .execute(c)?;
+
+                Ok(count == 1)
+            })
+            .await?;
+
+        if deleted {
+            self.changed.send_replace(());
+        }
+
+        Ok(deleted)
+    }
+
+    /// Apply a modification to the elapsed state of a timer.
+    async fn modify<F>(&self, channel: &Channel, name: &str, f: F) -> Result<Option<Timer>>
+    where
+        F: 'static + Send + FnOnce(&mut Timer, NaiveDateTime),
+    {
+        use self::schema::timers::dsl;
+
+        let channel = channel.to_owned();
+        let name = name.to_lowercase();
+
+        let timer = self
+            .db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let filter =
+                        dsl::timers.filter(dsl::channel.eq(&channel).and(dsl::name.eq(&name)));
+
+                    let Some(mut timer) = filter.first::<Timer>(c).optional()? else {
+                        return Ok(None);
+                    };
+
+                    f(&mut timer, Utc::now().naive_utc());
+
+                    diesel::update(filter)
+                        .set((
+                            dsl::elapsed.eq(timer.elapsed),
+                            dsl::started_at.eq(timer.started_at),
+                        ))
+                        .execute(c)?;
+
+                    Ok(Some(timer))
+                })
+            })
+            .await?;
+
+        if timer.is_some() {
+            self.changed.send_replace(());
+        }
+
+        Ok(timer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDateTime};
+    use common::Channel;
+
+    use super::{Timer, TimerKind};
+
+    fn timer(kind: TimerKind, duration: Option<i64>, started_at: NaiveDateTime) -> Timer {
+        Timer {
+            channel: Channel::new("#setbac").to_owned(),
+            name: String::from("test"),
+            kind: kind.as_str().to_owned(),
+            duration,
+            elapsed: 10,
+            started_at: Some(started_at),
+            template: None,
+        }
+    }
+
+    #[test]
+    fn test_elapsed() {
+        let start = NaiveDateTime::default();
+        let now = start + Duration::seconds(30);
+
+        let mut countdown = timer(TimerKind::Countdown, Some(60), start);
+        assert_eq!(countdown.elapsed_at(now), 40);
+        assert_eq!(countdown.remaining_at(now), Some(20));
+        assert!(!countdown.is_finished_at(now));
+        assert!(countdown.is_finished_at(now + Duration::seconds(20)));
+        assert_eq!(countdown.remaining_at(now + Duration::seconds(60)), Some(0));
+
+        countdown.pause_at(now);
+        assert!(!countdown.is_running());
+        assert_eq!(countdown.elapsed, 40);
+        assert_eq!(
+            countdown.remaining_at(now + Duration::seconds(60)),
+            Some(20)
+        );
+
+        // Pausing an overdue countdown clamps it to its duration.
+        let mut overdue = timer(TimerKind::Break, Some(30), start);
+        overdue.pause_at(now);
+        assert_eq!(overdue.elapsed, 30);
+
+        let stopwatch = timer(TimerKind::Stopwatch, None, start);
+        assert_eq!(stopwatch.elapsed_at(now), 40);
+        assert_eq!(stopwatch.remaining_at(now), None);
+        assert!(!stopwatch.is_finished_at(now));
+    }
+
+    #[test]
+    fn test_kind() {
+        for kind in [TimerKind::Countdown, TimerKind::Stopwatch, TimerKind::Break] {
+            assert_eq!(str::parse::<TimerKind>(kind.as_str()).unwrap(), kind);
+        }
+
+        assert!(str::parse::<TimerKind>("egg").is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::Utc;
+use common::Channel;
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::{WatchTimeSession, WatchTimeTotal};
+
+/// Watch time of a single user.
+#[derive(Debug, Clone, Copy, Default, serde::Serialize)]
+pub struct WatchTimeOf {
+    /// Total watch time in seconds.
+    pub total: i64,
+    /// Watch time during the current stream in seconds.
+    pub stream: i64,
+}
+
+#[derive(Clone)]
+pub struct WatchTime {
+    db: crate::Database,
+}
+
+impl WatchTime {
+    /// Open the watch time database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// Add the given number of seconds of watch time to all users, both in
+    /// total and for the stream identified by `stream_id`.
+    pub async fn record(
+        &self,
+        channel: &Channel,
+        stream_id: Option<&str>,
+        users: Vec<String>,
+        seconds: i64,
+    ) -> Result<()> {
+        use self::schema::watch_time::dsl as t;
+        use self::schema::watch_time_sessions::dsl as s;
+
+        let channel = channel.to_owned();
+        let stream_id = stream_id.map(str::to_string);
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let now = Utc::now().naive_utc();
+
+                    for user in users {
+                        let user = crate::user_id(&user);
+
+                        let filter =
+                            t::watch_time.filter(t::channel.eq(&channel).and(t::user.eq(&user)));
+
+                        let count = diesel::update(filter)
+                            .set((t::seconds.eq(t::seconds + seconds), t::last_seen.eq(now)))
+                            .execute(c)?;
+
+                        if count == 0 {
+                            diesel::insert_into(t::watch_time)
+                                .values(&models::WatchTimeTotal {
+                                    channel: channel.clone(),
+                                    user: user.clone(),
+                                    seconds,
+                                    last_seen: now,
+                                })
+                                .execute(c)?;
+                        }
+
+                        let Some(stream_id) = &stream_id else {
+                            continue;
+                        };
+
+                        let filter = s::watch_time_sessions.filter(
+                            s::channel
+                                .eq(&channel)
+                                .and(s::stream_id.eq(stream_id))
+                                .and(s::user.eq(&user)),
+                        );
+
+                        let count = diesel::update(filter)
+                            .set(s::seconds.eq(s::seconds + seconds))
+                            .execute(c)?;
+
+                        if count == 0 {
+                            diesel::insert_into(s::watch_time_sessions)
+                                .values(&models::WatchTimeSession {
+                                    channel: channel.clone(),
+                                    stream_id: stream_id.clone(),
+                                    user,
+                                    seconds,
+                                })
+                                .execute(c)?;
+                        }
+                    }
+
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    /// Get the watch time of the given user.
+    pub async fn get(
+        &self,
+        channel: &Channel,
+        stream_id: Option<&str>,
+        user: &str,
+    ) -> Result<WatchTimeOf> {
+        use self::schema::watch_time::dsl as t;
+        use self::schema::watch_time_sessions::dsl as s;
+
+        let channel = channel.to_owned();
+        let stream_id = stream_id.map(str::to_string);
+        let user = crate::user_id(user);
+
+        self.db
+            .asyncify(move |c| {
+                let total = t::watch_time
+                    .select(t::seconds)
+                    .filter(t::channel.eq(&channel).and(t::user.eq(&user)))
+                    .first::<i64>(c)
+                    .optional()?
+                    .unwrap_or_default();
+
+                let stream = match &stream_id {
+                    Some(stream_id) => s::watch_time_sessions
+                        .select(s::seconds)
+                        .filter(
+                            s::channel
+                                .eq(&channel)
+                                .and(s::stream_id.eq(stream_id))
+                                .and(s::user.eq(&user)),
+                        )
+                        .first::<i64>(c)
+                        .optional()?
+                        .unwrap_or_default(),
+                    None => 0,
+                };
+
+                Ok(WatchTimeOf { total, stream })
+            })
+            .await
+    }
+
+    /// List the users with the most watch time, either in total or for the
+    /// stream identified by `stream_id`, skipping the first `offset` of them.
+    pub async fn top(
+        &self,
+        channel: &Channel,
+        stream_id: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<(String, i64)>> {
+        use self::schema::watch_time::dsl as t;
+        use self::schema::watch_time_sessions::dsl as s;
+
+        let channel = channel.to_owned();
+        let stream_id = stream_id.map(str::to_string);
+
+        self.db
+            .asyncify(move |c| {
+                let top = match &stream_id {
+                    Some(stream_id) => s::watch_time_sessions
+                        .select((s::user, s::seconds))
+                        .filter(s::channel.eq(&channel).and(s::stream_id.eq(stream_id)))
+                        .order((s::seconds.desc(), s::user.asc()))
+                        .limit(limit)
+                        .offset(offset)
+                        .load::<(String, i64)>(c)?,
+                    None => t::watch_time
+                        .select((t::user, t::seconds))
+                        .filter(t::channel.eq(&channel))
+                        .order((t::seconds.desc(), t::user.asc()))
+                        .limit(limit)
+                        .offset(offset)
+                        .load::<(String, i64)>(c)?,
+                };
+
+                Ok(top)
+            })
+            .await
+    }
+
+    /// Count the users with watch time, either in total or for the stream
+    /// identified by `stream_id`.
+    pub async fn count(&self, channel: &Channel, stream_id: Option<&str>) -> Result<i64> {
+        use self::schema::watch_time::dsl as t;
+        use self::schema::watch_time_sessions::dsl as s;
+
+        let channel = channel.to_owned();
+        let stream_id = stream_id.map(str::to_string);
+
+        self.db
+            .asyncify(move |c| {
+                let count = match &stream_id {
+                    Some(stream_id) => s::watch_time_sessions
+                        .filter(s::channel.eq(&channel).and(s::stream_id.eq(stream_id)))
+                        .count()
+                        .get_result::<i64>(c)?,
+                    None => t::watch_time
+                        .filter(t::channel.eq(&channel))
+                        .count()
+                        .get_result::<i64>(c)?,
+                };
+
+                Ok(count)
+            })
+            .await
+    }
+
+    /// Get all users with at least the given amount of total watch time.
+    pub async fn users_above(&self, channel: &Channel, seconds: i64) -> Result<HashSet<String>> {
+        use self::schema::watch_time::dsl;
+
+        let channel = channel.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                let users = dsl::watch_time
+                    .select(dsl::user)
+                    .filter(dsl::channel.eq(&channel).and(dsl::seconds.ge(seconds)))
+                    .load::<String>(c)?;
+
+                Ok(users.into_iter().collect())
+            })
+            .await
+    }
+
+    /// Get all users who haven't been seen for at least the given number of
+    /// seconds.
+    pub async fn inactive(&self, channel: &Channel, seconds: i64) -> Result<Vec<String>> {
+        use self::schema::watch_time::dsl;
+
+        let channel = channel.to_owned();
+        let since = (Utc::now() - chrono::Duration::seconds(seconds)).naive_utc();
+
+        self.db
+            .asyncify(move |c| {
+                let users = dsl::watch_time
+                    .select(dsl::user)
+                    .filter(dsl::channel.eq(&channel).and(dsl::last_seen.lt(since)))
+                    .load::<String>(c)?;
+
+                Ok(users)
+            })
+            .await
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{bail, Result};
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::WebToken;
+
+/// Tokens used to access the web API and overlays.
+#[derive(Clone)]
+pub struct WebTokens {
+    db: crate::Database,
+}
+
+impl WebTokens {
+    /// Open the web tokens database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// List all tokens, ordered by name.
+    pub async fn list(&self) -> Result<Vec<WebToken>> {
+        use self::schema::web_tokens::dsl;
+
+        self.db
+            .asyncify(move |c| Ok(dsl::web_tokens.order(dsl::name.asc()).load::<WebToken>(c)?))
+            .await
+    }
+
+    /// Find the token matching the given secret.
+    pub async fn find(&self, token: &str) -> Result<Option<WebToken>> {
+        use self::schema::web_tokens::dsl;
+
+        let token = token.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::web_tokens
+                    .filter(dsl::token.eq(&token))
+                    .first::<WebToken>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// Store a new token with the given name and access.
+    ///
+    /// Fails if a token with the same name already exists.
+    pub async fn create(&self, name: &str, token: &str, access: &str) -> Result<WebToken> {
+        use self::schema::web_tokens::dsl;
+
+        let name = name.to_lowercase();
+        let token = token.to_owned();
+        let access = access.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let existing = dsl::web_tokens
+                        .filter(dsl::name.eq(&name))
+                        .first::<WebToken>(c)
+                        .optional()?;
+
+                    if existing.is_some() {
+                        bail!("a token named `{}` already exists", name);
+                    }
+
+                    diesel::insert_into(dsl::web_tokens)
+                        .values((
+                            dsl::name.eq(&name),
+                            dsl::token.eq(&token),
+                            dsl::access.eq(&access),
+                        ))
+                        .execute(c)?;
+
+                    Ok(dsl::web_tokens
+                        .filter(dsl::name.eq(&name))
+                        .first::<WebToken>(c)?)
+                })
+            })
+            .await
+    }
+
+    /// Delete the token with the given name.
+    ///
+    /// Returns `true` if the token existed.
+    pub async fn delete(&self, name: &str) -> Result<bool> {
+        use self::schema::web_tokens::dsl;
+
+        let name = name.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                let count =
+                    diesel::delete(dsl::web_tokens.filter(dsl::name.eq(&name))).execute(c)?;
+                Ok(count == 1)
+            })
+            .await
+    }
+}
@@ -0,0 +1,292 @@
+use anyhow::{bail, Result};
+use diesel::prelude::*;
+
+use crate::models;
+use crate::schema;
+
+pub use self::models::{Webhook, WebhookDelivery};
+
+/// Maximum number of deliveries to keep in the delivery log.
+const DELIVERY_LOG_LIMIT: i64 = 1000;
+
+/// Editable fields of a webhook.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WebhookEdit {
+    pub url: String,
+    /// The secret to sign deliveries with, keeps the existing secret if
+    /// unspecified.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Events to deliver, all events if empty.
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// The outcome of delivering an event to a webhook.
+#[derive(Debug, Clone)]
+pub struct WebhookOutcome {
+    pub attempts: i32,
+    pub status: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl Webhook {
+    /// Iterate over the events this webhook is interested in.
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        self.events
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+    }
+
+    /// Test if the webhook should receive the given event.
+    pub fn accepts(&self, event: &str) -> bool {
+        let mut events = self.events().peekable();
+        events.peek().is_none() || events.any(|e| e == event)
+    }
+}
+
+/// Webhooks which receive bot events, and the log of deliveries made to them.
+#[derive(Clone)]
+pub struct Webhooks {
+    db: crate::Database,
+}
+
+impl Webhooks {
+    /// Open the webhooks database.
+    pub async fn load(db: crate::Database) -> Result<Self> {
+        Ok(Self { db })
+    }
+
+    /// List all webhooks, ordered by name.
+    pub async fn list(&self) -> Result<Vec<Webhook>> {
+        use self::schema::webhooks::dsl;
+
+        self.db
+            .asyncify(move |c| Ok(dsl::webhooks.order(dsl::name.asc()).load::<Webhook>(c)?))
+            .await
+    }
+
+    /// Get the webhook with the given name.
+    pub async fn get(&self, name: &str) -> Result<Option<Webhook>> {
+        use self::schema::webhooks::dsl;
+
+        let name = name.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::webhooks
+                    .filter(dsl::name.eq(&name))
+                    .first::<Webhook>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// Insert or update the webhook with the given name.
+    pub async fn edit(&self, name: &str, edit: WebhookEdit) -> Result<Webhook> {
+        use self::schema::webhooks::dsl;
+
+        let name = name.to_lowercase();
+        let events = edit.events.join(",");
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    let filter = dsl::webhooks.filter(dsl::name.eq(&name));
+
+                    match filter.first::<Webhook>(c).optional()? {
+                        Some(existing) => {
+                            let secret = edit.secret.unwrap_or(existing.secret);
+
+                            diesel::update(filter)
+                                .set((
+                                    dsl::url.eq(&edit.url),
+                                    dsl::secret.eq(&secret),
+                                    dsl::events.eq(&events),
+                                    dsl::enabled.eq(edit.enabled),
+                                ))
+                                .execute(c)?;
+                        }
+                        None => {
+                            let Some(secret) = edit.secret else {
+                                bail!("a new webhook requires a secret");
+                            };
+
+                            diesel::insert_into(dsl::webhooks)
+                                .values((
+                                    dsl::name.eq(&name),
+                                    dsl::url.eq(&edit.url),
+                                    dsl::secret.eq(&secret),
+                                    dsl::events.eq(&events),
+                                    dsl::enabled.eq(edit.enabled),
+                                ))
+                                .execute(c)?;
+                        }
+                    }
+
+                    Ok(filter.first::<Webhook>(c)?)
+                })
+            })
+            .await
+    }
+
+    /// Delete the webhook with the given name, including its delivery log.
+    ///
+    /// Returns `true` if the webhook existed.
+    pub async fn delete(&self, name: &str) -> Result<bool> {
+        use self::schema::webhook_deliveries::dsl as deliveries;
+        use self::schema::webhooks::dsl;
+
+        let name = name.to_lowercase();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    diesel::delete(
+                        deliveries::webhook_deliveries.filter(deliveries::webhook.eq(&name)),
+                    )
+                    .execute(c)?;
+
+                    let count =
+                        diesel::delete(dsl::webhooks.filter(dsl::name.eq(&name))).execute(c)?;
+                    Ok(count == 1)
+                })
+            })
+            .await
+    }
+
+    /// Record a delivery made to the given webhook.
+    ///
+    /// Only the most recent deliveries are kept.
+    pub async fn record(
+        &self,
+        webhook: &str,
+        event: &str,
+        payload: &str,
+        outcome: WebhookOutcome,
+    ) -> Result<()> {
+        use self::schema::webhook_deliveries::dsl;
+
+        let webhook = webhook.to_owned();
+        let event = event.to_owned();
+        let payload = payload.to_owned();
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction::<_, anyhow::Error, _>(move |c| {
+                    diesel::insert_into(dsl::webhook_deliveries)
+                        .values((
+                            dsl::webhook.eq(&webhook),
+                            dsl::event.eq(&event),
+                            dsl::payload.eq(&payload),
+                            dsl::attempts.eq(outcome.attempts),
+                            dsl::status.eq(outcome.status),
+                            dsl::error.eq(outcome.error),
+                        ))
+                        .execute(c)?;
+
+                    let oldest = dsl::webhook_deliveries
+                        .select(dsl::id)
+                        .order(dsl::id.desc())
+                        .offset(DELIVERY_LOG_LIMIT)
+                        .first::<i32>(c)
+                        .optional()?;
+
+                    if let Some(oldest) = oldest {
+                        diesel::delete(dsl::webhook_deliveries.filter(dsl::id.le(oldest)))
+                            .execute(c)?;
+                    }
+
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    /// List the most recent deliveries, optionally only for the given webhook,
+    /// skipping the first `offset` of them.
+    pub async fn deliveries(
+        &self,
+        webhook: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        use self::schema::webhook_deliveries::dsl;
+
+        let webhook = webhook.map(str::to_lowercase);
+
+        self.db
+            .asyncify(move |c| {
+                let mut query = dsl::webhook_deliveries.into_boxed();
+
+                if let Some(webhook) = webhook {
+                    query = query.filter(dsl::webhook.eq(webhook));
+                }
+
+                Ok(query
+                    .order(dsl::id.desc())
+                    .limit(limit)
+                    .offset(offset)
+                    .load::<WebhookDelivery>(c)?)
+            })
+            .await
+    }
+
+    /// Count the deliveries, optionally only for the given webhook.
+    pub async fn count_deliveries(&self, webhook: Option<&str>) -> Result<i64> {
+        use self::schema::webhook_deliveries::dsl;
+
+        let webhook = webhook.map(str::to_lowercase);
+
+        self.db
+            .asyncify(move |c| {
+                let mut query = dsl::webhook_deliveries.into_boxed();
+
+                if let Some(webhook) = webhook {
+                    query = query.filter(dsl::webhook.eq(webhook));
+                }
+
+                Ok(query.count().get_result::<i64>(c)?)
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Webhook;
+
+    fn webhook(events: &str) -> Webhook {
+        Webhook {
+            id: 1,
+            name: String::from("test"),
+            url: String::from("http://localhost"),
+            secret: String::from("secret"),
+            events: events.to_owned(),
+            enabled: true,
+            created_at: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_accepts() {
+        assert!(webhook("").accepts("song/current"));
+        assert!(webhook(" , ").accepts("song/current"));
+        assert!(webhook("song/current").accepts("song/current"));
+        assert!(webhook("stream/started, song/current").accepts("song/current"));
+        assert!(!webhook("stream/started").accepts("song/current"));
+        assert_eq!(
+            webhook("stream/started, ,moderation")
+                .events()
+                .collect::<Vec<_>>(),
+            vec!["stream/started", "moderation"]
+        );
+    }
+}
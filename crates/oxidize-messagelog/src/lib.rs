@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::{RwLock, RwLockReadGuard};
@@ -26,9 +27,9 @@ pub enum Event {
 
 impl bus::Message for Event {
     /// The ID of a bussed message.
-    fn id(&self) -> Option<&'static str> {
+    fn id(&self) -> Option<Cow<'static, str>> {
         match *self {
-            Event::Enabled { .. } => Some("enabled"),
+            Event::Enabled { .. } => Some(Cow::Borrowed("enabled")),
             _ => None,
         }
     }
@@ -208,10 +208,21 @@ impl PlayerInternal {
     async fn notify_song_change(&self, song: Option<&Song>) -> Result<()> {
         tracing::trace!("Notify song change");
         self.global_bus.send(bus::Global::song(song)?).await;
+        self.notify_queue().await;
         self.global_bus.send(bus::Global::SongModified).await;
         Ok(())
     }
 
+    /// Publish a snapshot of the songs queued up after the current song.
+    async fn notify_queue(&self) {
+        let queue = {
+            let queue = self.mixer.queue().await;
+            bus::Global::song_queue(queue.iter().map(|item| item.as_ref()))
+        };
+
+        self.global_bus.send(queue).await;
+    }
+
     /// Switch the current player and send the appropriate play commands.
     #[tracing::instrument(skip(self), fields(state = ?self.state()))]
     async fn switch_current_player(&self, player: PlayerKind) -> Result<()> {
@@ -560,6 +571,7 @@ impl PlayerInternal {
                 }
             }
 
+            self.notify_queue().await;
             self.global_bus.send(bus::Global::SongModified).await;
             self.bus.send_sync(Event::Modified);
         }
@@ -1,6 +1,6 @@
 //! Utilities for dealing with dynamic configuration and settings.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::future::Future;
 use std::marker;
@@ -87,10 +87,16 @@ pub enum Error {
     ),
     #[error("{0}")]
     BadTimeZone(String),
+    #[error("No such setting: {0}")]
+    NoSuchSetting(String),
+    #[error("Secret settings can't be stored in profiles: {0}")]
+    SecretInProfile(String),
 }
 
 /// Separator in configuration hierarchy.
 const SEP: char = '/';
+/// The number of changes kept in the settings history.
+const HISTORY_LIMIT: i32 = 1000;
 
 /// Indication that a value has been updated.
 type Update = Event<serde_json::Value>;
@@ -225,6 +231,124 @@ where
     }
 }
 
+/// A change to a single setting, as produced by an import.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingChange {
+    /// The key of the setting.
+    pub key: String,
+    /// The current value of the setting.
+    pub before: Option<serde_json::Value>,
+    /// The imported value of the setting, or `None` if it will be cleared.
+    pub after: Option<serde_json::Value>,
+}
+
+/// A setting which couldn't be imported.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportError {
+    /// The key of the setting.
+    pub key: String,
+    /// Why the setting couldn't be imported.
+    pub message: String,
+}
+
+/// The outcome of importing settings.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    /// If the changes were applied.
+    pub applied: bool,
+    /// Settings whose values differ from the imported ones.
+    pub changes: Vec<SettingChange>,
+    /// Settings which failed validation.
+    pub errors: Vec<ImportError>,
+}
+
+/// Where a change to a setting originated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Source {
+    /// Changed by the bot itself.
+    Bot,
+    /// Changed through a chat command.
+    Chat,
+    /// Changed through the web UI.
+    Web,
+    /// Changed by a settings migration.
+    Migration,
+    /// Changed by switching to a profile.
+    Profile,
+    /// Changed by importing settings.
+    Import,
+    /// Changed by undoing an earlier change.
+    Undo,
+}
+
+impl Source {
+    /// The stored representation of the source.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Bot => "bot",
+            Source::Chat => "chat",
+            Source::Web => "web",
+            Source::Migration => "migration",
+            Source::Profile => "profile",
+            Source::Import => "import",
+            Source::Undo => "undo",
+        }
+    }
+
+    /// Sources of changes which were made by a user and can be undone.
+    ///
+    /// Changes made by the bot itself, like updating the player volume, are
+    /// recorded but never undone.
+    const UNDOABLE: [Source; 4] = [Source::Chat, Source::Web, Source::Profile, Source::Import];
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// A recorded change to a single setting.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    /// The identifier of the change.
+    pub id: i32,
+    /// The key of the setting.
+    pub key: String,
+    /// The value before the change, or `None` if it wasn't set.
+    pub before: Option<serde_json::Value>,
+    /// The value after the change, or `None` if it was cleared.
+    pub after: Option<serde_json::Value>,
+    /// Where the change originated from.
+    pub source: String,
+    /// The user responsible for the change, if known.
+    pub user: Option<String>,
+    /// When the change happened.
+    pub changed_at: chrono::NaiveDateTime,
+    /// If the change has been undone.
+    pub undone: bool,
+}
+
+/// A named snapshot of a subset of settings.
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    /// The name of the profile.
+    pub name: String,
+    /// The settings stored in the profile.
+    pub settings: Vec<ProfileSetting>,
+}
+
+/// A single setting stored in a profile.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSetting {
+    /// The key of the setting.
+    pub key: String,
+    /// The value of the setting, or `None` if the setting should be cleared
+    /// when the profile is applied.
+    pub value: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchemaType<S> {
     /// Documentation for this type.
@@ -387,6 +511,10 @@ where
     S: Scope,
 {
     scope: Box<str>,
+    /// Where changes made through this handle originate from.
+    source: Source,
+    /// The user responsible for changes made through this handle.
+    user: Option<Box<str>>,
     inner: Arc<Inner<S>>,
 }
 
@@ -401,6 +529,8 @@ where
 
         Self {
             scope: Default::default(),
+            source: Source::Bot,
+            user: None,
             inner: Arc::new(Inner {
                 db,
                 subscriptions,
@@ -412,13 +542,33 @@ where
         }
     }
 
+    /// Attribute changes made through the returned handle to the given
+    /// source in the settings history.
+    pub fn with_source(&self, source: Source) -> Settings<S> {
+        Settings {
+            source,
+            ..self.clone()
+        }
+    }
+
+    /// Attribute changes made through the returned handle to the given user
+    /// in the settings history.
+    pub fn with_user(&self, user: &str) -> Settings<S> {
+        Settings {
+            user: Some(user.into()),
+            ..self.clone()
+        }
+    }
+
     /// Run all settings migrations.
     pub async fn run_migrations(&self) -> Result<(), Error> {
+        let this = self.with_source(Source::Migration);
+
         for m in &self.inner.schema.migrations {
             if m.prefix {
-                self.migrate_prefix(&m.from, &m.to).await?;
+                this.migrate_prefix(&m.from, &m.to).await?;
             } else {
-                self.migrate_exact(&m.from, &m.to).await?;
+                this.migrate_exact(&m.from, &m.to).await?;
             }
         }
 
@@ -625,34 +775,48 @@ where
         }
 
         let key = key.to_string();
+        let change = self.change(&key);
 
         let (key, value) = self
             .inner
             .db
             .asyncify(move |c| {
-                let filter = dsl::settings.filter(dsl::key.eq(&key));
+                c.transaction::<_, Error, _>(move |c| {
+                    let filter = dsl::settings.filter(dsl::key.eq(&key));
 
-                let b = filter
-                    .select((dsl::key, dsl::value))
-                    .first::<(String, String)>(c)
-                    .optional()?;
+                    let b = filter.select(dsl::value).first::<String>(c).optional()?;
 
-                let json = serde_json::to_string(&value)?;
+                    let json = serde_json::to_string(&value)?;
 
-                match b {
-                    None => {
-                        diesel::insert_into(dsl::settings)
-                            .values((dsl::key.eq(&key), dsl::value.eq(json)))
-                            .execute(c)?;
+                    match &b {
+                        None => {
+                            diesel::insert_into(dsl::settings)
+                                .values((dsl::key.eq(&key), dsl::value.eq(&json)))
+                                .execute(c)?;
+                        }
+                        Some(_) => {
+                            diesel::update(filter)
+                                .set((dsl::key.eq(&key), dsl::value.eq(&json)))
+                                .execute(c)?;
+                        }
                     }
-                    Some(_) => {
-                        diesel::update(filter)
-                            .set((dsl::key.eq(&key), dsl::value.eq(json)))
-                            .execute(c)?;
+
+                    if let Some(change) = change {
+                        let unchanged = match &b {
+                            Some(b) => {
+                                serde_json::from_str::<serde_json::Value>(b).ok().as_ref()
+                                    == Some(&value)
+                            }
+                            None => false,
+                        };
+
+                        if !unchanged {
+                            change.record(c, &key, b.as_deref(), Some(&json))?;
+                        }
                     }
-                }
 
-                Ok::<_, Error>((key, value))
+                    Ok((key, value))
+                })
             })
             .await?;
 
@@ -706,6 +870,421 @@ where
         self.inner_clear(&key).await
     }
 
+    /// List all profiles.
+    pub async fn profiles(&self) -> Result<Vec<Profile>, Error> {
+        use db::schema::settings_profiles::dsl;
+
+        let rows = self
+            .inner
+            .db
+            .asyncify(move |c| {
+                Ok::<_, Error>(
+                    dsl::settings_profiles
+                        .select((dsl::profile, dsl::key, dsl::value))
+                        .order((dsl::profile, dsl::key))
+                        .load::<(String, String, Option<String>)>(c)?,
+                )
+            })
+            .await?;
+
+        let mut profiles = Vec::<Profile>::new();
+
+        for (name, key, value) in rows {
+            let setting = ProfileSetting {
+                key,
+                value: value.map(|v| serde_json::from_str(&v)).transpose()?,
+            };
+
+            match profiles.last_mut() {
+                Some(profile) if profile.name == name => profile.settings.push(setting),
+                _ => profiles.push(Profile {
+                    name,
+                    settings: vec![setting],
+                }),
+            }
+        }
+
+        Ok(profiles)
+    }
+
+    /// Get the profile with the given name.
+    pub async fn profile(&self, name: &str) -> Result<Option<Profile>, Error> {
+        let name = name.to_lowercase();
+
+        Ok(self
+            .profiles()
+            .await?
+            .into_iter()
+            .find(|profile| profile.name == name))
+    }
+
+    /// Store a snapshot of the current value of the given settings as a
+    /// profile, replacing any existing profile with the same name.
+    ///
+    /// Keys can either be full settings keys or prefixes, in which case every
+    /// setting under the prefix is stored.
+    pub async fn profile_save(&self, name: &str, keys: &[String]) -> Result<Profile, Error> {
+        use db::schema::settings::dsl as s;
+        use db::schema::settings_profiles::dsl as p;
+
+        let mut expanded = Vec::new();
+
+        for key in keys {
+            let key = self.inner_key(key);
+
+            let prefix = match self.inner.prefixes.get(&*key) {
+                Some(prefix) => prefix,
+                None => return Err(Error::NoSuchSetting(key.to_string())),
+            };
+
+            for key in &prefix.keys {
+                let secret = match self.inner.schema.types.get(key) {
+                    Some(schema) => is_secret(key, schema),
+                    None => continue,
+                };
+
+                if secret {
+                    return Err(Error::SecretInProfile(key.clone()));
+                }
+
+                if !expanded.contains(key) {
+                    expanded.push(key.clone());
+                }
+            }
+        }
+
+        expanded.sort();
+
+        let name = name.to_lowercase();
+        let profile_name = name.clone();
+
+        let settings = self
+            .inner
+            .db
+            .asyncify(move |c| {
+                c.transaction::<_, Error, _>(move |c| {
+                    let values = s::settings
+                        .select((s::key, s::value))
+                        .filter(s::key.eq_any(&expanded))
+                        .load::<(String, String)>(c)?
+                        .into_iter()
+                        .collect::<HashMap<_, _>>();
+
+                    diesel::delete(p::settings_profiles.filter(p::profile.eq(&name))).execute(c)?;
+
+                    let mut settings = Vec::with_capacity(expanded.len());
+
+                    for key in expanded {
+                        let value = values.get(&key).cloned();
+
+                        diesel::insert_into(p::settings_profiles)
+                            .values((p::profile.eq(&name), p::key.eq(&key), p::value.eq(&value)))
+                            .execute(c)?;
+
+                        settings.push(ProfileSetting {
+                            key,
+                            value: value.map(|v| serde_json::from_str(&v)).transpose()?,
+                        });
+                    }
+
+                    Ok(settings)
+                })
+            })
+            .await?;
+
+        Ok(Profile {
+            name: profile_name,
+            settings,
+        })
+    }
+
+    /// Apply the profile with the given name, notifying every subscriber of
+    /// the settings it contains.
+    ///
+    /// Returns `false` if there is no such profile.
+    pub async fn profile_apply(&self, name: &str) -> Result<bool, Error> {
+        let profile = match self.profile(name).await? {
+            Some(profile) => profile,
+            None => return Ok(false),
+        };
+
+        let this = self.with_source(Source::Profile);
+
+        for setting in profile.settings {
+            if !self.inner.schema.types.contains_key(&setting.key) {
+                tracing::warn!(
+                    "Ignoring setting without schema in profile {}: {}",
+                    profile.name,
+                    setting.key
+                );
+                continue;
+            }
+
+            match setting.value {
+                Some(value) => this.inner_set_json(&setting.key, value, true).await?,
+                None => {
+                    this.inner_clear(&setting.key).await?;
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Delete the profile with the given name. Returning `true` if it was
+    /// removed.
+    pub async fn profile_delete(&self, name: &str) -> Result<bool, Error> {
+        use db::schema::settings_profiles::dsl;
+
+        let name = name.to_lowercase();
+
+        self.inner
+            .db
+            .asyncify(move |c| {
+                let count = diesel::delete(dsl::settings_profiles.filter(dsl::profile.eq(name)))
+                    .execute(c)?;
+                Ok::<_, Error>(count > 0)
+            })
+            .await
+    }
+
+    /// Export the value of every setting which has been set and isn't
+    /// secret.
+    pub async fn export(&self) -> Result<BTreeMap<String, serde_json::Value>, Error> {
+        let values = self.raw_values().await?;
+        let mut out = BTreeMap::new();
+
+        for (key, value) in values {
+            match self.inner.schema.types.get(&key) {
+                Some(schema) if !is_secret(&key, schema) => {
+                    out.insert(key, serde_json::from_str(&value)?);
+                }
+                _ => continue,
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Import settings, validating each value against the schema.
+    ///
+    /// Settings which aren't part of the import are left untouched, and a
+    /// `null` value clears the setting. Nothing is applied if `dry_run` is
+    /// set or if any of the settings fail validation.
+    pub async fn import(
+        &self,
+        values: BTreeMap<String, serde_json::Value>,
+        dry_run: bool,
+    ) -> Result<ImportReport, Error> {
+        let current = self.raw_values().await?;
+        let mut report = ImportReport::default();
+
+        for (key, value) in values {
+            let schema = match self.inner.schema.types.get(&key) {
+                Some(schema) => schema,
+                None => {
+                    report.errors.push(ImportError {
+                        key,
+                        message: String::from("no such setting"),
+                    });
+                    continue;
+                }
+            };
+
+            if is_secret(&key, schema) {
+                report.errors.push(ImportError {
+                    key,
+                    message: String::from("secret settings can't be imported"),
+                });
+                continue;
+            }
+
+            if !schema.ty.is_compatible_with_json(&value) {
+                report.errors.push(ImportError {
+                    key,
+                    message: format!("expected {}", schema.ty),
+                });
+                continue;
+            }
+
+            let before = current
+                .get(&key)
+                .map(|v| serde_json::from_str::<serde_json::Value>(v))
+                .transpose()?;
+
+            let after = match value {
+                serde_json::Value::Null => None,
+                value => Some(value),
+            };
+
+            if before != after {
+                report.changes.push(SettingChange { key, before, after });
+            }
+        }
+
+        if dry_run || !report.errors.is_empty() {
+            return Ok(report);
+        }
+
+        let this = self.with_source(Source::Import);
+
+        for change in &report.changes {
+            match &change.after {
+                Some(value) => {
+                    this.inner_set_json(&change.key, value.clone(), true)
+                        .await?
+                }
+                None => {
+                    this.inner_clear(&change.key).await?;
+                }
+            }
+        }
+
+        report.applied = true;
+        Ok(report)
+    }
+
+    /// Load the raw value of every stored setting.
+    async fn raw_values(&self) -> Result<HashMap<String, String>, Error> {
+        use db::schema::settings::dsl;
+
+        self.inner
+            .db
+            .asyncify(move |c| {
+                Ok::<_, Error>(
+                    dsl::settings
+                        .select((dsl::key, dsl::value))
+                        .load::<(String, String)>(c)?
+                        .into_iter()
+                        .collect::<HashMap<_, _>>(),
+                )
+            })
+            .await
+    }
+
+    /// List the most recent changes to settings, newest first, skipping the
+    /// first `offset` of them.
+    pub async fn history(&self, limit: usize, offset: usize) -> Result<Vec<HistoryEntry>, Error> {
+        self.load_history(limit, offset, false).await
+    }
+
+    /// Count the changes recorded in the settings history.
+    pub async fn history_count(&self) -> Result<i64, Error> {
+        use db::schema::settings_history::dsl;
+
+        self.inner
+            .db
+            .asyncify(move |c| Ok::<_, Error>(dsl::settings_history.count().get_result::<i64>(c)?))
+            .await
+    }
+
+    /// Undo the most recent `count` changes which haven't already been
+    /// undone, newest first.
+    ///
+    /// Only changes made by a user are undone. Changes made by undoing are
+    /// recorded in the history, but are never themselves undone. So undoing
+    /// repeatedly walks further back in history. Returns the changes which
+    /// were undone.
+    pub async fn undo(&self, count: usize) -> Result<Vec<HistoryEntry>, Error> {
+        use db::schema::settings_history::dsl;
+
+        let entries = self.load_history(count, 0, true).await?;
+        let this = self.with_source(Source::Undo);
+
+        for entry in &entries {
+            match &entry.before {
+                Some(value) => this.inner_set_json(&entry.key, value.clone(), true).await?,
+                None => {
+                    this.inner_clear(&entry.key).await?;
+                }
+            }
+
+            let id = entry.id;
+
+            self.inner
+                .db
+                .asyncify(move |c| {
+                    diesel::update(dsl::settings_history.filter(dsl::id.eq(id)))
+                        .set(dsl::undone.eq(true))
+                        .execute(c)?;
+                    Ok::<_, Error>(())
+                })
+                .await?;
+        }
+
+        Ok(entries)
+    }
+
+    /// Load entries from the settings history, newest first.
+    ///
+    /// If `undoable` is set, only changes which can be undone are loaded.
+    async fn load_history(
+        &self,
+        limit: usize,
+        offset: usize,
+        undoable: bool,
+    ) -> Result<Vec<HistoryEntry>, Error> {
+        use db::schema::settings_history::dsl;
+
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let offset = i64::try_from(offset).unwrap_or(i64::MAX);
+
+        let rows = self
+            .inner
+            .db
+            .asyncify(move |c| {
+                let mut query = dsl::settings_history
+                    .select((
+                        dsl::id,
+                        dsl::key,
+                        dsl::old_value,
+                        dsl::new_value,
+                        dsl::source,
+                        dsl::user,
+                        dsl::changed_at,
+                        dsl::undone,
+                    ))
+                    .order(dsl::id.desc())
+                    .limit(limit)
+                    .offset(offset)
+                    .into_boxed();
+
+                if undoable {
+                    let sources = Source::UNDOABLE.map(|s| s.as_str());
+                    query = query.filter(dsl::undone.eq(false).and(dsl::source.eq_any(sources)));
+                }
+
+                Ok::<_, Error>(query.load::<(
+                    i32,
+                    String,
+                    Option<String>,
+                    Option<String>,
+                    String,
+                    Option<String>,
+                    chrono::NaiveDateTime,
+                    bool,
+                )>(c)?)
+            })
+            .await?;
+
+        let mut entries = Vec::with_capacity(rows.len());
+
+        for (id, key, before, after, source, user, changed_at, undone) in rows {
+            entries.push(HistoryEntry {
+                id,
+                key,
+                before: before.map(|v| serde_json::from_str(&v)).transpose()?,
+                after: after.map(|v| serde_json::from_str(&v)).transpose()?,
+                source,
+                user,
+                changed_at,
+                undone,
+            });
+        }
+
+        Ok(entries)
+    }
+
     /// Create a scoped setting.
     pub fn scoped(&self, s: &str) -> Settings<S> {
         let mut it = s.split('/').filter(|s| !s.is_empty());
@@ -742,6 +1321,8 @@ where
 
         Settings {
             scope: scope.into(),
+            source: self.source,
+            user: self.user.clone(),
             inner: self.inner.clone(),
         }
     }
@@ -845,18 +1426,52 @@ where
         use db::schema::settings::dsl;
 
         let key = key.to_string();
+        let change = self.change(&key);
 
         self.try_send(&key, Event::Clear).await;
 
         self.inner
             .db
             .asyncify(move |c| {
-                let count = diesel::delete(dsl::settings.filter(dsl::key.eq(key))).execute(c)?;
-                Ok(count == 1)
+                c.transaction::<_, Error, _>(move |c| {
+                    let filter = dsl::settings.filter(dsl::key.eq(&key));
+
+                    let b = filter.select(dsl::value).first::<String>(c).optional()?;
+
+                    let Some(b) = b else {
+                        return Ok(false);
+                    };
+
+                    diesel::delete(filter).execute(c)?;
+
+                    if let Some(change) = change {
+                        change.record(c, &key, Some(&b), None)?;
+                    }
+
+                    Ok(true)
+                })
             })
             .await
     }
 
+    /// Construct the record of a change to the given key, or `None` if
+    /// changes to it shouldn't be recorded in the history.
+    fn change(&self, key: &str) -> Option<Change> {
+        let secret = match self.inner.schema.types.get(key) {
+            Some(schema) => is_secret(key, schema),
+            None => key.starts_with("secrets/"),
+        };
+
+        if secret {
+            return None;
+        }
+
+        Some(Change {
+            source: self.source,
+            user: self.user.clone(),
+        })
+    }
+
     /// Get the value of the given key from the database.
     async fn inner_get<T>(&self, key: &str) -> Result<Option<T>, Error>
     where
@@ -973,6 +1588,83 @@ where
     }
 }
 
+/// The origin of a change which is about to be recorded in the history.
+struct Change {
+    source: Source,
+    user: Option<Box<str>>,
+}
+
+impl Change {
+    /// Record a change to the given key, pruning the oldest changes once the
+    /// history grows past [HISTORY_LIMIT].
+    ///
+    /// Changes which can be undone are pruned separately from other changes,
+    /// so that frequent changes made by the bot don't push them out.
+    fn record(
+        &self,
+        c: &mut diesel::SqliteConnection,
+        key: &str,
+        old_value: Option<&str>,
+        new_value: Option<&str>,
+    ) -> Result<(), Error> {
+        use db::schema::settings_history::dsl;
+
+        diesel::insert_into(dsl::settings_history)
+            .values((
+                dsl::key.eq(key),
+                dsl::old_value.eq(old_value),
+                dsl::new_value.eq(new_value),
+                dsl::source.eq(self.source.as_str()),
+                dsl::user.eq(self.user.as_deref()),
+            ))
+            .execute(c)?;
+
+        let sources = Source::UNDOABLE.map(|s| s.as_str());
+        let offset = i64::from(HISTORY_LIMIT);
+
+        if Source::UNDOABLE.contains(&self.source) {
+            let oldest = dsl::settings_history
+                .select(dsl::id)
+                .filter(dsl::source.eq_any(sources))
+                .order(dsl::id.desc())
+                .offset(offset)
+                .first::<i32>(c)
+                .optional()?;
+
+            if let Some(oldest) = oldest {
+                diesel::delete(
+                    dsl::settings_history
+                        .filter(dsl::id.le(oldest).and(dsl::source.eq_any(sources))),
+                )
+                .execute(c)?;
+            }
+        } else {
+            let oldest = dsl::settings_history
+                .select(dsl::id)
+                .filter(dsl::source.ne_all(sources))
+                .order(dsl::id.desc())
+                .offset(offset)
+                .first::<i32>(c)
+                .optional()?;
+
+            if let Some(oldest) = oldest {
+                diesel::delete(
+                    dsl::settings_history
+                        .filter(dsl::id.le(oldest).and(dsl::source.ne_all(sources))),
+                )
+                .execute(c)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Test if the given setting must never leave the bot.
+fn is_secret<S>(key: &str, schema: &SchemaType<S>) -> bool {
+    schema.is_secret() || key.starts_with("secrets/")
+}
+
 /// Internal key holder, reduces the number of copies necessary when there's no
 /// key specified or we can rely solely on scope.
 #[derive(Clone)]
@@ -10,10 +10,83 @@ lazy_static::lazy_static! {
     static ref REGISTRY: handlebars::Handlebars<'static> = {
         let mut reg = handlebars::Handlebars::new();
         reg.register_escape_fn(|s| s.to_string());
+        reg.register_helper("counter", Box::new(counter_helper));
+        reg.register_helper("watch_time", Box::new(watch_time_helper));
         reg
     };
 }
 
+/// Helper to access named counters, like `{{counter "deaths"}}`.
+///
+/// Counters are looked up in the `counters` object of the data being
+/// rendered. By default the all-time value is used, but the per-stream value
+/// can be accessed with `{{counter "deaths" "stream"}}`.
+fn counter_helper(
+    h: &handlebars::Helper<'_, '_>,
+    _: &handlebars::Handlebars<'_>,
+    ctx: &handlebars::Context,
+    _: &mut handlebars::RenderContext<'_, '_>,
+    out: &mut dyn handlebars::Output,
+) -> handlebars::HelperResult {
+    let name = h
+        .param(0)
+        .and_then(|p| p.value().as_str())
+        .ok_or_else(|| handlebars::RenderError::new("counter: expected name of counter"))?;
+
+    let field = match h.param(1).and_then(|p| p.value().as_str()) {
+        None | Some("total") => "total",
+        Some("stream") => "stream",
+        Some(other) => {
+            return Err(handlebars::RenderError::new(format!(
+                "counter: expected `total` or `stream`, but got `{other}`"
+            )));
+        }
+    };
+
+    let value = ctx
+        .data()
+        .get("counters")
+        .and_then(|c| c.get(name))
+        .and_then(|c| c.get(field))
+        .and_then(|v| v.as_i64())
+        .unwrap_or_default();
+
+    out.write(&value.to_string())?;
+    Ok(())
+}
+
+/// Helper to access the watch time of the current user, like `{{watch_time}}`.
+///
+/// Watch time is looked up in the `watch_time` object of the data being
+/// rendered. By default the total is used, but `{{watch_time "stream"}}` gives
+/// the watch time during the current stream. Appending `_seconds` to either
+/// gives the raw number of seconds.
+fn watch_time_helper(
+    h: &handlebars::Helper<'_, '_>,
+    _: &handlebars::Handlebars<'_>,
+    ctx: &handlebars::Context,
+    _: &mut handlebars::RenderContext<'_, '_>,
+    out: &mut dyn handlebars::Output,
+) -> handlebars::HelperResult {
+    let field = match h.param(0).and_then(|p| p.value().as_str()) {
+        None => "total",
+        Some(field @ ("total" | "stream" | "total_seconds" | "stream_seconds")) => field,
+        Some(other) => {
+            return Err(handlebars::RenderError::new(format!(
+                "watch_time: expected `total`, `stream`, `total_seconds`, or `stream_seconds`, but got `{other}`"
+            )));
+        }
+    };
+
+    match ctx.data().get("watch_time").and_then(|w| w.get(field)) {
+        Some(handlebars::JsonValue::String(s)) => out.write(s)?,
+        Some(value) => out.write(&value.to_string())?,
+        None => (),
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct Template {
     source: String,
@@ -259,4 +332,39 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    pub(crate) fn test_counter_helper() -> Result<(), Error> {
+        let data = serde_json::json!({
+            "counters": {
+                "deaths": {"total": 42, "stream": 3},
+            },
+        });
+
+        let template = Template::compile(
+            "{{counter \"deaths\"}} {{counter \"deaths\" \"stream\"}} {{counter \"wins\"}}",
+        )?;
+        assert_eq!("42 3 0", template.render_to_string(&data)?);
+        assert!(template.vars().contains("counter"));
+        Ok(())
+    }
+
+    #[test]
+    pub(crate) fn test_watch_time_helper() -> Result<(), Error> {
+        let data = serde_json::json!({
+            "watch_time": {
+                "total": "1h 2m",
+                "stream": "5m",
+                "total_seconds": 3720,
+                "stream_seconds": 300,
+            },
+        });
+
+        let template = Template::compile(
+            "{{watch_time}} {{watch_time \"stream\"}} {{watch_time \"stream_seconds\"}}",
+        )?;
+        assert_eq!("1h 2m 5m 300", template.render_to_string(&data)?);
+        assert!(template.vars().contains("watch_time"));
+        Ok(())
+    }
 }
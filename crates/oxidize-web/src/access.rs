@@ -0,0 +1,136 @@
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+use warp::{filters, Filter};
+
+use crate::WebError;
+
+/// Access granted by a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Access {
+    /// Read-only access, like the one used by overlays.
+    Read,
+    /// Full access to the web API.
+    Admin,
+}
+
+impl Access {
+    /// Parse the stored representation of an access level.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s {
+            "read" => Some(Access::Read),
+            "admin" => Some(Access::Admin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// Guards access to the web API and websockets.
+#[derive(Clone)]
+pub(crate) struct Guard {
+    enabled: ::settings::Var<bool>,
+    allow_local: ::settings::Var<bool>,
+    tokens: async_injector::Ref<db::WebTokens>,
+}
+
+impl Guard {
+    /// Construct a new guard from the given settings.
+    pub(crate) async fn new(
+        settings: ::settings::Settings<::auth::Scope>,
+        tokens: async_injector::Ref<db::WebTokens>,
+    ) -> Result<Self, ::settings::Error> {
+        Ok(Self {
+            enabled: settings.var("enabled", false).await?,
+            allow_local: settings.var("allow-local", false).await?,
+            tokens,
+        })
+    }
+
+    /// Filter which only passes requests with at least the given access.
+    ///
+    /// Tokens are accepted either as a bearer token in the `Authorization`
+    /// header, or through the `token` query parameter since that's the only
+    /// option available to websockets and links.
+    pub(crate) fn require(&self, access: Access) -> filters::BoxedFilter<()> {
+        let guard = self.clone();
+
+        warp::addr::remote()
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::query::<TokenQuery>())
+            .and_then(
+                move |addr: Option<SocketAddr>, header: Option<String>, query: TokenQuery| {
+                    let guard = guard.clone();
+
+                    async move {
+                        let token = header
+                            .as_deref()
+                            .and_then(|h| h.strip_prefix("Bearer "))
+                            .map(|t| t.trim().to_owned())
+                            .or(query.token);
+
+                        guard
+                            .check(access, addr, token)
+                            .await
+                            .map_err(warp::reject::custom)
+                    }
+                },
+            )
+            .untuple_one()
+            .boxed()
+    }
+
+    /// Check that a request is permitted the given access.
+    async fn check(
+        &self,
+        access: Access,
+        addr: Option<SocketAddr>,
+        token: Option<String>,
+    ) -> Result<(), WebError> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        if self.allow_local.load().await && addr.map_or(false, |a| a.ip().is_loopback()) {
+            return Ok(());
+        }
+
+        let Some(token) = token else {
+            return Err(WebError::Unauthorized);
+        };
+
+        let tokens = self.tokens.read().await;
+
+        let Some(tokens) = tokens.as_deref() else {
+            return Err(WebError::Unauthorized);
+        };
+
+        let Some(found) = tokens.find(&token).await? else {
+            return Err(WebError::Unauthorized);
+        };
+
+        match Access::parse(&found.access) {
+            Some(granted) if granted >= access => Ok(()),
+            _ => Err(WebError::Forbidden),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Access;
+
+    #[test]
+    fn test_access() {
+        assert_eq!(Access::parse("read"), Some(Access::Read));
+        assert_eq!(Access::parse("admin"), Some(Access::Admin));
+        assert_eq!(Access::parse("owner"), None);
+        assert!(Access::Admin >= Access::Read);
+        assert!(Access::Read < Access::Admin);
+    }
+}
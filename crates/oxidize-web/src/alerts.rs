@@ -0,0 +1,73 @@
+//! Images and sounds used by the alerts overlay.
+
+use std::path::{Component, Path, PathBuf};
+
+use warp::{filters, path, Filter};
+
+/// Set up the route serving files from the configured assets directory.
+pub(crate) fn route(
+    assets_dir: ::settings::Var<Option<PathBuf>>,
+) -> filters::BoxedFilter<(impl warp::Reply,)> {
+    path::param::<String>()
+        .and(path::end())
+        .and_then(move |name: String| {
+            let assets_dir = assets_dir.clone();
+
+            async move {
+                let Some(dir) = assets_dir.load().await else {
+                    return Err(warp::reject::not_found());
+                };
+
+                let Some(path) = asset_path(&dir, &name) else {
+                    return Err(warp::reject::not_found());
+                };
+
+                let data = match tokio::fs::read(&path).await {
+                    Ok(data) => data,
+                    Err(e) => {
+                        tracing::trace!(path = ?path, "Failed to read asset: {}", e);
+                        return Err(warp::reject::not_found());
+                    }
+                };
+
+                let mime = mime_guess::from_path(&path).first_or_octet_stream();
+
+                let res = warp::http::Response::builder()
+                    .header("content-type", mime.to_string())
+                    .body(data);
+
+                Ok(res)
+            }
+        })
+        .boxed()
+}
+
+/// Resolve the path of an asset, making sure that it refers to a file
+/// directly inside of the assets directory.
+fn asset_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    let mut components = Path::new(name).components();
+
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) => Some(dir.join(name)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::asset_path;
+
+    #[test]
+    fn test_asset_path() {
+        let dir = Path::new("assets");
+
+        assert_eq!(asset_path(dir, "raid.gif"), Some(dir.join("raid.gif")));
+        assert_eq!(asset_path(dir, ""), None);
+        assert_eq!(asset_path(dir, ".."), None);
+        assert_eq!(asset_path(dir, "../secret"), None);
+        assert_eq!(asset_path(dir, "sounds/raid.mp3"), None);
+        assert_eq!(asset_path(dir, "/etc/passwd"), None);
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::{body, filters, path, Filter};
+
+use crate::Fragment;
+
+/// How imported balances are combined with existing ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Strategy {
+    /// Replace the existing value.
+    #[default]
+    Replace,
+    /// Add to the existing value.
+    Add,
+    /// Keep whichever value is the largest.
+    Max,
+}
+
+impl Strategy {
+    /// Combine an existing value with an imported one.
+    fn merge(self, existing: i64, imported: i64) -> i64 {
+        match self {
+            Strategy::Replace => imported,
+            Strategy::Add => existing.saturating_add(imported),
+            Strategy::Max => existing.max(imported),
+        }
+    }
+}
+
+/// The unit that the watch time column is expressed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Unit {
+    #[default]
+    Seconds,
+    Minutes,
+    Hours,
+}
+
+impl Unit {
+    /// Convert a value in this unit into seconds.
+    fn to_seconds(self, value: f64) -> i64 {
+        let factor = match self {
+            Unit::Seconds => 1.0,
+            Unit::Minutes => 60.0,
+            Unit::Hours => 3600.0,
+        };
+
+        (value * factor) as i64
+    }
+}
+
+/// Which CSV columns hold which piece of data.
+#[derive(Debug, serde::Deserialize)]
+struct Mapping {
+    #[serde(default = "default_user_column")]
+    user: String,
+    #[serde(default = "default_balance_column")]
+    balance: String,
+    #[serde(default)]
+    watch_time: Option<String>,
+}
+
+impl Default for Mapping {
+    fn default() -> Self {
+        Self {
+            user: default_user_column(),
+            balance: default_balance_column(),
+            watch_time: None,
+        }
+    }
+}
+
+fn default_user_column() -> String {
+    String::from("user")
+}
+
+fn default_balance_column() -> String {
+    String::from("balance")
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ImportCsv {
+    /// The raw CSV data, including a header row.
+    data: String,
+    #[serde(default)]
+    mapping: Mapping,
+    #[serde(default)]
+    strategy: Strategy,
+    #[serde(default)]
+    delimiter: Option<char>,
+    #[serde(default)]
+    watch_time_unit: Unit,
+    /// Only report what would be imported.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// A single successfully parsed row.
+#[derive(Debug, PartialEq)]
+struct Row {
+    line: u64,
+    user: String,
+    amount: i64,
+    watch_time: Option<i64>,
+}
+
+/// A row which failed to parse.
+#[derive(Debug, PartialEq, serde::Serialize)]
+struct RowError {
+    line: u64,
+    message: String,
+}
+
+/// The outcome of importing a single user.
+#[derive(serde::Serialize)]
+struct Change {
+    user: String,
+    /// Balance before the import, if the user had one.
+    before: Option<i64>,
+    amount: i64,
+    watch_time: i64,
+}
+
+#[derive(serde::Serialize)]
+struct Report {
+    dry_run: bool,
+    changes: Vec<Change>,
+    errors: Vec<RowError>,
+}
+
+/// Endpoint for importing balances exported by other bots.
+#[derive(Clone)]
+pub(crate) struct BalanceImport(async_injector::Ref<currency::Currency>);
+
+impl BalanceImport {
+    pub(crate) fn route(
+        currency: async_injector::Ref<currency::Currency>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = BalanceImport(currency);
+
+        let import = warp::post()
+            .and(path!("currency" / Fragment / "import" / "csv").and(path::end()))
+            .and(body::json())
+            .and_then({
+                move |channel: Fragment, body: ImportCsv| {
+                    let api = api.clone();
+                    async move {
+                        api.import_csv(channel.as_channel(), body)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        import.boxed()
+    }
+
+    /// Access underlying currency.
+    async fn currency(&self) -> Result<RwLockReadGuard<'_, currency::Currency>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("currency not configured"),
+        }
+    }
+
+    /// Import balances from CSV.
+    async fn import_csv(&self, channel: &Channel, body: ImportCsv) -> Result<impl warp::Reply> {
+        let delimiter = match body.delimiter {
+            Some(c) if c.is_ascii() => c as u8,
+            Some(c) => bail!("unsupported delimiter `{}`", c),
+            None => b',',
+        };
+
+        let (rows, errors) = parse(&body.data, delimiter, &body.mapping, body.watch_time_unit)?;
+
+        let currency = self.currency().await?;
+
+        let mut changes = Vec::with_capacity(rows.len());
+        let mut balances = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            // Look up each balance individually, since not all backends
+            // export balances under the channel they belong to.
+            let before = currency.balance_of(channel, &row.user).await?;
+
+            let amount = body.strategy.merge(
+                before.as_ref().map(|b| b.balance).unwrap_or_default(),
+                row.amount,
+            );
+
+            let existing_watch_time = before
+                .as_ref()
+                .map(|b| b.watch_time().num_seconds() as i64)
+                .unwrap_or_default();
+
+            let watch_time = match row.watch_time {
+                Some(watch_time) => body.strategy.merge(existing_watch_time, watch_time),
+                None => existing_watch_time,
+            };
+
+            changes.push(Change {
+                user: row.user.clone(),
+                before: before.map(|b| b.balance),
+                amount,
+                watch_time,
+            });
+
+            balances.push(db::models::Balance {
+                channel: channel.to_owned(),
+                user: row.user,
+                amount,
+                watch_time,
+            });
+        }
+
+        if !body.dry_run {
+            currency.import_balances(balances).await?;
+        }
+
+        Ok(warp::reply::json(&Report {
+            dry_run: body.dry_run,
+            changes,
+            errors,
+        }))
+    }
+}
+
+/// Parse the given CSV data into rows, collecting errors for rows which could
+/// not be parsed.
+///
+/// Errors in the header row fail the whole import.
+fn parse(
+    data: &str,
+    delimiter: u8,
+    mapping: &Mapping,
+    unit: Unit,
+) -> Result<(Vec<Row>, Vec<RowError>)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data.as_bytes());
+
+    let headers = reader.headers()?.clone();
+
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case(name.trim()))
+    };
+
+    let Some(user_column) = column(&mapping.user) else {
+        bail!("missing user column `{}`", mapping.user);
+    };
+
+    let Some(balance_column) = column(&mapping.balance) else {
+        bail!("missing balance column `{}`", mapping.balance);
+    };
+
+    let watch_time_column = match &mapping.watch_time {
+        Some(name) => match column(name) {
+            Some(index) => Some(index),
+            None => bail!("missing watch time column `{}`", name),
+        },
+        None => None,
+    };
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen = HashMap::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or_default();
+
+                errors.push(RowError {
+                    line,
+                    message: e.to_string(),
+                });
+
+                continue;
+            }
+        };
+
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+
+        let row = (|| {
+            let user = match record.get(user_column) {
+                Some(user) if !user.is_empty() => db::user_id(user),
+                _ => return Err(String::from("missing user")),
+            };
+
+            if let Some(first) = seen.get(&user) {
+                return Err(format!(
+                    "duplicate user `{}`, first seen on line {}",
+                    user, first
+                ));
+            }
+
+            let amount = match record.get(balance_column) {
+                Some(amount) => match parse_number(amount) {
+                    Some(amount) => amount as i64,
+                    None => return Err(format!("bad balance `{}`", amount)),
+                },
+                None => return Err(String::from("missing balance")),
+            };
+
+            let watch_time = match watch_time_column.map(|index| record.get(index)) {
+                Some(Some("")) | Some(None) => Some(0),
+                Some(Some(watch_time)) => match parse_number(watch_time) {
+                    Some(watch_time) => Some(unit.to_seconds(watch_time)),
+                    None => return Err(format!("bad watch time `{}`", watch_time)),
+                },
+                None => None,
+            };
+
+            Ok(Row {
+                line,
+                user,
+                amount,
+                watch_time,
+            })
+        })();
+
+        match row {
+            Ok(row) => {
+                seen.insert(row.user.clone(), row.line);
+                rows.push(row);
+            }
+            Err(message) => {
+                errors.push(RowError { line, message });
+            }
+        }
+    }
+
+    Ok((rows, errors))
+}
+
+/// Parse a number, permitting thousands separators and fractions.
+///
+/// Commas are only accepted between groups of three digits, since in
+/// something like `1,5` the comma is more likely a decimal separator.
+fn parse_number(s: &str) -> Option<f64> {
+    let s = s.replace(['_', ' '], "");
+
+    let (integer, fraction) = match s.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (s.as_str(), None),
+    };
+
+    let mut groups = integer.split(',');
+    let mut number = groups.next()?.to_owned();
+
+    if integer.contains(',') {
+        let digits = number.trim_start_matches(['-', '+']).len();
+
+        if !(1..=3).contains(&digits) {
+            return None;
+        }
+
+        for group in groups {
+            if group.len() != 3 || !group.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+
+            number.push_str(group);
+        }
+    }
+
+    if let Some(fraction) = fraction {
+        number.push('.');
+        number.push_str(fraction);
+    }
+
+    match number.parse::<f64>() {
+        Ok(value) if value.is_finite() => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, parse_number, Mapping, Row, RowError, Strategy, Unit};
+
+    #[test]
+    fn test_parse() {
+        let data = "Username;Points;Hours\n@Foo;1,000;1.5\nbar;abc;2\nbaz;10\nfoo;5;0\n";
+
+        let mapping = Mapping {
+            user: String::from("username"),
+            balance: String::from("points"),
+            watch_time: Some(String::from("hours")),
+        };
+
+        let (rows, errors) = parse(data, b';', &mapping, Unit::Hours).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                Row {
+                    line: 2,
+                    user: String::from("foo"),
+                    amount: 1000,
+                    watch_time: Some(5400),
+                },
+                Row {
+                    line: 4,
+                    user: String::from("baz"),
+                    amount: 10,
+                    watch_time: Some(0),
+                },
+            ]
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                RowError {
+                    line: 3,
+                    message: String::from("bad balance `abc`"),
+                },
+                RowError {
+                    line: 5,
+                    message: String::from("duplicate user `foo`, first seen on line 2"),
+                },
+            ]
+        );
+
+        assert!(parse("name,amount\n", b',', &Mapping::default(), Unit::Seconds).is_err());
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("1,000"), Some(1000.0));
+        assert_eq!(parse_number("-1,000,000.5"), Some(-1000000.5));
+        assert_eq!(parse_number("1 000"), Some(1000.0));
+        assert_eq!(parse_number("12.5"), Some(12.5));
+        assert_eq!(parse_number("1,5"), None);
+        assert_eq!(parse_number("1,00"), None);
+        assert_eq!(parse_number("1000,000"), None);
+        assert_eq!(parse_number("1.000,5"), None);
+        assert_eq!(parse_number(",000"), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        assert_eq!(Strategy::Replace.merge(10, 5), 5);
+        assert_eq!(Strategy::Add.merge(10, 5), 15);
+        assert_eq!(Strategy::Max.merge(10, 5), 10);
+        assert_eq!(Strategy::Max.merge(10, 15), 15);
+    }
+}
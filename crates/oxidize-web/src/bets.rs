@@ -0,0 +1,73 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::{filters, path, Filter};
+
+use crate::Fragment;
+
+/// Bets endpoint.
+#[derive(Clone)]
+pub(crate) struct Bets(async_injector::Ref<db::Bets>);
+
+impl Bets {
+    pub(crate) fn route(
+        bets: async_injector::Ref<db::Bets>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Bets(bets);
+
+        let list = warp::get()
+            .and(path!("bets" / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.list(channel.as_channel())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let wagers = warp::get()
+            .and(path!("bets" / Fragment / i32 / "wagers").and(path::end()))
+            .and_then({
+                move |channel: Fragment, id: i32| {
+                    let api = api.clone();
+                    async move {
+                        api.wagers(channel.as_channel(), id)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        list.or(wagers).boxed()
+    }
+
+    /// Access underlying bets abstraction.
+    async fn bets(&self) -> Result<RwLockReadGuard<'_, db::Bets>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("bets not configured"),
+        }
+    }
+
+    /// Get the list of all bets.
+    async fn list(&self, channel: &Channel) -> Result<impl warp::Reply> {
+        let bets = self.bets().await?.list(channel).await?;
+        Ok(warp::reply::json(&bets))
+    }
+
+    /// Get all wagers of the given bet.
+    async fn wagers(&self, channel: &Channel, id: i32) -> Result<impl warp::Reply> {
+        let bets = self.bets().await?;
+
+        if !bets.list(channel).await?.iter().any(|b| b.id == id) {
+            bail!("no bet with id {}", id);
+        }
+
+        let wagers = bets.wagers(id).await?;
+        Ok(warp::reply::json(&wagers))
+    }
+}
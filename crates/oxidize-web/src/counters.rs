@@ -0,0 +1,67 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::{filters, path, Filter};
+
+use crate::{Fragment, EMPTY};
+
+/// Counters endpoint.
+#[derive(Clone)]
+pub(crate) struct Counters(async_injector::Ref<db::Counters>);
+
+impl Counters {
+    pub(crate) fn route(
+        counters: async_injector::Ref<db::Counters>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Counters(counters);
+
+        let list = warp::get()
+            .and(path!("counters" / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.list(channel.as_channel())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let delete = warp::delete()
+            .and(path!("counters" / Fragment / Fragment).and(path::end()))
+            .and_then({
+                move |channel: Fragment, name: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.delete(channel.as_channel(), name.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        list.or(delete).boxed()
+    }
+
+    /// Access underlying counters abstraction.
+    async fn counters(&self) -> Result<RwLockReadGuard<'_, db::Counters>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("counters not configured"),
+        }
+    }
+
+    /// Get the list of all counters.
+    async fn list(&self, channel: &Channel) -> Result<impl warp::Reply> {
+        let counters = self.counters().await?.list(channel).await?;
+        Ok(warp::reply::json(&counters))
+    }
+
+    /// Delete the given counter.
+    async fn delete(&self, channel: &Channel, name: &str) -> Result<impl warp::Reply> {
+        self.counters().await?.delete(channel, name).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::{filters, path, Filter};
+
+use crate::Fragment;
+
+/// Giveaways endpoint.
+#[derive(Clone)]
+pub(crate) struct Giveaways(async_injector::Ref<db::Giveaways>);
+
+impl Giveaways {
+    pub(crate) fn route(
+        giveaways: async_injector::Ref<db::Giveaways>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Giveaways(giveaways);
+
+        let list = warp::get()
+            .and(path!("giveaways" / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.list(channel.as_channel())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let entrants = warp::get()
+            .and(path!("giveaways" / Fragment / i32 / "entrants").and(path::end()))
+            .and_then({
+                move |channel: Fragment, id: i32| {
+                    let api = api.clone();
+                    async move {
+                        api.entrants(channel.as_channel(), id)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        list.or(entrants).boxed()
+    }
+
+    /// Access underlying giveaways abstraction.
+    async fn giveaways(&self) -> Result<RwLockReadGuard<'_, db::Giveaways>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("giveaways not configured"),
+        }
+    }
+
+    /// Get the list of all giveaways.
+    async fn list(&self, channel: &Channel) -> Result<impl warp::Reply> {
+        let giveaways = self.giveaways().await?.list(channel).await?;
+        Ok(warp::reply::json(&giveaways))
+    }
+
+    /// Get all entrants of the given giveaway.
+    async fn entrants(&self, channel: &Channel, id: i32) -> Result<impl warp::Reply> {
+        let giveaways = self.giveaways().await?;
+
+        if !giveaways.list(channel).await?.iter().any(|g| g.id == id) {
+            bail!("no giveaway with id {}", id);
+        }
+
+        let entrants = giveaways.entrants(id).await?;
+        Ok(warp::reply::json(&entrants))
+    }
+}
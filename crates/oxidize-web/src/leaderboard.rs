@@ -0,0 +1,77 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::{filters, path, Filter};
+
+use crate::Fragment;
+
+/// Default number of users to show in the leaderboard.
+const DEFAULT_LIMIT: i64 = 10;
+/// Maximum number of users to show in the leaderboard.
+const MAX_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize)]
+struct LeaderboardQuery {
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+struct Response {
+    /// Name of the currency.
+    name: String,
+    entries: Vec<currency::LeaderboardEntry>,
+}
+
+/// Currency leaderboard endpoint.
+#[derive(Clone)]
+pub(crate) struct Leaderboard(async_injector::Ref<currency::Currency>);
+
+impl Leaderboard {
+    pub(crate) fn route(
+        currency: async_injector::Ref<currency::Currency>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Leaderboard(currency);
+
+        let leaderboard = warp::get()
+            .and(path!("currency" / Fragment / "leaderboard").and(path::end()))
+            .and(warp::query::<LeaderboardQuery>())
+            .and_then({
+                move |channel: Fragment, query: LeaderboardQuery| {
+                    let api = api.clone();
+                    async move {
+                        api.leaderboard(channel.as_channel(), query)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        leaderboard.boxed()
+    }
+
+    /// Access underlying currency.
+    async fn currency(&self) -> Result<RwLockReadGuard<'_, currency::Currency>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("currency not configured"),
+        }
+    }
+
+    /// Get the users with the highest balances.
+    async fn leaderboard(
+        &self,
+        channel: &Channel,
+        query: LeaderboardQuery,
+    ) -> Result<impl warp::Reply> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let currency = self.currency().await?;
+
+        let leaderboard = Response {
+            name: currency.name.to_string(),
+            entries: currency.top(channel, limit).await?,
+        };
+
+        Ok(warp::reply::json(&leaderboard))
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::reply::Response;
+use warp::{filters, path, Filter, Reply};
+
+use crate::v1::{self, PageQuery};
+use crate::Fragment;
+
+/// Default number of ledger entries to show.
+const DEFAULT_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize)]
+struct LedgerQuery {
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+struct Reversal {
+    id: i32,
+}
+
+/// Currency ledger endpoint.
+#[derive(Clone)]
+pub(crate) struct Ledger(async_injector::Ref<currency::Currency>);
+
+impl Ledger {
+    pub(crate) fn route(
+        currency: async_injector::Ref<currency::Currency>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Ledger(currency);
+
+        let list = warp::get()
+            .and(path!("ledger" / Fragment).and(path::end()))
+            .and(warp::query::<LedgerQuery>())
+            .and(warp::query::<PageQuery>())
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, query: LedgerQuery, page: PageQuery| {
+                    let api = api.clone();
+                    async move {
+                        api.list(channel.as_channel(), query, page)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let reverse = warp::post()
+            .and(path!("ledger" / Fragment / i32 / "reverse").and(path::end()))
+            .and_then({
+                move |channel: Fragment, id: i32| {
+                    let api = api.clone();
+                    async move {
+                        api.reverse(channel.as_channel(), id)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        list.or(reverse).boxed()
+    }
+
+    /// Access underlying currency.
+    async fn currency(&self) -> Result<RwLockReadGuard<'_, currency::Currency>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("currency not configured"),
+        }
+    }
+
+    /// List the latest ledger entries.
+    ///
+    /// If a page is requested, it takes precedence over the limit.
+    async fn list(
+        &self,
+        channel: &Channel,
+        query: LedgerQuery,
+        page: PageQuery,
+    ) -> Result<Response> {
+        let currency = self.currency().await?;
+        let user = query.user.as_deref();
+
+        let Some(page) = page.page() else {
+            let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+            let entries = currency.history(channel, user, limit, 0).await?;
+            return Ok(warp::reply::json(&entries).into_response());
+        };
+
+        let entries = currency
+            .history(channel, user, page.limit, page.offset)
+            .await?;
+        let total = currency.history_count(channel, user).await?;
+        Ok(v1::paged(&entries, total))
+    }
+
+    /// Reverse the given transaction.
+    async fn reverse(&self, channel: &Channel, id: i32) -> Result<impl warp::Reply> {
+        let id = self.currency().await?.reverse(channel, id).await?;
+        Ok(warp::reply::json(&Reversal { id }))
+    }
+}
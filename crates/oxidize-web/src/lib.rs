@@ -1,14 +1,34 @@
 #![allow(clippy::too_many_arguments)]
 
+mod access;
+mod alerts;
+mod balance_import;
+mod bets;
 mod cache;
 mod chat;
+mod counters;
+mod giveaways;
+mod leaderboard;
+mod ledger;
+mod openapi;
+mod polls;
+mod quotes;
+mod redemptions;
 mod settings;
+mod shop;
+mod timers;
+mod tokens;
+mod topics;
+mod v1;
+mod watch_time;
+mod webhooks;
 
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 use std::future::Future;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::pin::pin;
 use std::sync::Arc;
 
@@ -25,10 +45,25 @@ use tokio::sync::{mpsc, RwLock, RwLockReadGuard};
 use tracing::Instrument;
 use warp::{body, filters, path, Filter};
 
+use self::access::{Access, Guard};
 use self::assets::Asset;
+use self::balance_import::BalanceImport;
+use self::bets::Bets;
 use self::cache::Cache;
 use self::chat::Chat;
+use self::counters::Counters;
+use self::giveaways::Giveaways;
+use self::leaderboard::Leaderboard;
+use self::ledger::Ledger;
+use self::polls::Polls;
+use self::quotes::Quotes;
+use self::redemptions::Redemptions;
 use self::settings::Settings;
+use self::shop::Shop;
+use self::timers::Timers;
+use self::tokens::Tokens;
+use self::watch_time::WatchTime;
+use self::webhooks::Webhooks;
 
 /// URL of public web interface.
 pub const URL: &str = "http://localhost:12345";
@@ -52,9 +87,13 @@ pub(crate) fn custom_reject(error: impl Into<Error>) -> warp::Rejection {
 enum WebError {
     BadRequest,
     NotFound,
+    Unauthorized,
+    Forbidden,
     Custom(Error),
 }
 
+impl warp::reject::Reject for WebError {}
+
 impl From<Error> for WebError {
     fn from(value: Error) -> Self {
         WebError::Custom(value)
@@ -66,6 +105,8 @@ impl fmt::Display for WebError {
         match self {
             WebError::BadRequest => "bad request".fmt(fmt),
             WebError::NotFound => "not found".fmt(fmt),
+            WebError::Unauthorized => "missing or invalid token".fmt(fmt),
+            WebError::Forbidden => "token does not grant access".fmt(fmt),
             WebError::Custom(e) => e.fmt(fmt),
         }
     }
@@ -984,27 +1025,27 @@ impl Api {
     }
 }
 
-/// Set up the web endpoint.
-#[tracing::instrument(skip_all)]
-pub async fn run(
+/// Set up the routes mounted under `/api`.
+///
+/// Returns the endpoints used by overlays, which only need read access,
+/// separately from all other endpoints.
+async fn routes(
     version: &'static str,
     injector: &Injector,
-    message_log: messagelog::MessageLog,
-    message_bus: bus::Bus<messagelog::Event>,
-    global_bus: bus::Bus<bus::Global>,
-    youtube_bus: bus::Bus<bus::YouTube>,
-    command_bus: bus::Bus<bus::Command>,
     auth: auth::Auth,
+    active_connections: Arc<RwLock<HashMap<String, ConnectionMeta>>>,
+    command_bus: bus::Bus<bus::Command>,
+    message_log: messagelog::MessageLog,
     latest: ::settings::Var<Option<api::github::Release>>,
-) -> Result<(Server, impl Future<Output = Result<()>>)> {
-    let addr: SocketAddr = str::parse("0.0.0.0:12345")?;
-
+) -> Result<(
+    filters::BoxedFilter<(impl warp::Reply,)>,
+    filters::BoxedFilter<(impl warp::Reply,)>,
+)> {
     let channel = injector
         .var_key(Key::<String>::tagged(tags::Globals::Channel)?)
         .await;
 
     let player = injector.var().await;
-    let active_connections: Arc<RwLock<HashMap<String, ConnectionMeta>>> = Default::default();
 
     let api = Api {
         version,
@@ -1014,137 +1055,209 @@ pub async fn run(
         latest,
     };
 
-    let api = {
-        let route = warp::post()
-            .and(path!("device" / String))
-            .and_then({
+    let route = warp::post()
+        .and(path!("device" / String))
+        .and_then({
+            let api = api.clone();
+            move |id| {
                 let api = api.clone();
-                move |id| {
-                    let api = api.clone();
-                    async move { api.clone().set_device(id).await.map_err(custom_reject) }
-                }
-            })
-            .boxed();
+                async move { api.clone().set_device(id).await.map_err(custom_reject) }
+            }
+        })
+        .boxed();
 
-        let route = route
-            .or(warp::get().and(warp::path("version")).and_then({
+    let route = route
+        .or(warp::get().and(warp::path("version")).and_then({
+            let api = api.clone();
+            move || {
                 let api = api.clone();
-                move || {
-                    let api = api.clone();
-                    async move { api.version().await.map_err(custom_reject) }
-                }
-            }))
-            .boxed();
+                async move { api.version().await.map_err(custom_reject) }
+            }
+        }))
+        .boxed();
 
-        let route = route
-            .or(warp::get().and(warp::path("devices")).and_then({
+    let route = route
+        .or(warp::get().and(warp::path("devices")).and_then({
+            let api = api.clone();
+            move || {
                 let api = api.clone();
-                move || {
-                    let api = api.clone();
-                    async move { api.clone().devices().await.map_err(custom_reject) }
-                }
-            }))
-            .boxed();
+                async move { api.clone().devices().await.map_err(custom_reject) }
+            }
+        }))
+        .boxed();
 
-        let route = route
-            .or(warp::delete().and(path!("after-stream" / i32)).and_then({
+    let route = route
+        .or(warp::delete().and(path!("after-stream" / i32)).and_then({
+            let api = api.clone();
+            move |id| {
                 let api = api.clone();
-                move |id| {
-                    let api = api.clone();
-                    async move { api.delete_after_stream(id).await.map_err(custom_reject) }
-                }
-            }))
-            .boxed();
+                async move { api.delete_after_stream(id).await.map_err(custom_reject) }
+            }
+        }))
+        .boxed();
 
-        let route = route
-            .or(warp::get().and(warp::path("after-streams")).and_then({
+    let route = route
+        .or(warp::get().and(warp::path("after-streams")).and_then({
+            let api = api.clone();
+            move || {
                 let api = api.clone();
-                move || {
-                    let api = api.clone();
-                    async move { api.get_after_streams().await.map_err(custom_reject) }
-                }
-            }))
-            .boxed();
+                async move { api.get_after_streams().await.map_err(custom_reject) }
+            }
+        }))
+        .boxed();
 
-        let route = route
-            .or(warp::put()
-                .and(warp::path("balances"))
-                .and(body::json())
-                .and_then({
+    let route = route
+        .or(warp::put()
+            .and(warp::path("balances"))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |balances: Vec<db::models::Balance>| {
                     let api = api.clone();
-                    move |balances: Vec<db::models::Balance>| {
-                        let api = api.clone();
 
-                        async move {
-                            api.clone()
-                                .import_balances(balances)
-                                .await
-                                .map_err(custom_reject)
-                        }
+                    async move {
+                        api.clone()
+                            .import_balances(balances)
+                            .await
+                            .map_err(custom_reject)
                     }
-                }))
-            .boxed();
+                }
+            }))
+        .boxed();
 
-        let route = route
-            .or(warp::get().and(warp::path("balances")).and_then({
-                move || {
-                    let api = api.clone();
+    let route = route
+        .or(warp::get().and(warp::path("balances")).and_then({
+            move || {
+                let api = api.clone();
 
-                    async move { api.clone().export_balances().await.map_err(custom_reject) }
+                async move { api.clone().export_balances().await.map_err(custom_reject) }
+            }
+        }))
+        .boxed();
+
+    let route = route.or(warp::path("auth")
+        .and(Auth::route(
+            auth,
+            active_connections.clone(),
+            injector.var().await,
+        ))
+        .boxed());
+    let route = route.or(Aliases::route(injector.var().await));
+    let route = route.or(Commands::route(injector.var().await));
+    let route = route.or(Promotions::route(injector.var().await));
+    let route = route.or(Themes::route(injector.var().await));
+    let route = route.or(Quotes::route(injector.var().await));
+    let route = route.or(Counters::route(injector.var().await));
+    let route = route.or(Giveaways::route(injector.var().await));
+    let route = route.or(Polls::route(injector.var().await));
+    let route = route.or(Bets::route(injector.var().await));
+    let route = route.or(Ledger::route(injector.var().await));
+    let route = route.or(Shop::route(injector.var().await));
+    let route = route.or(WatchTime::route(injector.var().await));
+    let route = route.or(BalanceImport::route(injector.var().await));
+    let route = route.or(Redemptions::route(injector.var().await));
+    let route = route.or(Settings::route(injector.var().await));
+    let route = route.or(Cache::route(injector.var().await));
+    let route = route.or(Tokens::route(injector.var().await));
+    let route = route.or(Timers::route(injector.var().await));
+    let route = route.or(Webhooks::route(injector.var().await));
+    let route = route.or(Chat::route(command_bus, message_log));
+
+    // NB: endpoints used by overlays, which only need read access.
+    let overlay = Leaderboard::route(injector.var().await).boxed();
+
+    // TODO: move endpoint into abstraction thingie.
+    let overlay = overlay
+        .or(
+            warp::get().and(path!("current").and(path::end()).and_then(move || {
+                let channel = channel.clone();
+
+                async move {
+                    let current = Current {
+                        channel: channel.load().await,
+                    };
+                    Ok::<_, warp::Rejection>(warp::reply::json(&current))
                 }
-            }))
-            .boxed();
+            })),
+        )
+        .boxed();
 
-        let route = route.or(warp::path("auth")
-            .and(Auth::route(
-                auth,
-                active_connections.clone(),
-                injector.var().await,
-            ))
-            .boxed());
-        let route = route.or(Aliases::route(injector.var().await));
-        let route = route.or(Commands::route(injector.var().await));
-        let route = route.or(Promotions::route(injector.var().await));
-        let route = route.or(Themes::route(injector.var().await));
-        let route = route.or(Settings::route(injector.var().await));
-        let route = route.or(Cache::route(injector.var().await));
-        let route = route.or(Chat::route(command_bus, message_log));
-
-        // TODO: move endpoint into abstraction thingie.
-        let route = route
-            .or(
-                warp::get().and(path!("current").and(path::end()).and_then(move || {
-                    let channel = channel.clone();
+    Ok((overlay, route.boxed()))
+}
 
-                    async move {
-                        let current = Current {
-                            channel: channel.load().await,
-                        };
-                        Ok::<_, warp::Rejection>(warp::reply::json(&current))
-                    }
-                })),
-            )
+/// Set up the web endpoint.
+#[tracing::instrument(skip_all)]
+pub async fn run(
+    version: &'static str,
+    injector: &Injector,
+    message_log: messagelog::MessageLog,
+    message_bus: bus::Bus<messagelog::Event>,
+    global_bus: bus::Bus<bus::Global>,
+    youtube_bus: bus::Bus<bus::YouTube>,
+    command_bus: bus::Bus<bus::Command>,
+    auth: auth::Auth,
+    settings: ::settings::Settings<::auth::Scope>,
+    latest: ::settings::Var<Option<api::github::Release>>,
+) -> Result<(Server, impl Future<Output = Result<()>>)> {
+    let addr: SocketAddr = str::parse("0.0.0.0:12345")?;
+
+    let guard = Guard::new(settings.scoped("web/auth"), injector.var().await).await?;
+    let assets_dir = settings.optional::<PathBuf>("alerts/assets-dir").await?;
+
+    let active_connections: Arc<RwLock<HashMap<String, ConnectionMeta>>> = Default::default();
+
+    let api = {
+        let (overlay, route) = routes(
+            version,
+            injector,
+            auth,
+            active_connections.clone(),
+            command_bus,
+            message_log,
+            latest,
+        )
+        .await?;
+
+        let route = guard
+            .require(Access::Read)
+            .and(overlay)
+            .or(guard.require(Access::Admin).and(route))
             .boxed();
 
-        warp::path("api").and(route)
+        warp::path("api").and(v1::route(version, route.clone()).or(route))
     };
 
     let ws_messages = warp::get()
         .and(warp::path!("ws" / "messages"))
+        .and(guard.require(Access::Read))
         .and(send_bus(message_bus).recover(recover));
 
+    let ws_topics = warp::get()
+        .and(warp::path!("ws" / "topics"))
+        .and(guard.require(Access::Read))
+        .and(topics::route(global_bus.clone(), message_bus.clone()).recover(recover));
+
     let ws_overlay = warp::get()
         .and(warp::path!("ws" / "overlay"))
+        .and(guard.require(Access::Read))
         .and(send_bus(global_bus).recover(recover));
 
     let ws_youtube = warp::get()
         .and(warp::path!("ws" / "youtube"))
+        .and(guard.require(Access::Read))
         .and(send_bus(youtube_bus).recover(recover));
 
+    let alert_assets = warp::get()
+        .and(warp::path!("alerts" / "assets" / ..))
+        .and(guard.require(Access::Read))
+        .and(alerts::route(assets_dir));
+
     let routes = api.recover(recover);
     let routes = routes.or(ws_messages.recover(recover));
     let routes = routes.or(ws_overlay.recover(recover));
     let routes = routes.or(ws_youtube.recover(recover));
+    let routes = routes.or(ws_topics.recover(recover));
+    let routes = routes.or(alert_assets.recover(recover));
 
     let fallback = Asset::get("index.html");
     let fallback = fallback.map(|f| f.data);
@@ -1262,6 +1375,8 @@ async fn recover(err: warp::Rejection) -> Result<impl warp::Reply, warp::Rejecti
         let code = match *e {
             WebError::BadRequest => warp::http::StatusCode::BAD_REQUEST,
             WebError::NotFound => warp::http::StatusCode::NOT_FOUND,
+            WebError::Unauthorized => warp::http::StatusCode::UNAUTHORIZED,
+            WebError::Forbidden => warp::http::StatusCode::FORBIDDEN,
             WebError::Custom(_) => warp::http::StatusCode::INTERNAL_SERVER_ERROR,
         };
 
@@ -0,0 +1,456 @@
+//! OpenAPI description of the `/api/v1` surface.
+//!
+//! The document is generated from the table of [`ENDPOINTS`] below, which
+//! needs to be kept up to date as routes are added.
+
+use serde_json::{json, Map, Value};
+use warp::{filters, path, Filter};
+
+use crate::v1::{DEFAULT_PER_PAGE, MAX_PER_PAGE, TOTAL_COUNT};
+
+/// Description of a single endpoint.
+struct Endpoint {
+    method: &'static str,
+    /// Path of the endpoint relative to `/api/v1`, with parameters in braces.
+    path: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    /// Query parameters accepted by the endpoint.
+    query: &'static [(&'static str, &'static str)],
+    /// Description of the JSON request body, if any.
+    body: Option<&'static str>,
+    /// If the endpoint returns a list which can be paginated.
+    paginated: bool,
+}
+
+macro_rules! endpoints {
+    ($($method:ident $path:literal $tag:literal $summary:literal $({ $($field:ident: $value:expr),* $(,)? })?;)*) => {
+        &[$(Endpoint {
+            method: stringify!($method),
+            path: $path,
+            tag: $tag,
+            summary: $summary,
+            $($($field: $value,)*)?
+            ..Endpoint::DEFAULT
+        },)*]
+    };
+}
+
+impl Endpoint {
+    const DEFAULT: Endpoint = Endpoint {
+        method: "",
+        path: "",
+        tag: "",
+        summary: "",
+        query: &[],
+        body: None,
+        paginated: false,
+    };
+}
+
+const ENDPOINTS: &[Endpoint] = endpoints! {
+    get "/version" "meta" "Get version information, including the latest release.";
+    get "/current" "meta" "Get the channel the bot is currently connected to.";
+    get "/openapi.json" "meta" "Get this document.";
+
+    get "/devices" "player" "List the devices available for playback.";
+    post "/device/{id}" "player" "Switch playback to the given device.";
+
+    get "/after-streams" "after-streams" "List after stream notes." { paginated: true };
+    delete "/after-stream/{id}" "after-streams" "Delete an after stream note.";
+
+    get "/balances" "currency" "Export all balances." { paginated: true };
+    put "/balances" "currency" "Import balances, replacing existing ones." { body: Some("List of balances.") };
+    post "/currency/{channel}/import/csv" "currency" "Import balances from CSV." {
+        body: Some("CSV data, column mapping, and merge strategy."),
+    };
+    get "/currency/{channel}/leaderboard" "currency" "Get the users with the highest balances." {
+        query: &[("limit", "Number of users to include.")],
+    };
+    get "/ledger/{channel}" "currency" "List currency ledger entries, newest first." {
+        query: &[("user", "Only include entries for the given user."), ("limit", "Number of entries to load, unless a page is requested.")],
+        paginated: true,
+    };
+    post "/ledger/{channel}/{id}/reverse" "currency" "Reverse a currency transaction.";
+    get "/watch-time/{channel}" "currency" "List the users with the most watch time." {
+        query: &[("stream", "Only include the stream with the given id."), ("limit", "Number of users to include, unless a page is requested.")],
+        paginated: true,
+    };
+
+    get "/aliases/{channel}" "aliases" "List aliases." { paginated: true };
+    put "/aliases/{channel}/{name}" "aliases" "Create or update an alias." { body: Some("The template of the alias.") };
+    delete "/aliases/{channel}/{name}" "aliases" "Delete an alias.";
+    post "/aliases/{channel}/{name}/disabled" "aliases" "Enable or disable an alias." { body: Some("If the alias is disabled.") };
+
+    get "/commands/{channel}" "commands" "List custom commands." { paginated: true };
+    put "/commands/{channel}/{name}" "commands" "Create or update a custom command." { body: Some("The template of the command.") };
+    delete "/commands/{channel}/{name}" "commands" "Delete a custom command.";
+    post "/commands/{channel}/{name}/disabled" "commands" "Enable or disable a custom command." { body: Some("If the command is disabled.") };
+
+    get "/promotions/{channel}" "promotions" "List promotions." { paginated: true };
+    put "/promotions/{channel}/{name}" "promotions" "Create or update a promotion." { body: Some("The template and frequency of the promotion.") };
+    delete "/promotions/{channel}/{name}" "promotions" "Delete a promotion.";
+    post "/promotions/{channel}/{name}/disabled" "promotions" "Enable or disable a promotion." { body: Some("If the promotion is disabled.") };
+
+    get "/themes/{channel}" "themes" "List themes." { paginated: true };
+    put "/themes/{channel}/{name}" "themes" "Create or update a theme." { body: Some("The track and offsets of the theme.") };
+    delete "/themes/{channel}/{name}" "themes" "Delete a theme.";
+    post "/themes/{channel}/{name}/disabled" "themes" "Enable or disable a theme." { body: Some("If the theme is disabled.") };
+
+    get "/quotes/{channel}" "quotes" "List quotes." { paginated: true };
+    post "/quotes/{channel}" "quotes" "Add a quote." { body: Some("The quote to add.") };
+    put "/quotes/{channel}/{id}" "quotes" "Edit a quote." { body: Some("The new content of the quote.") };
+    delete "/quotes/{channel}/{id}" "quotes" "Delete a quote.";
+
+    get "/counters/{channel}" "counters" "List counters." { paginated: true };
+    delete "/counters/{channel}/{name}" "counters" "Delete a counter.";
+
+    get "/timers/{channel}" "timers" "List timers." { paginated: true };
+    put "/timers/{channel}/{name}" "timers" "Start a timer, replacing any existing timer with the same name." { body: Some("The kind, duration and template of the timer.") };
+    post "/timers/{channel}/{name}/{action}" "timers" "Pause, resume, or reset a timer.";
+    delete "/timers/{channel}/{name}" "timers" "Delete a timer.";
+
+    get "/giveaways/{channel}" "giveaways" "List giveaways." { paginated: true };
+    get "/giveaways/{channel}/{id}/entrants" "giveaways" "List the entrants of a giveaway." { paginated: true };
+
+    get "/polls/{channel}" "polls" "List polls." { paginated: true };
+    get "/polls/{channel}/{id}/results" "polls" "Get the results of a poll.";
+
+    get "/bets/{channel}" "bets" "List bets." { paginated: true };
+    get "/bets/{channel}/{id}/wagers" "bets" "List the wagers placed on a bet." { paginated: true };
+
+    get "/shop/{channel}/items" "shop" "List shop items." { paginated: true };
+    put "/shop/{channel}/items/{name}" "shop" "Create or update a shop item." { body: Some("The shop item.") };
+    delete "/shop/{channel}/items/{name}" "shop" "Delete a shop item.";
+    get "/shop/{channel}/purchases" "shop" "List shop purchases, newest first." {
+        query: &[("limit", "Number of purchases to load, unless a page is requested."), ("pending", "Only include purchases which haven't been fulfilled.")],
+        paginated: true,
+    };
+    post "/shop/{channel}/purchases/{id}/fulfill" "shop" "Mark a purchase as fulfilled.";
+
+    get "/redemptions/{channel}" "redemptions" "List the actions mapped to channel point rewards." { paginated: true };
+    put "/redemptions/{channel}/{reward}" "redemptions" "Map an action to a channel point reward." { body: Some("The action to run.") };
+    delete "/redemptions/{channel}/{reward}" "redemptions" "Remove the action for a channel point reward.";
+
+    get "/settings" "settings" "List settings." {
+        query: &[("key", "Comma-separated list of keys to include."), ("prefix", "Comma-separated list of prefixes to include."), ("feature", "Only include feature toggles.")],
+        paginated: true,
+    };
+    get "/settings/{key}" "settings" "Get a single setting.";
+    put "/settings/{key}" "settings" "Update a setting." { body: Some("The new value of the setting.") };
+    delete "/settings/{key}" "settings" "Clear a setting, restoring its default.";
+    get "/settings/export" "settings" "Export all settings which aren't secret." {
+        query: &[("format", "Either `json` or `yaml`.")],
+    };
+    post "/settings/import" "settings" "Import settings from JSON or YAML." {
+        query: &[("dry_run", "Only report what would change.")],
+        body: Some("A map of setting keys to values, as JSON or YAML."),
+    };
+    get "/settings/history" "settings" "List the most recent changes to settings." {
+        query: &[("limit", "Number of changes to load, unless a page is requested.")],
+        paginated: true,
+    };
+    post "/settings/undo" "settings" "Undo the most recent changes to settings." {
+        query: &[("count", "Number of changes to undo.")],
+    };
+
+    get "/profiles" "profiles" "List settings profiles.";
+    put "/profiles/{name}" "profiles" "Save the current value of settings as a profile." { body: Some("The settings or prefixes to store.") };
+    post "/profiles/{name}/apply" "profiles" "Switch to a profile.";
+    delete "/profiles/{name}" "profiles" "Delete a profile.";
+
+    get "/auth/connections" "auth" "List active connections.";
+    get "/auth/roles" "auth" "List roles.";
+    get "/auth/scopes" "auth" "List scopes.";
+    get "/auth/grants" "auth" "List granted scopes." { paginated: true };
+    put "/auth/grants" "auth" "Grant a scope to a role." { body: Some("The scope and role.") };
+    delete "/auth/grants/{scope}/{role}" "auth" "Revoke a scope from a role.";
+    get "/auth/key" "auth" "Set the key used to authenticate against the remote API." {
+        query: &[("key", "The new key.")],
+    };
+
+    get "/tokens" "tokens" "List tokens used to access the API.";
+    put "/tokens/{name}" "tokens" "Generate a new token." { body: Some("The access of the token, `read` or `admin`.") };
+    delete "/tokens/{name}" "tokens" "Delete a token.";
+
+    get "/webhooks" "webhooks" "List webhooks.";
+    get "/webhooks/events" "webhooks" "List the events which can be delivered to webhooks.";
+    get "/webhooks/deliveries" "webhooks" "List the most recent deliveries, newest first." {
+        query: &[("webhook", "Only include deliveries to the given webhook."), ("limit", "Number of deliveries to load, unless a page is requested.")],
+        paginated: true,
+    };
+    put "/webhooks/{name}" "webhooks" "Create or update a webhook." { body: Some("The url, secret, and events of the webhook.") };
+    delete "/webhooks/{name}" "webhooks" "Delete a webhook and its deliveries.";
+
+    get "/cache" "cache" "List cached entries." { paginated: true };
+    delete "/cache" "cache" "Delete a cached entry." { body: Some("The namespace and key of the entry.") };
+
+    get "/chat/messages" "chat" "List recent chat messages." { paginated: true };
+    get "/chat/command" "chat" "Run a command as the streamer." {
+        query: &[("command", "The command to run.")],
+    };
+};
+
+/// Serves the OpenAPI document.
+pub(crate) struct OpenApi;
+
+impl OpenApi {
+    pub(crate) fn route(version: &'static str) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let document = document(version);
+
+        warp::get()
+            .and(path!("openapi.json").and(path::end()))
+            .map(move || warp::reply::json(&document))
+            .boxed()
+    }
+}
+
+/// Extract the names of parameters in the given path.
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|s| s.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+}
+
+/// Generate the OpenAPI document.
+fn document(version: &str) -> Value {
+    let mut paths = Map::new();
+
+    for endpoint in ENDPOINTS {
+        let mut parameters = Vec::new();
+
+        for name in path_parameters(endpoint.path) {
+            let schema = if name == "id" {
+                json!({"type": "integer"})
+            } else {
+                json!({"type": "string"})
+            };
+
+            parameters.push(json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": schema,
+            }));
+        }
+
+        for (name, description) in endpoint.query {
+            parameters.push(json!({
+                "name": name,
+                "in": "query",
+                "description": description,
+                "schema": {"type": "string"},
+            }));
+        }
+
+        let mut success = json!({
+            "description": "Success.",
+            "content": {"application/json": {"schema": {}}},
+        });
+
+        if endpoint.paginated {
+            parameters.push(json!({"$ref": "#/components/parameters/page"}));
+            parameters.push(json!({"$ref": "#/components/parameters/per_page"}));
+
+            success["headers"] = json!({
+                TOTAL_COUNT: {
+                    "description": "Total number of items, only present if the list was paginated.",
+                    "schema": {"type": "integer"},
+                },
+            });
+        }
+
+        let mut operation = json!({
+            "tags": [endpoint.tag],
+            "summary": endpoint.summary,
+            "parameters": parameters,
+            "responses": {
+                "200": success,
+                "default": {"$ref": "#/components/responses/Error"},
+            },
+        });
+
+        if let Some(body) = endpoint.body {
+            operation["requestBody"] = json!({
+                "description": body,
+                "required": true,
+                "content": {"application/json": {"schema": {}}},
+            });
+        }
+
+        let item = paths
+            .entry(endpoint.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+
+        item[endpoint.method] = operation;
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "OxidizeBot",
+            "description": "Local API of OxidizeBot. Errors are always reported as an `Error` object.",
+            "version": version,
+        },
+        "servers": [{"url": "/api/v1"}],
+        "security": [{"bearer": []}, {"query": []}],
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearer": {"type": "http", "scheme": "bearer"},
+                "query": {"type": "apiKey", "in": "query", "name": "token"},
+            },
+            "parameters": {
+                "page": {
+                    "name": "page",
+                    "in": "query",
+                    "description": "The page to get, starting at 1. Lists are only paginated if `page` or `per_page` is present.",
+                    "schema": {"type": "integer", "minimum": 1},
+                },
+                "per_page": {
+                    "name": "per_page",
+                    "in": "query",
+                    "description": "The number of items in a page.",
+                    "schema": {"type": "integer", "minimum": 1, "maximum": MAX_PER_PAGE, "default": DEFAULT_PER_PAGE},
+                },
+            },
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "required": ["code", "message"],
+                    "properties": {
+                        "code": {"type": "integer", "description": "The HTTP status code."},
+                        "message": {"type": "string", "description": "Description of the error."},
+                    },
+                },
+            },
+            "responses": {
+                "Error": {
+                    "description": "An error.",
+                    "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Error"}}},
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{document, path_parameters, OpenApi, ENDPOINTS};
+
+    #[test]
+    fn test_path_parameters() {
+        assert_eq!(
+            path_parameters("/quotes/{channel}/{id}").collect::<Vec<_>>(),
+            vec!["channel", "id"]
+        );
+        assert_eq!(path_parameters("/version").count(), 0);
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let mut seen = HashSet::new();
+
+        for e in ENDPOINTS {
+            assert!(
+                matches!(e.method, "get" | "put" | "post" | "delete"),
+                "bad method for {}",
+                e.path
+            );
+            assert!(e.path.starts_with('/'), "bad path {}", e.path);
+            assert!(!e.summary.is_empty(), "missing summary for {}", e.path);
+            assert!(
+                seen.insert((e.method, e.path)),
+                "duplicate {} {}",
+                e.method,
+                e.path
+            );
+        }
+    }
+
+    /// Test that every documented endpoint is served by a route.
+    ///
+    /// Nothing is configured, so endpoints are expected to fail. But they
+    /// should fail in their handlers, or while reading their request, rather
+    /// than because no route matched.
+    #[tokio::test]
+    async fn test_endpoints_routed() -> anyhow::Result<()> {
+        use std::path::Path;
+
+        use warp::filters::body::BodyDeserializeError;
+        use warp::reject::InvalidQuery;
+        use warp::Filter;
+
+        use crate::{CustomReject, WebError};
+
+        let db = db::Database::open(Path::new(":memory:"))?;
+        let schema = auth::Schema::load_static(b"{roles: {}, scopes: {}}")?;
+        let auth = auth::Auth::new(db, schema).await?;
+        let injector = async_injector::Injector::new();
+
+        let (overlay, route) = crate::routes(
+            "1.0.0",
+            &injector,
+            auth,
+            Default::default(),
+            bus::Bus::new(),
+            messagelog::MessageLog::builder().build(),
+            ::settings::Var::new(None),
+        )
+        .await?;
+
+        let route = OpenApi::route("1.0.0").or(overlay).or(route);
+
+        for e in ENDPOINTS {
+            let path = e
+                .path
+                .split('/')
+                .map(|s| match s {
+                    "{id}" => "1",
+                    s if s.starts_with('{') => "test",
+                    s => s,
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+
+            let result = warp::test::request()
+                .method(&e.method.to_uppercase())
+                .path(&path)
+                .filter(&route)
+                .await;
+
+            if let Err(rejection) = result {
+                let routed = rejection.find::<WebError>().is_some()
+                    || rejection.find::<CustomReject>().is_some()
+                    || rejection.find::<BodyDeserializeError>().is_some()
+                    || rejection.find::<InvalidQuery>().is_some();
+
+                assert!(
+                    routed,
+                    "no route for {} {}: {:?}",
+                    e.method, e.path, rejection
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_document() {
+        let document = document("1.0.0");
+
+        assert_eq!(document["openapi"], "3.0.3");
+        assert_eq!(document["info"]["version"], "1.0.0");
+
+        let op = &document["paths"]["/quotes/{channel}/{id}"]["put"];
+        assert_eq!(op["parameters"][0]["name"], "channel");
+        assert_eq!(op["parameters"][1]["name"], "id");
+        assert_eq!(op["parameters"][1]["schema"]["type"], "integer");
+        assert!(op["requestBody"].is_object());
+
+        let op = &document["paths"]["/quotes/{channel}"]["get"];
+        assert!(op["responses"]["200"]["headers"]["x-total-count"].is_object());
+    }
+}
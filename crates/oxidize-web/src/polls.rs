@@ -0,0 +1,73 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::{filters, path, Filter};
+
+use crate::Fragment;
+
+/// Polls endpoint.
+#[derive(Clone)]
+pub(crate) struct Polls(async_injector::Ref<db::Polls>);
+
+impl Polls {
+    pub(crate) fn route(
+        polls: async_injector::Ref<db::Polls>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Polls(polls);
+
+        let list = warp::get()
+            .and(path!("polls" / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.list(channel.as_channel())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let results = warp::get()
+            .and(path!("polls" / Fragment / i32 / "results").and(path::end()))
+            .and_then({
+                move |channel: Fragment, id: i32| {
+                    let api = api.clone();
+                    async move {
+                        api.results(channel.as_channel(), id)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        list.or(results).boxed()
+    }
+
+    /// Access underlying polls abstraction.
+    async fn polls(&self) -> Result<RwLockReadGuard<'_, db::Polls>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("polls not configured"),
+        }
+    }
+
+    /// Get the list of all polls.
+    async fn list(&self, channel: &Channel) -> Result<impl warp::Reply> {
+        let polls = self.polls().await?.list(channel).await?;
+        Ok(warp::reply::json(&polls))
+    }
+
+    /// Get the results of the given poll.
+    async fn results(&self, channel: &Channel, id: i32) -> Result<impl warp::Reply> {
+        let polls = self.polls().await?;
+
+        if polls.get(channel, id).await?.is_none() {
+            bail!("no poll with id {}", id);
+        }
+
+        let results = polls.results(id).await?;
+        Ok(warp::reply::json(&results))
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::{body, filters, path, Filter};
+
+use crate::{Fragment, EMPTY};
+
+#[derive(serde::Deserialize)]
+pub(crate) struct PostQuote {
+    author: String,
+    text: String,
+    #[serde(default)]
+    game: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct PutQuote {
+    author: String,
+    text: String,
+}
+
+#[derive(serde::Serialize)]
+struct Created {
+    id: i32,
+}
+
+/// Quotes endpoint.
+#[derive(Clone)]
+pub(crate) struct Quotes(async_injector::Ref<db::Quotes>);
+
+impl Quotes {
+    pub(crate) fn route(
+        quotes: async_injector::Ref<db::Quotes>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Quotes(quotes);
+
+        let list = warp::get()
+            .and(path!("quotes" / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.list(channel.as_channel())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let add = warp::post()
+            .and(path!("quotes" / Fragment).and(path::end()))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, body: PostQuote| {
+                    let api = api.clone();
+                    async move {
+                        api.add(channel.as_channel(), body)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let edit = warp::put()
+            .and(path!("quotes" / Fragment / i32).and(path::end()))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, id: i32, body: PutQuote| {
+                    let api = api.clone();
+                    async move {
+                        api.edit(channel.as_channel(), id, body)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let delete = warp::delete()
+            .and(path!("quotes" / Fragment / i32).and(path::end()))
+            .and_then({
+                move |channel: Fragment, id: i32| {
+                    let api = api.clone();
+                    async move {
+                        api.delete(channel.as_channel(), id)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        list.or(add).or(edit).or(delete).boxed()
+    }
+
+    /// Access underlying quotes abstraction.
+    async fn quotes(&self) -> Result<RwLockReadGuard<'_, db::Quotes>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("quotes not configured"),
+        }
+    }
+
+    /// Get the list of all quotes.
+    async fn list(&self, channel: &Channel) -> Result<impl warp::Reply> {
+        let quotes = self.quotes().await?.list(channel).await?;
+        Ok(warp::reply::json(&quotes))
+    }
+
+    /// Add a new quote.
+    async fn add(&self, channel: &Channel, body: PostQuote) -> Result<impl warp::Reply> {
+        let id = self
+            .quotes()
+            .await?
+            .insert(
+                channel,
+                "web",
+                &body.author,
+                body.game.as_deref(),
+                &body.text,
+            )
+            .await?;
+
+        Ok(warp::reply::json(&Created { id }))
+    }
+
+    /// Edit the given quote.
+    async fn edit(&self, channel: &Channel, id: i32, body: PutQuote) -> Result<impl warp::Reply> {
+        if !self
+            .quotes()
+            .await?
+            .edit(channel, id, &body.author, &body.text)
+            .await?
+        {
+            bail!("no quote with id {}", id);
+        }
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Delete the given quote.
+    async fn delete(&self, channel: &Channel, id: i32) -> Result<impl warp::Reply> {
+        self.quotes().await?.delete(channel, id).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::{body, filters, path, Filter};
+
+use crate::{Fragment, EMPTY};
+
+/// Actions which can be mapped to a redemption.
+const ACTIONS: &[&str] = &["command", "currency", "gtav", "theme"];
+
+/// Channel point redemptions endpoint.
+#[derive(Clone)]
+pub(crate) struct Redemptions(async_injector::Ref<db::Redemptions>);
+
+impl Redemptions {
+    pub(crate) fn route(
+        redemptions: async_injector::Ref<db::Redemptions>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Redemptions(redemptions);
+
+        let list = warp::get()
+            .and(path!("redemptions" / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.list(channel.as_channel())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let edit = warp::put()
+            .and(path!("redemptions" / Fragment / Fragment).and(path::end()))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, reward: Fragment, body: db::RedemptionActionEdit| {
+                    let api = api.clone();
+                    async move {
+                        api.edit(channel.as_channel(), reward.as_str(), body)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let delete = warp::delete()
+            .and(path!("redemptions" / Fragment / Fragment).and(path::end()))
+            .and_then({
+                move |channel: Fragment, reward: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.delete(channel.as_channel(), reward.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        list.or(edit).or(delete).boxed()
+    }
+
+    /// Access underlying redemptions abstraction.
+    async fn redemptions(&self) -> Result<RwLockReadGuard<'_, db::Redemptions>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("redemptions not configured"),
+        }
+    }
+
+    /// Get the list of all redemption actions.
+    async fn list(&self, channel: &Channel) -> Result<impl warp::Reply> {
+        let actions = self.redemptions().await?.list(channel).await?;
+        Ok(warp::reply::json(&actions))
+    }
+
+    /// Insert or update the action for the given reward.
+    async fn edit(
+        &self,
+        channel: &Channel,
+        reward: &str,
+        edit: db::RedemptionActionEdit,
+    ) -> Result<impl warp::Reply> {
+        if !ACTIONS.contains(&edit.action.as_str()) {
+            bail!("unsupported action `{}`", edit.action);
+        }
+
+        if edit.action == "command" {
+            template::Template::compile(&edit.argument)?;
+        }
+
+        self.redemptions()
+            .await?
+            .edit(channel, reward, edit)
+            .await?;
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Delete the action for the given reward.
+    async fn delete(&self, channel: &Channel, reward: &str) -> Result<impl warp::Reply> {
+        self.redemptions().await?.delete(channel, reward).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
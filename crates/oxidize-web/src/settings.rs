@@ -1,8 +1,9 @@
 use anyhow::{bail, Result};
-use std::collections::HashSet;
-use tokio::sync::RwLockReadGuard;
-use warp::{body, filters, path, Filter};
+use std::collections::{BTreeMap, HashSet};
+use warp::reply::Response;
+use warp::{body, filters, path, Filter, Reply};
 
+use crate::v1::{self, PageQuery};
 use crate::{Fragment, EMPTY};
 
 #[derive(serde::Deserialize)]
@@ -10,6 +11,53 @@ pub(crate) struct PutSetting {
     value: serde_json::Value,
 }
 
+/// Format used when exporting settings.
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Format {
+    #[default]
+    Json,
+    Yaml,
+}
+
+#[derive(serde::Deserialize)]
+struct ExportQuery {
+    #[serde(default)]
+    format: Format,
+}
+
+#[derive(serde::Deserialize)]
+struct ImportQuery {
+    /// Only report what would change.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct HistoryQuery {
+    #[serde(default = "default_history_limit")]
+    limit: usize,
+}
+
+fn default_history_limit() -> usize {
+    100
+}
+
+#[derive(serde::Deserialize)]
+struct UndoQuery {
+    #[serde(default = "default_undo_count")]
+    count: usize,
+}
+
+fn default_undo_count() -> usize {
+    1
+}
+
+#[derive(serde::Deserialize)]
+struct PutProfile {
+    keys: Vec<String>,
+}
+
 #[derive(serde::Deserialize)]
 struct SettingsQuery {
     #[serde(default)]
@@ -30,6 +78,68 @@ impl Settings {
     ) -> filters::BoxedFilter<(impl warp::Reply,)> {
         let api = Settings(settings);
 
+        let export = warp::get()
+            .and(path!("settings" / "export").and(path::end()))
+            .and(warp::query::<ExportQuery>())
+            .and_then({
+                let api = api.clone();
+                move |query: ExportQuery| {
+                    let api = api.clone();
+                    async move {
+                        api.export_settings(query.format)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let import = warp::post()
+            .and(path!("settings" / "import").and(path::end()))
+            .and(warp::query::<ImportQuery>())
+            .and(body::bytes())
+            .and_then({
+                let api = api.clone();
+                move |query: ImportQuery, body: warp::hyper::body::Bytes| {
+                    let api = api.clone();
+                    async move {
+                        api.import_settings(&body, query.dry_run)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let history = warp::get()
+            .and(path!("settings" / "history").and(path::end()))
+            .and(warp::query::<HistoryQuery>())
+            .and(warp::query::<PageQuery>())
+            .and_then({
+                let api = api.clone();
+                move |query: HistoryQuery, page: PageQuery| {
+                    let api = api.clone();
+                    async move {
+                        api.history(query.limit, page)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let undo = warp::post()
+            .and(path!("settings" / "undo").and(path::end()))
+            .and(warp::query::<UndoQuery>())
+            .and_then({
+                let api = api.clone();
+                move |query: UndoQuery| {
+                    let api = api.clone();
+                    async move { api.undo(query.count).await.map_err(super::custom_reject) }
+                }
+            })
+            .boxed();
+
         let list = warp::get()
             .and(warp::path("settings").and(warp::query::<SettingsQuery>()))
             .and_then({
@@ -82,6 +192,7 @@ impl Settings {
                 warp::path("settings")
                     .and(path::tail().and(body::json()))
                     .and_then({
+                        let api = api.clone();
                         move |key: path::Tail, body: PutSetting| {
                             let api = api.clone();
 
@@ -97,13 +208,82 @@ impl Settings {
             )
             .boxed();
 
-        list.or(get).or(delete).or(edit).boxed()
+        let profiles = warp::get()
+            .and(path!("profiles").and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move || {
+                    let api = api.clone();
+                    async move { api.profiles().await.map_err(super::custom_reject) }
+                }
+            })
+            .boxed();
+
+        let profile_edit = warp::put()
+            .and(path!("profiles" / Fragment).and(path::end()))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |name: Fragment, body: PutProfile| {
+                    let api = api.clone();
+                    async move {
+                        api.edit_profile(name.as_str(), body.keys)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let profile_apply = warp::post()
+            .and(path!("profiles" / Fragment / "apply").and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |name: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.apply_profile(name.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let profile_delete = warp::delete()
+            .and(path!("profiles" / Fragment).and(path::end()))
+            .and_then({
+                move |name: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.delete_profile(name.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        export
+            .or(import)
+            .or(history)
+            .or(undo)
+            .or(list)
+            .or(get)
+            .or(delete)
+            .or(edit)
+            .or(profiles)
+            .or(profile_edit)
+            .or(profile_apply)
+            .or(profile_delete)
+            .boxed()
     }
 
-    /// Access underlying settings abstraction.
-    async fn settings(&self) -> Result<RwLockReadGuard<'_, ::settings::Settings<::auth::Scope>>> {
-        match self.0.read().await {
-            Some(out) => Ok(out),
+    /// Access underlying settings abstraction, attributing any changes to
+    /// the web UI.
+    async fn settings(&self) -> Result<::settings::Settings<::auth::Scope>> {
+        match self.0.read().await.as_deref() {
+            Some(out) => Ok(out.with_source(::settings::Source::Web)),
             None => bail!("settings not configured"),
         }
     }
@@ -179,4 +359,78 @@ impl Settings {
         settings.set_json(key, value).await?;
         Ok(warp::reply::json(&EMPTY))
     }
+
+    /// Export all settings which aren't secret.
+    async fn export_settings(&self, format: Format) -> Result<impl warp::Reply> {
+        let settings = self.settings().await?.export().await?;
+
+        let (content_type, body) = match format {
+            Format::Json => ("application/json", serde_json::to_string_pretty(&settings)?),
+            Format::Yaml => ("application/yaml", serde_yaml::to_string(&settings)?),
+        };
+
+        Ok(warp::reply::with_header(body, "content-type", content_type))
+    }
+
+    /// Import settings from either JSON or YAML.
+    async fn import_settings(&self, body: &[u8], dry_run: bool) -> Result<impl warp::Reply> {
+        // NB: YAML is a superset of JSON, so this handles both.
+        let values: BTreeMap<String, serde_json::Value> = serde_yaml::from_slice(body)?;
+        let report = self.settings().await?.import(values, dry_run).await?;
+        Ok(warp::reply::json(&report))
+    }
+
+    /// List the most recent changes to settings.
+    ///
+    /// If a page is requested, it takes precedence over the limit.
+    async fn history(&self, limit: usize, page: PageQuery) -> Result<Response> {
+        let settings = self.settings().await?;
+
+        let Some(page) = page.page() else {
+            let history = settings.history(limit, 0).await?;
+            return Ok(warp::reply::json(&history).into_response());
+        };
+
+        let offset = usize::try_from(page.offset).unwrap_or(usize::MAX);
+        let history = settings.history(page.limit as usize, offset).await?;
+        let total = settings.history_count().await?;
+        Ok(v1::paged(&history, total))
+    }
+
+    /// Undo the most recent changes to settings.
+    async fn undo(&self, count: usize) -> Result<impl warp::Reply> {
+        let undone = self.settings().await?.undo(count).await?;
+        Ok(warp::reply::json(&undone))
+    }
+
+    /// List all settings profiles.
+    async fn profiles(&self) -> Result<impl warp::Reply> {
+        let profiles = self.settings().await?.profiles().await?;
+        Ok(warp::reply::json(&profiles))
+    }
+
+    /// Store the current value of the given keys as a profile.
+    async fn edit_profile(&self, name: &str, keys: Vec<String>) -> Result<impl warp::Reply> {
+        if keys.is_empty() {
+            bail!("a profile must contain at least one setting");
+        }
+
+        let profile = self.settings().await?.profile_save(name, &keys).await?;
+        Ok(warp::reply::json(&profile))
+    }
+
+    /// Apply the given profile.
+    async fn apply_profile(&self, name: &str) -> Result<impl warp::Reply> {
+        if !self.settings().await?.profile_apply(name).await? {
+            bail!("no profile named `{}`", name);
+        }
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Delete the given profile.
+    async fn delete_profile(&self, name: &str) -> Result<impl warp::Reply> {
+        self.settings().await?.profile_delete(name).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
 }
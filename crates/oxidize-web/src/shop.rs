@@ -0,0 +1,188 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::reply::Response;
+use warp::{body, filters, path, Filter, Reply};
+
+use crate::v1::{self, PageQuery};
+use crate::{Fragment, EMPTY};
+
+/// Default number of purchases to show.
+const DEFAULT_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize)]
+struct PurchasesQuery {
+    #[serde(default)]
+    limit: Option<i64>,
+    #[serde(default)]
+    pending: bool,
+}
+
+/// Shop endpoint.
+#[derive(Clone)]
+pub(crate) struct Shop(async_injector::Ref<db::Shop>);
+
+impl Shop {
+    pub(crate) fn route(
+        shop: async_injector::Ref<db::Shop>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Shop(shop);
+
+        let items = warp::get()
+            .and(path!("shop" / Fragment / "items").and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.items(channel.as_channel())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let edit = warp::put()
+            .and(path!("shop" / Fragment / "items" / Fragment).and(path::end()))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, name: Fragment, body: db::ShopItemEdit| {
+                    let api = api.clone();
+                    async move {
+                        api.edit(channel.as_channel(), name.as_str(), body)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let delete = warp::delete()
+            .and(path!("shop" / Fragment / "items" / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, name: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.delete(channel.as_channel(), name.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let purchases = warp::get()
+            .and(path!("shop" / Fragment / "purchases").and(path::end()))
+            .and(warp::query::<PurchasesQuery>())
+            .and(warp::query::<PageQuery>())
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, query: PurchasesQuery, page: PageQuery| {
+                    let api = api.clone();
+                    async move {
+                        api.purchases(channel.as_channel(), query, page)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let fulfill = warp::post()
+            .and(path!("shop" / Fragment / "purchases" / i32 / "fulfill").and(path::end()))
+            .and_then({
+                move |channel: Fragment, id: i32| {
+                    let api = api.clone();
+                    async move {
+                        api.fulfill(channel.as_channel(), id)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        items.or(edit).or(delete).or(purchases).or(fulfill).boxed()
+    }
+
+    /// Access underlying shop abstraction.
+    async fn shop(&self) -> Result<RwLockReadGuard<'_, db::Shop>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("shop not configured"),
+        }
+    }
+
+    /// Get the list of all items.
+    async fn items(&self, channel: &Channel) -> Result<impl warp::Reply> {
+        let items = self.shop().await?.items(channel).await?;
+        Ok(warp::reply::json(&items))
+    }
+
+    /// Insert or update the given item.
+    async fn edit(
+        &self,
+        channel: &Channel,
+        name: &str,
+        edit: db::ShopItemEdit,
+    ) -> Result<impl warp::Reply> {
+        if edit.price < 0 {
+            bail!("price must not be negative");
+        }
+
+        if matches!(edit.stock, Some(stock) if stock < 0) {
+            bail!("stock must not be negative");
+        }
+
+        if edit.cooldown < 0 {
+            bail!("cooldown must not be negative");
+        }
+
+        if let Some(command) = &edit.command {
+            template::Template::compile(command)?;
+        }
+
+        self.shop().await?.edit(channel, name, edit).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Delete the given item.
+    async fn delete(&self, channel: &Channel, name: &str) -> Result<impl warp::Reply> {
+        self.shop().await?.delete(channel, name).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// List purchases, optionally only the ones waiting to be fulfilled.
+    ///
+    /// If a page is requested, it takes precedence over the limit.
+    async fn purchases(
+        &self,
+        channel: &Channel,
+        query: PurchasesQuery,
+        page: PageQuery,
+    ) -> Result<Response> {
+        let shop = self.shop().await?;
+
+        if query.pending {
+            let purchases = shop.pending(channel).await?;
+            return Ok(warp::reply::json(&purchases).into_response());
+        }
+
+        let Some(page) = page.page() else {
+            let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+            let purchases = shop.purchases(channel, limit, 0).await?;
+            return Ok(warp::reply::json(&purchases).into_response());
+        };
+
+        let purchases = shop.purchases(channel, page.limit, page.offset).await?;
+        let total = shop.count_purchases(channel).await?;
+        Ok(v1::paged(&purchases, total))
+    }
+
+    /// Mark the given purchase as fulfilled.
+    async fn fulfill(&self, channel: &Channel, id: i32) -> Result<impl warp::Reply> {
+        if !self.shop().await?.fulfill(channel, id, None).await? {
+            bail!("no purchase #{} waiting to be fulfilled", id);
+        }
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
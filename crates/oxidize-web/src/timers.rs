@@ -0,0 +1,187 @@
+use anyhow::{bail, Result};
+use chrono::Utc;
+use common::{Channel, Duration};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLockReadGuard;
+use warp::{body, filters, path, Filter};
+
+use crate::{Fragment, EMPTY};
+
+#[derive(Deserialize)]
+struct StartRequest {
+    kind: db::TimerKind,
+    #[serde(default)]
+    duration: Option<Duration>,
+    #[serde(default)]
+    template: Option<String>,
+}
+
+/// A timer with its current elapsed and remaining time.
+#[derive(Serialize)]
+struct TimerState {
+    name: String,
+    kind: String,
+    running: bool,
+    /// Seconds elapsed.
+    elapsed: i64,
+    /// Seconds remaining, if the timer has a duration.
+    remaining: Option<i64>,
+    /// The duration of the timer in seconds, if it has one.
+    duration: Option<i64>,
+    template: Option<String>,
+}
+
+/// Timers endpoint.
+#[derive(Clone)]
+pub(crate) struct Timers(async_injector::Ref<db::Timers>);
+
+impl Timers {
+    pub(crate) fn route(
+        timers: async_injector::Ref<db::Timers>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Timers(timers);
+
+        let list = warp::get()
+            .and(path!("timers" / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.list(channel.as_channel())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let start = warp::put()
+            .and(path!("timers" / Fragment / Fragment).and(path::end()))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, name: Fragment, body: StartRequest| {
+                    let api = api.clone();
+                    async move {
+                        api.start(channel.as_channel(), name.as_str(), body)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let action = warp::post()
+            .and(path!("timers" / Fragment / Fragment / Fragment).and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |channel: Fragment, name: Fragment, action: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.action(channel.as_channel(), name.as_str(), action.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let delete = warp::delete()
+            .and(path!("timers" / Fragment / Fragment).and(path::end()))
+            .and_then({
+                move |channel: Fragment, name: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.delete(channel.as_channel(), name.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        list.or(start).or(action).or(delete).boxed()
+    }
+
+    /// Access underlying timers abstraction.
+    async fn timers(&self) -> Result<RwLockReadGuard<'_, db::Timers>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("timers not configured"),
+        }
+    }
+
+    /// Get the list of all timers.
+    async fn list(&self, channel: &Channel) -> Result<impl warp::Reply> {
+        let now = Utc::now().naive_utc();
+        let timers = self.timers().await?.list(channel).await?;
+
+        let timers = timers
+            .into_iter()
+            .map(|t| TimerState {
+                running: t.is_running(),
+                elapsed: t.elapsed_at(now),
+                remaining: t.remaining_at(now),
+                name: t.name,
+                kind: t.kind,
+                duration: t.duration,
+                template: t.template,
+            })
+            .collect::<Vec<_>>();
+
+        Ok(warp::reply::json(&timers))
+    }
+
+    /// Start a timer, replacing any existing timer with the same name.
+    async fn start(
+        &self,
+        channel: &Channel,
+        name: &str,
+        request: StartRequest,
+    ) -> Result<impl warp::Reply> {
+        let template = match request.template.as_deref().map(str::trim) {
+            Some("") | None => None,
+            Some(template) => Some(template::Template::compile(template)?.source().to_owned()),
+        };
+
+        let duration = request.duration.map(|d| d.as_std().as_secs() as i64);
+
+        let timer = self
+            .timers()
+            .await?
+            .start(channel, name, request.kind, duration, template)
+            .await?;
+
+        Ok(warp::reply::json(&timer))
+    }
+
+    /// Pause, resume, or reset the given timer.
+    async fn action(
+        &self,
+        channel: &Channel,
+        name: &str,
+        action: &str,
+    ) -> Result<impl warp::Reply> {
+        let timers = self.timers().await?;
+
+        let timer = match action {
+            "pause" => timers.pause(channel, name).await?,
+            "resume" => timers.resume(channel, name).await?,
+            "reset" => timers.reset(channel, name).await?,
+            other => bail!("unsupported action `{}`", other),
+        };
+
+        let Some(timer) = timer else {
+            bail!("no timer named `{}`", name);
+        };
+
+        Ok(warp::reply::json(&timer))
+    }
+
+    /// Delete the given timer.
+    async fn delete(&self, channel: &Channel, name: &str) -> Result<impl warp::Reply> {
+        self.timers().await?.delete(channel, name).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::{bail, Result};
+use rand::distributions::{Alphanumeric, DistString};
+use tokio::sync::RwLockReadGuard;
+use warp::{body, filters, path, Filter};
+
+use crate::access::Access;
+use crate::{Fragment, EMPTY};
+
+/// Length of generated tokens.
+const TOKEN_LENGTH: usize = 32;
+
+#[derive(serde::Deserialize)]
+struct PutToken {
+    access: String,
+}
+
+/// Web API tokens endpoint.
+#[derive(Clone)]
+pub(crate) struct Tokens(async_injector::Ref<db::WebTokens>);
+
+impl Tokens {
+    pub(crate) fn route(
+        tokens: async_injector::Ref<db::WebTokens>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Tokens(tokens);
+
+        let list = warp::get().and(path!("tokens").and(path::end())).and_then({
+            let api = api.clone();
+            move || {
+                let api = api.clone();
+                async move { api.list().await.map_err(super::custom_reject) }
+            }
+        });
+
+        let create = warp::put()
+            .and(path!("tokens" / Fragment).and(path::end()))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |name: Fragment, body: PutToken| {
+                    let api = api.clone();
+                    async move {
+                        api.create(name.as_str(), &body.access)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        let delete = warp::delete()
+            .and(path!("tokens" / Fragment).and(path::end()))
+            .and_then({
+                move |name: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.delete(name.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        list.or(create).or(delete).boxed()
+    }
+
+    /// Access underlying tokens abstraction.
+    async fn tokens(&self) -> Result<RwLockReadGuard<'_, db::WebTokens>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("tokens not configured"),
+        }
+    }
+
+    /// Get the list of all tokens.
+    async fn list(&self) -> Result<impl warp::Reply> {
+        let tokens = self.tokens().await?.list().await?;
+        Ok(warp::reply::json(&tokens))
+    }
+
+    /// Generate a new token with the given access.
+    async fn create(&self, name: &str, access: &str) -> Result<impl warp::Reply> {
+        if Access::parse(access).is_none() {
+            bail!(
+                "unsupported access `{}`, expected `read` or `admin`",
+                access
+            );
+        }
+
+        let token = Alphanumeric.sample_string(&mut rand::thread_rng(), TOKEN_LENGTH);
+        let token = self.tokens().await?.create(name, &token, access).await?;
+        Ok(warp::reply::json(&token))
+    }
+
+    /// Delete the given token.
+    async fn delete(&self, name: &str) -> Result<impl warp::Reply> {
+        self.tokens().await?.delete(name).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
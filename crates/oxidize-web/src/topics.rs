@@ -0,0 +1,307 @@
+//! A multiplexed websocket where clients subscribe to the topics they are
+//! interested in.
+//!
+//! Clients send `{"type": "subscribe", "topics": ["song", "polls"]}` or
+//! `{"type": "unsubscribe", "topics": [..]}`, and can subscribe up front with
+//! the `topics` query parameter, like `/ws/topics?topics=song,chat`.
+//!
+//! Every message sent to the client is tagged with the topic it belongs to.
+//! When subscribing to a topic the latest cached messages are sent first and
+//! are marked as a `snapshot`.
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use common::sink::SinkExt;
+use common::stream::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use warp::filters::ws::{Message, WebSocket};
+use warp::{filters, Filter};
+
+/// Topics which can be subscribed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Topic {
+    /// The current song and its progress.
+    Song,
+    /// Chat messages and moderation of them.
+    Chat,
+    /// Live poll results.
+    Polls,
+    /// Named counters.
+    Counters,
+    /// Alerts, like follows and subscriptions.
+    Alerts,
+    /// Countdowns and timers.
+    Countdown,
+}
+
+impl Topic {
+    /// Parse a single topic.
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "song" => Some(Topic::Song),
+            "chat" => Some(Topic::Chat),
+            "polls" => Some(Topic::Polls),
+            "counters" => Some(Topic::Counters),
+            "alerts" => Some(Topic::Alerts),
+            "countdown" => Some(Topic::Countdown),
+            _ => None,
+        }
+    }
+
+    /// The topic a message on the global bus belongs to.
+    fn of_global(m: &bus::Global) -> Option<Self> {
+        match m {
+            bus::Global::SongProgress { .. }
+            | bus::Global::SongCurrent { .. }
+            | bus::Global::SongModified
+            | bus::Global::SongQueue { .. } => Some(Topic::Song),
+            bus::Global::Poll { .. } => Some(Topic::Polls),
+            bus::Global::Counter { .. } => Some(Topic::Counters),
+            bus::Global::Alert { .. } => Some(Topic::Alerts),
+            bus::Global::Timers { .. } => Some(Topic::Countdown),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TopicsQuery {
+    #[serde(default)]
+    topics: Option<String>,
+}
+
+/// Requests sent by the client.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Request {
+    Subscribe { topics: Vec<Topic> },
+    Unsubscribe { topics: Vec<Topic> },
+}
+
+/// Responses sent to the client.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Response<'a, T> {
+    /// The set of topics currently subscribed to.
+    Subscribed { topics: &'a BTreeSet<Topic> },
+    /// A message on a topic.
+    Message {
+        topic: Topic,
+        snapshot: bool,
+        data: &'a T,
+    },
+    /// The client sent something we didn't understand.
+    Error { message: String },
+}
+
+/// Set up the multiplexed websocket.
+pub(crate) fn route(
+    global_bus: bus::Bus<bus::Global>,
+    message_bus: bus::Bus<messagelog::Event>,
+) -> filters::BoxedFilter<(impl warp::Reply,)> {
+    warp::ws()
+        .and(warp::query::<TopicsQuery>())
+        .map(move |ws: warp::ws::Ws, query: TopicsQuery| {
+            let global_bus = global_bus.clone();
+            let message_bus = message_bus.clone();
+
+            let topics = query
+                .topics
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|t| Topic::parse(t.trim()))
+                .collect::<Vec<_>>();
+
+            ws.on_upgrade(move |websocket: WebSocket| async move {
+                let mut connection = Connection {
+                    global_bus,
+                    message_bus,
+                    topics: BTreeSet::new(),
+                    ws: websocket,
+                };
+
+                if let Err(e) = connection.run(topics).await {
+                    common::log_error!(e, "Websocket error");
+                }
+            })
+        })
+        .boxed()
+}
+
+struct Connection {
+    global_bus: bus::Bus<bus::Global>,
+    message_bus: bus::Bus<messagelog::Event>,
+    topics: BTreeSet<Topic>,
+    ws: WebSocket,
+}
+
+impl Connection {
+    /// Forward messages on subscribed topics until the client disconnects.
+    async fn run(&mut self, initial: Vec<Topic>) -> Result<()> {
+        // NB: subscribe before sending snapshots so nothing is missed.
+        let mut global_rx = self.global_bus.subscribe();
+        let mut message_rx = self.message_bus.subscribe();
+
+        if !initial.is_empty() {
+            self.subscribe(initial).await?;
+        }
+
+        loop {
+            tokio::select! {
+                m = self.ws.next() => {
+                    let Some(m) = m else {
+                        return Ok(());
+                    };
+
+                    let m = m?;
+
+                    if m.is_close() {
+                        return Ok(());
+                    }
+
+                    let Ok(text) = m.to_str() else {
+                        continue;
+                    };
+
+                    match serde_json::from_str::<Request>(text) {
+                        Ok(Request::Subscribe { topics }) => {
+                            self.subscribe(topics).await?;
+                        }
+                        Ok(Request::Unsubscribe { topics }) => {
+                            for topic in topics {
+                                self.topics.remove(&topic);
+                            }
+
+                            send(&mut self.ws, &Response::<()>::Subscribed { topics: &self.topics }).await?;
+                        }
+                        Err(e) => {
+                            send(&mut self.ws, &Response::<()>::Error { message: e.to_string() }).await?;
+                        }
+                    }
+                }
+                m = global_rx.recv() => {
+                    let m = match m {
+                        Ok(m) => m,
+                        Err(RecvError::Lagged(..)) => continue,
+                        Err(RecvError::Closed) => return Ok(()),
+                    };
+
+                    if let Some(topic) = Topic::of_global(&m) {
+                        self.publish(topic, false, &m).await?;
+                    }
+                }
+                m = message_rx.recv() => {
+                    let m = match m {
+                        Ok(m) => m,
+                        Err(RecvError::Lagged(..)) => continue,
+                        Err(RecvError::Closed) => return Ok(()),
+                    };
+
+                    self.publish(Topic::Chat, false, &m).await?;
+                }
+            }
+        }
+    }
+
+    /// Subscribe to the given topics, sending a snapshot of the latest
+    /// messages for the ones we weren't already subscribed to.
+    async fn subscribe(&mut self, topics: Vec<Topic>) -> Result<()> {
+        let added = topics
+            .into_iter()
+            .filter(|t| self.topics.insert(*t))
+            .collect::<BTreeSet<_>>();
+
+        send(
+            &mut self.ws,
+            &Response::<()>::Subscribed {
+                topics: &self.topics,
+            },
+        )
+        .await?;
+
+        for m in self.global_bus.latest().await {
+            match Topic::of_global(&m) {
+                Some(topic) if added.contains(&topic) => {
+                    self.publish(topic, true, &m).await?;
+                }
+                _ => (),
+            }
+        }
+
+        if added.contains(&Topic::Chat) {
+            for m in self.message_bus.latest().await {
+                self.publish(Topic::Chat, true, &m).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send a message on the given topic, if it's subscribed to.
+    async fn publish<T>(&mut self, topic: Topic, snapshot: bool, data: &T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        if !self.topics.contains(&topic) {
+            return Ok(());
+        }
+
+        send(
+            &mut self.ws,
+            &Response::Message {
+                topic,
+                snapshot,
+                data,
+            },
+        )
+        .await
+    }
+}
+
+/// Send a response to the client.
+async fn send<T>(ws: &mut WebSocket, response: &Response<'_, T>) -> Result<()>
+where
+    T: Serialize,
+{
+    let m = Message::text(serde_json::to_string(response)?);
+    ws.send(m).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Request, Topic};
+
+    #[test]
+    fn test_topics() {
+        assert_eq!(Topic::parse("song"), Some(Topic::Song));
+        assert_eq!(Topic::parse("countdown"), Some(Topic::Countdown));
+        assert_eq!(Topic::parse("weather"), None);
+
+        assert_eq!(
+            Topic::of_global(&bus::Global::SongModified),
+            Some(Topic::Song)
+        );
+        assert_eq!(Topic::of_global(&bus::Global::Ping), None);
+
+        let request =
+            serde_json::from_str::<Request>(r#"{"type": "subscribe", "topics": ["song", "chat"]}"#)
+                .unwrap();
+
+        assert_eq!(
+            request,
+            Request::Subscribe {
+                topics: vec![Topic::Song, Topic::Chat]
+            }
+        );
+
+        assert!(
+            serde_json::from_str::<Request>(r#"{"type": "subscribe", "topics": ["weather"]}"#)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! The versioned `/api/v1` surface.
+//!
+//! This mounts the same routes as the unversioned API, but guarantees that
+//! every error is reported as an [`ErrorMessage`] and that list endpoints can
+//! be paginated.
+//!
+//! Endpoints which limit their queries paginate those queries themselves
+//! through [`PageQuery`] and [`paged`], everything else is paginated after
+//! the fact.
+
+use std::convert::Infallible;
+
+use serde::Serialize;
+use warp::http::{header, HeaderValue, StatusCode};
+use warp::reply::Response;
+use warp::{filters, Filter, Rejection, Reply};
+
+use crate::openapi::OpenApi;
+use crate::{CustomReject, ErrorMessage, WebError};
+
+/// Default number of items in a page.
+pub(crate) const DEFAULT_PER_PAGE: usize = 50;
+/// Maximum number of items in a page.
+pub(crate) const MAX_PER_PAGE: usize = 500;
+/// Header containing the total number of items in a paginated list.
+pub(crate) const TOTAL_COUNT: &str = "x-total-count";
+
+/// Query parameters used to request a page.
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize)]
+pub(crate) struct PageQuery {
+    #[serde(default)]
+    page: Option<usize>,
+    #[serde(default)]
+    per_page: Option<usize>,
+}
+
+impl PageQuery {
+    /// The requested page, if pagination was requested.
+    ///
+    /// Pages start at 1.
+    pub(crate) fn page(&self) -> Option<Page> {
+        if self.page.is_none() && self.per_page.is_none() {
+            return None;
+        }
+
+        let per_page = self
+            .per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE);
+        let page = self.page.unwrap_or(1).max(1);
+
+        Some(Page {
+            offset: i64::try_from((page - 1).saturating_mul(per_page)).unwrap_or(i64::MAX),
+            limit: per_page as i64,
+        })
+    }
+}
+
+/// A page to load from the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Page {
+    /// The number of items to skip.
+    pub(crate) offset: i64,
+    /// The number of items in the page.
+    pub(crate) limit: i64,
+}
+
+/// Reply with a page of items, out of `total` items.
+pub(crate) fn paged<T>(items: &T, total: i64) -> Response
+where
+    T: Serialize,
+{
+    let mut response = warp::reply::json(items).into_response();
+    response
+        .headers_mut()
+        .insert(TOTAL_COUNT, HeaderValue::from(total));
+    response
+}
+
+/// Set up the versioned API on top of the given routes.
+pub(crate) fn route<F, R>(version: &'static str, routes: F) -> filters::BoxedFilter<(Response,)>
+where
+    F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+    R: Reply + Send,
+{
+    let routes = warp::query::<PageQuery>()
+        .and(routes)
+        .then(|query: PageQuery, reply: R| paginate(query, reply.into_response()));
+
+    let api = OpenApi::route(version)
+        .map(Reply::into_response)
+        .or(routes)
+        .unify()
+        .recover(recover)
+        .unify();
+
+    warp::path("v1").and(api).boxed()
+}
+
+/// Paginate the response if it's a list and pagination was requested.
+///
+/// The total number of items is reported through the `X-Total-Count` header.
+async fn paginate(query: PageQuery, response: Response) -> Response {
+    if query.page.is_none() && query.per_page.is_none() {
+        return response;
+    }
+
+    // NB: the endpoint already loaded the requested page.
+    if !response.status().is_success() || response.headers().contains_key(TOTAL_COUNT) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+
+    let bytes = match warp::hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let items = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Array(items)) => items,
+        _ => return Response::from_parts(parts, bytes.into()),
+    };
+
+    let total = items.len();
+    let items = page(items, query.page, query.per_page);
+
+    let body = match serde_json::to_vec(&items) {
+        Ok(body) => body,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(TOTAL_COUNT, HeaderValue::from(total));
+    Response::from_parts(parts, body.into())
+}
+
+/// Pick the requested page out of a list of items.
+fn page<T>(items: Vec<T>, page: Option<usize>, per_page: Option<usize>) -> Vec<T> {
+    let page = PageQuery { page, per_page }.page().unwrap_or(Page {
+        offset: 0,
+        limit: DEFAULT_PER_PAGE as i64,
+    });
+
+    items
+        .into_iter()
+        .skip(usize::try_from(page.offset).unwrap_or(usize::MAX))
+        .take(page.limit as usize)
+        .collect()
+}
+
+/// Render any rejection as an error message.
+async fn recover(err: Rejection) -> Result<Response, Infallible> {
+    let (code, message) = if let Some(e) = err.find::<WebError>() {
+        let code = match e {
+            WebError::BadRequest => StatusCode::BAD_REQUEST,
+            WebError::NotFound => StatusCode::NOT_FOUND,
+            WebError::Unauthorized => StatusCode::UNAUTHORIZED,
+            WebError::Forbidden => StatusCode::FORBIDDEN,
+            WebError::Custom(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (code, e.to_string())
+    } else if let Some(e) = err.find::<CustomReject>() {
+        tracing::error!("Endpoint error caused by: {}", e.0);
+        (StatusCode::INTERNAL_SERVER_ERROR, e.0.to_string())
+    } else if err.is_not_found() {
+        (StatusCode::NOT_FOUND, String::from("not found"))
+    } else if let Some(e) = err.find::<warp::filters::body::BodyDeserializeError>() {
+        (StatusCode::BAD_REQUEST, e.to_string())
+    } else if let Some(e) = err.find::<warp::reject::InvalidQuery>() {
+        (StatusCode::BAD_REQUEST, e.to_string())
+    } else if let Some(e) = err.find::<warp::reject::MissingHeader>() {
+        (StatusCode::BAD_REQUEST, e.to_string())
+    } else if let Some(e) = err.find::<warp::reject::UnsupportedMediaType>() {
+        (StatusCode::UNSUPPORTED_MEDIA_TYPE, e.to_string())
+    } else if let Some(e) = err.find::<warp::reject::PayloadTooLarge>() {
+        (StatusCode::PAYLOAD_TOO_LARGE, e.to_string())
+    } else if let Some(e) = err.find::<warp::reject::MethodNotAllowed>() {
+        (StatusCode::METHOD_NOT_ALLOWED, e.to_string())
+    } else {
+        tracing::error!("Unhandled rejection: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            String::from("unhandled rejection"),
+        )
+    };
+
+    Ok(error(code, message))
+}
+
+/// Construct an error response.
+fn error(code: StatusCode, message: String) -> Response {
+    let json = warp::reply::json(&ErrorMessage {
+        code: code.as_u16(),
+        message,
+    });
+
+    warp::reply::with_status(json, code).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{page, Page, PageQuery, DEFAULT_PER_PAGE, MAX_PER_PAGE};
+
+    #[test]
+    fn test_page() {
+        let items = (0..10).collect::<Vec<_>>();
+
+        assert_eq!(page(items.clone(), None, Some(3)), vec![0, 1, 2]);
+        assert_eq!(page(items.clone(), Some(2), Some(3)), vec![3, 4, 5]);
+        assert_eq!(page(items.clone(), Some(4), Some(3)), vec![9]);
+        assert!(page(items.clone(), Some(5), Some(3)).is_empty());
+        // Page 0 is treated as the first page, and empty pages aren't allowed.
+        assert_eq!(page(items.clone(), Some(0), Some(0)), vec![0]);
+        assert_eq!(page(items, Some(usize::MAX), Some(3)), Vec::<i32>::new());
+
+        let items = (0..1000).collect::<Vec<_>>();
+        assert_eq!(page(items.clone(), None, None).len(), DEFAULT_PER_PAGE);
+        assert_eq!(page(items, None, Some(10_000)).len(), MAX_PER_PAGE);
+    }
+
+    #[test]
+    fn test_page_query() {
+        let query = |page, per_page| PageQuery { page, per_page }.page();
+
+        assert_eq!(query(None, None), None);
+        assert_eq!(
+            query(Some(1), None),
+            Some(Page {
+                offset: 0,
+                limit: 50
+            })
+        );
+        assert_eq!(
+            query(Some(3), Some(20)),
+            Some(Page {
+                offset: 40,
+                limit: 20
+            })
+        );
+        assert_eq!(
+            query(Some(0), Some(0)),
+            Some(Page {
+                offset: 0,
+                limit: 1
+            })
+        );
+        assert_eq!(
+            query(Some(2), Some(10_000)),
+            Some(Page {
+                offset: 500,
+                limit: 500
+            })
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::{bail, Result};
+use common::Channel;
+use tokio::sync::RwLockReadGuard;
+use warp::reply::Response;
+use warp::{filters, path, Filter, Reply};
+
+use crate::v1::{self, PageQuery};
+use crate::Fragment;
+
+/// Default number of users to show.
+const DEFAULT_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize)]
+struct TopQuery {
+    /// Only include watch time from the stream with the given id.
+    #[serde(default)]
+    stream: Option<String>,
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+struct Entry {
+    user: String,
+    seconds: i64,
+}
+
+/// Watch time endpoint.
+#[derive(Clone)]
+pub(crate) struct WatchTime(async_injector::Ref<db::WatchTime>);
+
+impl WatchTime {
+    pub(crate) fn route(
+        watch_time: async_injector::Ref<db::WatchTime>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = WatchTime(watch_time);
+
+        let top = warp::get()
+            .and(path!("watch-time" / Fragment).and(path::end()))
+            .and(warp::query::<TopQuery>())
+            .and(warp::query::<PageQuery>())
+            .and_then({
+                move |channel: Fragment, query: TopQuery, page: PageQuery| {
+                    let api = api.clone();
+                    async move {
+                        api.top(channel.as_channel(), query, page)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            });
+
+        top.boxed()
+    }
+
+    /// Access underlying watch time abstraction.
+    async fn watch_time(&self) -> Result<RwLockReadGuard<'_, db::WatchTime>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("watch time not configured"),
+        }
+    }
+
+    /// List the users with the most watch time.
+    ///
+    /// If a page is requested, it takes precedence over the limit.
+    async fn top(&self, channel: &Channel, query: TopQuery, page: PageQuery) -> Result<Response> {
+        let watch_time = self.watch_time().await?;
+        let stream = query.stream.as_deref();
+
+        let page = page.page();
+
+        let (limit, offset) = match page {
+            Some(page) => (page.limit, page.offset),
+            None => (query.limit.unwrap_or(DEFAULT_LIMIT), 0),
+        };
+
+        let top = watch_time
+            .top(channel, stream, limit, offset)
+            .await?
+            .into_iter()
+            .map(|(user, seconds)| Entry { user, seconds })
+            .collect::<Vec<_>>();
+
+        if page.is_none() {
+            return Ok(warp::reply::json(&top).into_response());
+        }
+
+        let total = watch_time.count(channel, stream).await?;
+        Ok(v1::paged(&top, total))
+    }
+}
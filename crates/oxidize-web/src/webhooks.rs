@@ -0,0 +1,165 @@
+use anyhow::{bail, Result};
+use rand::distributions::{Alphanumeric, DistString};
+use tokio::sync::RwLockReadGuard;
+use url::Url;
+use warp::reply::Response;
+use warp::{body, filters, path, Filter, Reply};
+
+use crate::v1::{self, PageQuery};
+use crate::{Fragment, EMPTY};
+
+/// Length of generated secrets.
+const SECRET_LENGTH: usize = 32;
+
+#[derive(serde::Deserialize)]
+struct DeliveriesQuery {
+    #[serde(default)]
+    webhook: Option<String>,
+    #[serde(default = "default_deliveries_limit")]
+    limit: i64,
+}
+
+fn default_deliveries_limit() -> i64 {
+    100
+}
+
+/// Webhooks endpoint.
+#[derive(Clone)]
+pub(crate) struct Webhooks(async_injector::Ref<db::Webhooks>);
+
+impl Webhooks {
+    pub(crate) fn route(
+        webhooks: async_injector::Ref<db::Webhooks>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Webhooks(webhooks);
+
+        let list = warp::get()
+            .and(path!("webhooks").and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move || {
+                    let api = api.clone();
+                    async move { api.list().await.map_err(super::custom_reject) }
+                }
+            })
+            .boxed();
+
+        let events = warp::get()
+            .and(path!("webhooks" / "events").and(path::end()))
+            .map(|| warp::reply::json(&api::webhook::EVENTS))
+            .boxed();
+
+        let deliveries = warp::get()
+            .and(path!("webhooks" / "deliveries").and(path::end()))
+            .and(warp::query::<DeliveriesQuery>())
+            .and(warp::query::<PageQuery>())
+            .and_then({
+                let api = api.clone();
+                move |query: DeliveriesQuery, page: PageQuery| {
+                    let api = api.clone();
+                    async move {
+                        api.deliveries(query, page)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let edit = warp::put()
+            .and(path!("webhooks" / Fragment).and(path::end()))
+            .and(body::json())
+            .and_then({
+                let api = api.clone();
+                move |name: Fragment, body: db::WebhookEdit| {
+                    let api = api.clone();
+                    async move {
+                        api.edit(name.as_str(), body)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        let delete = warp::delete()
+            .and(path!("webhooks" / Fragment).and(path::end()))
+            .and_then({
+                move |name: Fragment| {
+                    let api = api.clone();
+                    async move {
+                        api.delete(name.as_str())
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            })
+            .boxed();
+
+        list.or(events).or(deliveries).or(edit).or(delete).boxed()
+    }
+
+    /// Access underlying webhooks abstraction.
+    async fn webhooks(&self) -> Result<RwLockReadGuard<'_, db::Webhooks>> {
+        match self.0.read().await {
+            Some(out) => Ok(out),
+            None => bail!("webhooks not configured"),
+        }
+    }
+
+    /// Get the list of all webhooks.
+    async fn list(&self) -> Result<impl warp::Reply> {
+        let webhooks = self.webhooks().await?.list().await?;
+        Ok(warp::reply::json(&webhooks))
+    }
+
+    /// Get the most recent deliveries.
+    ///
+    /// If a page is requested, it takes precedence over the limit.
+    async fn deliveries(&self, query: DeliveriesQuery, page: PageQuery) -> Result<Response> {
+        let webhooks = self.webhooks().await?;
+        let webhook = query.webhook.as_deref();
+
+        let Some(page) = page.page() else {
+            let deliveries = webhooks.deliveries(webhook, query.limit, 0).await?;
+            return Ok(warp::reply::json(&deliveries).into_response());
+        };
+
+        let deliveries = webhooks
+            .deliveries(webhook, page.limit, page.offset)
+            .await?;
+        let total = webhooks.count_deliveries(webhook).await?;
+        Ok(v1::paged(&deliveries, total))
+    }
+
+    /// Insert or update a webhook.
+    ///
+    /// A secret is generated for new webhooks unless one is specified.
+    async fn edit(&self, name: &str, mut edit: db::WebhookEdit) -> Result<impl warp::Reply> {
+        match str::parse::<Url>(&edit.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => (),
+            _ => bail!("bad url `{}`, expected an http or https url", edit.url),
+        }
+
+        for event in &edit.events {
+            if !api::webhook::EVENTS.contains(&event.as_str()) {
+                bail!("unsupported event `{}`", event);
+            }
+        }
+
+        let webhooks = self.webhooks().await?;
+
+        if edit.secret.is_none() && webhooks.get(name).await?.is_none() {
+            edit.secret = Some(Alphanumeric.sample_string(&mut rand::thread_rng(), SECRET_LENGTH));
+        }
+
+        let webhook = webhooks.edit(name, edit).await?;
+        Ok(warp::reply::json(&webhook))
+    }
+
+    /// Delete the given webhook.
+    async fn delete(&self, name: &str) -> Result<impl warp::Reply> {
+        self.webhooks().await?.delete(name).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+}